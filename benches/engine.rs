@@ -0,0 +1,150 @@
+//! Benchmarks of the engine's move path.
+//!
+//! Run with `cargo bench`. The timing loop is written out here rather than
+//! taken from a benchmark crate so the suite builds without extra
+//! dependencies; each benchmark runs for about a second and prints the time
+//! per iteration and the rate it reaches.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use tui_2048::ai::{
+    DIRECTIONS, ExpectimaxConfig, TableStats, TranspositionTable, expectimax_move_with, greedy_move,
+};
+use tui_2048::board::Board;
+use tui_2048::game::bitboard::{pack, slide_bits};
+use tui_2048::game::{BitboardGame2048, Game2048, GameEngine, merge_line, slide_board};
+
+/// How long each benchmark is measured for
+const MEASURE_FOR: Duration = Duration::from_secs(1);
+
+/// Call `f` repeatedly for about [`MEASURE_FOR`] and print the mean time of
+/// a call, and the number of `per_call` units handled per second
+fn bench(name: &str, per_call: u64, mut f: impl FnMut()) {
+    // Warm up caches and find a batch size taking about a millisecond
+    let mut batch = 1u64;
+    loop {
+        let start = Instant::now();
+        for _ in 0..batch {
+            f();
+        }
+        if start.elapsed() >= Duration::from_millis(1) {
+            break;
+        }
+        batch *= 2;
+    }
+
+    let mut calls = 0;
+    let start = Instant::now();
+    while start.elapsed() < MEASURE_FOR {
+        for _ in 0..batch {
+            f();
+        }
+        calls += batch;
+    }
+    let elapsed = start.elapsed();
+    let per_call_ns = elapsed.as_nanos() as f64 / calls as f64;
+    let rate = (calls * per_call) as f64 / elapsed.as_secs_f64();
+    println!("{name:<36} {per_call_ns:>10.1} ns/iter {rate:>14.0} /s");
+}
+
+/// Games played by the greedy bot, in positions which slide in some
+/// directions and not in others
+fn sample_games() -> Vec<Game2048> {
+    let mut game = Game2048::with_seed(42);
+    let mut games = Vec::new();
+    while games.len() < 64 {
+        let Some(direction) = greedy_move(&game) else {
+            game = Game2048::with_seed(games.len() as u64);
+            continue;
+        };
+        game.move_in_direction(&direction);
+        games.push(game.clone());
+    }
+    games
+}
+
+fn main() {
+    let games = sample_games();
+    let boards: Vec<&Board> = games.iter().map(Game2048::tiles).collect();
+
+    let rows: Vec<[u32; 4]> = boards
+        .iter()
+        .flat_map(|board| {
+            board
+                .rows()
+                .iter()
+                .map(|row| [row[0], row[1], row[2], row[3]])
+        })
+        .collect();
+    let mut i = 0;
+    bench("merge_line", 1, || {
+        let mut line = rows[i % rows.len()];
+        black_box(merge_line(black_box(&mut line)));
+        i += 1;
+    });
+
+    let mut scratch = Board::new(4);
+    let mut i = 0;
+    bench("slide_board (4 directions)", 4, || {
+        let board = &boards[i % boards.len()];
+        for direction in DIRECTIONS {
+            scratch.clone_from(*board);
+            black_box(slide_board(&mut scratch, &direction, |_, _| {}));
+        }
+        i += 1;
+    });
+
+    let packed: Vec<u64> = boards.iter().map(|board| pack(board).unwrap()).collect();
+    let mut i = 0;
+    bench("slide_bits (4 directions)", 4, || {
+        let board = packed[i % packed.len()];
+        for direction in DIRECTIONS {
+            black_box(slide_bits(black_box(board), &direction));
+        }
+        i += 1;
+    });
+
+    let game = Game2048::with_seed(7);
+    let mut i = 0;
+    bench("Game2048::move_in_direction", 1, || {
+        let mut next = game.clone();
+        black_box(next.move_in_direction(&DIRECTIONS[i % 4]));
+        i += 1;
+    });
+
+    let bitboard = BitboardGame2048::with_seed(7);
+    let mut i = 0;
+    bench("BitboardGame2048::move_in_direction", 1, || {
+        let mut next = bitboard.clone();
+        black_box(next.move_in_direction(&DIRECTIONS[i % 4]));
+        i += 1;
+    });
+
+    let mut i = 0;
+    bench("greedy_move", 1, || {
+        black_box(greedy_move(&games[i % games.len()]));
+        i += 1;
+    });
+
+    // A fresh table per search, so the hits are positions reached twice
+    // within one search
+    let config = ExpectimaxConfig::default();
+    let mut stats = TableStats::default();
+    let mut i = 0;
+    bench("expectimax_move (depth 3)", 1, || {
+        let table = TranspositionTable::default();
+        black_box(expectimax_move_with(
+            &games[i % games.len()],
+            &config,
+            &table,
+        ));
+        stats.lookups += table.stats().lookups;
+        stats.hits += table.stats().hits;
+        i += 1;
+    });
+    println!(
+        "transposition table hit rate {:.1}%",
+        stats.hit_rate() * 100.0
+    );
+}
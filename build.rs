@@ -0,0 +1,17 @@
+//! Build script generating the C header when the `ffi` feature is enabled.
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+/// Write `include/tui_2048.h` from the `ffi` module
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("set by cargo");
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    cbindgen::generate(&crate_dir)
+        .expect("Unable to generate C header")
+        .write_to_file(format!("{}/include/tui_2048.h", crate_dir));
+}
@@ -0,0 +1,202 @@
+#ifndef TUI_2048_H
+#define TUI_2048_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Largest tile; two of them don't merge, as their sum wouldn't fit
+#define MAX_TILE (1 << 31)
+
+// Most past states a game keeps for browsing its history, unless its undo
+// limit keeps more, so long and endless games don't grow without bound
+#define HISTORY_LIMIT 500
+
+// Number of undos per game in hard mode
+#define HARD_MODE_UNDOS 3
+
+// Largest combo multiplier of arcade games
+#define MAX_MULTIPLIER 5
+
+// Rows and columns of a bitboard
+#define BITBOARD_SIZE 4
+
+// Largest tile a bitboard holds; two of them don't merge
+#define MAX_BITBOARD_TILE (1 << 15)
+
+// Default number of cells along each edge of the cube
+#define DEFAULT_CUBE_SIZE 3
+
+// Size after which the log file is rotated
+#define MAX_FILE_SIZE (1024 * 1024)
+
+// Rotated files kept next to the current one
+#define KEEP_FILES 3
+
+// Default minimum time between two moves in the same direction, in
+// milliseconds; terminals repeat held keys about every 30 ms
+#define DEFAULT_MOVE_INTERVAL_MS 50
+
+// Default idle time before the main menu plays a demo game, in seconds
+#define DEFAULT_DEMO_AFTER_SECS 30
+
+// Default minutes of play without a break before a break is suggested
+#define DEFAULT_BREAK_REMINDER_MINS 60
+
+// Moves looked ahead when rating the moves of a game, kept low so the
+// whole game is reviewed in a moment
+#define ANALYSIS_DEPTH 2
+
+// Rating a move may fall short of the best one by before it counts as a
+// blunder; a random move typically falls short by a few thousand
+#define BLUNDER_LOSS 10000.0
+
+// Slots of a table created with [`Default`]
+#define TranspositionTable_DEFAULT_BITS 16
+
+// Number of scores kept in a [`ScoreHistory`] by default
+#define SCORE_HISTORY_SIZE 512
+
+// Longest name of a save slot
+#define MAX_SLOT_NAME_LEN 24
+
+// Direction value for moving up
+#define TUI2048_UP 0
+
+// Direction value for moving down
+#define TUI2048_DOWN 1
+
+// Direction value for moving left
+#define TUI2048_LEFT 2
+
+// Direction value for moving right
+#define TUI2048_RIGHT 3
+
+// Games kept at most; creating another drops the oldest
+#define MAX_GAMES 1000
+
+// An achievement that can be unlocked once
+typedef struct Achievement Achievement;
+
+// Direction tiles slide in on a cube
+typedef struct Direction3D Direction3D;
+
+// Implementation of the 2048 game.
+//
+// With the `serde` feature the whole state is serialized, including the
+// undo history and the random generator, so a restored game spawns the same
+// tiles as the original would have. The spawn policy is left out; restored
+// games use [`StandardSpawn`].
+typedef struct Game2048 Game2048;
+
+// Languages the interface is translated to
+typedef struct Language Language;
+
+// An entry of the main menu
+typedef struct MenuItem MenuItem;
+
+// Color theme used by the colored renderer
+typedef struct Theme Theme;
+
+// How the numbers of the tiles are written
+typedef struct TileLabels TileLabels;
+
+// Game offered by the Variant entry of the menu
+typedef struct Variant Variant;
+
+
+
+
+
+// Patterns filling tiles of the accessibility themes, from sparse to dense
+#define PATTERNS { '\u{b7}', ':', U'\U00002591', U'\U00002592', U'\U00002593', }
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+// Create a default 4x4 game whose tile spawns are determined by `seed`.
+//
+// The game must be released with [`tui2048_free`].
+struct Game2048 *tui2048_new(uint64_t seed);
+
+// Release a game. Passing NULL does nothing.
+//
+// # Safety
+//
+// `game` must be NULL or a pointer returned by [`tui2048_new`] that was not
+// freed yet.
+void tui2048_free(struct Game2048 *game);
+
+// Move the tiles in a direction (`TUI2048_UP` etc.).
+//
+// Returns whether the board changed; unknown directions change nothing.
+//
+// # Safety
+//
+// `game` must be a valid game pointer.
+bool tui2048_move(struct Game2048 *game, uint32_t direction);
+
+// Undo the last move, returning whether there was one to undo.
+//
+// # Safety
+//
+// `game` must be a valid game pointer.
+bool tui2048_undo(struct Game2048 *game);
+
+// Current score.
+//
+// # Safety
+//
+// `game` must be a valid game pointer.
+uint64_t tui2048_score(const struct Game2048 *game);
+
+// Whether no move is possible anymore.
+//
+// # Safety
+//
+// `game` must be a valid game pointer.
+bool tui2048_game_over(const struct Game2048 *game);
+
+// Number of rows of the board, which is the number of columns too unless
+// the board is rectangular.
+//
+// # Safety
+//
+// `game` must be a valid game pointer.
+uintptr_t tui2048_size(const struct Game2048 *game);
+
+// Number of columns of the board.
+//
+// # Safety
+//
+// `game` must be a valid game pointer.
+uintptr_t tui2048_columns(const struct Game2048 *game);
+
+// Copy the board row by row into `cells` (0 for empty cells).
+//
+// Returns the number of cells written, or 0 if `len` is smaller than
+// rows * columns.
+//
+// # Safety
+//
+// `game` must be a valid game pointer and `cells` must point to at least
+// `len` writable values.
+uintptr_t tui2048_board(const struct Game2048 *game, uint32_t *cells, uintptr_t len);
+
+#endif  /* TUI_2048_H */
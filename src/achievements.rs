@@ -0,0 +1,143 @@
+//! Achievements unlocked by notable moves.
+//!
+//! Achievements and the counters behind them are kept across games in a file
+//! in the data directory, together with the cosmetics they unlocked.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cosmetics::{Cosmetic, Cosmetics};
+use crate::error::{GameError, GameResult};
+use crate::game::MoveResult;
+use crate::profile;
+
+/// Name of the achievements file inside the data directory
+const ACHIEVEMENTS_FILE: &str = "achievements.toml";
+
+/// Number of merges in one move needed for a quad merge
+pub const QUAD_MERGE: usize = 4;
+
+/// An achievement that can be unlocked once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    /// Two merges in the same row or column with one move
+    DoubleMerge,
+    /// Four merges with one move
+    QuadMerge,
+}
+
+impl Achievement {
+    /// All achievements, in the order they are listed
+    pub const ALL: [Achievement; 2] = [Achievement::DoubleMerge, Achievement::QuadMerge];
+
+    /// Name shown when the achievement is unlocked
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::DoubleMerge => "Double merge",
+            Achievement::QuadMerge => "Quad merge",
+        }
+    }
+}
+
+/// Unlocked achievements and merge chain counters
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    unlocked: BTreeSet<Achievement>,
+    /// Lines with more than one merge, over all games
+    pub chains: u32,
+    /// Moves with at least [`QUAD_MERGE`] merges, over all games
+    pub quad_merges: u32,
+    /// Selected border style and accent
+    pub cosmetics: Cosmetics,
+}
+
+impl Achievements {
+    /// Default location of the achievements file, if a data directory exists
+    pub fn default_path() -> Option<PathBuf> {
+        profile::data_dir().map(|dir| dir.join(ACHIEVEMENTS_FILE))
+    }
+
+    /// Load achievements from a file, starting empty if it is missing
+    pub fn load(path: &Path) -> GameResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            GameError::SaveError(format!(
+                "Corrupt achievements {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })
+    }
+
+    /// Write the achievements to a file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(|e| {
+            GameError::SerializationError(format!("Cannot encode achievements: {}", e))
+        })?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Whether an achievement was unlocked
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// Select a cosmetic if its milestone was reached, returning whether it was
+    pub fn equip(&mut self, cosmetic: Cosmetic) -> bool {
+        let reached = cosmetic.milestone().is_reached(self);
+        if reached {
+            self.cosmetics.select(cosmetic);
+        }
+        reached
+    }
+
+    /// Count the chains of a move, returning the achievements it newly unlocked
+    pub fn record(&mut self, result: &MoveResult) -> Vec<Achievement> {
+        self.chains += result.chains.len() as u32;
+        let mut earned = vec![];
+        if !result.chains.is_empty() {
+            earned.push(Achievement::DoubleMerge);
+        }
+        if result.merges >= QUAD_MERGE {
+            self.quad_merges += 1;
+            earned.push(Achievement::QuadMerge);
+        }
+        earned.retain(|&achievement| self.unlocked.insert(achievement));
+        earned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game2048, GameEngine, GameView, MovementDirection};
+
+    #[test]
+    fn test_quad_merge_unlocks_once() {
+        let board = vec![vec![2, 2, 4, 4], vec![8, 8, 16, 16], vec![0; 4], vec![0; 4]];
+        let mut game = Game2048::with_board(board, 1);
+        game.move_in_direction(&MovementDirection::Left);
+
+        let mut achievements = Achievements::default();
+        let result = game.last_move().unwrap();
+        assert_eq!(
+            achievements.record(result),
+            vec![Achievement::DoubleMerge, Achievement::QuadMerge]
+        );
+        assert!(achievements.record(result).is_empty());
+        assert_eq!(achievements.chains, 4);
+        assert_eq!(achievements.quad_merges, 2);
+    }
+}
@@ -0,0 +1,387 @@
+//! Automatic players for the 2048 game.
+//!
+//! This module provides strategies that pick a move for a given game state:
+//! random moves, a greedy one looking a single move ahead, an expectimax
+//! search and a Monte Carlo search playing random games to the end. They are
+//! used by the headless subcommands, and the expectimax ratings also grade
+//! the moves of finished games.
+
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rand::prelude::*;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::game::bitboard::{pack, slide_bits, spawn_bits};
+use crate::game::{Game2048, GameView, MovementDirection, slide_board_by};
+use crate::heuristics::{empty_cells, monotonicity};
+
+mod analysis;
+mod expectimax;
+mod transposition;
+
+pub use analysis::{
+    ANALYSIS_DEPTH, BLUNDER_LOSS, GameAnalysis, MissedMerge, MoveGrade, MoveReview, analyze_game,
+    review_move,
+};
+pub use expectimax::{
+    ExpectimaxConfig, expectimax_move, expectimax_move_with, rate_board, rate_moves,
+};
+pub use transposition::{TableStats, TranspositionTable, zobrist_hash};
+
+/// All movement directions, in the order strategies try them
+pub const DIRECTIONS: [MovementDirection; 4] = [
+    MovementDirection::Up,
+    MovementDirection::Left,
+    MovementDirection::Right,
+    MovementDirection::Down,
+];
+
+/// What a search for a move did, for the log and the debug overlay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchStats {
+    /// Name of the search, as in `--algo`
+    pub strategy: &'static str,
+    pub elapsed: Duration,
+    /// Moves looked ahead by the deepest search that finished
+    pub depth: Option<u32>,
+    /// Random games played
+    pub rollouts: Option<u32>,
+    /// Lookups in the transposition table so far
+    pub table: Option<TableStats>,
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} in {:.1} ms",
+            self.strategy,
+            self.elapsed.as_secs_f64() * 1000.0
+        )?;
+        if let Some(depth) = self.depth {
+            write!(f, ", depth {}", depth)?;
+        }
+        if let Some(rollouts) = self.rollouts {
+            write!(f, ", {} rollouts", rollouts)?;
+        }
+        if let Some(table) = self.table {
+            write!(f, ", {:.0}% table hits", table.hit_rate() * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Statistics of the last search of any thread
+static LAST_SEARCH: Mutex<Option<SearchStats>> = Mutex::new(None);
+
+/// Statistics of the last search by [`expectimax_move_with`] or
+/// [`mcts_move`], `None` before the first one
+pub fn last_search() -> Option<SearchStats> {
+    LAST_SEARCH.lock().ok().and_then(|last| *last)
+}
+
+/// Remember the statistics of a search and log them
+fn record_search(stats: SearchStats) {
+    log::debug!("Searched {}", stats);
+    if let Ok(mut last) = LAST_SEARCH.lock() {
+        *last = Some(stats);
+    }
+}
+
+/// Pick a random move among those that change the board, `None` if there
+/// is none
+pub fn random_move(game: &Game2048, rng: &mut impl Rng) -> Option<MovementDirection> {
+    let moves: Vec<MovementDirection> = DIRECTIONS
+        .into_iter()
+        .filter(|direction| {
+            let mut board = game.tiles().clone();
+            slide_board_by(&mut board, direction, game.merge_rule(), |_, _| {}).moved
+        })
+        .collect();
+    moves.choose(rng).copied()
+}
+
+/// Pick the move with the best immediate result.
+///
+/// Moves are ranked by the points they score, then by the number of empty
+/// cells they leave and finally by the [`monotonicity`] of the resulting
+/// board. Returns `None` if no move changes the board.
+pub fn greedy_move(game: &Game2048) -> Option<MovementDirection> {
+    DIRECTIONS
+        .iter()
+        .filter_map(|&direction| {
+            // Only the board is copied, the tile spawned after the move
+            // isn't known yet anyway
+            let mut board = game.tiles().clone();
+            let slide = slide_board_by(&mut board, &direction, game.merge_rule(), |_, _| {});
+            slide.moved.then(|| {
+                let rows = board.rows();
+                (
+                    direction,
+                    (slide.points, empty_cells(rows), monotonicity(rows)),
+                )
+            })
+        })
+        // max_by_key returns the last maximum, so reverse to prefer earlier directions
+        .rev()
+        .max_by_key(|&(_, rank)| rank)
+        .map(|(direction, _)| direction)
+}
+
+/// Settings of the Monte Carlo search, see [`mcts_move`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MctsConfig {
+    /// Random games played per move
+    pub rollouts: usize,
+    /// Time after which the search stops even if rollouts are left
+    pub time_budget: Option<Duration>,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            rollouts: 200,
+            time_budget: None,
+        }
+    }
+}
+
+/// Statistics of one move at the root of the search
+#[derive(Debug, Clone, Copy)]
+struct Arm {
+    direction: MovementDirection,
+    /// Board after the move, before a tile spawns
+    board: u64,
+    points: u64,
+    visits: u32,
+    total: f64,
+}
+
+impl Arm {
+    fn mean(&self) -> f64 {
+        self.total / f64::from(self.visits.max(1))
+    }
+}
+
+/// Pick a move by Monte Carlo tree search.
+///
+/// Every rollout chooses a first move with the UCB1 rule, which balances
+/// moves with good results so far against rarely tried ones, then plays
+/// random moves until the game is over. A move is worth the points scored
+/// along the way, and the move with the best average is played. Searches
+/// below the first move stay random, as the spawned tiles make every node
+/// after it a chance node.
+///
+/// Rollouts run on a packed 4x4 board; other boards are left to
+/// [`greedy_move`]. With the `parallel` feature the rollouts are shared out
+/// between the threads of the rayon pool. Returns `None` if no move changes
+/// the board.
+pub fn mcts_move(
+    game: &Game2048,
+    config: &MctsConfig,
+    rng: &mut impl Rng,
+) -> Option<MovementDirection> {
+    let Ok(board) = pack(game.tiles()) else {
+        return greedy_move(game);
+    };
+    let arms: Vec<Arm> = DIRECTIONS
+        .iter()
+        .filter_map(|&direction| {
+            let (next, points) = slide_bits(board, &direction);
+            (next != board).then_some(Arm {
+                direction,
+                board: next,
+                points,
+                visits: 0,
+                total: 0.0,
+            })
+        })
+        .collect();
+    if arms.len() <= 1 {
+        return arms.first().map(|arm| arm.direction);
+    }
+
+    let started = Instant::now();
+    // With the `parallel` feature every thread searches on its own, from its
+    // own seed, and the statistics of the searches are added up
+    #[cfg(feature = "parallel")]
+    let arms = {
+        let threads = rayon::current_num_threads();
+        let seeds: Vec<u64> = (0..threads).map(|_| rng.random()).collect();
+        let rollouts = config.rollouts.div_ceil(threads);
+        seeds
+            .into_par_iter()
+            .map(|seed| {
+                let mut rng = StdRng::seed_from_u64(seed);
+                run_rollouts(
+                    arms.clone(),
+                    rollouts,
+                    config.time_budget,
+                    started,
+                    &mut rng,
+                )
+            })
+            .reduce_with(|mut sum, arms| {
+                for (sum, arm) in sum.iter_mut().zip(arms) {
+                    sum.visits += arm.visits;
+                    sum.total += arm.total;
+                }
+                sum
+            })
+            .unwrap_or(arms)
+    };
+    #[cfg(not(feature = "parallel"))]
+    let arms = run_rollouts(arms, config.rollouts, config.time_budget, started, rng);
+
+    record_search(SearchStats {
+        strategy: "mcts",
+        elapsed: started.elapsed(),
+        depth: None,
+        rollouts: Some(arms.iter().map(|arm| arm.visits).sum()),
+        table: None,
+    });
+    arms.iter()
+        .max_by(|a, b| a.mean().total_cmp(&b.mean()))
+        .map(|arm| arm.direction)
+}
+
+/// Play up to `rollouts` rollouts, choosing the first move of each by UCB1,
+/// and stop early once the search has run for `time_budget`. Every move is
+/// tried at least once.
+fn run_rollouts(
+    mut arms: Vec<Arm>,
+    rollouts: usize,
+    time_budget: Option<Duration>,
+    started: Instant,
+    rng: &mut impl Rng,
+) -> Vec<Arm> {
+    for rollout in 0..rollouts.max(arms.len()) {
+        if time_budget.is_some_and(|budget| rollout >= arms.len() && started.elapsed() >= budget) {
+            break;
+        }
+        // Scale the means to [0, 1] so the exploration term fits the scores
+        let best = arms.iter().map(Arm::mean).fold(1.0, f64::max);
+        let log_total = (rollout as f64).ln();
+        let arm = arms
+            .iter_mut()
+            .max_by(|a, b| ucb(a, best, log_total).total_cmp(&ucb(b, best, log_total)))
+            .expect("at least one move");
+        let points = arm.points + random_playout(arm.board, rng);
+        arm.visits += 1;
+        arm.total += points as f64;
+    }
+    arms
+}
+
+/// UCB1 value of a root move, infinite for moves not tried yet
+fn ucb(arm: &Arm, best: f64, log_total: f64) -> f64 {
+    if arm.visits == 0 {
+        return f64::INFINITY;
+    }
+    let visits = f64::from(arm.visits);
+    arm.mean() / best + (2.0 * log_total / visits).sqrt()
+}
+
+/// Spawn a tile and play random moves until the game is over, returning the
+/// points scored
+fn random_playout(mut board: u64, rng: &mut impl Rng) -> u64 {
+    let mut points = 0;
+    loop {
+        if let Some((spawned, _)) = spawn_bits(board, rng) {
+            board = spawned;
+        }
+        let mut moves = [(0, 0); 4];
+        let mut count = 0;
+        for direction in &DIRECTIONS {
+            let (next, gained) = slide_bits(board, direction);
+            if next != board {
+                moves[count] = (next, gained);
+                count += 1;
+            }
+        }
+        let Some(&(next, gained)) = moves[..count].choose(rng) else {
+            return points;
+        };
+        board = next;
+        points += gained;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameEngine;
+
+    #[test]
+    fn test_greedy_prefers_merge() {
+        let game = Game2048::with_board(
+            vec![vec![0, 0, 0, 2], vec![0; 4], vec![0; 4], vec![0, 0, 0, 2]],
+            1,
+        );
+        assert!(matches!(
+            greedy_move(&game),
+            Some(MovementDirection::Up) | Some(MovementDirection::Down)
+        ));
+    }
+
+    #[test]
+    fn test_greedy_no_move_on_stuck_board() {
+        let board = vec![vec![2, 4, 2], vec![4, 2, 4], vec![2, 4, 2]];
+        assert!(greedy_move(&Game2048::with_board(board, 1)).is_none());
+    }
+
+    #[test]
+    fn test_mcts_plays_well() {
+        let mut game = Game2048::with_seed(5);
+        let mut rng = StdRng::seed_from_u64(5);
+        let config = MctsConfig {
+            rollouts: 30,
+            time_budget: None,
+        };
+        while game.tiles().max_tile() < 256 {
+            let direction = mcts_move(&game, &config, &mut rng).expect("no move left");
+            assert!(game.move_in_direction(&direction));
+        }
+    }
+
+    #[test]
+    fn test_mcts_stops_on_time_and_when_stuck() {
+        let game = Game2048::with_seed(3);
+        let mut rng = StdRng::seed_from_u64(3);
+        let config = MctsConfig {
+            rollouts: usize::MAX,
+            time_budget: Some(Duration::from_millis(20)),
+        };
+        assert!(mcts_move(&game, &config, &mut rng).is_some());
+
+        let board = vec![
+            vec![2, 4, 2, 4],
+            vec![4, 2, 4, 2],
+            vec![2, 4, 2, 4],
+            vec![4, 2, 4, 2],
+        ];
+        let stuck = Game2048::with_board(board, 1);
+        assert!(mcts_move(&stuck, &MctsConfig::default(), &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_search_stats_display() {
+        let stats = SearchStats {
+            strategy: "expectimax",
+            elapsed: Duration::from_micros(12_340),
+            depth: Some(3),
+            rollouts: None,
+            table: Some(TableStats {
+                lookups: 200,
+                hits: 90,
+            }),
+        };
+        assert_eq!(
+            stats.to_string(),
+            "expectimax in 12.3 ms, depth 3, 45% table hits"
+        );
+    }
+}
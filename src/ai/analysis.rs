@@ -0,0 +1,290 @@
+//! Review of moves and finished games against the expectimax search.
+//!
+//! Every move is rated like [`expectimax_move`] rates the moves it chooses
+//! from, and compared with the best one. Moves far worse than the best are
+//! mistakes (the blunders of a game), and the largest tile another move would
+//! have merged when the move played didn't is the biggest missed merge.
+//!
+//! [`expectimax_move`]: super::expectimax_move
+
+use super::{TranspositionTable, rate_moves};
+use crate::board::Board;
+use crate::error::GameResult;
+use crate::game::bitboard::pack;
+use crate::game::{MovementDirection, slide_board};
+
+/// Moves looked ahead when rating the moves of a game, kept low so the
+/// whole game is reviewed in a moment
+pub const ANALYSIS_DEPTH: u32 = 2;
+
+/// Rating a move may fall short of the best one by before it counts as a
+/// blunder; a random move typically falls short by a few thousand
+pub const BLUNDER_LOSS: f32 = 10_000.0;
+
+/// How a move compares with the best one on the same board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveGrade {
+    /// The move the search would have played
+    Best,
+    /// Not the best move, but less than [`BLUNDER_LOSS`] behind it
+    Ok,
+    /// A move far worse than the best one
+    Mistake,
+}
+
+/// The grade of a move and the move the search would have played instead
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveReview {
+    pub grade: MoveGrade,
+    pub best: MovementDirection,
+    /// Rating of the move relative to the best and the worst move, from 0
+    /// for the worst to 1 for the best
+    pub quality: f64,
+}
+
+/// Grade a move played on a board of 4x4 tiles, remembering ratings in a
+/// table kept by the caller. `None` if the move doesn't change the board or
+/// was the only one that does.
+pub fn review_move(
+    rows: &[Vec<u32>],
+    played: MovementDirection,
+    table: &TranspositionTable,
+) -> GameResult<Option<MoveReview>> {
+    let board = Board::from_rows(rows.to_vec())?;
+    let ratings = rate_moves(pack(&board)?, ANALYSIS_DEPTH, table);
+    Ok(grade(&ratings, played))
+}
+
+/// Grade a move among the rated moves of its board
+fn grade(ratings: &[(MovementDirection, f32)], played: MovementDirection) -> Option<MoveReview> {
+    let &(_, rating) = ratings
+        .iter()
+        .find(|&&(direction, _)| direction == played)?;
+    if ratings.len() < 2 {
+        return None;
+    }
+    // The first of equally good moves, as the search picks them
+    let (best, top) = ratings
+        .iter()
+        .copied()
+        .rev()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    let worst = ratings.iter().map(|&(_, r)| r).fold(f32::MAX, f32::min);
+    // Ratings summed on several threads may differ in the last bits
+    let grade = if top - rating <= top.abs() * f32::EPSILON {
+        MoveGrade::Best
+    } else if top - rating > BLUNDER_LOSS {
+        MoveGrade::Mistake
+    } else {
+        MoveGrade::Ok
+    };
+    let quality = if top > worst {
+        f64::from((rating - worst) / (top - worst))
+    } else {
+        1.0
+    };
+    Some(MoveReview {
+        grade,
+        best: if grade == MoveGrade::Best {
+            played
+        } else {
+            best
+        },
+        quality,
+    })
+}
+
+/// The largest merge a game missed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissedMerge {
+    /// Number of the move that missed it, starting at 1
+    pub move_number: usize,
+    /// Tile another move would have created
+    pub tile: u32,
+}
+
+/// How the moves of a game compare with the expectimax search
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameAnalysis {
+    /// Moves rated; moves without a choice are left out
+    pub moves: usize,
+    /// Moves the search would have played too
+    pub best_moves: usize,
+    /// Numbers of the moves far worse than the best one, starting at 1
+    pub blunders: Vec<usize>,
+    /// Largest tile a move could have merged that the move played didn't
+    pub missed_merge: Option<MissedMerge>,
+    /// Rating of the moves played relative to the best and the worst move,
+    /// averaged over the moves rated: 1 when the best move was always
+    /// played, 0 when the worst was
+    pub efficiency: f64,
+}
+
+/// Largest tile a move creates by merging on a board, `0` if none
+fn largest_merge(board: &Board, direction: MovementDirection) -> u32 {
+    let mut next = board.clone();
+    let mut merged = Vec::new();
+    slide_board(&mut next, &direction, |_, pos| merged.push(pos));
+    merged
+        .into_iter()
+        .map(|pos| next.get(pos))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Review the moves of a game, given as the board before every move and the
+/// direction played on it. Only 4x4 boards can be rated.
+pub fn analyze_game(positions: &[(Vec<Vec<u32>>, MovementDirection)]) -> GameResult<GameAnalysis> {
+    let table = TranspositionTable::default();
+    let mut analysis = GameAnalysis {
+        moves: 0,
+        best_moves: 0,
+        blunders: Vec::new(),
+        missed_merge: None,
+        efficiency: 1.0,
+    };
+    let mut quality = 0.0;
+    for (index, (rows, played)) in positions.iter().enumerate() {
+        let move_number = index + 1;
+        let board = Board::from_rows(rows.clone())?;
+        let ratings = rate_moves(pack(&board)?, ANALYSIS_DEPTH, &table);
+
+        let merges: Vec<u32> = ratings
+            .iter()
+            .map(|&(direction, _)| largest_merge(&board, direction))
+            .collect();
+        let merged = largest_merge(&board, *played);
+        let missed = merges.iter().copied().max().unwrap_or(0);
+        if missed > merged
+            && analysis
+                .missed_merge
+                .is_none_or(|biggest| missed > biggest.tile)
+        {
+            analysis.missed_merge = Some(MissedMerge {
+                move_number,
+                tile: missed,
+            });
+        }
+
+        let Some(review) = grade(&ratings, *played) else {
+            continue;
+        };
+        analysis.moves += 1;
+        match review.grade {
+            MoveGrade::Best => analysis.best_moves += 1,
+            MoveGrade::Ok => {}
+            MoveGrade::Mistake => analysis.blunders.push(move_number),
+        }
+        quality += review.quality;
+    }
+    if analysis.moves > 0 {
+        analysis.efficiency = quality / analysis.moves as f64;
+    }
+    Ok(analysis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{ExpectimaxConfig, expectimax_move};
+    use crate::game::{Game2048, GameEngine, GameView};
+
+    /// Play a seeded game with a strategy, returning the board before every
+    /// move and the move
+    fn play(
+        moves: usize,
+        mut strategy: impl FnMut(&Game2048) -> Option<MovementDirection>,
+    ) -> Vec<(Vec<Vec<u32>>, MovementDirection)> {
+        let mut game = Game2048::with_seed(5);
+        let mut positions = Vec::new();
+        for _ in 0..moves {
+            let Some(direction) = strategy(&game) else {
+                break;
+            };
+            positions.push((game.board().to_vec(), direction));
+            game.move_in_direction(&direction);
+        }
+        positions
+    }
+
+    #[test]
+    fn test_search_moves_beat_poor_ones() {
+        let config = ExpectimaxConfig {
+            depth: ANALYSIS_DEPTH,
+            time_budget: None,
+        };
+        let good = analyze_game(&play(60, |game| expectimax_move(game, &config))).unwrap();
+        assert_eq!(good.best_moves, good.moves);
+        assert!(good.blunders.is_empty());
+        assert!(good.efficiency > 0.99);
+
+        // Moving up whenever possible, and right or down otherwise
+        let poor = analyze_game(&play(60, |game| {
+            [
+                MovementDirection::Up,
+                MovementDirection::Right,
+                MovementDirection::Down,
+            ]
+            .into_iter()
+            .find(|direction| game.clone().move_in_direction(direction))
+        }))
+        .unwrap();
+        assert!(poor.moves > 0);
+        assert!(poor.efficiency < good.efficiency);
+        assert!(poor.best_moves < poor.moves);
+    }
+
+    #[test]
+    fn test_missed_merges_and_boards_that_cannot_be_rated() {
+        let board = vec![
+            vec![64, 64, 0, 0],
+            vec![2, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+        ];
+        let analysis = analyze_game(&[(board.clone(), MovementDirection::Down)]).unwrap();
+        assert_eq!(
+            analysis.missed_merge,
+            Some(MissedMerge {
+                move_number: 1,
+                tile: 128
+            })
+        );
+        let analysis = analyze_game(&[(board, MovementDirection::Left)]).unwrap();
+        assert_eq!(analysis.missed_merge, None);
+
+        let small = vec![vec![2, 0, 0], vec![0, 0, 0], vec![0, 0, 0]];
+        assert!(analyze_game(&[(small, MovementDirection::Left)]).is_err());
+    }
+
+    #[test]
+    fn test_moves_are_graded() {
+        let table = TranspositionTable::default();
+        // Moving the 2 away from the pair of 64s in the corner ruins the row
+        let board = vec![
+            vec![64, 64, 0, 0],
+            vec![2, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+        ];
+        let best = review_move(&board, MovementDirection::Left, &table)
+            .unwrap()
+            .unwrap();
+        assert_eq!(best.grade, MoveGrade::Best);
+        assert_eq!(best.best, MovementDirection::Left);
+        assert_eq!(best.quality, 1.0);
+        let worse = review_move(&board, MovementDirection::Down, &table)
+            .unwrap()
+            .unwrap();
+        assert_ne!(worse.grade, MoveGrade::Best);
+        assert_eq!(worse.best, MovementDirection::Left);
+        assert!(worse.quality < 1.0);
+
+        // Moving up doesn't change the board, so there is nothing to grade
+        assert!(
+            review_move(&board, MovementDirection::Up, &table)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
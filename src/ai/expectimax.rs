@@ -0,0 +1,353 @@
+//! Expectimax search over packed boards.
+//!
+//! The search alternates between the player's moves, where the best one
+//! counts, and the spawned tiles, where every empty cell and both tiles count
+//! with their chance. Positions at the depth limit, or too unlikely to be
+//! worth searching further, are rated with a heuristic built from tables of
+//! row scores, like the moves of [`slide_bits`].
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::{DIRECTIONS, SearchStats, TranspositionTable, greedy_move, record_search};
+use crate::game::bitboard::{pack, slide_bits, transpose};
+use crate::game::{FOUR_CHANCE, Game2048, MovementDirection};
+
+/// Chance below which a line of play isn't searched further
+const MIN_PROBABILITY: f64 = 1e-4;
+
+/// Settings of the expectimax search, see [`expectimax_move`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectimaxConfig {
+    /// Moves to look ahead, the first one included
+    pub depth: u32,
+    /// Time after which the search settles for a shallower depth
+    pub time_budget: Option<Duration>,
+}
+
+impl Default for ExpectimaxConfig {
+    fn default() -> Self {
+        Self {
+            depth: 3,
+            time_budget: None,
+        }
+    }
+}
+
+/// Heuristic score of a packed row: many empty cells and possible merges,
+/// few large tiles and tiles ordered along the row
+fn rate_row(row: u16) -> f32 {
+    let ranks: [u32; 4] = std::array::from_fn(|j| u32::from((row >> (4 * j)) & 0xF));
+    let sum: f32 = ranks.iter().map(|&rank| (rank as f32).powf(3.5)).sum();
+    let empty = ranks.iter().filter(|&&rank| rank == 0).count() as f32;
+
+    // Equal tiles next to each other, ignoring the empty cells between them
+    let mut merges = 0.0;
+    let mut previous = 0;
+    let mut run = 0;
+    for &rank in ranks.iter().filter(|&&rank| rank != 0) {
+        if rank == previous {
+            run += 1;
+        } else {
+            if run > 0 {
+                merges += 1.0 + run as f32;
+            }
+            previous = rank;
+            run = 0;
+        }
+    }
+    if run > 0 {
+        merges += 1.0 + run as f32;
+    }
+
+    let (mut decreasing, mut increasing) = (0.0, 0.0);
+    for pair in ranks.windows(2) {
+        let (a, b) = ((pair[0] as f32).powi(4), (pair[1] as f32).powi(4));
+        if a > b {
+            decreasing += a - b;
+        } else {
+            increasing += b - a;
+        }
+    }
+
+    200_000.0 + 270.0 * empty + 700.0 * merges
+        - 47.0 * f32::min(decreasing, increasing)
+        - 11.0 * sum
+}
+
+/// Heuristic score of every possible row
+fn row_ratings() -> &'static [f32] {
+    static RATINGS: OnceLock<Vec<f32>> = OnceLock::new();
+    RATINGS.get_or_init(|| (0..=u16::MAX).map(rate_row).collect())
+}
+
+/// Heuristic score of a packed board, the scores of its rows and columns
+pub fn rate_board(board: u64) -> f32 {
+    let ratings = row_ratings();
+    let rate_rows = |board: u64| -> f32 {
+        (0..4)
+            .map(|i| ratings[usize::from((board >> (16 * i)) as u16)])
+            .sum()
+    };
+    rate_rows(board) + rate_rows(transpose(board))
+}
+
+/// Subtrees at least this many moves deep are searched on several threads
+/// with the `parallel` feature; smaller ones aren't worth sending off
+#[cfg(feature = "parallel")]
+const PARALLEL_DEPTH: u32 = 2;
+
+/// Sum of `rate` over `items`, on the rayon pool with the `parallel` feature
+/// if the subtrees are `depth` moves deep. `None` if any rating is.
+fn sum_ratings<T: Sync>(
+    items: &[T],
+    depth: u32,
+    rate: impl Fn(&T) -> Option<f32> + Sync + Send,
+) -> Option<f32> {
+    #[cfg(feature = "parallel")]
+    if depth >= PARALLEL_DEPTH {
+        return items.par_iter().map(rate).sum();
+    }
+    let _ = depth;
+    items.iter().map(rate).sum()
+}
+
+/// A search with the time it must end by and the ratings found so far
+struct Search<'a> {
+    deadline: Option<Instant>,
+    table: &'a TranspositionTable,
+}
+
+impl Search<'_> {
+    /// Expected score of a board before a tile spawns, `None` if the search
+    /// ran out of time
+    fn chance_node(&self, board: u64, depth: u32, probability: f64) -> Option<f32> {
+        if depth == 0 || probability < MIN_PROBABILITY {
+            return Some(rate_board(board));
+        }
+        // Checking the clock on the way down keeps deep searches from
+        // overrunning, without slowing down the many nodes near the leaves
+        if depth >= 2
+            && self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return None;
+        }
+        if let Some(rating) = self.table.get(board, depth) {
+            return Some(rating);
+        }
+        let empty: Vec<u32> = (0..16)
+            .filter(|cell| (board >> (4 * cell)) & 0xF == 0)
+            .collect();
+        if empty.is_empty() {
+            return self.max_node(board, depth, probability);
+        }
+        let per_cell = probability / empty.len() as f64;
+        let total = sum_ratings(&empty, depth, |&cell| {
+            let two = self.max_node(
+                board | 1 << (4 * cell),
+                depth,
+                per_cell * (1.0 - FOUR_CHANCE),
+            )?;
+            let four = self.max_node(board | 2 << (4 * cell), depth, per_cell * FOUR_CHANCE)?;
+            Some(two * (1.0 - FOUR_CHANCE) as f32 + four * FOUR_CHANCE as f32)
+        })?;
+        let rating = total / empty.len() as f32;
+        self.table.insert(board, depth, rating);
+        Some(rating)
+    }
+
+    /// Score of the best move on a board, `0` if no move is left
+    fn max_node(&self, board: u64, depth: u32, probability: f64) -> Option<f32> {
+        let mut best = 0.0;
+        for direction in &DIRECTIONS {
+            let (next, _) = slide_bits(board, direction);
+            if next != board {
+                best = f32::max(best, self.chance_node(next, depth - 1, probability)?);
+            }
+        }
+        Some(best)
+    }
+
+    /// Best first move with its expected score, `None` if the search ran
+    /// out of time
+    fn best_move(&self, board: u64, depth: u32) -> Option<Option<MovementDirection>> {
+        let moves: Vec<(MovementDirection, u64)> = DIRECTIONS
+            .iter()
+            .map(|&direction| (direction, slide_bits(board, &direction).0))
+            .filter(|&(_, next)| next != board)
+            .collect();
+        let mut rated = Vec::with_capacity(moves.len());
+        #[cfg(feature = "parallel")]
+        moves
+            .par_iter()
+            .map(|&(direction, next)| Some((direction, self.chance_node(next, depth - 1, 1.0)?)))
+            .collect_into_vec(&mut rated);
+        #[cfg(not(feature = "parallel"))]
+        rated.extend(
+            moves.iter().map(|&(direction, next)| {
+                Some((direction, self.chance_node(next, depth - 1, 1.0)?))
+            }),
+        );
+        let rated: Vec<(MovementDirection, f32)> = rated.into_iter().collect::<Option<_>>()?;
+        Some(
+            rated
+                .into_iter()
+                // max_by returns the last maximum, so reverse to prefer earlier directions
+                .rev()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(direction, _)| direction),
+        )
+    }
+}
+
+/// Pick the move with the best expected heuristic score `depth` moves
+/// ahead, assuming tiles spawn like [`StandardSpawn`].
+///
+/// With a time budget the search deepens one move at a time and plays the
+/// best move of the deepest search that finished in time, so it answers
+/// quickly enough for interactive use; a search one move deep always
+/// finishes. With the `parallel` feature the moves and the spawns of large
+/// subtrees are searched on the threads of the rayon pool.
+///
+/// The search runs on a packed 4x4 board; other boards are left to
+/// [`greedy_move`]. Returns `None` if no move changes the board.
+///
+/// [`StandardSpawn`]: crate::game::StandardSpawn
+pub fn expectimax_move(game: &Game2048, config: &ExpectimaxConfig) -> Option<MovementDirection> {
+    expectimax_move_with(game, config, &TranspositionTable::default())
+}
+
+/// [`expectimax_move`] remembering ratings in a table kept by the caller,
+/// which saves searching positions again on later moves and tells how
+/// often it helped with [`TranspositionTable::stats`]
+pub fn expectimax_move_with(
+    game: &Game2048,
+    config: &ExpectimaxConfig,
+    table: &TranspositionTable,
+) -> Option<MovementDirection> {
+    let Ok(board) = pack(game.tiles()) else {
+        return greedy_move(game);
+    };
+    let started = Instant::now();
+    let depth = config.depth.max(1);
+    let unlimited = Search {
+        deadline: None,
+        table,
+    };
+    let Some(budget) = config.time_budget else {
+        let best = unlimited.best_move(board, depth).flatten();
+        record_search(searched(started, depth, table));
+        return best;
+    };
+
+    let complete = unlimited.best_move(board, 1).flatten();
+    let search = Search {
+        deadline: Some(started + budget),
+        table,
+    };
+    let (reached, best) = (2..=depth)
+        .map_while(|depth| Some((depth, search.best_move(board, depth)?)))
+        .last()
+        .unwrap_or((1, complete));
+    record_search(searched(started, reached, table));
+    best
+}
+
+/// Expected heuristic score of every move that changes a packed board,
+/// looking `depth` moves ahead with the first one included, in the order of
+/// [`DIRECTIONS`]. Used to grade the moves of a game after it is over.
+pub fn rate_moves(
+    board: u64,
+    depth: u32,
+    table: &TranspositionTable,
+) -> Vec<(MovementDirection, f32)> {
+    let search = Search {
+        deadline: None,
+        table,
+    };
+    DIRECTIONS
+        .iter()
+        .map(|&direction| (direction, slide_bits(board, &direction).0))
+        .filter(|&(_, next)| next != board)
+        .filter_map(|(direction, next)| {
+            // Without a deadline the search always finishes
+            Some((direction, search.chance_node(next, depth.max(1) - 1, 1.0)?))
+        })
+        .collect()
+}
+
+/// Statistics of a search that reached `depth`
+fn searched(started: Instant, depth: u32, table: &TranspositionTable) -> SearchStats {
+    SearchStats {
+        strategy: "expectimax",
+        elapsed: started.elapsed(),
+        depth: Some(depth),
+        rollouts: None,
+        table: Some(table.stats()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEngine, GameView};
+
+    #[test]
+    fn test_ratings_prefer_ordered_rows() {
+        // 2 4 8 16 against 4 16 2 8, as exponents from the first column
+        assert!(rate_row(0x4321) > rate_row(0x3142));
+    }
+
+    #[test]
+    fn test_expectimax_plays_well() {
+        let mut game = Game2048::with_seed(9);
+        let config = ExpectimaxConfig {
+            depth: 2,
+            time_budget: None,
+        };
+        while game.tiles().max_tile() < 512 {
+            let direction = expectimax_move(&game, &config).expect("no move left");
+            assert!(game.move_in_direction(&direction));
+        }
+
+        let stuck = vec![
+            vec![2, 4, 2, 4],
+            vec![4, 2, 4, 2],
+            vec![2, 4, 2, 4],
+            vec![4, 2, 4, 2],
+        ];
+        let stuck = Game2048::with_board(stuck, 1);
+        assert!(stuck.game_over());
+        assert!(expectimax_move(&stuck, &config).is_none());
+    }
+
+    #[test]
+    fn test_time_budget_bounds_the_search() {
+        let game = Game2048::with_seed(2);
+        let config = ExpectimaxConfig {
+            depth: 50,
+            time_budget: Some(Duration::from_millis(50)),
+        };
+        let started = Instant::now();
+        assert!(expectimax_move(&game, &config).is_some());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_table_is_used_and_keeps_the_choice() {
+        let game = Game2048::with_seed(4);
+        let config = ExpectimaxConfig::default();
+        let table = TranspositionTable::default();
+        let direction = expectimax_move_with(&game, &config, &table);
+        let first = table.stats();
+        // The same search again finds the ratings of the first one
+        assert_eq!(expectimax_move_with(&game, &config, &table), direction);
+        assert!(table.stats().hits > first.hits);
+        assert_eq!(expectimax_move(&game, &config), direction);
+    }
+}
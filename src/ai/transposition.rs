@@ -0,0 +1,177 @@
+//! Memory of positions already rated by the expectimax search.
+//!
+//! Different orders of moves and spawns often lead to the same board, so the
+//! search keeps the ratings of the boards it has seen in a
+//! [`TranspositionTable`]. Boards are spread over the table by their Zobrist
+//! hash: every combination of a cell and a tile has a random key, and a board
+//! hashes to the XOR of the keys of its tiles.
+//!
+//! Entries are two atomics each, the board and the rating stored XORed
+//! together, so threads of a parallel search share the table without locks:
+//! an entry torn by two threads writing at once no longer matches its board
+//! and is treated as a miss.
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rand::prelude::*;
+
+/// Random key of every (cell, exponent) pair
+fn zobrist_keys() -> &'static [[u64; 16]; 16] {
+    static KEYS: OnceLock<[[u64; 16]; 16]> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        // A fixed seed keeps hashes, and so searches, the same in every run
+        let mut rng = StdRng::seed_from_u64(0x2048);
+        std::array::from_fn(|_| std::array::from_fn(|_| rng.random()))
+    })
+}
+
+/// Zobrist hash of a packed board, `0` for the empty board
+pub fn zobrist_hash(board: u64) -> u64 {
+    let keys = zobrist_keys();
+    (0..16)
+        .map(|cell| (cell, (board >> (4 * cell)) & 0xF))
+        .filter(|&(_, exponent)| exponent != 0)
+        .fold(0, |hash, (cell, exponent)| {
+            hash ^ keys[cell][exponent as usize]
+        })
+}
+
+/// How often lookups found a usable entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableStats {
+    pub lookups: u64,
+    pub hits: u64,
+}
+
+impl TableStats {
+    /// Share of the lookups that were hits, between 0 and 1
+    pub fn hit_rate(&self) -> f64 {
+        self.hits as f64 / self.lookups.max(1) as f64
+    }
+}
+
+/// One slot of the table
+#[derive(Debug, Default)]
+struct Entry {
+    /// The board XORed with `data`
+    check: AtomicU64,
+    /// Depth searched in the high half, rating in the low half
+    data: AtomicU64,
+}
+
+/// Fixed-size table of board ratings, shared by the threads of a search.
+///
+/// A slot holds the last board stored in it; a rating is reused for a
+/// search at most as deep as the one that produced it. Ratings depend on the
+/// board alone, so a table can be kept from one move to the next.
+#[derive(Debug)]
+pub struct TranspositionTable {
+    entries: Box<[Entry]>,
+    lookups: AtomicU64,
+    hits: AtomicU64,
+}
+
+impl TranspositionTable {
+    /// Slots of a table created with [`Default`]
+    pub const DEFAULT_BITS: u32 = 16;
+
+    /// Table with `2^bits` slots of 16 bytes each
+    pub fn new(bits: u32) -> Self {
+        Self {
+            entries: (0..1usize << bits).map(|_| Entry::default()).collect(),
+            lookups: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+        }
+    }
+
+    fn slot(&self, board: u64) -> &Entry {
+        // The length is a power of two, so the mask keeps the low bits
+        &self.entries[zobrist_hash(board) as usize & (self.entries.len() - 1)]
+    }
+
+    /// Rating of a board searched at least `depth` moves deep, if stored
+    pub fn get(&self, board: u64, depth: u32) -> Option<f32> {
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        let entry = self.slot(board);
+        let data = entry.data.load(Ordering::Relaxed);
+        let check = entry.check.load(Ordering::Relaxed);
+        // An empty slot has no data and only matches the empty board, which
+        // the search never rates
+        if check ^ data != board || data == 0 || (data >> 32) < u64::from(depth) {
+            return None;
+        }
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(f32::from_bits(data as u32))
+    }
+
+    /// Remember the rating of a board searched `depth` moves deep
+    pub fn insert(&self, board: u64, depth: u32, rating: f32) {
+        let data = u64::from(depth) << 32 | u64::from(rating.to_bits());
+        let entry = self.slot(board);
+        entry.check.store(board ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+
+    /// Lookups and hits since the table was created or cleared
+    pub fn stats(&self) -> TableStats {
+        TableStats {
+            lookups: self.lookups.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Forget every board and reset the statistics
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = Entry::default();
+        }
+        self.lookups = AtomicU64::new(0);
+        self.hits = AtomicU64::new(0);
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_BITS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zobrist_hash_is_the_xor_of_the_tiles() {
+        let a = 0x1;
+        let b = 0x3 << 20;
+        assert_eq!(zobrist_hash(0), 0);
+        assert_eq!(zobrist_hash(a | b), zobrist_hash(a) ^ zobrist_hash(b));
+        assert_ne!(zobrist_hash(a), zobrist_hash(0x2));
+    }
+
+    #[test]
+    fn test_table_keeps_ratings_and_counts_hits() {
+        let mut table = TranspositionTable::new(4);
+        let board = 0x1234;
+        assert_eq!(table.get(board, 1), None);
+        table.insert(board, 2, 1.5);
+        assert_eq!(table.get(board, 2), Some(1.5));
+        assert_eq!(table.get(board, 1), Some(1.5));
+        // Too shallow for a deeper search
+        assert_eq!(table.get(board, 3), None);
+        assert_eq!(table.get(board ^ 0x10, 1), None);
+        assert_eq!(
+            table.stats(),
+            TableStats {
+                lookups: 5,
+                hits: 2
+            }
+        );
+        assert_eq!(table.stats().hit_rate(), 0.4);
+
+        table.clear();
+        assert_eq!(table.get(board, 1), None);
+        assert_eq!(table.stats().hits, 0);
+    }
+}
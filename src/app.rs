@@ -3,144 +3,2810 @@
 //! This module provides the main application logic for the 2048 game,
 //! including key handling, drawing, and game state management.
 
+use std::collections::VecDeque;
+use std::fs;
 use std::io;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::clipboard::CopyToClipboard;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
 use ratatui::{
-    Terminal,
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    Frame, Terminal,
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    text::Line,
+    widgets::{
+        Axis, Block, Borders, Chart, Clear, Dataset, GraphType, Paragraph, Row, Sparkline, Table,
+        Wrap,
+    },
 };
 
-use crate::error::GameResult;
-use crate::game::{GameEngine, MovementDirection};
-use crate::ui::GameRenderer;
+use crate::achievements::{Achievement, Achievements};
+use crate::ai::{self, GameAnalysis};
+use crate::autosave::Autosave;
+use crate::cast::CastRecorder;
+use crate::config::Config;
+#[cfg(feature = "watch")]
+use crate::config::ConfigWatcher;
+use crate::cosmetics::Cosmetic;
+use crate::daily::DailyScores;
+use crate::date::Date;
+use crate::error::{GameError, GameResult, TerminalContext};
+use crate::export::{self, ExportFormat, Position, SnapshotFormat, TextSnapshot};
+use crate::game::{
+    GameEngine, GameView, MergeRule, MoveResult, MovementDirection, TileOdds, WIN_TILE,
+};
+use crate::heuristics::BoardMetrics;
+use crate::i18n;
+use crate::input::{InputSource, TerminalInput, ThreadedInput};
+use crate::keymap::{Action, Keymap, KeymapIssue, PendingChord, key_name};
+use crate::leaderboard::{Leaderboard, LeaderboardEntry, MAX_NAME_LEN};
+use crate::net::{NetSession, RemoteState};
+use crate::puzzles::{Puzzle, PuzzleProgress, PuzzleStatus};
+use crate::replay::{Ghost, Replay, ReplayStep};
+use crate::saves::{MAX_SLOT_NAME_LEN, SavedGame, Saves};
+use crate::share::ShareCode;
+use crate::spectate::{Broadcaster, GameEvent};
+use crate::stats::{self, Heatmap, HeatmapKind, PlayTime, ScoreHistory};
+use crate::terminal::{self, Output, TerminalGuard};
+use crate::tutorial::{Onboarding, Tutorial, TutorialEvent, direction_name};
+use crate::ui::ascii::to_ascii;
+use crate::ui::{GameRenderer, mini_board};
+
+mod debug;
+mod history;
+mod notifications;
+mod powerups;
+#[cfg(feature = "sandbox")]
+mod sandbox;
+mod screen;
+mod session;
+mod settings;
+mod trainer;
+mod versus;
 
-/// Duration to wait for key events in the main game loop
+use debug::DebugOverlay;
+use notifications::{Notifications, Severity};
+use powerups::PowerUps;
+use screen::{GameOverView, Screen};
+use session::PlaySession;
+use settings::settings_screen;
+use trainer::Trainer;
+use versus::LocalVersus;
+
+/// Duration to wait for key events in the main game loop while nothing is
+/// animated
 const POLL_TIMEOUT: Duration = Duration::from_millis(500);
 
+/// Time between frames while something is animated, for about 60 per second
+const FRAME_DURATION: Duration = Duration::from_millis(16);
+
 /// Duration to wait for key events in the game over screen
 const GAME_OVER_POLL_TIMEOUT: Duration = Duration::from_millis(100);
 
-/// The application state
-pub struct App<'a, G: GameEngine + Default, R: GameRenderer> {
+/// How long keys are ignored once the game is over, so moves meant for the
+/// board don't restart or quit the game
+const GAME_OVER_INPUT_DELAY: Duration = Duration::from_millis(300);
+
+/// How long tiles changed by a move stay highlighted
+pub(crate) const HIGHLIGHT_DURATION: Duration = Duration::from_millis(200);
+
+/// Duration to wait for key events while racing a network opponent
+const NET_POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Width of the network opponent's side panel
+const OPPONENT_PANEL_WIDTH: u16 = 24;
+
+/// Height of the network opponent's panel when it is stacked below the board
+const OPPONENT_PANEL_HEIGHT: u16 = 12;
+
+/// Width a rendered board needs, including its border
+const BOARD_WIDTH: u16 = 34;
+
+/// Width of the side panel next to the board
+const SIDE_PANEL_WIDTH: u16 = 30;
+
+/// Height of the score history sparkline, including its border
+const SPARKLINE_HEIGHT: u16 = 5;
+
+/// Number of entries kept in the event log
+const EVENT_LOG_SIZE: usize = 4;
+
+/// Width of the popup listing the options of a pending chord
+const CHORD_POPUP_WIDTH: u16 = 22;
+
+/// Height of the tutorial box below the board, including its border
+const TUTORIAL_HEIGHT: u16 = 5;
+
+/// Height of the prompt for the name of a save slot, including its border
+const SLOT_PROMPT_HEIGHT: u16 = 4;
+
+/// A line in the event log below the board
+struct LogEntry {
+    message: String,
+    /// Merge chains are highlighted
+    is_chain: bool,
+}
+
+/// Scores, score history and key bindings shown next to the board
+struct SidePanel {
+    stats: Vec<Line<'static>>,
+    history: Vec<u64>,
+    keys: Vec<Line<'static>>,
+}
+
+impl SidePanel {
+    fn render(&self, f: &mut Frame, area: Rect) {
+        let [stats, history, keys] = Layout::vertical([
+            Constraint::Length(self.stats.len() as u16 + 2),
+            Constraint::Length(SPARKLINE_HEIGHT),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        f.render_widget(
+            Paragraph::new(self.stats.clone()).block(Block::default().borders(Borders::ALL)),
+            stats,
+        );
+        f.render_widget(
+            Sparkline::default()
+                .block(
+                    Block::default()
+                        .title(i18n::text("panel.history"))
+                        .borders(Borders::ALL),
+                )
+                .data(&self.history),
+            history,
+        );
+        f.render_widget(
+            Paragraph::new(self.keys.clone()).block(
+                Block::default()
+                    .title(i18n::text("panel.keys"))
+                    .borders(Borders::ALL),
+            ),
+            keys,
+        );
+    }
+}
+
+/// A board without a game behind it, drawn for the undo preview and the
+/// history
+struct Snapshot {
+    board: Vec<Vec<u32>>,
+    score: u64,
+}
+
+impl GameView for Snapshot {
+    fn score(&self) -> u64 {
+        self.score
+    }
+
+    fn board(&self) -> &[Vec<u32>] {
+        &self.board
+    }
+}
+
+/// How a call to [`App::run`] ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppOutcome {
+    /// The player quit without reaching the winning tile
+    Quit,
+    /// The player started a new game; call `run` again to play it
+    Restarted,
+    /// The player quit after reaching the winning tile, with the final score
+    GameWonScore(u64),
+    /// The player pressed Ctrl+C
+    Interrupted,
+}
+
+/// An operation that can fail without ending the game and may be retried
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operation {
+    /// Re-read the config file after it changed on disk
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    ReloadConfig,
+    /// Write the leaderboard to disk
+    SaveLeaderboard,
+    /// Write the daily challenge scores to disk
+    SaveDailyScores,
+    /// Write the finished game as the new ghost replay
+    SaveGhost,
+    /// Write unlocked achievements and merge counters to disk
+    SaveAchievements,
+    /// Write the solved puzzles to disk
+    SavePuzzles,
+    /// Remember that the tutorial was seen
+    SaveOnboarding,
+    /// Write the settings changed on the settings screen to the config file
+    SaveConfig,
+    /// Save an animation of the finished game to the export directory
+    ExportReplay(ExportFormat),
+    /// Copy the board to the clipboard and save it in the snapshot formats
+    SaveSnapshot,
+    /// Save the game to the save slot with this name
+    SaveSlot(String),
+    /// Add the time played to the play time file
+    SavePlayTime,
+}
+
+impl Operation {
+    /// What failed, used in the error dialog title
+    fn failure(&self) -> &'static str {
+        i18n::text(match self {
+            Operation::ReloadConfig => "dialog.reload_config",
+            Operation::SaveLeaderboard => "dialog.save_leaderboard",
+            Operation::SaveDailyScores => "dialog.save_daily_scores",
+            Operation::SaveGhost => "dialog.save_ghost",
+            Operation::SaveAchievements => "dialog.save_achievements",
+            Operation::SavePuzzles => "dialog.save_puzzles",
+            Operation::SaveOnboarding => "dialog.save_onboarding",
+            Operation::SaveConfig => "dialog.save_config",
+            Operation::ExportReplay(_) => "dialog.export_replay",
+            Operation::SaveSnapshot => "dialog.save_snapshot",
+            Operation::SaveSlot(_) => "dialog.save_slot",
+            Operation::SavePlayTime => "dialog.save_play_time",
+        })
+    }
+}
+
+/// Dialog shown when a recoverable operation failed
+struct ErrorDialog {
+    operation: Operation,
+    error: GameError,
+    /// Whether "Retry" (as opposed to "Ignore") is selected
+    retry_selected: bool,
+}
+
+/// State of a daily challenge session
+struct DailyChallenge {
+    date: Date,
+    scores: DailyScores,
+    path: PathBuf,
+}
+
+/// Race against the replay of the best previous game on the same seed
+struct GhostDuel {
+    ghost: Option<Ghost>,
+    recording: Replay,
+    path: PathBuf,
+}
+
+impl GhostDuel {
+    /// Record a step of the player and the tile it spawned, and move the
+    /// ghost along
+    fn record(&mut self, step: ReplayStep, game: &impl GameView) {
+        self.recording.record(step, game);
+        let moves = self.recording.steps.len();
+        if let Some(ghost) = self.ghost.as_mut() {
+            ghost.advance_to(moves);
+        }
+    }
+
+    /// Whether the recording should replace the stored ghost
+    fn is_new_best(&self) -> bool {
+        self.ghost
+            .as_ref()
+            .is_none_or(|ghost| self.recording.score > ghost.final_score())
+    }
+}
+
+/// Creates a new game with the settings of a config
+type GameFactory<G> = Box<dyn Fn(&Config) -> G>;
+
+/// Captures the state of a game for the autosave and the save slots
+type SaveCapture<G> = Box<dyn Fn(&G) -> SavedGame>;
+
+/// Creates a fresh attempt at a puzzle
+type PuzzleLoader<G> = Box<dyn Fn(&Puzzle) -> GameResult<G>>;
+
+/// Puzzle collection played in puzzle mode
+struct PuzzleSession<G> {
+    puzzles: Vec<Puzzle>,
+    progress: PuzzleProgress,
+    path: PathBuf,
+    /// Index of the puzzle being played
+    current: usize,
+    load: PuzzleLoader<G>,
+}
+
+impl<G> PuzzleSession<G> {
+    fn puzzle(&self) -> &Puzzle {
+        &self.puzzles[self.current]
+    }
+}
+
+/// Network opponent in a race
+struct Opponent {
+    session: NetSession,
+    state: Option<RemoteState>,
+}
+
+/// The application state, drawing to a terminal with backend `B`
+pub struct App<G: GameEngine, R: GameRenderer, B: Backend = CrosstermBackend<io::Stdout>> {
     game: G,
     renderer: R,
-    pub terminal: Terminal<CrosstermBackend<&'a mut io::Stdout>>,
+    pub terminal: Terminal<B>,
+    input: Box<dyn InputSource>,
+    keymap: Keymap,
+    /// Settings applied last, changed on the settings screen
+    config: Config,
+    /// Key binding mistakes shown on the diagnostics screen until dismissed
+    keymap_issues: Vec<KeymapIssue>,
+    animations: bool,
+    highlight_until: Option<Instant>,
+    /// Direction and time of the last move, to drop the repeats of a held key
+    last_move: Option<(MovementDirection, Instant)>,
+    /// Whether the board before the last move is shown instead of the game
+    peeking: bool,
+    /// First key of a chord waiting for the second one
+    pending_chord: Option<PendingChord>,
+    /// Screen shown and taking the keys
+    screen: Screen,
+    /// When to drop the keys pressed but not read yet, set when the screen
+    /// changes so they don't act on the new one
+    discard_input_at: Option<Instant>,
+    notifications: Notifications,
+    error_dialog: Option<ErrorDialog>,
+    debug: DebugOverlay,
+    config_path: Option<PathBuf>,
+    leaderboard: Option<(Leaderboard, PathBuf)>,
+    daily: Option<DailyChallenge>,
+    game_factory: Option<GameFactory<G>>,
+    autosave: Option<(Autosave, SaveCapture<G>)>,
+    save_slots: Option<(Saves, SaveCapture<G>)>,
+    duel: Option<GhostDuel>,
+    opponent: Option<Opponent>,
+    /// Board of player 2 in a local versus game
+    versus: Option<LocalVersus<G>>,
+    /// Charges of the power-ups, in a game with power-ups
+    power_ups: Option<PowerUps>,
+    /// Grades of the moves played, in trainer mode
+    trainer: Option<Trainer>,
+    events: VecDeque<LogEntry>,
+    achievements: Option<(Achievements, PathBuf)>,
+    puzzles: Option<PuzzleSession<G>>,
+    /// Position sandbox games start from, once one was played
+    #[cfg(feature = "sandbox")]
+    sandbox: Option<Vec<Vec<u32>>>,
+    /// Tutorial steps shown over the game on the first launch
+    tutorial: Option<Tutorial>,
+    /// File remembering that the tutorial was seen
+    onboarding_path: Option<PathBuf>,
+    spectators: Option<Broadcaster>,
+    /// Recording of the frames drawn, stopped if writing it fails
+    recorder: Option<CastRecorder>,
+    /// Board quality after every move of the current game
+    metrics: Vec<BoardMetrics>,
+    /// Score after every move of the current game
+    score_history: ScoreHistory,
+    /// Board at the start of the current game and after every move
+    boards: Vec<Vec<Vec<u32>>>,
+    /// Board before every move of the current game and the move played on
+    /// it, for the analysis
+    played: Vec<(Vec<Vec<u32>>, MovementDirection)>,
+    /// Review of the moves of the finished game, once it was asked for
+    analysis: Option<GameAnalysis>,
+    /// Where tiles spawned and merged during the session
+    heatmap: Heatmap,
+    /// Counts the heatmap shades the board by, if it is shown
+    heatmap_shown: Option<HeatmapKind>,
+    /// Directory the game over screen exports replays to, and snapshots
+    /// are saved in
+    export_dir: Option<PathBuf>,
+    /// Formats snapshots of the board are saved in
+    snapshot_formats: Vec<Box<dyn SnapshotFormat>>,
+    /// When the current game was started
+    started_at: Instant,
+    /// Play time of the current game once it is over
+    finished_in: Option<Duration>,
+    /// Time played without a break, for the break reminders
+    session: PlaySession,
+    /// Play time over all games and the file it is kept in
+    total_play: Option<(PlayTime, PathBuf)>,
+    #[cfg(feature = "watch")]
+    config_watcher: Option<ConfigWatcher>,
+    /// Restores the terminal when the app is dropped, if the app set it up
+    terminal_guard: Option<TerminalGuard>,
+    /// Whether something shown changed since the last frame was drawn
+    dirty: bool,
+    /// When the last frame was drawn, and the play time it showed in whole
+    /// seconds
+    last_frame: Option<(Instant, u64)>,
 }
 
-impl<'a, G: GameEngine + Default, R: GameRenderer> App<'a, G, R> {
-    /// Create a new app instance
-    pub fn new(
-        game: G,
-        renderer: R,
-        terminal: Terminal<CrosstermBackend<&'a mut io::Stdout>>,
-    ) -> Self {
+impl<G: GameEngine, R: GameRenderer> App<G, R> {
+    /// Set up the terminal and create an app drawing to it.
+    ///
+    /// The terminal enters raw mode and the alternate screen, and is
+    /// restored when the app is dropped (or the program panics). Its events
+    /// are read on a background thread (see [`ThreadedInput`]):
+    ///
+    /// ```no_run
+    /// use tui_2048::prelude::*;
+    ///
+    /// let mut app = App::init(Game2048::default(), DefaultRenderer::default())?;
+    /// app.run()?;
+    /// # Ok::<(), GameError>(())
+    /// ```
+    pub fn init(game: G, renderer: R) -> GameResult<Self> {
+        Self::init_on(game, renderer, Output::Stdout, io::stdout())
+    }
+}
+
+impl<G: GameEngine, R: GameRenderer> App<G, R, CrosstermBackend<io::Stderr>> {
+    /// Like [`App::init`], but drawing to standard error, so standard output
+    /// can be piped to another program
+    pub fn init_on_stderr(game: G, renderer: R) -> GameResult<Self> {
+        Self::init_on(game, renderer, Output::Stderr, io::stderr())
+    }
+}
+
+impl<G: GameEngine, R: GameRenderer, W: io::Write> App<G, R, CrosstermBackend<W>> {
+    /// Set up the terminal on `output` and draw to it through `writer`
+    fn init_on(game: G, renderer: R, output: Output, writer: W) -> GameResult<Self> {
+        let guard = TerminalGuard::enter_on(output)?;
+        let mut terminal =
+            Terminal::new(CrosstermBackend::new(writer)).context("opening the terminal")?;
+        terminal.clear().context("clearing the screen")?;
+        let mut app = Self::new(game, renderer, terminal).with_input(ThreadedInput::default());
+        app.terminal_guard = Some(guard);
+        Ok(app)
+    }
+}
+
+impl<G: GameEngine, R: GameRenderer, B: Backend> App<G, R, B> {
+    /// Create a new app instance drawing to a terminal and reading its events
+    pub fn new(game: G, renderer: R, terminal: Terminal<B>) -> Self {
+        let boards = vec![game.board().to_vec()];
         Self {
             game,
             renderer,
             terminal,
+            input: Box::new(TerminalInput),
+            keymap: Keymap::default(),
+            config: Config::default(),
+            keymap_issues: Vec::new(),
+            animations: true,
+            highlight_until: None,
+            last_move: None,
+            peeking: false,
+            pending_chord: None,
+            screen: Screen::Playing,
+            discard_input_at: None,
+            notifications: Notifications::default(),
+            debug: DebugOverlay::default(),
+            error_dialog: None,
+            config_path: None,
+            leaderboard: None,
+            daily: None,
+            game_factory: None,
+            autosave: None,
+            save_slots: None,
+            duel: None,
+            opponent: None,
+            versus: None,
+            power_ups: None,
+            trainer: None,
+            events: VecDeque::new(),
+            achievements: None,
+            puzzles: None,
+            #[cfg(feature = "sandbox")]
+            sandbox: None,
+            tutorial: None,
+            onboarding_path: None,
+            spectators: None,
+            recorder: None,
+            metrics: Vec::new(),
+            score_history: ScoreHistory::default(),
+            boards,
+            played: Vec::new(),
+            analysis: None,
+            heatmap: Heatmap::default(),
+            heatmap_shown: None,
+            export_dir: None,
+            snapshot_formats: vec![Box::new(TextSnapshot)],
+            started_at: Instant::now(),
+            finished_in: None,
+            session: PlaySession::default(),
+            total_play: None,
+            #[cfg(feature = "watch")]
+            config_watcher: None,
+            terminal_guard: None,
+            dirty: true,
+            last_frame: None,
+        }
+    }
+
+    /// Read events from another source than the terminal, e.g. a script
+    pub fn with_input(mut self, input: impl InputSource + 'static) -> Self {
+        self.input = Box::new(input);
+        self
+    }
+
+    /// Apply the settings from a config.
+    ///
+    /// Mistakes in the key bindings are worked around and listed on a
+    /// diagnostics screen when the game starts.
+    pub fn with_config(mut self, config: &Config) -> GameResult<Self> {
+        self.apply_config(config)?;
+        Ok(self)
+    }
+
+    /// Load the leaderboard from a file and record high scores to it
+    pub fn with_leaderboard(mut self, path: PathBuf) -> GameResult<Self> {
+        self.leaderboard = Some((Leaderboard::load(&path)?, path));
+        Ok(self)
+    }
+
+    /// Load achievements from a file and record merge chains to it
+    pub fn with_achievements(mut self, path: PathBuf) -> GameResult<Self> {
+        let achievements = Achievements::load(&path)?;
+        self.renderer.set_cosmetics(achievements.cosmetics);
+        self.achievements = Some((achievements, path));
+        Ok(self)
+    }
+
+    /// Load the play time over all games from a file and add the time
+    /// played to it
+    pub fn with_play_time(mut self, path: PathBuf) -> GameResult<Self> {
+        self.total_play = Some((PlayTime::load(&path)?, path));
+        Ok(self)
+    }
+
+    /// Play a collection of puzzles, recording solved ones to a file.
+    ///
+    /// The first puzzle replaces the current game and the puzzle select
+    /// screen is opened. `load` creates an attempt at a puzzle, e.g.
+    /// [`Puzzle::game`] for [`crate::game::Game2048`].
+    pub fn with_puzzles(
+        mut self,
+        puzzles: Vec<Puzzle>,
+        path: PathBuf,
+        load: impl Fn(&Puzzle) -> GameResult<G> + 'static,
+    ) -> GameResult<Self> {
+        let Some(first) = puzzles.first() else {
+            return Err(GameError::InputError("No puzzles to play".to_string()));
+        };
+        let game = load(first)?;
+        self.reset_game(game);
+        self.puzzles = Some(PuzzleSession {
+            progress: PuzzleProgress::load(&path)?,
+            puzzles,
+            path,
+            current: 0,
+            load: Box::new(load),
+        });
+        self.screen = Screen::Puzzles(0);
+        Ok(self)
+    }
+
+    /// Offer to export finished games as animated SVGs and GIFs saved in
+    /// `dir`, and save snapshots of the board there (see [`crate::export`])
+    pub fn with_export_dir(mut self, dir: PathBuf) -> Self {
+        self.export_dir = Some(dir);
+        self
+    }
+
+    /// Also save snapshots of the board in another format; they are saved
+    /// as text by default
+    pub fn with_snapshot_format(mut self, format: impl SnapshotFormat + 'static) -> Self {
+        self.snapshot_formats.push(Box::new(format));
+        self
+    }
+
+    /// Show the tutorial over the game, remembering in a file once it was
+    /// finished or skipped (see [`Onboarding`])
+    pub fn with_tutorial(mut self, path: PathBuf) -> Self {
+        self.tutorial = Some(Tutorial::default());
+        self.onboarding_path = Some(path);
+        self
+    }
+
+    /// Create new games with a custom function instead of resetting the game
+    /// (see [`GameEngine::reset`])
+    pub fn with_game_factory(mut self, factory: impl Fn() -> G + 'static) -> Self {
+        self.game_factory = Some(Box::new(move |_: &Config| factory()));
+        self
+    }
+
+    /// Create new games from the settings of the config, so changes to the
+    /// board settings on the settings screen start a matching game
+    pub fn with_configured_game_factory(
+        mut self,
+        factory: impl Fn(&Config) -> G + 'static,
+    ) -> Self {
+        self.game_factory = Some(Box::new(factory));
+        self
+    }
+
+    /// Autosave the state captured by `capture` after every move and undo,
+    /// and remove the autosave once the game is over
+    pub fn with_autosave(
+        mut self,
+        autosave: Autosave,
+        capture: impl Fn(&G) -> SavedGame + 'static,
+    ) -> Self {
+        self.autosave = Some((autosave, Box::new(capture)));
+        self
+    }
+
+    /// Remove the autosave, e.g. once the game was saved the regular way on
+    /// quitting
+    pub fn discard_autosave(&self) {
+        if let Some((autosave, _)) = &self.autosave {
+            autosave.remove();
+        }
+    }
+
+    /// Hand the current state to the autosave
+    fn autosave(&self) {
+        if let Some((autosave, capture)) = &self.autosave {
+            autosave.store(capture(&self.game));
+        }
+    }
+
+    /// Let the player save the state captured by `capture` to named slots
+    /// in `saves`
+    pub fn with_save_slots(
+        mut self,
+        saves: Saves,
+        capture: impl Fn(&G) -> SavedGame + 'static,
+    ) -> Self {
+        self.save_slots = Some((saves, Box::new(capture)));
+        self
+    }
+
+    /// Save the changes made on the settings screen to a config file
+    pub fn with_config_path(mut self, path: PathBuf) -> Self {
+        self.config_path = Some(path);
+        self
+    }
+
+    /// Start on the settings screen
+    pub fn with_settings_open(mut self) -> Self {
+        self.show_settings();
+        self
+    }
+
+    /// Start on the sandbox screen to set up the board, editing the current
+    /// game. Restarts return to the screen with the position played from.
+    ///
+    /// The game must support [`GameEngine::set_cell`]. Sandbox games aren't
+    /// ranked.
+    #[cfg(feature = "sandbox")]
+    pub fn with_sandbox(mut self) -> Self {
+        self.screen = Screen::Sandbox(sandbox::SandboxView::default());
+        self
+    }
+
+    /// Play the daily challenge of the given date, recording best scores to a file.
+    ///
+    /// The game itself must be seeded by the caller, see [`crate::daily::daily_seed`].
+    pub fn with_daily(mut self, date: Date, path: PathBuf) -> GameResult<Self> {
+        self.daily = Some(DailyChallenge {
+            date,
+            scores: DailyScores::load(&path)?,
+            path,
+        });
+        Ok(self)
+    }
+
+    /// Race against the ghost replay stored in `dir` for the given seed.
+    ///
+    /// The game must use the same seed, and restarts should recreate it (see
+    /// [`App::with_game_factory`]). Better games replace the stored ghost.
+    pub fn with_ghost(mut self, seed: u64, dir: PathBuf) -> GameResult<Self> {
+        let path = dir.join(Replay::ghost_file(seed));
+        let mut recording = Replay::new(seed);
+        recording.begin(&self.game);
+        self.duel = Some(GhostDuel {
+            ghost: Replay::load(&path)?.map(Ghost::new),
+            recording,
+            path,
+        });
+        Ok(self)
+    }
+
+    /// Race against a network opponent playing the same seed
+    pub fn with_opponent(mut self, session: NetSession) -> Self {
+        self.opponent = Some(Opponent {
+            session,
+            state: None,
+        });
+        self
+    }
+
+    /// Play against a second player on the same keyboard. The app's game is
+    /// player 1's, played with WASD; `second` is player 2's, played with the
+    /// arrow keys. Restarting gives both players a new game.
+    pub fn with_versus(mut self, second: G) -> Self {
+        self.versus = Some(LocalVersus::new(second));
+        self
+    }
+
+    /// Charge power-ups with merges, spent with the number keys: 1 swaps two
+    /// tiles, 2 removes one and 3 shuffles the board. Restarting takes the
+    /// charges away.
+    pub fn with_power_ups(mut self) -> Self {
+        self.power_ups = Some(PowerUps::default());
+        self
+    }
+
+    /// Grade every move against the expectimax search and show the move it
+    /// would have played. The accuracy covers the whole session, restarts
+    /// included.
+    pub fn with_trainer(mut self) -> Self {
+        self.trainer = Some(Trainer::default());
+        self
+    }
+
+    /// Publish the game to spectators connecting to the broadcaster
+    pub fn with_spectators(mut self, broadcaster: Broadcaster) -> Self {
+        self.spectators = Some(broadcaster);
+        self
+    }
+
+    /// Record the frames drawn to an asciinema file at `path`, to replay or
+    /// share the session with `asciinema play`
+    pub fn with_recording(mut self, path: &Path) -> GameResult<Self> {
+        let size = self.terminal.size()?;
+        self.recorder = Some(CastRecorder::create(path, size.width, size.height)?);
+        Ok(self)
+    }
+
+    /// Drop a recording that can no longer be written, and say why
+    fn stop_recording(&mut self, error: GameError) {
+        self.recorder = None;
+        self.notify(
+            i18n::format("notify.recording_stopped", &[("error", &error)]),
+            Severity::Error,
+        );
+    }
+
+    /// Send the current state to spectators, if any are being served
+    fn publish_state(&self) {
+        if let Some(spectators) = &self.spectators {
+            spectators.publish(GameEvent::State {
+                score: self.game.score(),
+                game_over: self.game.game_over(),
+                board: self.game.board().to_vec(),
+            });
+        }
+    }
+
+    /// Tell spectators about a move or undo
+    fn publish_step(&self, step: ReplayStep, points: u64) {
+        if let Some(spectators) = &self.spectators {
+            spectators.publish(GameEvent::Move { step, points });
+        }
+    }
+
+    /// Exchange states with the network opponent
+    fn sync_opponent(&mut self) {
+        let Some(opponent) = self.opponent.as_mut() else {
+            return;
+        };
+        if !opponent.session.is_connected() {
+            return;
+        }
+        let state = RemoteState {
+            score: self.game.score(),
+            game_over: self.game.game_over(),
+            board: self.game.board().to_vec(),
+        };
+        let result = opponent.session.send_state(&state).and_then(|()| {
+            if let Some(state) = opponent.session.poll()? {
+                opponent.state = Some(state);
+                self.dirty = true;
+            }
+            Ok(())
+        });
+        if result.is_err() || !opponent.session.is_connected() {
+            self.notify(i18n::text("notify.opponent_left"), Severity::Warning);
+        }
+    }
+
+    /// How long to wait for input before checking for updates again
+    fn poll_timeout(&self, default: Duration) -> Duration {
+        if self.opponent.is_some() {
+            default.min(NET_POLL_TIMEOUT)
+        } else {
+            default
+        }
+    }
+
+    /// Replace `game` with a fresh one for a restart: made by the factory,
+    /// or the same game reset
+    fn renew(factory: Option<&GameFactory<G>>, config: &Config, game: &mut G) {
+        match factory {
+            Some(factory) => *game = factory(config),
+            None => game.reset(),
+        }
+    }
+
+    /// Start a fresh game and forget what was recorded about the previous one
+    fn restart_game(&mut self) {
+        Self::renew(self.game_factory.as_ref(), &self.config, &mut self.game);
+        self.start_over();
+    }
+
+    /// Whether the mode decides the board instead of the settings: puzzles,
+    /// daily challenges and races
+    fn has_fixed_rules(&self) -> bool {
+        self.puzzles.is_some()
+            || self.daily.is_some()
+            || self.duel.is_some()
+            || self.opponent.is_some()
+    }
+
+    /// Whether the game counts for the leaderboard and the daily scores.
+    ///
+    /// Adaptive games and games with custom tile odds are easier or harder
+    /// than regular ones, free undos beyond the usual limit make games easier,
+    /// arcade and divide games score differently, power-ups change the board,
+    /// the trainer gives moves away and versus games are played by two, so
+    /// they aren't ranked
+    fn is_ranked(&self) -> bool {
+        let ranked = !self.game.is_adaptive()
+            && self.game.tile_odds() == TileOdds::default()
+            && !self.game.has_lenient_undo()
+            && self.game.combo().is_none()
+            && self.power_ups.is_none()
+            && self.trainer.is_none()
+            && self.game.merge_rule() == MergeRule::Double
+            && self.versus.is_none();
+        #[cfg(feature = "sandbox")]
+        let ranked = ranked && self.sandbox.is_none();
+        ranked
+    }
+
+    /// Watch the config file and hot-apply changes while running
+    #[cfg(feature = "watch")]
+    pub fn watch_config(mut self, path: PathBuf) -> GameResult<Self> {
+        self.config_watcher = Some(ConfigWatcher::new(path.clone())?);
+        self.config_path = Some(path);
+        Ok(self)
+    }
+
+    /// Apply theme, key binding and animation settings, and keep the board
+    /// settings for new games
+    fn apply_config(&mut self, config: &Config) -> GameResult<()> {
+        (self.keymap, self.keymap_issues) = Keymap::diagnose(&config.keys)?;
+        self.renderer.set_theme(config.theme);
+        self.renderer.set_tile_labels(config.tile_labels);
+        self.animations = config.animations;
+        self.config = config.clone();
+        Ok(())
+    }
+
+    /// Whether a move in the direction follows the last one sooner than the
+    /// configured interval, as the repeats of a held key do
+    fn is_repeat(&self, direction: MovementDirection) -> bool {
+        let interval = Duration::from_millis(self.config.move_interval_ms);
+        self.last_move
+            .is_some_and(|(last, at)| last == direction && at.elapsed() < interval)
+    }
+
+    /// Move in a direction and highlight the changed tiles if it succeeded
+    fn make_move(&mut self, direction: MovementDirection) {
+        self.peeking = false;
+        self.last_move = Some((direction, Instant::now()));
+        let before = self.game.board().to_vec();
+        if !self.game.move_in_direction(&direction) {
+            return;
+        }
+        self.grade_move(&before, direction);
+        self.played.push((before, direction));
+        if let Some(duel) = self.duel.as_mut() {
+            duel.record(direction.into(), &self.game);
+        }
+        if self.animations {
+            self.highlight_until = Some(Instant::now() + HIGHLIGHT_DURATION);
+        }
+        self.metrics.push(BoardMetrics::of(self.game.board()));
+        self.score_history.push(self.game.score());
+        self.boards.push(self.game.board().to_vec());
+        self.heatmap.record(&self.game);
+        let points = self.game.last_move().map_or(0, |result| result.points);
+        self.publish_step(direction.into(), points);
+        self.autosave();
+        self.route_attacks(true);
+        if let Some(result) = self.game.last_move().cloned() {
+            self.log_move(direction, &result);
+            if result.cleared > 0 {
+                self.notify(
+                    i18n::format("notify.zen_cleared", &[("count", &result.cleared)]),
+                    Severity::Info,
+                );
+            }
+            self.charge_power_ups(result.merges);
+            self.record_achievements(&result);
+            self.advance_tutorial(TutorialEvent::Moved {
+                merged: result.merges > 0,
+            });
+        }
+    }
+
+    /// Add the points and merge chains of a move to the event log
+    fn log_move(&mut self, direction: MovementDirection, result: &MoveResult) {
+        if result.points == 0 {
+            return;
+        }
+        let key = match direction {
+            MovementDirection::Left | MovementDirection::Right => "log.chain_in_row",
+            MovementDirection::Up | MovementDirection::Down => "log.chain_in_column",
+        };
+        let message = i18n::format(
+            "log.move",
+            &[
+                ("direction", &direction_name(direction)),
+                ("points", &result.points),
+            ],
+        );
+        self.log(message, false);
+        for chain in &result.chains {
+            let message = i18n::format(
+                key,
+                &[("chain", &chain.name()), ("number", &(chain.line + 1))],
+            );
+            self.log(message, true);
+        }
+        if result.multiplier > 1 {
+            let message = i18n::format("log.combo", &[("multiplier", &result.multiplier)]);
+            self.log(message, true);
+        }
+    }
+
+    /// Append an entry to the event log, dropping the oldest if it is full
+    fn log(&mut self, message: String, is_chain: bool) {
+        if self.events.len() == EVENT_LOG_SIZE {
+            self.events.pop_front();
+        }
+        self.events.push_back(LogEntry { message, is_chain });
+    }
+
+    /// Count merge chains and announce newly unlocked achievements
+    fn record_achievements(&mut self, result: &MoveResult) {
+        let Some((achievements, _)) = self.achievements.as_mut() else {
+            return;
+        };
+        let available = Cosmetic::unlocked(achievements);
+        let unlocked = achievements.record(result);
+        let rewards: Vec<Cosmetic> = Cosmetic::unlocked(achievements)
+            .into_iter()
+            .filter(|cosmetic| !available.contains(cosmetic))
+            .collect();
+        if unlocked.is_empty() && rewards.is_empty() {
+            return;
+        }
+        self.attempt(Operation::SaveAchievements);
+        if self.error_dialog.is_none() {
+            let mut names: Vec<&str> = unlocked.iter().map(|a| a.name()).collect();
+            names.extend(rewards.iter().map(|cosmetic| cosmetic.name()));
+            let names = names.join(", ");
+            self.notify(
+                i18n::format("notify.unlocked", &[("names", &names)]),
+                Severity::Info,
+            );
+        }
+    }
+
+    /// Undo the last move
+    fn undo(&mut self) {
+        self.highlight_until = None;
+        self.peeking = false;
+        // Undoing would let player 1 take back moves player 2 can't
+        if self.versus.is_some() {
+            self.notify(i18n::text("notify.no_undo_in_versus"), Severity::Warning);
+            return;
+        }
+        if !self.game.can_undo() {
+            let key = match self.game.undos_left() {
+                Some(0) => "notify.no_undos_left",
+                _ => "notify.nothing_to_undo",
+            };
+            self.notify(i18n::text(key), Severity::Warning);
+            return;
+        }
+        if !self.game.undo() {
+            return;
+        }
+        self.metrics.pop();
+        self.score_history.pop();
+        self.boards.pop();
+        self.played.pop();
+        self.publish_step(ReplayStep::Undo, 0);
+        if let Some(duel) = self.duel.as_mut() {
+            duel.record(ReplayStep::Undo, &self.game);
+        }
+        self.autosave();
+        self.advance_tutorial(TutorialEvent::Undone);
+    }
+
+    /// Move the tutorial along, closing it after the last step
+    fn advance_tutorial(&mut self, event: TutorialEvent) {
+        if let Some(tutorial) = self.tutorial.as_mut()
+            && tutorial.advance(event)
+        {
+            self.finish_tutorial();
+        }
+    }
+
+    /// Close the tutorial and remember that it was seen
+    fn finish_tutorial(&mut self) {
+        self.tutorial = None;
+        if self.onboarding_path.is_some() {
+            self.attempt(Operation::SaveOnboarding);
+        }
+    }
+
+    /// Show the board before the last move until the key is released
+    fn peek_undo(&mut self) {
+        self.peeking = self.game.peek_undo().is_some();
+        if !self.peeking {
+            self.notify(i18n::text("notify.nothing_to_undo"), Severity::Warning);
+        }
+    }
+
+    /// Text of the info panel shown, if the screen is one
+    fn info_text(&self) -> Option<Paragraph<'static>> {
+        let (title, lines) = match self.screen {
+            Screen::Stats => {
+                let max_tile = self
+                    .game
+                    .board()
+                    .iter()
+                    .flatten()
+                    .copied()
+                    .max()
+                    .unwrap_or(0);
+                let rate = format!("{:.1}", self.moves_per_second());
+                let mut lines = vec![
+                    format!("{}: {}", i18n::text("panel.score"), self.game.score()),
+                    format!("{}: {}", i18n::text("panel.best_tile"), max_tile),
+                    format!("{}: {}", i18n::text("panel.moves"), self.game.moves()),
+                    i18n::format(
+                        "screen.time",
+                        &[
+                            ("time", &format_duration(self.play_time())),
+                            ("rate", &rate),
+                        ],
+                    ),
+                ];
+                if let Some(undos) = self.game.undos_left() {
+                    lines.push(i18n::format("status.undos_left", &[("undos", &undos)]));
+                }
+                lines.extend(
+                    metrics_report(self.game.board(), &self.metrics)
+                        .lines()
+                        .map(str::to_string),
+                );
+                (i18n::text("screen.statistics"), lines)
+            }
+            Screen::Leaderboard => (
+                i18n::text("screen.leaderboard"),
+                vec![i18n::text("screen.not_recorded").to_string()],
+            ),
+            Screen::Achievements => match &self.achievements {
+                Some((achievements, _)) => {
+                    let mut lines: Vec<String> = Achievement::ALL
+                        .iter()
+                        .map(|&achievement| {
+                            let mark = if achievements.is_unlocked(achievement) {
+                                "x"
+                            } else {
+                                " "
+                            };
+                            format!("[{}] {}", mark, achievement.name())
+                        })
+                        .collect();
+                    lines.push(String::new());
+                    lines.push(i18n::format(
+                        "screen.merge_chains",
+                        &[("count", &achievements.chains)],
+                    ));
+                    lines.push(i18n::format(
+                        "screen.quad_merges",
+                        &[("count", &achievements.quad_merges)],
+                    ));
+                    (i18n::text("screen.achievements"), lines)
+                }
+                None => (
+                    i18n::text("screen.achievements"),
+                    vec![i18n::text("screen.not_tracked").to_string()],
+                ),
+            },
+            Screen::Help => {
+                let lines = Action::ALL
+                    .iter()
+                    .map(|&action| {
+                        let keys: Vec<String> = self
+                            .keymap
+                            .keys(action)
+                            .iter()
+                            .map(|key| key.to_string())
+                            .collect();
+                        format!("{:<14}{}", action.name(), keys.join(", "))
+                    })
+                    .collect();
+                (i18n::text("screen.key_bindings"), lines)
+            }
+            _ => return None,
+        };
+        let lines: Vec<Line> = lines.into_iter().map(Line::from).collect();
+        Some(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .title(i18n::format("screen.press_any_key", &[("title", &title)]))
+                    .borders(Borders::ALL),
+            ),
+        )
+    }
+
+    /// Best score on record for this mode, or the current one if it is higher
+    fn best_score(&self) -> u64 {
+        let recorded = match (&self.daily, &self.leaderboard) {
+            (Some(daily), _) => daily.scores.best(daily.date),
+            (None, Some((leaderboard, _))) => {
+                leaderboard.entries().first().map(|entry| entry.score)
+            }
+            (None, None) => None,
+        };
+        recorded.unwrap_or(0).max(self.game.score())
+    }
+
+    /// Contents of the side panel next to the board
+    fn side_panel(&self) -> SidePanel {
+        let max_tile = self
+            .game
+            .board()
+            .iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0);
+        let mut stats = vec![
+            stat_line("panel.score", self.game.score()),
+            stat_line("panel.best", self.best_score()),
+            stat_line("panel.moves", self.game.moves()),
+            stat_line("panel.best_tile", max_tile),
+        ];
+        if let Some(power_ups) = &self.power_ups {
+            stats.extend(power_ups.panel_lines());
+        }
+        if let Some(trainer) = &self.trainer {
+            stats.extend(trainer.panel_lines());
+        }
+        let undo_style = if self.game.can_undo() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(Modifier::DIM)
+        };
+        let keys = Action::ALL
+            .iter()
+            .filter_map(|&action| {
+                let keys = self.keymap.keys(action);
+                let first = keys.first()?;
+                let line = Line::from(format!("{:<13}{}", action.name(), first));
+                // Greyed out while there is nothing to take back
+                Some(match action {
+                    Action::Undo | Action::PeekUndo => line.style(undo_style),
+                    _ => line,
+                })
+            })
+            .collect();
+        SidePanel {
+            stats,
+            // One score per column of the sparkline
+            history: self
+                .score_history
+                .recent(SIDE_PANEL_WIDTH as usize - 2)
+                .collect(),
+            keys,
+        }
+    }
+
+    /// The game being played
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    /// Wall-clock time spent on the current game, stopped while paused and
+    /// at game over
+    pub fn play_time(&self) -> Duration {
+        match (self.finished_in, &self.screen) {
+            (Some(time), _) => time,
+            (None, Screen::Paused { since }) => since.saturating_duration_since(self.started_at),
+            (None, _) => self.started_at.elapsed(),
+        }
+    }
+
+    /// Average moves per second of the current game
+    fn moves_per_second(&self) -> f64 {
+        let seconds = self.play_time().as_secs_f64();
+        if seconds < 1.0 {
+            0.0
+        } else {
+            self.game.moves() as f64 / seconds
+        }
+    }
+
+    /// Time left until the move highlight should be cleared
+    fn highlight_remaining(&self) -> Option<Duration> {
+        self.highlight_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Reload the config if the watched file changed
+    #[cfg(feature = "watch")]
+    fn check_config_reload(&mut self) {
+        let Some(result) = self.config_watcher.as_ref().and_then(|w| w.poll()) else {
+            return;
+        };
+        // Saving the settings screen changes the file too
+        if result.as_ref().is_ok_and(|config| *config == self.config) {
+            return;
+        }
+        match result.and_then(|config| self.apply_config(&config)) {
+            Ok(()) => self.notify(i18n::text("notify.config_reloaded"), Severity::Success),
+            Err(error) => self.show_error(Operation::ReloadConfig, error),
+        }
+    }
+
+    /// Run a recoverable operation, opening the error dialog if it fails
+    fn attempt(&mut self, operation: Operation) {
+        let result = match &operation {
+            Operation::ReloadConfig => match self.config_path.clone() {
+                Some(path) => Config::load(&path)
+                    .and_then(|config| self.apply_config(&config))
+                    .map(|()| "notify.config_reloaded"),
+                None => Ok("notify.config_reloaded"),
+            },
+            Operation::SaveLeaderboard => match self.leaderboard.as_ref() {
+                Some((leaderboard, path)) => {
+                    leaderboard.save(path).map(|()| "notify.leaderboard_saved")
+                }
+                None => Ok("notify.leaderboard_saved"),
+            },
+            Operation::SaveGhost => match self.duel.as_ref() {
+                Some(duel) => duel
+                    .recording
+                    .save(&duel.path)
+                    .map(|()| "notify.ghost_saved"),
+                None => Ok("notify.ghost_saved"),
+            },
+            Operation::SaveAchievements => match self.achievements.as_ref() {
+                Some((achievements, path)) => achievements
+                    .save(path)
+                    .map(|()| "notify.achievements_saved"),
+                None => Ok("notify.achievements_saved"),
+            },
+            Operation::SaveOnboarding => match self.onboarding_path.as_ref() {
+                Some(path) => Onboarding {
+                    tutorial_seen: true,
+                }
+                .save(path)
+                .map(|()| "notify.tutorial_finished"),
+                None => Ok("notify.tutorial_finished"),
+            },
+            Operation::SaveConfig => match self.config_path.as_ref() {
+                Some(path) => self.config.save(path).map(|()| "notify.settings_saved"),
+                None => Ok("notify.settings_applied"),
+            },
+            Operation::SavePuzzles => match self.puzzles.as_ref() {
+                Some(session) => session
+                    .progress
+                    .save(&session.path)
+                    .map(|()| "notify.puzzles_saved"),
+                None => Ok("notify.puzzles_saved"),
+            },
+            Operation::ExportReplay(format) => match self.export_replay(*format) {
+                Ok(path) => {
+                    self.notify(
+                        i18n::format("notify.replay_saved", &[("path", &path.display())]),
+                        Severity::Success,
+                    );
+                    return;
+                }
+                Err(error) => Err(error),
+            },
+            Operation::SaveSnapshot => match self.save_snapshot() {
+                Ok(message) => {
+                    self.notify(message, Severity::Success);
+                    return;
+                }
+                Err(error) => Err(error),
+            },
+            Operation::SaveSlot(name) => match self.save_to_slot(name) {
+                Ok(()) => {
+                    self.notify(
+                        i18n::format("notify.slot_saved", &[("name", name)]),
+                        Severity::Success,
+                    );
+                    return;
+                }
+                Err(error) => Err(error),
+            },
+            // Saved after every game, so only failures are worth a message
+            Operation::SavePlayTime => match self.total_play.as_ref() {
+                Some((play_time, path)) => match play_time.save(path) {
+                    Ok(()) => return,
+                    Err(error) => Err(error),
+                },
+                None => return,
+            },
+            Operation::SaveDailyScores => match self.daily.as_ref() {
+                Some(daily) => daily
+                    .scores
+                    .save(&daily.path)
+                    .map(|()| "notify.daily_saved"),
+                None => Ok("notify.daily_saved"),
+            },
+        };
+        match result {
+            Ok(message) => self.notify(i18n::text(message), Severity::Success),
+            Err(error) => self.show_error(operation, error),
+        }
+    }
+
+    /// Save an animation of the current game in the export directory and
+    /// return the path of the file
+    fn export_replay(&self, format: ExportFormat) -> GameResult<PathBuf> {
+        let Some(dir) = &self.export_dir else {
+            return Err(GameError::InputError(
+                "No directory to export to".to_string(),
+            ));
+        };
+        let bytes = export::export(&self.boards, format)?;
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "2048-{}-{}.{}",
+            Date::today(),
+            self.game.score(),
+            format.extension()
+        ));
+        fs::write(&path, bytes)?;
+        Ok(path)
+    }
+
+    /// Save the current game to the slot `name`, replacing the game in it
+    fn save_to_slot(&self, name: &str) -> GameResult<()> {
+        let Some((saves, capture)) = &self.save_slots else {
+            return Err(GameError::SaveError(
+                "No directory for save slots".to_string(),
+            ));
+        };
+        saves.save(&capture(&self.game).in_slot(name))
+    }
+
+    /// Open the prompt for the name of a save slot
+    fn show_save_slot(&mut self) {
+        match self.save_slots {
+            Some(_) => self.screen = Screen::SaveSlot(String::new()),
+            None => self.notify(i18n::text("notify.no_save_slots"), Severity::Warning),
+        }
+    }
+
+    /// Copy the board to the clipboard of the terminal, if the app runs in
+    /// one, and save it in every snapshot format in the export directory.
+    ///
+    /// Returns a message saying where the board went.
+    fn save_snapshot(&self) -> GameResult<String> {
+        let position = Position {
+            board: self.game.board().to_vec(),
+            score: self.game.score(),
+            moves: self.game.moves(),
+        };
+        // The clipboard is set with an escape sequence, which only a real
+        // terminal understands
+        let copied = self.terminal_guard.is_some();
+        if copied {
+            let mut text = TextSnapshot::text(&position);
+            if let Some(code) = ShareCode::of(&self.game) {
+                let command = format!("tui_2048 --import {}", code.encode());
+                text.push_str(&i18n::format("notify.share_code", &[("command", &command)]));
+                text.push('\n');
+            }
+            execute!(
+                terminal::output().writer(),
+                CopyToClipboard::to_clipboard_from(text)
+            )?;
+        }
+        let mut saved = Vec::new();
+        if let Some(dir) = &self.export_dir {
+            fs::create_dir_all(dir)?;
+            for format in &self.snapshot_formats {
+                let path = dir.join(format!(
+                    "2048-{}-move-{}.{}",
+                    Date::today(),
+                    position.moves,
+                    format.extension()
+                ));
+                fs::write(&path, format.render(&position)?)?;
+                saved.push(path.display().to_string());
+            }
+        }
+        Ok(match (copied, saved.is_empty()) {
+            (true, true) => i18n::text("notify.board_copied").to_string(),
+            (true, false) => i18n::format(
+                "notify.board_copied_and_saved",
+                &[("paths", &saved.join(", "))],
+            ),
+            (false, false) => i18n::format("notify.board_saved", &[("paths", &saved.join(", "))]),
+            (false, true) => i18n::text("notify.board_not_saved").to_string(),
+        })
+    }
+
+    /// Open the error dialog for a failed operation
+    fn show_error(&mut self, operation: Operation, error: GameError) {
+        log::error!("{:?} failed: {}", operation, error);
+        self.dirty = true;
+        self.error_dialog = Some(ErrorDialog {
+            operation,
+            error,
+            retry_selected: true,
+        });
+    }
+
+    /// Handle a key press while the error dialog is open
+    fn handle_dialog_key(&mut self, code: KeyCode) {
+        let Some(dialog) = self.error_dialog.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Left | KeyCode::Right | KeyCode::Tab => {
+                dialog.retry_selected = !dialog.retry_selected;
+            }
+            KeyCode::Enter if dialog.retry_selected => self.retry(),
+            KeyCode::Char('r') => self.retry(),
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('i') => {
+                // Keep the failure in sight for a while after the dialog closes
+                let message = dialog.operation.failure();
+                self.error_dialog = None;
+                self.notify(message, Severity::Error);
+            }
+            _ => {}
+        }
+    }
+
+    /// Dismiss the key binding diagnostics with Enter or Esc.
+    ///
+    /// Returns whether the diagnostics screen was open and took the key.
+    fn handle_diagnostics_key(&mut self, code: KeyCode) -> bool {
+        if self.keymap_issues.is_empty() {
+            return false;
+        }
+        if matches!(code, KeyCode::Enter | KeyCode::Esc) {
+            self.keymap_issues.clear();
+        }
+        true
+    }
+
+    /// Open the cosmetics screen, if achievements are tracked
+    fn show_cosmetics(&mut self) {
+        if self.achievements.is_some() {
+            self.screen = Screen::Cosmetics(0);
+        } else {
+            self.notify(i18n::text("notify.no_cosmetics"), Severity::Warning);
         }
     }
 
-    /// Run the application
-    pub fn run(&mut self) -> GameResult<()> {
+    /// Select a cosmetic if it was unlocked and store the choice
+    fn equip(&mut self, cosmetic: Cosmetic) {
+        let Some((achievements, _)) = self.achievements.as_mut() else {
+            return;
+        };
+        if !achievements.equip(cosmetic) {
+            let message = i18n::format("notify.locked", &[("milestone", &cosmetic.milestone())]);
+            self.notify(message, Severity::Warning);
+            return;
+        }
+        self.renderer.set_cosmetics(achievements.cosmetics);
+        self.attempt(Operation::SaveAchievements);
+    }
+
+    /// Open the puzzle select screen, if puzzles are played
+    fn show_puzzles(&mut self) {
+        match self.puzzles.as_mut() {
+            Some(session) => self.screen = Screen::Puzzles(session.current),
+            None => self.notify(i18n::text("notify.no_puzzles"), Severity::Warning),
+        }
+    }
+
+    /// Start the current puzzle over
+    fn restart_puzzle(&mut self) {
+        let Some(session) = self.puzzles.as_ref() else {
+            return;
+        };
+        match (session.load)(session.puzzle()) {
+            Ok(game) => self.reset_game(game),
+            Err(error) => self.notify(error.to_string(), Severity::Error),
+        }
+    }
+
+    /// Record a solved puzzle, or start a failed one over
+    fn check_puzzle(&mut self) {
+        let Some(session) = self.puzzles.as_mut() else {
+            return;
+        };
+        let puzzle = &session.puzzles[session.current];
+        match puzzle.status(&self.game) {
+            PuzzleStatus::Playing => {}
+            PuzzleStatus::Solved => {
+                let moves = self.game.moves();
+                let key = if moves == 1 {
+                    "notify.puzzle_solved_in_one"
+                } else {
+                    "notify.puzzle_solved"
+                };
+                let message = i18n::format(key, &[("name", &puzzle.name), ("moves", &moves)]);
+                let new_best = session.progress.record(puzzle, moves);
+                // Move on to the next puzzle, or the last one again
+                session.current = (session.current + 1).min(session.puzzles.len() - 1);
+                let next = session.current;
+                self.restart_puzzle();
+                self.screen = Screen::Puzzles(next);
+                if new_best {
+                    self.attempt(Operation::SavePuzzles);
+                }
+                if self.error_dialog.is_none() {
+                    self.notify(message, Severity::Success);
+                }
+            }
+            PuzzleStatus::Failed => {
+                let message = i18n::format(
+                    "notify.puzzle_failed",
+                    &[("name", &puzzle.name), ("goal", puzzle)],
+                );
+                self.restart_puzzle();
+                self.notify(message, Severity::Warning);
+            }
+        }
+    }
+
+    /// Close the error dialog and run its operation again
+    fn retry(&mut self) {
+        if let Some(dialog) = self.error_dialog.take() {
+            self.attempt(dialog.operation);
+        }
+    }
+
+    /// Show a notification for as long as its severity asks for
+    fn notify(&mut self, message: impl Into<String>, severity: Severity) {
+        self.notify_for(message, severity, severity.ttl());
+    }
+
+    /// Show a notification for `ttl`
+    fn notify_for(&mut self, message: impl Into<String>, severity: Severity, ttl: Duration) {
+        let message = message.into();
+        self.dirty = true;
+        match severity {
+            Severity::Info | Severity::Success => log::info!("{}", message),
+            Severity::Warning => log::warn!("{}", message),
+            Severity::Error => log::error!("{}", message),
+        }
+        self.notifications.push(message, severity, ttl);
+    }
+
+    /// Outcome for the player quitting the current game
+    fn quit_outcome(&self) -> AppOutcome {
+        if self.game.has_won() {
+            AppOutcome::GameWonScore(self.game.score())
+        } else {
+            AppOutcome::Quit
+        }
+    }
+
+    /// Run the application until the current game is quit or restarted
+    pub fn run(&mut self) -> GameResult<AppOutcome> {
+        loop {
+            let timeout = self.frame()?;
+            if let Some(at) = self.discard_input_at.take() {
+                std::thread::sleep(at.saturating_duration_since(Instant::now()));
+                self.input.discard_pending()?;
+            }
+            let Some(event) = self.input.next_event(timeout)? else {
+                continue;
+            };
+            if let Some(key) = self.key_press(event)?
+                && let Some(outcome) = self.press(key)
+            {
+                self.record_play_time();
+                return Ok(outcome);
+            }
+        }
+    }
+
+    /// Run the application like [`App::run`], awaiting the terminal events
+    /// with crossterm's `EventStream` instead of reading them with
+    /// [`App::with_input`]'s source.
+    ///
+    /// Waiting for events doesn't block the thread, so other tasks of the
+    /// runtime make progress in the meantime.
+    #[cfg(feature = "tokio")]
+    pub async fn run_async(&mut self) -> GameResult<AppOutcome> {
+        self.run_async_with(&mut crossterm::event::EventStream::new())
+            .await
+    }
+
+    /// Run the application like [`App::run_async`], awaiting the events of
+    /// another stream, e.g. to script the game.
+    ///
+    /// Events that are ready in the stream when the screen changes are
+    /// dropped like those of [`crate::input::InputSource::discard_pending`].
+    /// Fails with an input error when the stream ends.
+    #[cfg(feature = "tokio")]
+    pub async fn run_async_with<S>(&mut self, events: &mut S) -> GameResult<AppOutcome>
+    where
+        S: futures::Stream<Item = io::Result<Event>> + Unpin,
+    {
+        use futures::{FutureExt, StreamExt};
+
         loop {
-            self.draw()?;
-
-            if event::poll(POLL_TIMEOUT)? {
-                if let Event::Key(key) = event::read()? {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                        KeyCode::Left | KeyCode::Char('a') => {
-                            self.game.move_in_direction(&MovementDirection::Left);
+            let timeout = self.frame()?;
+            if let Some(at) = self.discard_input_at.take() {
+                tokio::time::sleep(at.saturating_duration_since(Instant::now())).await;
+                while let Some(Some(_)) = events.next().now_or_never() {}
+            }
+            let event = tokio::select! {
+                event = events.next() => match event {
+                    Some(event) => event?,
+                    None => {
+                        return Err(GameError::InputError("The event stream ended".to_string()));
+                    }
+                },
+                () = tokio::time::sleep(timeout) => continue,
+            };
+            if let Some(key) = self.key_press(event)?
+                && let Some(outcome) = self.press(key)
+            {
+                self.record_play_time();
+                return Ok(outcome);
+            }
+        }
+    }
+
+    /// Catch up with the config file, the opponent and spectators, then draw
+    /// the next frame if anything shown changed.
+    ///
+    /// Returns how long to wait for an event before the next frame is due.
+    fn frame(&mut self) -> GameResult<Duration> {
+        #[cfg(feature = "watch")]
+        self.check_config_reload();
+
+        self.sync_opponent();
+        self.publish_state();
+
+        let started = Instant::now();
+        let timeout = self.update();
+        // The clock ticks without anything else changing, and the debug
+        // overlay shows how long ago events arrived
+        let clock = self.play_time().as_secs();
+        let changed = self.dirty
+            || self.debug.is_shown()
+            || self.last_frame.is_none_or(|(_, shown)| shown != clock);
+        if !changed {
+            return Ok(timeout);
+        }
+        if let Some(wait) = self.frame_cap_wait() {
+            return Ok(timeout.min(wait));
+        }
+        self.draw()?;
+        self.dirty = false;
+        self.last_frame = Some((Instant::now(), clock));
+        self.debug.record_frame(started.elapsed());
+        Ok(timeout)
+    }
+
+    /// Time left until `max_fps` allows the next frame, if it doesn't yet
+    fn frame_cap_wait(&self) -> Option<Duration> {
+        if self.config.max_fps == 0 {
+            return None;
+        }
+        let (drawn_at, _) = self.last_frame?;
+        let due = drawn_at + Duration::from_secs(1) / self.config.max_fps;
+        Some(due.saturating_duration_since(Instant::now())).filter(|wait| !wait.is_zero())
+    }
+
+    /// Pass a key press to the error dialog, the diagnostics screen or the
+    /// shown screen, whichever is on top.
+    ///
+    /// Returns how the call to `run` ends if the key ended it.
+    fn press(&mut self, key: KeyEvent) -> Option<AppOutcome> {
+        if is_interrupt(&key) {
+            return Some(AppOutcome::Interrupted);
+        }
+        self.session.activity(Instant::now());
+        if key.code == KeyCode::F(12) {
+            self.debug.toggle();
+            return None;
+        }
+        // The error dialog captures all input until it is dismissed
+        if self.error_dialog.is_some() {
+            self.handle_dialog_key(key.code);
+            return None;
+        }
+        if self.handle_diagnostics_key(key.code) {
+            return None;
+        }
+        let screen = std::mem::discriminant(&self.screen);
+        let outcome = self.handle_key(key.code);
+        if std::mem::discriminant(&self.screen) != screen {
+            log::debug!("Showing the {} screen", self.screen.name());
+            if self.discard_input_at.is_none() {
+                self.discard_input_at = Some(Instant::now());
+            }
+        }
+        outcome
+    }
+
+    /// The key press of an event.
+    ///
+    /// Returns `None` for other events and after a resize, so the caller
+    /// redraws right away instead of on the next key press.
+    fn key_press(&mut self, event: Event) -> GameResult<Option<KeyEvent>> {
+        self.debug.record_event(&event);
+        match event {
+            // Releases are only reported by some terminals, and only end the preview
+            Event::Key(key) if key.kind == KeyEventKind::Release => {
+                if self.keymap.action(key.code) == Some(Action::PeekUndo) {
+                    self.peeking = false;
+                    self.dirty = true;
+                }
+                Ok(None)
+            }
+            Event::Key(key) => {
+                self.dirty = true;
+                Ok(Some(key))
+            }
+            Event::Resize(..) => {
+                self.dirty = true;
+                // Clear leftovers of the old layout before redrawing
+                self.terminal.autoresize().context("resizing the screen")?;
+                self.terminal.clear().context("clearing the screen")?;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Replace the game and forget what was recorded about the previous one
+    fn reset_game(&mut self, game: G) {
+        self.game = game;
+        self.start_over();
+    }
+
+    /// Forget what was recorded about the previous game, once the game was
+    /// replaced
+    fn start_over(&mut self) {
+        self.highlight_until = None;
+        self.peeking = false;
+        self.events.clear();
+        self.metrics.clear();
+        self.score_history.clear();
+        self.boards = vec![self.game.board().to_vec()];
+        self.played.clear();
+        self.analysis = None;
+        self.started_at = Instant::now();
+        self.finished_in = None;
+        self.screen = Screen::Playing;
+        if let Some(versus) = self.versus.as_mut() {
+            Self::renew(self.game_factory.as_ref(), &self.config, &mut versus.game);
+            versus.last_move = None;
+        }
+        if self.power_ups.is_some() {
+            self.power_ups = Some(PowerUps::default());
+        }
+        if let Some(duel) = self.duel.as_mut() {
+            duel.recording.begin(&self.game);
+            if let Some(ghost) = duel.ghost.as_mut() {
+                ghost.restart();
+            }
+        }
+    }
+
+    /// Review the moves of the finished game against the expectimax search,
+    /// once, returning whether there is a review to show
+    fn analyze(&mut self) -> bool {
+        if self.analysis.is_some() {
+            return true;
+        }
+        if self.played.is_empty() {
+            self.notify(i18n::text("analysis.no_moves"), Severity::Warning);
+            return false;
+        }
+        // The search only knows regular merges, so divide games aren't reviewed
+        if self.game.merge_rule() != MergeRule::Double {
+            self.notify(i18n::text("analysis.unsupported"), Severity::Warning);
+            return false;
+        }
+        match ai::analyze_game(&self.played) {
+            Ok(analysis) => {
+                log::info!(
+                    "Analysed {} moves: {} blunders",
+                    analysis.moves,
+                    analysis.blunders.len()
+                );
+                self.analysis = Some(analysis);
+                true
+            }
+            Err(_) => {
+                self.notify(i18n::text("analysis.unsupported"), Severity::Warning);
+                false
+            }
+        }
+    }
+
+    /// Shade the board by the spawns, then by the merges, then not at all
+    fn toggle_heatmap(&mut self) {
+        let (shown, message) = match self.heatmap_shown {
+            None => (Some(HeatmapKind::Spawns), "notify.heatmap_spawns"),
+            Some(HeatmapKind::Spawns) => (Some(HeatmapKind::Merges), "notify.heatmap_merges"),
+            Some(HeatmapKind::Merges) => (None, "notify.heatmap_off"),
+        };
+        self.heatmap_shown = shown;
+        self.notify(i18n::text(message), Severity::Info);
+    }
+
+    /// Add the time played since the last call to the play time file
+    fn record_play_time(&mut self) {
+        let played = self.session.take_played();
+        let Some((play_time, _)) = self.total_play.as_mut() else {
+            return;
+        };
+        if !played.is_zero() {
+            play_time.record(played, self.session.length());
+            self.attempt(Operation::SavePlayTime);
+        }
+    }
+
+    /// Suggest a break once the player has been at it for another
+    /// `break_reminder_mins`
+    fn remind_of_break(&mut self) {
+        let every = Duration::from_secs(self.config.break_reminder_mins * 60);
+        if let Some(played) = self.session.reminder_due(Instant::now(), every) {
+            let message = i18n::format(
+                "notify.break_reminder",
+                &[("time", &stats::hours_and_minutes(played))],
+            );
+            self.notify_for(message, Severity::Info, session::REMINDER_TTL);
+        }
+    }
+
+    /// Add the current game to the leaderboard and save it
+    fn record_high_score(&mut self, name: String) {
+        let Some((leaderboard, _)) = self.leaderboard.as_mut() else {
+            return;
+        };
+        let board = self.game.board();
+        leaderboard.insert(LeaderboardEntry {
+            name,
+            score: self.game.score(),
+            date: Date::today(),
+            max_tile: board.iter().flatten().copied().max().unwrap_or(0),
+            board_size: board.len(),
+        });
+        self.attempt(Operation::SaveLeaderboard);
+    }
+
+    /// Draw the screen shown
+    fn draw(&mut self) -> GameResult<()> {
+        match &self.screen {
+            Screen::GameOver(view) => {
+                let view = view.clone();
+                self.draw_game_over(&view)
+            }
+            _ => self.draw_playing(),
+        }
+    }
+
+    /// Draw the game board, with the panel or menu of the screen over it
+    fn draw_playing(&mut self) -> GameResult<()> {
+        let mut title = match &self.daily {
+            Some(daily) => i18n::format("title.daily", &[("date", &daily.date)]),
+            None if self.versus.is_some() => i18n::text("versus.title").to_string(),
+            None => "2048".to_string(),
+        };
+        if self.game.is_adaptive() {
+            push_tag(&mut title, i18n::text("title.adaptive"));
+        }
+        if self.game.is_zen() {
+            push_tag(&mut title, i18n::text("title.zen"));
+        }
+        if self.game.combo().is_some() {
+            push_tag(&mut title, i18n::text("title.arcade"));
+        }
+        if self.power_ups.is_some() {
+            push_tag(&mut title, i18n::text("title.power_ups"));
+        }
+        if self.trainer.is_some() {
+            push_tag(&mut title, i18n::text("title.trainer"));
+        }
+        if let Screen::PowerUp(targeting) = &self.screen {
+            title = i18n::format("title.screen", &[("screen", &targeting.prompt())]);
+        }
+        if let Screen::History(cursor) = self.screen {
+            title = i18n::format("title.screen", &[("screen", &self.history_title(cursor))]);
+        }
+        if self.game.merge_rule() == MergeRule::Halve {
+            push_tag(&mut title, i18n::text("title.divide"));
+        } else if self.game.win_tile() != WIN_TILE {
+            let target = i18n::format("title.target", &[("tile", &self.game.win_tile())]);
+            push_tag(&mut title, &target);
+        }
+        if let Some(session) = &self.puzzles {
+            let puzzle = session.puzzle();
+            let moves_left = puzzle.max_moves.saturating_sub(self.game.moves());
+            title = i18n::format(
+                "title.puzzle",
+                &[
+                    ("name", &puzzle.name),
+                    ("goal", puzzle),
+                    ("left", &moves_left),
+                ],
+            );
+        }
+        #[cfg(feature = "sandbox")]
+        match self.screen {
+            Screen::Sandbox(view) => {
+                title = i18n::format(
+                    "title.sandbox_editing",
+                    &[
+                        ("row", &(view.cursor.0 + 1)),
+                        ("column", &(view.cursor.1 + 1)),
+                    ],
+                );
+            }
+            _ if self.sandbox.is_some() => push_tag(&mut title, i18n::text("title.sandbox")),
+            _ => {}
+        }
+        let peeked = self
+            .game
+            .peek_undo()
+            .filter(|_| self.peeking)
+            .map(|(board, score)| Snapshot {
+                board: board.to_vec(),
+                score,
+            });
+        if peeked.is_some() {
+            push_tag(
+                &mut title,
+                i18n::text(if terminal::reports_key_releases() {
+                    "title.peek_release"
+                } else {
+                    "title.peek_press"
+                }),
+            );
+        }
+        let preview = peeked.or_else(|| self.history_preview());
+
+        let rate = format!("{:.1}", self.moves_per_second());
+        let clock = format!(
+            " {} ",
+            i18n::format(
+                "title.clock",
+                &[
+                    ("time", &format_duration(self.play_time())),
+                    ("rate", &rate)
+                ],
+            )
+        );
+
+        let paused = matches!(self.screen, Screen::Paused { .. });
+        if paused {
+            push_tag(&mut title, i18n::text("title.paused"));
+        }
+        let info_text = self.info_text();
+        let score_points = self.score_history.points();
+        let side_panel = self.side_panel();
+        let tiles_width = self.renderer.board_width(self.game.dims().1);
+        let cosmetics_screen = match (&self.achievements, &self.screen) {
+            (Some((achievements, _)), &Screen::Cosmetics(cursor)) => {
+                Some(cosmetics_screen(achievements, cursor))
+            }
+            _ => None,
+        };
+        let puzzle_screen = match (&self.puzzles, &self.screen) {
+            (Some(session), &Screen::Puzzles(cursor)) => {
+                Some(puzzle_screen(&session.puzzles, &session.progress, cursor))
+            }
+            _ => None,
+        };
+        let accent = self
+            .achievements
+            .as_ref()
+            .and_then(|(achievements, _)| achievements.cosmetics.accent.color())
+            .filter(|_| self.renderer.is_color());
+        let tutorial = self
+            .tutorial
+            .as_ref()
+            .map(|tutorial| tutorial.text(&self.keymap, self.game.board()));
+        let chord_popup = self
+            .pending_chord
+            .map(|chord| chord_popup(&self.keymap, chord));
+        let chord_options = self
+            .pending_chord
+            .map_or(0, |chord| self.keymap.chord_options(chord.prefix).len());
+        let ascii = self.renderer.is_ascii();
+
+        let frame = self
+            .terminal
+            .draw(|f| {
+                let size = f.area();
+                let mut block = Block::default()
+                    .title(title)
+                    .title_bottom(Line::from(clock).right_aligned())
+                    .borders(Borders::ALL);
+                if let Some(color) = accent {
+                    block = block.border_style(Style::default().fg(color));
+                }
+                let area = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(2)
+                    .constraints([Constraint::Percentage(100)].as_ref())
+                    .split(size)[0];
+                let [area, log_area] = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Min(0),
+                        Constraint::Length(EVENT_LOG_SIZE as u16 + 2),
+                    ])
+                    .areas(area);
+
+                // Player first, then player 2 or the ghost, and the network
+                // opponent
+                let ghost = self.duel.as_ref().and_then(|duel| duel.ghost.as_ref());
+                let second_boards = ghost.is_some() as u16 + self.versus.is_some() as u16;
+                let columns = board_layout(area, second_boards, self.opponent.is_some());
+                let mut columns = columns.into_iter();
+
+                let column = columns.next().unwrap_or(area);
+                let mut inner = block.inner(column);
+                f.render_widget(block, column);
+                // The side panel is left out when the board needs the space
+                if inner.width >= tiles_width + SIDE_PANEL_WIDTH {
+                    let [board, panel] = Layout::horizontal([
+                        Constraint::Min(0),
+                        Constraint::Length(SIDE_PANEL_WIDTH),
+                    ])
+                    .areas(inner);
+                    side_panel.render(f, panel);
+                    inner = board;
+                }
+                match &preview {
+                    // The board is hidden, so the pause can't be used to plan
+                    _ if paused => f.render_widget(
+                        Paragraph::new(i18n::text("screen.paused")).centered(),
+                        inner,
+                    ),
+                    Some(snapshot) => {
+                        self.renderer.render(f, inner, snapshot);
+                        f.buffer_mut().set_style(inner, Modifier::DIM);
+                    }
+                    None => self.renderer.render(f, inner, &self.game),
+                }
+
+                if let (Some(ghost), Some(column)) = (ghost, columns.next()) {
+                    let key = if ghost.finished() {
+                        "screen.ghost_finished"
+                    } else {
+                        "screen.ghost"
+                    };
+                    let ghost_title = i18n::format(key, &[("score", &ghost.final_score())]);
+                    let ghost_block = Block::default().title(ghost_title).borders(Borders::ALL);
+                    let inner = ghost_block.inner(column);
+                    f.render_widget(ghost_block, column);
+                    self.renderer.render(f, inner, ghost.game());
+                }
+
+                if let (Some(versus), Some(column)) = (&self.versus, columns.next()) {
+                    let second_block = Block::default()
+                        .title(i18n::text("versus.second"))
+                        .borders(Borders::ALL);
+                    let inner = second_block.inner(column);
+                    f.render_widget(second_block, column);
+                    self.renderer.render(f, inner, &versus.game);
+                }
+
+                if let (Some(opponent), Some(column)) = (&self.opponent, columns.next()) {
+                    f.render_widget(opponent_panel(opponent), column);
+                }
+
+                let color = self.renderer.is_color();
+                f.render_widget(event_log(&self.events, color), log_area);
+                if let Some(text) = info_text {
+                    f.render_widget(Clear, area);
+                    match (&self.screen, &self.leaderboard) {
+                        (Screen::Leaderboard, Some((leaderboard, _))) => f.render_widget(
+                            leaderboard_table(leaderboard.entries(), self.game.score()),
+                            area,
+                        ),
+                        (Screen::Stats, _) => {
+                            let [text_area, chart_area] = Layout::vertical([
+                                Constraint::Percentage(50),
+                                Constraint::Percentage(50),
+                            ])
+                            .areas(area);
+                            f.render_widget(text, text_area);
+                            f.render_widget(score_chart(&score_points), chart_area);
                         }
-                        KeyCode::Right | KeyCode::Char('d') => {
-                            self.game.move_in_direction(&MovementDirection::Right);
+                        _ => f.render_widget(text, area),
+                    }
+                }
+                if let Some(screen) = cosmetics_screen {
+                    f.render_widget(Clear, area);
+                    f.render_widget(screen, area);
+                }
+                if let Some(screen) = puzzle_screen {
+                    f.render_widget(Clear, area);
+                    f.render_widget(screen, area);
+                }
+                if let Screen::SaveSlot(name) = &self.screen {
+                    let height = SLOT_PROMPT_HEIGHT.min(area.height);
+                    let prompt_area = Rect::new(
+                        area.x,
+                        area.y,
+                        (MAX_SLOT_NAME_LEN as u16 + 12).min(area.width),
+                        height,
+                    );
+                    let text = format!(
+                        "{}\n{}",
+                        i18n::format("slot.name", &[("name", name)]),
+                        i18n::text("slot.keys")
+                    );
+                    f.render_widget(Clear, prompt_area);
+                    f.render_widget(
+                        Paragraph::new(text).block(
+                            Block::default()
+                                .title(i18n::text("slot.title"))
+                                .borders(Borders::ALL),
+                        ),
+                        prompt_area,
+                    );
+                }
+                if let Screen::Settings(view) = self.screen {
+                    f.render_widget(Clear, area);
+                    f.render_widget(
+                        settings_screen(&self.config, &self.keymap, view, area.height),
+                        area,
+                    );
+                }
+                if let Some(text) = tutorial {
+                    let height = TUTORIAL_HEIGHT.min(area.height);
+                    let tutorial_area = Rect::new(
+                        area.x,
+                        area.bottom().saturating_sub(height),
+                        area.width,
+                        height,
+                    );
+                    let mut tutorial_block = Block::default()
+                        .title(i18n::text("tutorial.title"))
+                        .borders(Borders::ALL);
+                    if let Some(color) = accent {
+                        tutorial_block = tutorial_block.border_style(Style::default().fg(color));
+                    }
+                    f.render_widget(Clear, tutorial_area);
+                    f.render_widget(
+                        Paragraph::new(text)
+                            .block(tutorial_block)
+                            .wrap(Wrap { trim: true }),
+                        tutorial_area,
+                    );
+                }
+                if let Some(popup) = chord_popup {
+                    let height = (chord_options as u16 + 2).min(area.height);
+                    let popup_area = Rect::new(
+                        area.x,
+                        area.bottom().saturating_sub(height),
+                        CHORD_POPUP_WIDTH.min(area.width),
+                        height,
+                    );
+                    f.render_widget(Clear, popup_area);
+                    f.render_widget(popup, popup_area);
+                }
+                if !self.keymap_issues.is_empty() {
+                    f.render_widget(Clear, area);
+                    f.render_widget(
+                        keymap_diagnostics(&self.keymap_issues, &self.keymap, color),
+                        area,
+                    );
+                }
+                draw_overlays(f, &self.notifications, self.error_dialog.as_ref(), color);
+                self.debug.draw(f, self.screen.name(), color);
+                if ascii {
+                    to_ascii(f.buffer_mut());
+                }
+            })
+            .context("drawing the screen")?;
+        if let Some(recorder) = self.recorder.as_mut()
+            && let Err(e) = recorder.record(frame.buffer)
+        {
+            self.stop_recording(e);
+        }
+        Ok(())
+    }
+
+    /// Draw the game over screen
+    fn draw_game_over(&mut self, view: &GameOverView) -> GameResult<()> {
+        let play_time = i18n::format(
+            "game_over.time",
+            &[
+                ("time", &format_duration(self.play_time())),
+                ("moves", &self.game.moves()),
+                ("rate", &format!("{:.1}", self.moves_per_second())),
+            ],
+        );
+        let versus_result = self.versus.as_ref().map(|versus| {
+            let mut result = i18n::format(
+                "versus.scores",
+                &[
+                    ("first", &self.game.score()),
+                    ("second", &versus.game.score()),
+                ],
+            );
+            if let Some(winner) = self.versus_winner() {
+                result.push(' ');
+                result.push_str(winner.text());
+            }
+            result
+        });
+        let ascii = self.renderer.is_ascii();
+        let ranked = self.is_ranked();
+
+        let frame = self
+            .terminal
+            .draw(|f| {
+                let size = f.area();
+                let area = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(2)
+                    .constraints([Constraint::Percentage(100)].as_ref())
+                    .split(size)[0];
+                let color = self.renderer.is_color();
+                let style = if color {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default()
+                };
+
+                let score = self.game.score();
+                match view {
+                    GameOverView::Summary => {
+                        let block = Block::default()
+                            .title(i18n::text("game_over.title"))
+                            .borders(Borders::ALL);
+                        let mut hints = format!("\n{}", i18n::text("game_over.analysis_hint"));
+                        if self.leaderboard.is_some() {
+                            hints.push('\n');
+                            hints.push_str(i18n::text("game_over.leaderboard_hint"));
                         }
-                        KeyCode::Up | KeyCode::Char('w') => {
-                            self.game.move_in_direction(&MovementDirection::Up);
+                        if self.export_dir.is_some() {
+                            hints.push('\n');
+                            hints.push_str(i18n::text("game_over.export_hint"));
                         }
-                        KeyCode::Down | KeyCode::Char('s') => {
-                            self.game.move_in_direction(&MovementDirection::Down);
+                        let mut final_score =
+                            i18n::format("game_over.final_score", &[("score", &score)]);
+                        if !ranked {
+                            final_score.push(' ');
+                            final_score.push_str(i18n::text("game_over.unranked"));
                         }
-                        KeyCode::Char('u') | KeyCode::Char('z') => {
-                            self.game.undo();
+                        let mut results = play_time.clone();
+                        if let Some(daily) = &self.daily {
+                            let best = daily.scores.best(daily.date).unwrap_or(score);
+                            results.push('\n');
+                            results.push_str(&i18n::format(
+                                "game_over.daily_best",
+                                &[("score", &best)],
+                            ));
                         }
-                        _ => {}
-                    }
-
-                    if self.game.game_over() {
-                        self.draw_game_over()?;
-
-                        // Wait for a key press before quitting
-                        loop {
-                            if event::poll(GAME_OVER_POLL_TIMEOUT)? {
-                                if let Event::Key(key) = event::read()? {
-                                    match key.code {
-                                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                                        KeyCode::Char('r') => {
-                                            // Restart the game
-                                            self.game = G::default();
-                                            break;
-                                        }
-                                        _ => {}
-                                    }
-                                }
+                        if let Some(result) = &versus_result {
+                            results.push('\n');
+                            results.push_str(result);
+                        }
+                        if let Some(state) = self.opponent.as_ref().and_then(|o| o.state.as_ref()) {
+                            results.push('\n');
+                            results.push_str(&i18n::format(
+                                "game_over.opponent",
+                                &[("score", &state.score)],
+                            ));
+                            if !state.game_over {
+                                results.push(' ');
+                                results.push_str(i18n::text("game_over.still_playing"));
                             }
                         }
+                        let game_over_text = format!(
+                            "\n{}\n{}\n{}\n{}{}",
+                            final_score,
+                            results,
+                            metrics_report(self.game.board(), &self.metrics),
+                            i18n::text("game_over.restart"),
+                            hints
+                        );
+                        let para = Paragraph::new(game_over_text).block(block).style(style);
+                        f.render_widget(para, area);
+                    }
+                    GameOverView::NameEntry(name) => {
+                        let block = Block::default()
+                            .title(i18n::text("game_over.high_score"))
+                            .borders(Borders::ALL);
+                        let text = format!(
+                            "\n{}\n\n{}\n\n{}",
+                            i18n::format("game_over.final_score", &[("score", &score)]),
+                            i18n::format("game_over.enter_name", &[("name", name)]),
+                            i18n::text("game_over.save_name")
+                        );
+                        let para = Paragraph::new(text).block(block).style(style);
+                        f.render_widget(para, area);
+                    }
+                    GameOverView::Analysis => {
+                        if let Some(analysis) = &self.analysis {
+                            f.render_widget(analysis_table(analysis), area);
+                        }
+                    }
+                    GameOverView::Leaderboard => {
+                        let entries = self
+                            .leaderboard
+                            .as_ref()
+                            .map(|(board, _)| board.entries())
+                            .unwrap_or_default();
+                        f.render_widget(leaderboard_table(entries, score), area);
                     }
                 }
+
+                if !self.keymap_issues.is_empty() {
+                    f.render_widget(Clear, area);
+                    f.render_widget(
+                        keymap_diagnostics(&self.keymap_issues, &self.keymap, color),
+                        area,
+                    );
+                }
+                draw_overlays(f, &self.notifications, self.error_dialog.as_ref(), color);
+                self.debug.draw(f, self.screen.name(), color);
+                if ascii {
+                    to_ascii(f.buffer_mut());
+                }
+            })
+            .context("drawing the screen")?;
+        if let Some(recorder) = self.recorder.as_mut()
+            && let Err(e) = recorder.record(frame.buffer)
+        {
+            self.stop_recording(e);
+        }
+        Ok(())
+    }
+}
+
+/// Split the board area between the player, the boards shown next to theirs
+/// (player 2's or the ghost's) and the network opponent, stacking them when
+/// the terminal is too narrow to show them side by side
+fn board_layout(area: Rect, second_boards: u16, opponent: bool) -> Vec<Rect> {
+    let mut width = BOARD_WIDTH * (second_boards + 1);
+    if opponent {
+        width += OPPONENT_PANEL_WIDTH;
+    }
+    let direction = if area.width >= width {
+        Direction::Horizontal
+    } else {
+        Direction::Vertical
+    };
+
+    // Every board gets the same share of the area
+    let mut constraints = vec![Constraint::Min(0)];
+    constraints
+        .extend((0..second_boards).map(|_| Constraint::Ratio(1, u32::from(second_boards) + 1)));
+    if opponent {
+        constraints.push(Constraint::Length(match direction {
+            Direction::Horizontal => OPPONENT_PANEL_WIDTH,
+            Direction::Vertical => OPPONENT_PANEL_HEIGHT,
+        }));
+    }
+    Layout::default()
+        .direction(direction)
+        .constraints(constraints)
+        .split(area)
+        .to_vec()
+}
+
+/// Line of the stats in the side panel, the values lined up after the
+/// label with the given key
+fn stat_line(label: &'static str, value: impl Into<u64>) -> Line<'static> {
+    Line::from(format!(
+        "{:<11}{}",
+        format!("{}:", i18n::text(label)),
+        value.into()
+    ))
+}
+
+/// Build the side panel with the network opponent's score and board
+fn opponent_panel(opponent: &Opponent) -> Paragraph<'static> {
+    let mut lines = vec![];
+    match &opponent.state {
+        Some(state) => {
+            lines.push(Line::from(i18n::format(
+                "opponent.score",
+                &[("score", &state.score)],
+            )));
+            lines.push(Line::from(""));
+            lines.extend(mini_board(&state.board));
+            if state.game_over {
+                lines.push(Line::from(""));
+                lines.push(Line::from(i18n::text("opponent.game_over")));
             }
         }
+        None => lines.push(Line::from(i18n::text("opponent.waiting"))),
+    }
+    if !opponent.session.is_connected() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(i18n::text("opponent.disconnected")));
     }
+    Paragraph::new(lines).block(
+        Block::default()
+            .title(i18n::text("opponent.title"))
+            .borders(Borders::ALL),
+    )
+}
 
-    /// Draw the game board
-    fn draw(&mut self) -> GameResult<()> {
-        self.terminal.draw(|f| {
-            let size = f.area();
-            let block = Block::default().title("2048").borders(Borders::ALL);
-            let area = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(2)
-                .constraints([Constraint::Percentage(100)].as_ref())
-                .split(size)[0];
-            let para = Paragraph::new(self.renderer.render(&self.game)).block(block);
-            f.render_widget(para, area);
-        })?;
-        Ok(())
+/// Build the event log panel, highlighting merge chains
+fn event_log(events: &VecDeque<LogEntry>, color: bool) -> Paragraph<'static> {
+    let lines: Vec<Line> = events
+        .iter()
+        .map(|entry| {
+            let style = match (entry.is_chain, color) {
+                (false, _) => Style::default(),
+                (true, true) => Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+                (true, false) => Style::default().add_modifier(Modifier::BOLD),
+            };
+            Line::styled(entry.message.clone(), style)
+        })
+        .collect();
+    Paragraph::new(lines).block(
+        Block::default()
+            .title(i18n::text("panel.events"))
+            .borders(Borders::ALL),
+    )
+}
+
+/// Build the diagnostics screen listing key binding mistakes and their fixes
+fn keymap_diagnostics(issues: &[KeymapIssue], keymap: &Keymap, color: bool) -> Paragraph<'static> {
+    let warning = if color {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().add_modifier(Modifier::BOLD)
+    };
+    let mut lines = vec![Line::from(i18n::text("keymap.problems"))];
+    for issue in issues {
+        let fix = issue.suggestion(keymap);
+        lines.push(Line::from(""));
+        lines.push(Line::styled(format!("- {}", issue), warning));
+        lines.push(Line::from(format!(
+            "  {}",
+            i18n::format("keymap.fix", &[("fix", &fix)])
+        )));
     }
+    lines.push(Line::from(""));
+    lines.push(Line::from(i18n::text("keymap.continue")));
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(i18n::text("keymap.title"))
+                .borders(Borders::ALL),
+        )
+        .wrap(Wrap { trim: false })
+}
 
-    /// Draw the game over screen
-    fn draw_game_over(&mut self) -> GameResult<()> {
-        self.terminal.draw(|f| {
-            let size = f.area();
-            let block = Block::default().title("Game Over!").borders(Borders::ALL);
-            let area = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(2)
-                .constraints([Constraint::Percentage(100)].as_ref())
-                .split(size)[0];
-
-            let score = self.game.score();
-            let game_over_text = format!(
-                "\nFinal Score: {}\n\nPress 'r' to restart or 'q' to quit",
-                score
-            );
+/// List of the cosmetics with their milestones, the cursor row reversed
+fn cosmetics_screen(achievements: &Achievements, cursor: usize) -> Paragraph<'static> {
+    let lines: Vec<Line> = Cosmetic::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &cosmetic)| {
+            let mark = if achievements.cosmetics.is_selected(cosmetic) {
+                "*"
+            } else {
+                " "
+            };
+            let milestone = cosmetic.milestone();
+            let text = if milestone.is_reached(achievements) {
+                format!("{} {}", mark, cosmetic.name())
+            } else {
+                let locked = i18n::format("screen.locked", &[("milestone", &milestone)]);
+                format!("{} {:<16}{}", mark, cosmetic.name(), locked)
+            };
+            let style = if i == cursor {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::styled(text, style)
+        })
+        .collect();
+    Paragraph::new(lines).block(
+        Block::default()
+            .title(i18n::text("screen.cosmetics"))
+            .borders(Borders::ALL),
+    )
+}
 
-            let para =
-                Paragraph::new(game_over_text)
-                    .block(block)
-                    .style(if self.renderer.is_color() {
-                        Style::default().fg(Color::Red)
-                    } else {
-                        Style::default()
-                    });
-            f.render_widget(para, area);
-        })?;
-        Ok(())
+/// List of the puzzles with their goals and best solutions, the cursor row
+/// reversed
+fn puzzle_screen(
+    puzzles: &[Puzzle],
+    progress: &PuzzleProgress,
+    cursor: usize,
+) -> Paragraph<'static> {
+    let lines: Vec<Line> = puzzles
+        .iter()
+        .enumerate()
+        .map(|(i, puzzle)| {
+            let solved = match progress.best(puzzle) {
+                Some(moves) => i18n::format("screen.solved_in", &[("moves", &moves)]),
+                None => String::new(),
+            };
+            let text = format!("{:<20}{:<24}{}", puzzle.name, puzzle.to_string(), solved);
+            let style = if i == cursor {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::styled(text, style)
+        })
+        .collect();
+    Paragraph::new(lines).block(
+        Block::default()
+            .title(i18n::text("screen.puzzles"))
+            .borders(Borders::ALL),
+    )
+}
+
+/// Line chart of the score after each move
+fn score_chart(points: &[(f64, f64)]) -> Chart<'_> {
+    let first = points.first().map_or(0.0, |&(number, _)| number);
+    let last = points
+        .last()
+        .map_or(0.0, |&(number, _)| number)
+        .max(first + 1.0);
+    let top = points
+        .iter()
+        .map(|&(_, score)| score)
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .data(points);
+    Chart::new(vec![dataset])
+        .block(
+            Block::default()
+                .title(i18n::text("screen.score_chart"))
+                .borders(Borders::ALL),
+        )
+        .x_axis(
+            Axis::default()
+                .bounds([first, last])
+                .labels([format!("{}", first), format!("{}", last)]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, top])
+                .labels(["0".to_string(), format!("{}", top)]),
+        )
+}
+
+/// Which-key style popup listing the ways to complete a pending chord
+fn chord_popup(keymap: &Keymap, chord: PendingChord) -> Paragraph<'static> {
+    let lines: Vec<Line> = keymap
+        .chord_options(chord.prefix)
+        .into_iter()
+        .map(|(key, action)| Line::from(format!("{:<6}{}", key_name(key), action.name())))
+        .collect();
+    Paragraph::new(lines).block(
+        Block::default()
+            .title(format!("{} ...", key_name(chord.prefix)))
+            .borders(Borders::ALL),
+    )
+}
+
+/// Add a part to a window title, after a dash
+fn push_tag(title: &mut String, tag: &str) {
+    title.push_str(" - ");
+    title.push_str(tag);
+}
+
+/// Format a play time as m:ss, or h:mm:ss from an hour on
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Describe the final board quality and the average over the game
+fn metrics_report(board: &[Vec<u32>], history: &[BoardMetrics]) -> String {
+    let last = BoardMetrics::of(board);
+    let mut report = format!(
+        "\n{}",
+        i18n::format(
+            "game_over.metrics",
+            &[
+                ("monotonicity", &last.monotonicity),
+                ("fragmentation", &last.fragmentation)
+            ],
+        )
+    );
+    if let Some(average) = BoardMetrics::average(history) {
+        report.push('\n');
+        report.push_str(&i18n::format(
+            "game_over.average",
+            &[
+                ("monotonicity", &average.monotonicity),
+                ("fragmentation", &average.fragmentation),
+                ("empty", &average.empty),
+            ],
+        ));
+    }
+    report.push('\n');
+    report
+}
+
+/// Check for Ctrl+C, which raw mode delivers as a key event instead of a signal
+fn is_interrupt(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Build the leaderboard table, highlighting rows with the given score
+fn leaderboard_table(entries: &[LeaderboardEntry], score: u64) -> Table<'static> {
+    let header = Row::new([
+        "#",
+        i18n::text("leaderboard.name"),
+        i18n::text("leaderboard.score"),
+        i18n::text("leaderboard.best_tile"),
+        i18n::text("leaderboard.board"),
+        i18n::text("leaderboard.date"),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = entries.iter().enumerate().map(|(i, entry)| {
+        let row = Row::new([
+            (i + 1).to_string(),
+            entry.name.clone(),
+            entry.score.to_string(),
+            entry.max_tile.to_string(),
+            format!("{0}x{0}", entry.board_size),
+            entry.date.to_string(),
+        ]);
+        if entry.score == score {
+            row.style(Style::default().add_modifier(Modifier::REVERSED))
+        } else {
+            row
+        }
+    });
+    let widths = [
+        Constraint::Length(3),
+        Constraint::Length(MAX_NAME_LEN as u16),
+        Constraint::Length(8),
+        Constraint::Length(9),
+        Constraint::Length(6),
+        Constraint::Length(10),
+    ];
+    Table::new(rows, widths).header(header).block(
+        Block::default()
+            .title(i18n::format(
+                "screen.press_any_key",
+                &[("title", &i18n::text("screen.leaderboard"))],
+            ))
+            .borders(Borders::ALL),
+    )
+}
+
+/// Most blunders listed by their move numbers
+const BLUNDERS_LISTED: usize = 8;
+
+/// Table of the post-game analysis
+fn analysis_table(analysis: &GameAnalysis) -> Table<'static> {
+    let percent = |part: usize| (part * 100).checked_div(analysis.moves).unwrap_or(0);
+    let mut blunders = analysis.blunders.len().to_string();
+    if !analysis.blunders.is_empty() {
+        let mut numbers: Vec<String> = analysis
+            .blunders
+            .iter()
+            .take(BLUNDERS_LISTED)
+            .map(|number| format!("#{}", number))
+            .collect();
+        if analysis.blunders.len() > BLUNDERS_LISTED {
+            numbers.push("…".to_string());
+        }
+        blunders.push_str(&format!(" ({})", numbers.join(" ")));
+    }
+    let missed_merge = match analysis.missed_merge {
+        Some(missed) => i18n::format(
+            "analysis.missed_merge_at",
+            &[("tile", &missed.tile), ("move", &missed.move_number)],
+        ),
+        None => i18n::text("analysis.none").to_string(),
+    };
+    let rows = [
+        (i18n::text("analysis.moves"), analysis.moves.to_string()),
+        (
+            i18n::text("analysis.best_moves"),
+            format!(
+                "{} ({}%)",
+                analysis.best_moves,
+                percent(analysis.best_moves)
+            ),
+        ),
+        (i18n::text("analysis.blunders"), blunders),
+        (i18n::text("analysis.missed_merge"), missed_merge),
+        (
+            i18n::text("analysis.efficiency"),
+            format!("{:.0}%", analysis.efficiency * 100.0),
+        ),
+    ]
+    .map(|(label, value)| Row::new([label.to_string(), value]));
+    let widths = [Constraint::Length(32), Constraint::Min(0)];
+    Table::new(rows, widths).block(
+        Block::default()
+            .title(i18n::format(
+                "screen.press_any_key",
+                &[("title", &i18n::text("analysis.title"))],
+            ))
+            .borders(Borders::ALL),
+    )
+}
+
+/// Draw the notifications and error dialog on top of the current screen
+fn draw_overlays(
+    f: &mut Frame,
+    notifications: &Notifications,
+    dialog: Option<&ErrorDialog>,
+    color: bool,
+) {
+    let size = f.area();
+    notifications.draw(f, color);
+
+    if let Some(dialog) = dialog {
+        let width = 50.min(size.width);
+        let height = 8.min(size.height);
+        let dialog_area = Rect::new(
+            (size.width - width) / 2,
+            (size.height - height) / 2,
+            width,
+            height,
+        );
+        let (retry, ignore) = (i18n::text("dialog.retry"), i18n::text("dialog.ignore"));
+        let (retry, ignore) = if dialog.retry_selected {
+            (format!("[ {} ]", retry), format!("  {}  ", ignore))
+        } else {
+            (format!("  {}  ", retry), format!("[ {} ]", ignore))
+        };
+        let text = format!(
+            "{}\n\n{}    {}\n\n{}",
+            dialog.error,
+            retry,
+            ignore,
+            i18n::text("dialog.keys")
+        );
+        let title = dialog.operation.failure();
+        let para = Paragraph::new(text)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .wrap(Wrap { trim: true })
+            .style(if color {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            });
+        f.render_widget(Clear, dialog_area);
+        f.render_widget(para, dialog_area);
     }
 }
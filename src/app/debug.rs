@@ -0,0 +1,142 @@
+//! Debug overlay, toggled with F12.
+//!
+//! Shows how long the last frames took to draw, the last terminal events and
+//! what the last AI search did, in the top left corner on top of whatever
+//! screen is shown. Frames and events are recorded while the overlay is
+//! hidden too, so it has something to show as soon as it is opened.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyEventKind};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::ai;
+
+/// Frames the times are kept of
+const FRAMES_KEPT: usize = 60;
+
+/// Events listed on the overlay
+const EVENTS_KEPT: usize = 8;
+
+/// Width of the overlay, including its border
+const WIDTH: u16 = 48;
+
+/// Frame times, recent events and whether the overlay is shown
+#[derive(Debug, Default)]
+pub(super) struct DebugOverlay {
+    shown: bool,
+    /// Time to update and draw the last frames, oldest first
+    frame_times: VecDeque<Duration>,
+    /// The last events with the time they arrived, oldest first
+    events: VecDeque<(Instant, String)>,
+}
+
+impl DebugOverlay {
+    /// Show the overlay if it is hidden and hide it otherwise
+    pub(super) fn toggle(&mut self) {
+        self.shown = !self.shown;
+        log::debug!("Debug overlay {}", if self.shown { "on" } else { "off" });
+    }
+
+    /// Whether the overlay is shown
+    pub(super) fn is_shown(&self) -> bool {
+        self.shown
+    }
+
+    /// Remember how long a frame took
+    pub(super) fn record_frame(&mut self, time: Duration) {
+        if self.frame_times.len() == FRAMES_KEPT {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(time);
+    }
+
+    /// Remember a terminal event
+    pub(super) fn record_event(&mut self, event: &Event) {
+        let description = describe(event);
+        log::trace!("Event: {}", description);
+        if self.events.len() == EVENTS_KEPT {
+            self.events.pop_front();
+        }
+        self.events.push_back((Instant::now(), description));
+    }
+
+    /// Lines of the overlay, for the screen being `screen`
+    fn lines(&self, screen: &str) -> Vec<Line<'static>> {
+        let millis = |time: Duration| time.as_secs_f64() * 1000.0;
+        let last = self.frame_times.back().copied().unwrap_or_default();
+        let max = self.frame_times.iter().max().copied().unwrap_or_default();
+        let total: Duration = self.frame_times.iter().sum();
+        let average = total / self.frame_times.len().max(1) as u32;
+        let search = match ai::last_search() {
+            Some(stats) => stats.to_string(),
+            None => "no search yet".to_string(),
+        };
+        let mut lines = vec![
+            Line::from(format!(
+                "Frame {:.1} ms, avg {:.1}, max {:.1}",
+                millis(last),
+                millis(average),
+                millis(max)
+            )),
+            Line::from(format!("Screen {}", screen)),
+            Line::from(format!("AI {}", search)),
+            Line::from("Events:"),
+        ];
+        let now = Instant::now();
+        lines.extend(self.events.iter().rev().map(|(at, description)| {
+            let ago = now.saturating_duration_since(*at).as_secs_f64();
+            Line::from(format!("{:>6.1}s ago  {}", ago, description))
+        }));
+        lines
+    }
+
+    /// Draw the overlay in the top left corner, if it is shown
+    pub(super) fn draw(&self, f: &mut Frame, screen: &str, color: bool) {
+        if !self.shown {
+            return;
+        }
+        let lines = self.lines(screen);
+        let area = Rect::new(0, 0, WIDTH, lines.len() as u16 + 2).intersection(f.area());
+        let style = if color {
+            Style::default().fg(Color::Magenta)
+        } else {
+            Style::default()
+        };
+        let overlay = Paragraph::new(lines)
+            .block(Block::default().title("Debug (F12)").borders(Borders::ALL))
+            .style(style);
+        f.render_widget(Clear, area);
+        f.render_widget(overlay, area);
+    }
+}
+
+/// Short description of an event, like `Key Left` or `Resize 80x24`
+fn describe(event: &Event) -> String {
+    match event {
+        Event::Key(key) => {
+            let kind = match key.kind {
+                KeyEventKind::Press => "",
+                KeyEventKind::Repeat => " (repeat)",
+                KeyEventKind::Release => " (release)",
+            };
+            if key.modifiers.is_empty() {
+                format!("Key {}{}", key.code, kind)
+            } else {
+                format!("Key {}+{}{}", key.modifiers, key.code, kind)
+            }
+        }
+        Event::Resize(width, height) => format!("Resize {}x{}", width, height),
+        Event::Mouse(mouse) => format!("Mouse {:?}", mouse.kind),
+        Event::Paste(text) => format!("Paste of {} characters", text.chars().count()),
+        Event::FocusGained => "Focus gained".to_string(),
+        Event::FocusLost => "Focus lost".to_string(),
+    }
+}
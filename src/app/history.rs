@@ -0,0 +1,106 @@
+//! History screen, browsing the past boards of the game.
+//!
+//! The engine keeps the board and score before every move, further back
+//! than undo reaches. The left and right keys step through them with the
+//! current board as the last one, and Enter plays on from the board shown,
+//! dropping the later moves at the cost of one undo each.
+
+use crossterm::event::KeyCode;
+use ratatui::backend::Backend;
+
+use super::notifications::Severity;
+use super::{App, Screen, Snapshot};
+use crate::game::GameEngine;
+use crate::i18n;
+use crate::keymap::Action;
+use crate::ui::GameRenderer;
+
+impl<G: GameEngine, R: GameRenderer, B: Backend> App<G, R, B> {
+    /// Open the history at the current board
+    pub(super) fn show_history(&mut self) {
+        let last = self.game.past_states();
+        if last == 0 {
+            self.notify(i18n::text("history.empty"), Severity::Warning);
+            return;
+        }
+        self.peeking = false;
+        self.screen = Screen::History(last);
+    }
+
+    /// Step through the boards with the left and right keys, play on from
+    /// the board shown with Enter and go back to the current board with Esc
+    /// or the quit key
+    pub(super) fn handle_history_key(&mut self, code: KeyCode, cursor: usize) {
+        let last = self.game.past_states();
+        self.screen = match (code, self.keymap.action(code)) {
+            (KeyCode::Esc, _) | (_, Some(Action::Quit)) => Screen::Playing,
+            (KeyCode::Left, _) | (_, Some(Action::MoveLeft)) => {
+                Screen::History(cursor.saturating_sub(1))
+            }
+            (KeyCode::Right, _) | (_, Some(Action::MoveRight)) => {
+                Screen::History((cursor + 1).min(last))
+            }
+            (KeyCode::Home, _) => Screen::History(0),
+            (KeyCode::End, _) => Screen::History(last),
+            (KeyCode::Enter, _) if cursor < last => {
+                self.screen = Screen::Playing;
+                self.branch(cursor);
+                return;
+            }
+            (KeyCode::Enter, _) => Screen::Playing,
+            _ => Screen::History(cursor),
+        };
+    }
+
+    /// Board shown by the history screen, `None` for the current board
+    pub(super) fn history_preview(&self) -> Option<Snapshot> {
+        let Screen::History(cursor) = self.screen else {
+            return None;
+        };
+        self.game.past_state(cursor).map(|(board, score)| Snapshot {
+            board: board.to_vec(),
+            score,
+        })
+    }
+
+    /// What the history screen shows, for the title
+    pub(super) fn history_title(&self, cursor: usize) -> String {
+        let last = self.game.past_states();
+        // The engine may keep fewer states than moves, e.g. in resumed games
+        let first_move = (self.game.moves() as usize).saturating_sub(last);
+        i18n::format(
+            "history.title",
+            &[
+                ("move", &(first_move + cursor)),
+                ("moves", &self.game.moves()),
+            ],
+        )
+    }
+
+    /// Play on from past state `index`, forgetting what was recorded about
+    /// the moves dropped
+    fn branch(&mut self, index: usize) {
+        // The other player, the ghost and the opponent can't go back with us
+        if self.versus.is_some() || self.duel.is_some() || self.opponent.is_some() {
+            self.notify(i18n::text("history.no_branch"), Severity::Warning);
+            return;
+        }
+        let dropped = self.game.past_states() - index;
+        if let Err(error) = self.game.branch(index) {
+            self.notify(error.to_string(), Severity::Warning);
+            return;
+        }
+        for _ in 0..dropped {
+            self.metrics.pop();
+            self.score_history.pop();
+            self.boards.pop();
+            self.played.pop();
+        }
+        self.highlight_until = None;
+        self.autosave();
+        self.log(
+            i18n::format("history.branched", &[("move", &self.game.moves())]),
+            false,
+        );
+    }
+}
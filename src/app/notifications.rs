@@ -0,0 +1,128 @@
+//! Notifications popping up in the corner of the screen.
+//!
+//! Messages such as unlocked achievements, saved files and failures are
+//! queued with a severity and a time to live. The newest few are stacked in
+//! the bottom right corner on top of whatever screen is shown, and each one
+//! disappears once its time is up.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Most notifications shown at once; older ones make room for new ones
+const MAX_SHOWN: usize = 3;
+
+/// How important a notification is, which sets its color and how long it stays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Severity {
+    /// Something happened, e.g. an achievement was unlocked
+    Info,
+    /// Something the player asked for worked, e.g. a file was saved
+    Success,
+    /// Something the player asked for isn't possible right now
+    Warning,
+    /// Something failed, e.g. a file could not be written
+    Error,
+}
+
+impl Severity {
+    /// How long a notification stays on screen unless told otherwise;
+    /// failures stay longer so they aren't missed
+    pub(super) fn ttl(self) -> Duration {
+        match self {
+            Severity::Info | Severity::Success | Severity::Warning => Duration::from_secs(3),
+            Severity::Error => Duration::from_secs(6),
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Severity::Info => Color::Cyan,
+            Severity::Success => Color::Green,
+            Severity::Warning => Color::Yellow,
+            Severity::Error => Color::Red,
+        }
+    }
+}
+
+/// A message in the queue
+struct Notification {
+    message: String,
+    severity: Severity,
+    expires_at: Instant,
+}
+
+/// Queue of the notifications on screen, oldest first
+#[derive(Default)]
+pub(super) struct Notifications {
+    queue: VecDeque<Notification>,
+}
+
+impl Notifications {
+    /// Show a message for `ttl`. A message that is already shown is moved to
+    /// the front and kept longer instead of being stacked twice.
+    pub(super) fn push(&mut self, message: String, severity: Severity, ttl: Duration) {
+        self.queue.retain(|shown| shown.message != message);
+        self.queue.push_back(Notification {
+            message,
+            severity,
+            expires_at: Instant::now() + ttl,
+        });
+        if self.queue.len() > MAX_SHOWN {
+            self.queue.pop_front();
+        }
+    }
+
+    /// Remove the notifications whose time is up, returning whether there
+    /// were any
+    pub(super) fn expire(&mut self) -> bool {
+        let now = Instant::now();
+        let shown = self.queue.len();
+        self.queue
+            .retain(|notification| notification.expires_at > now);
+        self.queue.len() != shown
+    }
+
+    /// Time until the next notification disappears, if any is shown
+    pub(super) fn next_expiry(&self) -> Option<Duration> {
+        self.queue
+            .iter()
+            .map(|notification| {
+                notification
+                    .expires_at
+                    .saturating_duration_since(Instant::now())
+            })
+            .min()
+    }
+
+    /// Draw the notifications stacked in the bottom right corner, the newest
+    /// at the bottom
+    pub(super) fn draw(&self, f: &mut Frame, color: bool) {
+        let size = f.area();
+        let mut bottom = size.height;
+        for notification in self.queue.iter().rev() {
+            if bottom < 3 {
+                break;
+            }
+            let width = (notification.message.chars().count() as u16 + 4).min(size.width);
+            let area = Rect::new(size.width - width, bottom - 3, width, 3).intersection(size);
+            let style = if color {
+                Style::default().fg(notification.severity.color())
+            } else {
+                Style::default()
+            };
+            let popup = Paragraph::new(notification.message.as_str())
+                .block(Block::default().borders(Borders::ALL))
+                .style(style);
+            f.render_widget(Clear, area);
+            f.render_widget(popup, area);
+            bottom -= 3;
+        }
+    }
+}
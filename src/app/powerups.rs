@@ -0,0 +1,251 @@
+//! Power-ups, abilities charged by merging tiles.
+//!
+//! Every merge fills the meter of each power-up; once a meter holds the
+//! merges its power-up costs, the power-up gains a charge, up to
+//! [`MAX_CHARGES`]. The number keys spend a charge: 1 swaps two tiles and 2
+//! removes one, both picked with a cursor on the board, and 3 shuffles the
+//! board right away.
+
+use crossterm::event::KeyCode;
+use ratatui::backend::Backend;
+use ratatui::text::Line;
+
+use super::notifications::Severity;
+use super::{App, Screen};
+use crate::error::GameResult;
+use crate::game::GameEngine;
+use crate::i18n;
+use crate::keymap::Action;
+use crate::ui::GameRenderer;
+
+/// Charges a power-up can hold
+pub(super) const MAX_CHARGES: u32 = 3;
+
+/// An ability bought with merges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PowerUp {
+    /// Swap the tiles of two cells
+    Swap,
+    /// Remove a tile
+    Remove,
+    /// Move every tile to a random cell
+    Shuffle,
+}
+
+impl PowerUp {
+    /// All power-ups, in the order of their keys
+    const ALL: [PowerUp; 3] = [PowerUp::Swap, PowerUp::Remove, PowerUp::Shuffle];
+
+    /// Merges that charge the power-up once
+    fn cost(self) -> u32 {
+        match self {
+            PowerUp::Swap => 10,
+            PowerUp::Remove => 20,
+            PowerUp::Shuffle => 30,
+        }
+    }
+
+    /// Power-up spent with a number key
+    pub(super) fn from_key(code: KeyCode) -> Option<Self> {
+        match code {
+            KeyCode::Char('1') => Some(PowerUp::Swap),
+            KeyCode::Char('2') => Some(PowerUp::Remove),
+            KeyCode::Char('3') => Some(PowerUp::Shuffle),
+            _ => None,
+        }
+    }
+
+    /// Number key of the power-up
+    fn key(self) -> usize {
+        self as usize + 1
+    }
+
+    fn name(self) -> &'static str {
+        i18n::text(match self {
+            PowerUp::Swap => "powerups.swap",
+            PowerUp::Remove => "powerups.remove",
+            PowerUp::Shuffle => "powerups.shuffle",
+        })
+    }
+}
+
+/// Charges and meters of the power-ups
+#[derive(Debug, Default)]
+pub(super) struct PowerUps {
+    charges: [u32; 3],
+    /// Merges towards the next charge
+    meters: [u32; 3],
+}
+
+impl PowerUps {
+    /// Fill the meters with the merges of a move, returning the power-ups
+    /// that gained a charge. Meters of fully charged power-ups stay empty.
+    fn record_merges(&mut self, merges: u32) -> Vec<PowerUp> {
+        let mut charged = Vec::new();
+        for power_up in PowerUp::ALL {
+            let i = power_up as usize;
+            if self.charges[i] == MAX_CHARGES {
+                continue;
+            }
+            self.meters[i] += merges;
+            if self.meters[i] >= power_up.cost() {
+                self.meters[i] -= power_up.cost();
+                self.charges[i] += 1;
+                charged.push(power_up);
+            }
+            if self.charges[i] == MAX_CHARGES {
+                self.meters[i] = 0;
+            }
+        }
+        charged
+    }
+
+    /// Lines of the side panel, one per power-up with its key and charges
+    pub(super) fn panel_lines(&self) -> Vec<Line<'static>> {
+        PowerUp::ALL
+            .iter()
+            .map(|&power_up| {
+                Line::from(format!(
+                    "{} {:<11}{}/{}",
+                    power_up.key(),
+                    format!("{}:", power_up.name()),
+                    self.charges[power_up as usize],
+                    MAX_CHARGES
+                ))
+            })
+            .collect()
+    }
+}
+
+/// The cells picked for a power-up so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Targeting {
+    power_up: PowerUp,
+    /// Row and column of the cell under the cursor
+    pub(super) cursor: (usize, usize),
+    /// First cell of a swap, once picked
+    first: Option<(usize, usize)>,
+}
+
+impl Targeting {
+    /// What to do next, for the title
+    pub(super) fn prompt(&self) -> String {
+        let key = match self.first {
+            Some(_) => "powerups.pick_second",
+            None => "powerups.pick",
+        };
+        i18n::format(key, &[("name", &self.power_up.name())])
+    }
+}
+
+impl<G: GameEngine, R: GameRenderer, B: Backend> App<G, R, B> {
+    /// Charge the power-ups with the merges of a move and announce new
+    /// charges
+    pub(super) fn charge_power_ups(&mut self, merges: usize) {
+        let Some(power_ups) = self.power_ups.as_mut() else {
+            return;
+        };
+        let messages: Vec<String> = power_ups
+            .record_merges(merges as u32)
+            .into_iter()
+            .map(|power_up| {
+                let charges = power_ups.charges[power_up as usize];
+                i18n::format(
+                    "powerups.charged",
+                    &[("name", &power_up.name()), ("charges", &charges)],
+                )
+            })
+            .collect();
+        for message in messages {
+            self.notify(message, Severity::Success);
+        }
+    }
+
+    /// Spend a charge of a power-up: shuffle right away, or let the player
+    /// pick the cells for the others
+    pub(super) fn use_power_up(&mut self, power_up: PowerUp) {
+        let Some(power_ups) = &self.power_ups else {
+            return;
+        };
+        if power_ups.charges[power_up as usize] == 0 {
+            let message = i18n::format("powerups.no_charge", &[("name", &power_up.name())]);
+            self.notify(message, Severity::Warning);
+            return;
+        }
+        match power_up {
+            PowerUp::Shuffle => self.apply_power_up(power_up, |game| game.shuffle()),
+            _ => {
+                self.screen = Screen::PowerUp(Targeting {
+                    power_up,
+                    cursor: (0, 0),
+                    first: None,
+                })
+            }
+        }
+    }
+
+    /// Move the cursor with the arrow or move keys, pick the cell under it
+    /// with Enter or Space and cancel with Esc or the quit key
+    pub(super) fn handle_power_up_key(&mut self, code: KeyCode, mut targeting: Targeting) {
+        let (rows, columns) = self.game.dims();
+        let (i, j) = targeting.cursor;
+        match (code, self.keymap.action(code)) {
+            (KeyCode::Esc, _) | (_, Some(Action::Quit)) => {
+                self.screen = Screen::Playing;
+                return;
+            }
+            (KeyCode::Up, _) | (_, Some(Action::MoveUp)) => {
+                targeting.cursor.0 = i.saturating_sub(1);
+            }
+            (KeyCode::Down, _) | (_, Some(Action::MoveDown)) => {
+                targeting.cursor.0 = (i + 1).min(rows - 1);
+            }
+            (KeyCode::Left, _) | (_, Some(Action::MoveLeft)) => {
+                targeting.cursor.1 = j.saturating_sub(1);
+            }
+            (KeyCode::Right, _) | (_, Some(Action::MoveRight)) => {
+                targeting.cursor.1 = (j + 1).min(columns - 1);
+            }
+            (KeyCode::Enter | KeyCode::Char(' '), _) => {
+                let cursor = targeting.cursor;
+                match (targeting.power_up, targeting.first) {
+                    (PowerUp::Swap, None) => targeting.first = Some(cursor),
+                    (PowerUp::Swap, Some(first)) => {
+                        self.screen = Screen::Playing;
+                        self.apply_power_up(PowerUp::Swap, |game| game.swap_cells(first, cursor));
+                        return;
+                    }
+                    (power_up, _) => {
+                        self.screen = Screen::Playing;
+                        self.apply_power_up(power_up, |game| game.remove_cell(cursor));
+                        return;
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.screen = Screen::PowerUp(targeting);
+    }
+
+    /// Change the board with a power-up and spend its charge if that worked
+    fn apply_power_up(&mut self, power_up: PowerUp, apply: impl FnOnce(&mut G) -> GameResult<()>) {
+        if let Err(error) = apply(&mut self.game) {
+            log::warn!("Can't use {:?}: {}", power_up, error);
+            let message = i18n::format("powerups.cannot_use", &[("name", &power_up.name())]);
+            self.notify(message, Severity::Warning);
+            return;
+        }
+        if let Some(power_ups) = self.power_ups.as_mut() {
+            power_ups.charges[power_up as usize] -= 1;
+        }
+        self.autosave();
+        self.log(
+            i18n::format("powerups.used", &[("name", &power_up.name())]),
+            true,
+        );
+        // Swaps and shuffles can leave the board without a move
+        if self.game.game_over() {
+            self.finish_game();
+        }
+    }
+}
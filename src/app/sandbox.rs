@@ -0,0 +1,126 @@
+//! Sandbox screen, placing tiles anywhere and playing from the position.
+//!
+//! The board is edited in place while the screen is shown. Playing keeps the
+//! position, so restarting a sandbox game returns to the screen with the
+//! board it started from.
+
+use std::time::Instant;
+
+use crossterm::event::KeyCode;
+use ratatui::backend::Backend;
+
+use super::notifications::Severity;
+use super::{App, Screen};
+use crate::error::GameResult;
+use crate::game::GameEngine;
+use crate::i18n;
+use crate::keymap::Action;
+use crate::ui::GameRenderer;
+
+/// State of the sandbox screen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct SandboxView {
+    /// Row and column of the edited cell
+    pub(super) cursor: (usize, usize),
+}
+
+/// Next larger tile, starting with a 2 on an empty cell
+fn larger_tile(tile: u32) -> u32 {
+    if tile == 0 { 2 } else { tile.saturating_mul(2) }
+}
+
+/// Next smaller tile, emptying the cell after a 2
+fn smaller_tile(tile: u32) -> u32 {
+    if tile <= 2 { 0 } else { tile / 2 }
+}
+
+impl<G: GameEngine, R: GameRenderer, B: Backend> App<G, R, B> {
+    /// Start over on the sandbox screen with the position the sandbox game
+    /// started from. Does nothing outside of sandbox mode.
+    pub(super) fn edit_sandbox(&mut self) {
+        let Some(start) = self.sandbox.clone() else {
+            return;
+        };
+        Self::renew(self.game_factory.as_ref(), &self.config, &mut self.game);
+        if let Err(error) = self.place_tiles(&start) {
+            self.notify(error.to_string(), Severity::Error);
+        }
+        self.start_over();
+        self.screen = Screen::Sandbox(SandboxView::default());
+    }
+
+    /// Put the tiles of a position on the fresh game; the settings may have
+    /// changed the board size since, then the game is kept as it is
+    fn place_tiles(&mut self, board: &[Vec<u32>]) -> GameResult<()> {
+        let dims = (board.len(), board.first().map_or(0, Vec::len));
+        if dims != self.game.dims() {
+            return Ok(());
+        }
+        for (i, row) in board.iter().enumerate() {
+            for (j, &tile) in row.iter().enumerate() {
+                self.game.set_cell((i, j), tile)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the cursor with the arrow or move keys, make the tile under it
+    /// larger with `+` and smaller with `-`, empty it with Backspace or
+    /// Delete and play from the position with Enter or Esc
+    pub(super) fn handle_sandbox_key(&mut self, code: KeyCode, mut view: SandboxView) {
+        let (rows, columns) = self.game.dims();
+        let (i, j) = view.cursor;
+        let tile = self.game.board()[i][j];
+        let edit = match (code, self.keymap.action(code)) {
+            (KeyCode::Enter | KeyCode::Esc, _) | (_, Some(Action::Quit)) => {
+                self.play_sandbox();
+                return;
+            }
+            (KeyCode::Up, _) | (_, Some(Action::MoveUp)) => {
+                view.cursor.0 = i.saturating_sub(1);
+                None
+            }
+            (KeyCode::Down, _) | (_, Some(Action::MoveDown)) => {
+                view.cursor.0 = (i + 1).min(rows - 1);
+                None
+            }
+            (KeyCode::Left, _) | (_, Some(Action::MoveLeft)) => {
+                view.cursor.1 = j.saturating_sub(1);
+                None
+            }
+            (KeyCode::Right, _) | (_, Some(Action::MoveRight)) => {
+                view.cursor.1 = (j + 1).min(columns - 1);
+                None
+            }
+            (KeyCode::Char('+' | '='), _) => Some(larger_tile(tile)),
+            (KeyCode::Char('-'), _) => Some(smaller_tile(tile)),
+            (KeyCode::Backspace | KeyCode::Delete, _) => Some(0),
+            _ => None,
+        };
+        if let Some(tile) = edit
+            && let Err(error) = self.game.set_cell(view.cursor, tile)
+        {
+            self.notify(error.to_string(), Severity::Warning);
+        }
+        self.screen = Screen::Sandbox(view);
+    }
+
+    /// Play from the edited position, if it leaves a move to make
+    fn play_sandbox(&mut self) {
+        let board = self.game.board();
+        let problem = if board.iter().flatten().all(|&tile| tile == 0) {
+            Some(i18n::text("notify.place_a_tile"))
+        } else if self.game.game_over() {
+            Some(i18n::text("notify.no_move"))
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            self.notify(problem, Severity::Warning);
+            return;
+        }
+        self.sandbox = Some(board.to_vec());
+        self.started_at = Instant::now();
+        self.screen = Screen::Playing;
+    }
+}
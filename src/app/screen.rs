@@ -0,0 +1,440 @@
+//! Screens of the app and the keys they take.
+//!
+//! The app shows one [`Screen`] at a time. Each screen handles the keys
+//! pressed while it is shown and decides which screen comes next; overlays
+//! such as the error dialog, notifications and the tutorial are drawn on top of
+//! whatever screen is shown.
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+use ratatui::backend::Backend;
+
+use super::powerups::{PowerUp, Targeting};
+#[cfg(feature = "sandbox")]
+use super::sandbox::SandboxView;
+use super::settings::SettingsView;
+use super::versus;
+use super::{
+    App, AppOutcome, FRAME_DURATION, GAME_OVER_INPUT_DELAY, GAME_OVER_POLL_TIMEOUT, Operation,
+    POLL_TIMEOUT,
+};
+use crate::cosmetics::Cosmetic;
+use crate::export::ExportFormat;
+use crate::game::{GameEngine, MovementDirection};
+use crate::keymap::{Action, KeyInput};
+use crate::leaderboard::MAX_NAME_LEN;
+use crate::saves::MAX_SLOT_NAME_LEN;
+use crate::terminal;
+use crate::tutorial::TutorialEvent;
+use crate::ui::GameRenderer;
+
+/// What the app shows, and which keys it takes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Screen {
+    /// The board, taking moves
+    Playing,
+    /// The board hidden and the clock stopped since the given instant
+    Paused { since: Instant },
+    /// Statistics of the current game with a chart of the score
+    Stats,
+    /// The top scores
+    Leaderboard,
+    /// Unlocked achievements and merge counters
+    Achievements,
+    /// The key bindings
+    Help,
+    /// Cosmetics with the selected row
+    Cosmetics(usize),
+    /// Puzzle select screen with the selected row
+    Puzzles(usize),
+    /// Settings, changed while the game runs
+    Settings(SettingsView),
+    /// Prompt for the name of a save slot, with the name typed so far
+    SaveSlot(String),
+    /// Cells being picked for a power-up
+    PowerUp(Targeting),
+    /// Past boards of the game, with the one shown
+    History(usize),
+    /// The board being edited before it is played
+    #[cfg(feature = "sandbox")]
+    Sandbox(SandboxView),
+    /// The game is over
+    GameOver(GameOverView),
+}
+
+impl Screen {
+    /// Name of the screen, for the log and the debug overlay
+    pub(super) fn name(&self) -> &'static str {
+        match self {
+            Screen::Playing => "playing",
+            Screen::Paused { .. } => "paused",
+            Screen::Stats => "stats",
+            Screen::Leaderboard => "leaderboard",
+            Screen::Achievements => "achievements",
+            Screen::Help => "help",
+            Screen::Cosmetics(_) => "cosmetics",
+            Screen::Puzzles(_) => "puzzles",
+            Screen::Settings(_) => "settings",
+            Screen::SaveSlot(_) => "save slot",
+            Screen::PowerUp(_) => "power-up",
+            Screen::History(_) => "history",
+            #[cfg(feature = "sandbox")]
+            Screen::Sandbox(_) => "sandbox",
+            Screen::GameOver(_) => "game over",
+        }
+    }
+}
+
+/// Sub-screens shown after the game is over
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum GameOverView {
+    /// Final score with restart/quit options
+    Summary,
+    /// Text input for the name of a new high score
+    NameEntry(String),
+    /// The top scores
+    Leaderboard,
+    /// How the moves of the game compare with the expectimax search
+    Analysis,
+}
+
+impl<G: GameEngine, R: GameRenderer, B: Backend> App<G, R, B> {
+    /// Get the shown screen ready for drawing, returning how long to wait
+    /// for a key before it should be drawn again
+    pub(super) fn update(&mut self) -> Duration {
+        let mut timeout = match self.screen {
+            Screen::GameOver(_) => GAME_OVER_POLL_TIMEOUT,
+            _ => POLL_TIMEOUT,
+        };
+        if self.screen == Screen::Playing {
+            // Draw frames at a steady rate while the highlight is shown, and
+            // redraw as soon as it expires instead of on the next key press
+            let highlight = self.highlight_remaining();
+            self.renderer.set_highlight(highlight.is_some());
+            if let Some(remaining) = highlight {
+                self.dirty = true;
+                timeout = timeout.min(remaining).min(FRAME_DURATION);
+            } else if self.highlight_until.take().is_some() {
+                // One more frame without it
+                self.dirty = true;
+            }
+        }
+        self.renderer.set_cursor(match self.screen {
+            #[cfg(feature = "sandbox")]
+            Screen::Sandbox(view) => Some(view.cursor),
+            Screen::PowerUp(targeting) => Some(targeting.cursor),
+            _ => None,
+        });
+        self.renderer
+            .set_heatmap(self.heatmap_shown.map(|kind| self.heatmap.levels(kind)));
+        // Hide the chord popup once the second key is no longer awaited
+        if self
+            .pending_chord
+            .take_if(|chord| chord.remaining().is_zero())
+            .is_some()
+        {
+            self.dirty = true;
+        }
+        if let Some(chord) = &self.pending_chord {
+            timeout = timeout.min(chord.remaining());
+        }
+        self.remind_of_break();
+        if self.notifications.expire() {
+            self.dirty = true;
+        }
+        // Redraw as soon as a notification is due to disappear
+        if let Some(expiry) = self.notifications.next_expiry() {
+            timeout = timeout.min(expiry);
+        }
+        self.poll_timeout(timeout)
+    }
+
+    /// Pass a key press to the shown screen.
+    ///
+    /// Returns how the call to `run` ends if the key ended it.
+    pub(super) fn handle_key(&mut self, code: KeyCode) -> Option<AppOutcome> {
+        match self.screen.clone() {
+            Screen::Playing => return self.handle_playing_key(code),
+            Screen::Paused { since } => {
+                // The clock skips the pause
+                self.started_at += since.elapsed();
+                self.screen = Screen::Playing;
+            }
+            // Panels are closed by any key
+            Screen::Stats | Screen::Leaderboard | Screen::Achievements | Screen::Help => {
+                self.screen = Screen::Playing;
+            }
+            Screen::Cosmetics(cursor) => self.handle_cosmetics_key(code, cursor),
+            Screen::Puzzles(cursor) => self.handle_puzzles_key(code, cursor),
+            Screen::Settings(view) => self.handle_settings_key(code, view),
+            Screen::SaveSlot(name) => self.handle_save_slot_key(code, name),
+            Screen::PowerUp(targeting) => self.handle_power_up_key(code, targeting),
+            Screen::History(cursor) => self.handle_history_key(code, cursor),
+            #[cfg(feature = "sandbox")]
+            Screen::Sandbox(view) => self.handle_sandbox_key(code, view),
+            Screen::GameOver(view) => return self.handle_game_over_key(code, view),
+        }
+        None
+    }
+
+    /// Handle a key press on the board
+    fn handle_playing_key(&mut self, code: KeyCode) -> Option<AppOutcome> {
+        // Without key release events the preview ends with the next key press
+        if self.peeking && !terminal::reports_key_releases() {
+            self.peeking = false;
+            return None;
+        }
+
+        // Esc skips the tutorial, steps without a task take any key
+        if let Some(tutorial) = &self.tutorial {
+            if code == KeyCode::Esc {
+                self.finish_tutorial();
+                return None;
+            }
+            if tutorial.waits_for_key() {
+                self.advance_tutorial(TutorialEvent::KeyPressed);
+                return None;
+            }
+        }
+
+        // In a versus game the arrow keys belong to player 2
+        if self.versus.is_some()
+            && let Some(direction) = versus::second_player_direction(code)
+        {
+            self.make_second_move(direction);
+            if self.versus_winner().is_some() {
+                self.finish_game();
+            }
+            return None;
+        }
+
+        // With power-ups the number keys spend them
+        if self.power_ups.is_some()
+            && let Some(power_up) = PowerUp::from_key(code)
+        {
+            self.use_power_up(power_up);
+            return None;
+        }
+
+        let action = match self.keymap.press(&mut self.pending_chord, code) {
+            KeyInput::Action(action) => action,
+            KeyInput::ChordStarted | KeyInput::Unbound => return None,
+        };
+        // Held keys repeat faster than moves can be followed, so only some of
+        // the repeats are taken
+        if let Some(direction) = action.direction()
+            && self.is_repeat(direction)
+        {
+            return None;
+        }
+        match action {
+            Action::Quit => return Some(self.quit_outcome()),
+            Action::MoveLeft => self.make_move(MovementDirection::Left),
+            Action::MoveRight => self.make_move(MovementDirection::Right),
+            Action::MoveUp => self.make_move(MovementDirection::Up),
+            Action::MoveDown => self.make_move(MovementDirection::Down),
+            Action::Undo => self.undo(),
+            Action::PeekUndo => self.peek_undo(),
+            Action::Pause => {
+                self.screen = Screen::Paused {
+                    since: Instant::now(),
+                }
+            }
+            Action::ShowStats => self.screen = Screen::Stats,
+            Action::ShowLeaderboard => self.screen = Screen::Leaderboard,
+            Action::ShowAchievements => self.screen = Screen::Achievements,
+            Action::ShowHelp => self.screen = Screen::Help,
+            Action::ShowCosmetics => self.show_cosmetics(),
+            Action::ShowPuzzles => self.show_puzzles(),
+            Action::ShowSettings => self.show_settings(),
+            Action::Snapshot => self.attempt(Operation::SaveSnapshot),
+            Action::SaveSlot => self.show_save_slot(),
+            Action::ShowHistory => self.show_history(),
+            Action::ToggleHeatmap => self.toggle_heatmap(),
+            // Games are restarted from the game over screen, puzzles and
+            // sandbox positions any time
+            Action::Restart => {
+                self.restart_puzzle();
+                #[cfg(feature = "sandbox")]
+                self.edit_sandbox();
+            }
+        }
+        self.check_puzzle();
+
+        if self.game.game_over() || self.versus_winner().is_some() {
+            self.finish_game();
+        }
+        None
+    }
+
+    /// Record the finished game and show the game over screen
+    pub(super) fn finish_game(&mut self) {
+        self.finished_in = Some(self.started_at.elapsed());
+        self.record_play_time();
+        let score = self.game.score();
+        log::info!(
+            "Game over after {} moves with {} points",
+            self.game.moves(),
+            score
+        );
+        let ranked = self.is_ranked();
+        if let Some(winner) = self.versus_winner() {
+            log::info!("Versus game over: {:?}", winner);
+        }
+        self.discard_autosave();
+        if self.achievements.is_some() {
+            self.attempt(Operation::SaveAchievements);
+        }
+        if let Some(daily) = self.daily.as_mut()
+            && ranked
+            && daily.scores.record(daily.date, score)
+        {
+            self.attempt(Operation::SaveDailyScores);
+        }
+        if let Some(duel) = self.duel.as_mut()
+            && ranked
+        {
+            duel.recording.score = score;
+            if duel.is_new_best() {
+                self.attempt(Operation::SaveGhost);
+            }
+        }
+
+        let view = if ranked
+            && self
+                .leaderboard
+                .as_ref()
+                .is_some_and(|(board, _)| board.qualifies(score))
+        {
+            GameOverView::NameEntry(String::new())
+        } else {
+            GameOverView::Summary
+        };
+        self.screen = Screen::GameOver(view);
+        self.discard_input_at = Some(Instant::now() + GAME_OVER_INPUT_DELAY);
+    }
+
+    /// Handle a key press after the game is over
+    fn handle_game_over_key(&mut self, code: KeyCode, view: GameOverView) -> Option<AppOutcome> {
+        // A key held down at the end of the game keeps repeating; drop the
+        // repeats until it is released instead of typing them into the name
+        if let Some(direction) = self.keymap.action(code).and_then(Action::direction)
+            && self.is_repeat(direction)
+        {
+            self.last_move = Some((direction, Instant::now()));
+            return None;
+        }
+        let next = match view {
+            GameOverView::NameEntry(mut name) => match code {
+                KeyCode::Char(c) if name.chars().count() < MAX_NAME_LEN => {
+                    name.push(c);
+                    GameOverView::NameEntry(name)
+                }
+                KeyCode::Backspace => {
+                    name.pop();
+                    GameOverView::NameEntry(name)
+                }
+                KeyCode::Enter if !name.trim().is_empty() => {
+                    self.record_high_score(name.trim().to_string());
+                    GameOverView::Leaderboard
+                }
+                KeyCode::Esc => GameOverView::Summary,
+                _ => GameOverView::NameEntry(name),
+            },
+            GameOverView::Leaderboard | GameOverView::Analysis => GameOverView::Summary,
+            GameOverView::Summary => match self.keymap.action(code) {
+                Some(Action::Quit) => return Some(self.quit_outcome()),
+                Some(Action::Restart) => {
+                    self.restart_game();
+                    #[cfg(feature = "sandbox")]
+                    self.edit_sandbox();
+                    return Some(AppOutcome::Restarted);
+                }
+                None if code == KeyCode::Char('l') && self.leaderboard.is_some() => {
+                    GameOverView::Leaderboard
+                }
+                None if code == KeyCode::Char('v') => {
+                    if self.analyze() {
+                        GameOverView::Analysis
+                    } else {
+                        GameOverView::Summary
+                    }
+                }
+                None if code == KeyCode::Char('e') && self.export_dir.is_some() => {
+                    self.attempt(Operation::ExportReplay(ExportFormat::Svg));
+                    GameOverView::Summary
+                }
+                None if code == KeyCode::Char('g') && self.export_dir.is_some() => {
+                    self.attempt(Operation::ExportReplay(ExportFormat::Gif));
+                    GameOverView::Summary
+                }
+                _ => GameOverView::Summary,
+            },
+        };
+        self.screen = Screen::GameOver(next);
+        None
+    }
+
+    /// Type the name of a save slot, save to it with Enter and go back to
+    /// the board with Esc
+    fn handle_save_slot_key(&mut self, code: KeyCode, mut name: String) {
+        self.screen = match code {
+            KeyCode::Char(c) if name.chars().count() < MAX_SLOT_NAME_LEN => {
+                name.push(c);
+                Screen::SaveSlot(name)
+            }
+            KeyCode::Backspace => {
+                name.pop();
+                Screen::SaveSlot(name)
+            }
+            KeyCode::Enter if !name.trim().is_empty() => {
+                self.screen = Screen::Playing;
+                self.attempt(Operation::SaveSlot(name.trim().to_string()));
+                return;
+            }
+            KeyCode::Esc => Screen::Playing,
+            _ => Screen::SaveSlot(name),
+        };
+    }
+
+    /// Move through the cosmetics with the up and down keys, select one with
+    /// Enter or Space and close the screen with Esc or the quit key
+    fn handle_cosmetics_key(&mut self, code: KeyCode, cursor: usize) {
+        let last = Cosmetic::ALL.len() - 1;
+        match (code, self.keymap.action(code)) {
+            (KeyCode::Enter | KeyCode::Char(' '), _) => self.equip(Cosmetic::ALL[cursor]),
+            (KeyCode::Esc, _) | (_, Some(Action::Quit)) => self.screen = Screen::Playing,
+            (KeyCode::Up, _) | (_, Some(Action::MoveUp)) => {
+                self.screen = Screen::Cosmetics(cursor.saturating_sub(1));
+            }
+            (KeyCode::Down, _) | (_, Some(Action::MoveDown)) => {
+                self.screen = Screen::Cosmetics((cursor + 1).min(last));
+            }
+            _ => {}
+        }
+    }
+
+    /// Move through the puzzles with the up and down keys, start one with
+    /// Enter or Space and close the screen with Esc or the quit key
+    fn handle_puzzles_key(&mut self, code: KeyCode, cursor: usize) {
+        let Some(session) = self.puzzles.as_mut() else {
+            return;
+        };
+        let last = session.puzzles.len() - 1;
+        match (code, self.keymap.action(code)) {
+            (KeyCode::Enter | KeyCode::Char(' '), _) => {
+                session.current = cursor;
+                self.restart_puzzle();
+            }
+            (KeyCode::Esc, _) | (_, Some(Action::Quit)) => self.screen = Screen::Playing,
+            (KeyCode::Up, _) | (_, Some(Action::MoveUp)) => {
+                self.screen = Screen::Puzzles(cursor.saturating_sub(1));
+            }
+            (KeyCode::Down, _) | (_, Some(Action::MoveDown)) => {
+                self.screen = Screen::Puzzles((cursor + 1).min(last));
+            }
+            _ => {}
+        }
+    }
+}
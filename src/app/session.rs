@@ -0,0 +1,84 @@
+//! Time spent playing without a break.
+//!
+//! Every key press counts the time since the previous one as played, unless
+//! the player was away for [`BREAK_AFTER`], which starts a new stretch of
+//! play. A reminder to take a break is due every time the stretch grows by
+//! the configured interval, and the time played is handed over in whole
+//! seconds to be added to the play time kept across games.
+
+use std::time::{Duration, Instant};
+
+/// Time without a key press after which the player is taken to have had a
+/// break
+pub(super) const BREAK_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// How long a break reminder stays on screen
+pub(super) const REMINDER_TTL: Duration = Duration::from_secs(10);
+
+/// The current stretch of play
+#[derive(Debug)]
+pub(super) struct PlaySession {
+    /// When the stretch started
+    started_at: Instant,
+    /// When the last key was pressed
+    last_active: Instant,
+    /// Reminders shown during the stretch
+    reminders: u32,
+    /// Time played that wasn't handed over yet
+    unrecorded: Duration,
+}
+
+impl Default for PlaySession {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            started_at: now,
+            last_active: now,
+            reminders: 0,
+            unrecorded: Duration::ZERO,
+        }
+    }
+}
+
+impl PlaySession {
+    /// Count the time since the last key press as played, or start a new
+    /// stretch if the player was away for a break
+    pub(super) fn activity(&mut self, now: Instant) {
+        let idle = now.saturating_duration_since(self.last_active);
+        if idle >= BREAK_AFTER {
+            self.started_at = now;
+            self.reminders = 0;
+        } else {
+            self.unrecorded += idle;
+        }
+        self.last_active = now;
+    }
+
+    /// Length of the stretch up to the last key press
+    pub(super) fn length(&self) -> Duration {
+        self.last_active.saturating_duration_since(self.started_at)
+    }
+
+    /// Whether the stretch just grew past another `every` while the player
+    /// is still at it, returning how long it has lasted in whole intervals.
+    /// Never due when `every` is zero.
+    pub(super) fn reminder_due(&mut self, now: Instant, every: Duration) -> Option<Duration> {
+        if every.is_zero() || now.saturating_duration_since(self.last_active) >= BREAK_AFTER {
+            return None;
+        }
+        let next = every * (self.reminders + 1);
+        if now.saturating_duration_since(self.started_at) < next {
+            return None;
+        }
+        self.reminders += 1;
+        Some(next)
+    }
+
+    /// Take the time played since it was last taken, in whole seconds; the
+    /// rest is kept for the next time
+    pub(super) fn take_played(&mut self) -> Duration {
+        let played = Duration::from_secs(self.unrecorded.as_secs());
+        self.unrecorded -= played;
+        played
+    }
+}
@@ -0,0 +1,307 @@
+//! Settings screen, changing the config while the game runs.
+//!
+//! Changes are applied right away and written to the config file when the
+//! screen is closed. Board settings only apply to new games, so a new game is
+//! started when the screen is closed after they changed.
+
+use std::ops::RangeInclusive;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    backend::Backend,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use super::notifications::Severity;
+use super::{App, Operation, Screen};
+use crate::config::Config;
+use crate::game::GameEngine;
+use crate::i18n;
+use crate::keymap::{Action, Keymap, key_name, parse_key};
+use crate::ui::theme::Theme;
+use crate::ui::{GameRenderer, TileLabels};
+
+/// Board sizes offered on the settings screen
+const BOARD_SIZES: RangeInclusive<usize> = 3..=8;
+
+/// Step of the chances of a 4 and an 8, in percent
+const CHANCE_STEP: i64 = 5;
+
+/// A row of the settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    BoardSize,
+    Theme,
+    Animations,
+    TileLabels,
+    FourChance,
+    /// Chance of an 8 in hard mode
+    EightChance,
+    /// The keys of an action
+    Keys(Action),
+}
+
+impl Setting {
+    /// All rows, in the order they are listed
+    fn all() -> Vec<Setting> {
+        let mut settings = vec![
+            Setting::BoardSize,
+            Setting::Theme,
+            Setting::Animations,
+            Setting::TileLabels,
+            Setting::FourChance,
+            Setting::EightChance,
+        ];
+        settings.extend(Action::ALL.into_iter().map(Setting::Keys));
+        settings
+    }
+
+    fn label(self) -> String {
+        match self {
+            Setting::BoardSize => i18n::text("settings.board_size").to_string(),
+            Setting::Theme => i18n::text("settings.theme").to_string(),
+            Setting::Animations => i18n::text("settings.animations").to_string(),
+            Setting::TileLabels => i18n::text("settings.tile_labels").to_string(),
+            Setting::FourChance => i18n::text("settings.four_chance").to_string(),
+            Setting::EightChance => i18n::text("settings.eight_chance").to_string(),
+            Setting::Keys(action) => i18n::format("settings.keys", &[("action", &action.name())]),
+        }
+    }
+
+    /// Current value of the setting
+    fn value(self, config: &Config, keymap: &Keymap) -> String {
+        match self {
+            Setting::BoardSize => format!("{0}x{0}", config.size),
+            Setting::Theme => format!("{:?}", config.theme),
+            Setting::Animations => i18n::text(if config.animations {
+                "settings.on"
+            } else {
+                "settings.off"
+            })
+            .to_string(),
+            Setting::TileLabels => i18n::text(match config.tile_labels {
+                TileLabels::Compact => "settings.compact",
+                TileLabels::Exact => "settings.exact",
+            })
+            .to_string(),
+            Setting::FourChance => format!("{:.0}%", config.four_chance * 100.0),
+            Setting::EightChance => format!("{:.0}%", config.eight_chance * 100.0),
+            Setting::Keys(action) => keymap
+                .keys(action)
+                .iter()
+                .map(|key| key.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    /// Change the setting to the next or previous option. Key bindings are
+    /// changed with [`bind`] instead.
+    fn cycle(self, config: &mut Config, forward: bool) {
+        match self {
+            Setting::BoardSize => {
+                config.size = if forward {
+                    (config.size + 1).min(*BOARD_SIZES.end())
+                } else {
+                    config.size.saturating_sub(1).max(*BOARD_SIZES.start())
+                };
+            }
+            Setting::Theme => {
+                let count = Theme::ALL.len();
+                let index = Theme::ALL
+                    .iter()
+                    .position(|&theme| theme == config.theme)
+                    .unwrap_or(0);
+                let next = if forward {
+                    index + 1
+                } else {
+                    index + count - 1
+                };
+                config.theme = Theme::ALL[next % count];
+            }
+            Setting::Animations => config.animations = !config.animations,
+            Setting::TileLabels => {
+                let count = TileLabels::ALL.len();
+                let index = TileLabels::ALL
+                    .iter()
+                    .position(|&labels| labels == config.tile_labels)
+                    .unwrap_or(0);
+                let next = if forward {
+                    index + 1
+                } else {
+                    index + count - 1
+                };
+                config.tile_labels = TileLabels::ALL[next % count];
+            }
+            Setting::FourChance => {
+                config.four_chance = step_chance(config.four_chance, config.eight_chance, forward);
+            }
+            Setting::EightChance => {
+                config.eight_chance = step_chance(config.eight_chance, config.four_chance, forward);
+            }
+            Setting::Keys(_) => {}
+        }
+    }
+
+    /// Whether the setting only applies to new games
+    fn affects_board(self) -> bool {
+        matches!(
+            self,
+            Setting::BoardSize | Setting::FourChance | Setting::EightChance
+        )
+    }
+}
+
+/// Change a chance by a step up or down, keeping it and the `other` chance
+/// within 100% together
+fn step_chance(chance: f64, other: f64, forward: bool) -> f64 {
+    // Whole percents, so repeated steps don't accumulate rounding errors
+    let step = if forward { CHANCE_STEP } else { -CHANCE_STEP };
+    let percent = (chance * 100.0).round() as i64 + step;
+    let left = 100 - (other * 100.0).round() as i64;
+    percent.clamp(0, left.max(0)) as f64 / 100.0
+}
+
+/// Bind a single key to an action, replacing its other keys.
+///
+/// Returns `false` if the key can't be written to the config file.
+fn bind(config: &mut Config, action: Action, key: KeyCode) -> bool {
+    let name = key_name(key);
+    if parse_key(&name).ok() != Some(key) {
+        return false;
+    }
+    config.keys.insert(action.name().to_string(), vec![name]);
+    true
+}
+
+/// State of the settings screen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct SettingsView {
+    /// Index of the selected row in [`Setting::all`]
+    cursor: usize,
+    /// Whether the next key is bound to the selected action
+    binding: bool,
+    /// Whether the config changed and should be saved
+    changed: bool,
+    /// Whether board settings changed, so a new game is started on closing
+    new_board: bool,
+}
+
+impl<G: GameEngine, R: GameRenderer, B: Backend> App<G, R, B> {
+    /// Open the settings screen
+    pub(super) fn show_settings(&mut self) {
+        self.screen = Screen::Settings(SettingsView::default());
+    }
+
+    /// Move through the settings with the up and down keys and change them
+    /// with the left and right keys. Enter changes a setting or binds the
+    /// next key to an action, Backspace restores the default keys and Esc or
+    /// the quit key closes the screen.
+    pub(super) fn handle_settings_key(&mut self, code: KeyCode, mut view: SettingsView) {
+        let settings = Setting::all();
+        let setting = settings[view.cursor];
+        let mut config = self.config.clone();
+        if view.binding {
+            view.binding = false;
+            if let Setting::Keys(action) = setting
+                && code != KeyCode::Esc
+                && !bind(&mut config, action, code)
+            {
+                let key = key_name(code);
+                self.notify(
+                    i18n::format("notify.cannot_bind", &[("key", &key)]),
+                    Severity::Warning,
+                );
+            }
+        } else {
+            match (code, self.keymap.action(code), setting) {
+                (KeyCode::Esc, _, _) | (_, Some(Action::Quit), _) => {
+                    self.close_settings(view);
+                    return;
+                }
+                (KeyCode::Up, _, _) | (_, Some(Action::MoveUp), _) => {
+                    view.cursor = view.cursor.saturating_sub(1);
+                }
+                (KeyCode::Down, _, _) | (_, Some(Action::MoveDown), _) => {
+                    view.cursor = (view.cursor + 1).min(settings.len() - 1);
+                }
+                (KeyCode::Enter, _, Setting::Keys(_)) => view.binding = true,
+                (KeyCode::Backspace, _, Setting::Keys(action)) => {
+                    config.keys.remove(action.name());
+                }
+                (KeyCode::Left, _, _) | (_, Some(Action::MoveLeft), _) => {
+                    setting.cycle(&mut config, false);
+                }
+                (KeyCode::Right | KeyCode::Enter, _, _) | (_, Some(Action::MoveRight), _) => {
+                    setting.cycle(&mut config, true);
+                }
+                _ => {}
+            }
+        }
+        if config != self.config {
+            view.changed = true;
+            view.new_board |= setting.affects_board();
+            if let Err(error) = self.apply_config(&config) {
+                self.notify(error.to_string(), Severity::Error);
+            }
+        }
+        self.screen = Screen::Settings(view);
+    }
+
+    /// Close the settings screen, starting a new game if the board settings
+    /// changed and saving the config
+    fn close_settings(&mut self, view: SettingsView) {
+        self.screen = Screen::Playing;
+        if view.new_board {
+            if self.has_fixed_rules() {
+                self.notify(i18n::text("notify.fixed_rules"), Severity::Warning);
+            } else {
+                self.restart_game();
+            }
+        }
+        if view.changed {
+            self.attempt(Operation::SaveConfig);
+        }
+    }
+}
+
+/// List of the settings with their values, the cursor row reversed and
+/// scrolled into the `height` rows of the screen
+pub(super) fn settings_screen(
+    config: &Config,
+    keymap: &Keymap,
+    view: SettingsView,
+    height: u16,
+) -> Paragraph<'static> {
+    let lines: Vec<Line> = Setting::all()
+        .into_iter()
+        .enumerate()
+        .map(|(i, setting)| {
+            let value = if i == view.cursor && view.binding {
+                i18n::text("settings.press_a_key").to_string()
+            } else {
+                setting.value(config, keymap)
+            };
+            let text = format!("{:<20}{}", setting.label(), value);
+            let style = if i == view.cursor {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::styled(text, style)
+        })
+        .collect();
+    let title = if view.binding {
+        i18n::text("settings.binding")
+    } else {
+        i18n::text("settings.title")
+    };
+    // Keep the cursor row inside the border
+    let visible = height.saturating_sub(2).max(1);
+    let offset = (view.cursor as u16).saturating_sub(visible - 1);
+    Paragraph::new(lines)
+        .scroll((offset, 0))
+        .block(Block::default().title(title).borders(Borders::ALL))
+}
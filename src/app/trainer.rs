@@ -0,0 +1,123 @@
+//! Trainer mode, grading every move against the expectimax search.
+//!
+//! After each move the board before it is rated like the post-game analysis
+//! rates it, and the move is graded as the best one, an OK one or a mistake.
+//! Anything but the best move names the move the search would have played.
+//! The grades add up over the session, restarts included, to the accuracy in
+//! the side panel.
+
+use std::time::Duration;
+
+use ratatui::backend::Backend;
+use ratatui::text::Line;
+
+use super::App;
+use super::notifications::Severity;
+use crate::ai::{self, MoveGrade, TranspositionTable};
+use crate::error::GameError;
+use crate::game::{GameEngine, MergeRule, MovementDirection};
+use crate::i18n;
+use crate::tutorial::direction_name;
+use crate::ui::GameRenderer;
+
+/// How long the grade of a move stays on screen, short enough not to pile
+/// up while playing
+pub(super) const GRADE_TTL: Duration = Duration::from_secs(2);
+
+/// Grades of the moves played this session
+#[derive(Debug, Default)]
+pub(super) struct Trainer {
+    best: u32,
+    ok: u32,
+    mistakes: u32,
+    /// Ratings kept from one move to the next
+    table: TranspositionTable,
+    /// Whether the player was told that the board can't be graded
+    unsupported_shown: bool,
+}
+
+impl Trainer {
+    fn record(&mut self, grade: MoveGrade) {
+        match grade {
+            MoveGrade::Best => self.best += 1,
+            MoveGrade::Ok => self.ok += 1,
+            MoveGrade::Mistake => self.mistakes += 1,
+        }
+    }
+
+    /// Share of the graded moves that were the best one, in percent
+    fn accuracy(&self) -> Option<u32> {
+        let graded = self.best + self.ok + self.mistakes;
+        (self.best * 100).checked_div(graded)
+    }
+
+    /// Lines of the side panel: the accuracy and the count of every grade
+    pub(super) fn panel_lines(&self) -> Vec<Line<'static>> {
+        let accuracy = match self.accuracy() {
+            Some(percent) => format!("{}%", percent),
+            None => "-".to_string(),
+        };
+        [
+            ("trainer.accuracy", accuracy),
+            ("trainer.best", self.best.to_string()),
+            ("trainer.ok", self.ok.to_string()),
+            ("trainer.mistakes", self.mistakes.to_string()),
+        ]
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(format!(
+                "{:<11}{}",
+                format!("{}:", i18n::text(label)),
+                value
+            ))
+        })
+        .collect()
+    }
+}
+
+impl<G: GameEngine, R: GameRenderer, B: Backend> App<G, R, B> {
+    /// Grade a move played on `before` and show the grade. Moves without a
+    /// choice aren't graded.
+    pub(super) fn grade_move(&mut self, before: &[Vec<u32>], played: MovementDirection) {
+        let Some(trainer) = self.trainer.as_mut() else {
+            return;
+        };
+        // The search only knows regular merges, so divide games aren't graded
+        let review = if self.game.merge_rule() == MergeRule::Double {
+            ai::review_move(before, played, &trainer.table)
+        } else {
+            Err(GameError::GameStateError(
+                "The search only knows regular merges".to_string(),
+            ))
+        };
+        let review = match review {
+            Ok(Some(review)) => review,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!("Can't grade the move: {}", e);
+                if !trainer.unsupported_shown {
+                    trainer.unsupported_shown = true;
+                    self.notify(i18n::text("trainer.unsupported"), Severity::Warning);
+                }
+                return;
+            }
+        };
+        trainer.record(review.grade);
+        let best = direction_name(review.best);
+        let (message, severity) = match review.grade {
+            MoveGrade::Best => (
+                i18n::text("trainer.graded_best").to_string(),
+                Severity::Success,
+            ),
+            MoveGrade::Ok => (
+                i18n::format("trainer.graded_ok", &[("best", &best)]),
+                Severity::Info,
+            ),
+            MoveGrade::Mistake => (
+                i18n::format("trainer.graded_mistake", &[("best", &best)]),
+                Severity::Warning,
+            ),
+        };
+        self.notify_for(message, severity, GRADE_TTL);
+    }
+}
@@ -0,0 +1,174 @@
+//! Local versus mode, two players racing on one keyboard.
+//!
+//! The app's game belongs to player 1, who plays with WASD; player 2 gets a
+//! board of their own next to it and plays with the arrow keys. The first to
+//! reach the winning tile wins; if either player runs out of moves first, the
+//! higher score wins.
+//!
+//! Merging into a tile of [`ATTACK_TILE`] or more is an attack: it drops a
+//! junk tile on a random empty cell of the opponent's board, announced in the
+//! event log.
+
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+use ratatui::backend::Backend;
+
+use super::App;
+use crate::game::{GameEngine, GameView, MovementDirection};
+use crate::i18n;
+use crate::ui::GameRenderer;
+
+/// Merges into tiles at least this large send junk to the opponent
+pub(super) const ATTACK_TILE: u32 = 64;
+
+/// Tile dropped on the opponent's board by an attack
+pub(super) const JUNK_TILE: u32 = 2;
+
+/// Board of player 2
+pub(super) struct LocalVersus<G> {
+    pub(super) game: G,
+    /// Direction and time of player 2's last move, to drop the repeats of a
+    /// held key
+    pub(super) last_move: Option<(MovementDirection, Instant)>,
+}
+
+impl<G> LocalVersus<G> {
+    pub(super) fn new(game: G) -> Self {
+        Self {
+            game,
+            last_move: None,
+        }
+    }
+}
+
+/// How a versus game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Winner {
+    First,
+    Second,
+    Draw,
+}
+
+impl Winner {
+    /// Winner of the games of player 1 and 2, once one of them reached the
+    /// winning tile or ran out of moves
+    pub(super) fn of(first: &impl GameEngine, second: &impl GameEngine) -> Option<Self> {
+        match (first.has_won(), second.has_won()) {
+            (true, false) => return Some(Winner::First),
+            (false, true) => return Some(Winner::Second),
+            (true, true) => return Some(Winner::Draw),
+            (false, false) => {}
+        }
+        if !first.game_over() && !second.game_over() {
+            return None;
+        }
+        Some(match first.score().cmp(&second.score()) {
+            Ordering::Greater => Winner::First,
+            Ordering::Less => Winner::Second,
+            Ordering::Equal => Winner::Draw,
+        })
+    }
+
+    /// Announcement for the game over screen
+    pub(super) fn text(self) -> &'static str {
+        i18n::text(match self {
+            Winner::First => "versus.first_wins",
+            Winner::Second => "versus.second_wins",
+            Winner::Draw => "versus.draw",
+        })
+    }
+}
+
+/// Attacks made by the last move of a game: one for every merge into a tile
+/// of [`ATTACK_TILE`] or more
+fn attacks(game: &impl GameView) -> usize {
+    let board = game.board();
+    game.merged_cells()
+        .iter()
+        .filter(|&&(i, j)| board[i][j] >= ATTACK_TILE)
+        .count()
+}
+
+/// Drop a junk tile on `target` for every attack of the last move of
+/// `attacker`, returning how many landed
+fn send_junk(attacker: &impl GameView, target: &mut impl GameEngine) -> usize {
+    let mut landed = 0;
+    for _ in 0..attacks(attacker) {
+        match target.inject_tile(JUNK_TILE) {
+            Ok(Some(_)) => landed += 1,
+            Ok(None) => break,
+            Err(error) => {
+                log::warn!("Cannot send junk: {}", error);
+                break;
+            }
+        }
+    }
+    landed
+}
+
+/// Direction player 2 moves in with a key, leaving the other keys to player 1
+pub(super) fn second_player_direction(code: KeyCode) -> Option<MovementDirection> {
+    match code {
+        KeyCode::Up => Some(MovementDirection::Up),
+        KeyCode::Down => Some(MovementDirection::Down),
+        KeyCode::Left => Some(MovementDirection::Left),
+        KeyCode::Right => Some(MovementDirection::Right),
+        _ => None,
+    }
+}
+
+impl<G: GameEngine, R: GameRenderer, B: Backend> App<G, R, B> {
+    /// Move player 2's board, dropping the repeats of a held key like for
+    /// player 1
+    pub(super) fn make_second_move(&mut self, direction: MovementDirection) {
+        let interval = Duration::from_millis(self.config.move_interval_ms);
+        let Some(versus) = self.versus.as_mut() else {
+            return;
+        };
+        if versus
+            .last_move
+            .is_some_and(|(last, at)| last == direction && at.elapsed() < interval)
+        {
+            return;
+        }
+        versus.last_move = Some((direction, Instant::now()));
+        if versus.game.move_in_direction(&direction) {
+            self.route_attacks(false);
+        }
+    }
+
+    /// Send the junk of the last move of player 1, or player 2 if not
+    /// `from_first`, to the other player and log it
+    pub(super) fn route_attacks(&mut self, from_first: bool) {
+        let Some(versus) = self.versus.as_mut() else {
+            return;
+        };
+        let (landed, key) = if from_first {
+            (
+                send_junk(&self.game, &mut versus.game),
+                "versus.first_attacks",
+            )
+        } else {
+            (
+                send_junk(&versus.game, &mut self.game),
+                "versus.second_attacks",
+            )
+        };
+        if landed > 0 {
+            log::debug!(
+                "{} junk tiles sent by player {}",
+                landed,
+                if from_first { 1 } else { 2 }
+            );
+            self.log(i18n::format(key, &[("count", &landed)]), true);
+        }
+    }
+
+    /// Winner of the versus game, once it is decided
+    pub(super) fn versus_winner(&self) -> Option<Winner> {
+        let versus = self.versus.as_ref()?;
+        Winner::of(&self.game, &versus.game)
+    }
+}
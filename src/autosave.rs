@@ -0,0 +1,182 @@
+//! Autosave after every move, to recover games after a crash.
+//!
+//! The app hands the state after each move to an [`Autosave`], which writes
+//! it on a background thread once no newer state arrived for a short while,
+//! so quick moves cost one write instead of one each and never wait for the
+//! disk. Games that end normally remove the file; if it is still there at the
+//! next launch, the game crashed or the terminal went away, and the file has
+//! the game as it was before.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::error::{GameError, GameResult};
+use crate::profile;
+use crate::saves::SavedGame;
+
+/// Time to wait for newer states before one is written
+pub const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Name of the autosave file in the data directory
+const FILE_NAME: &str = "autosave.toml";
+
+/// What the writer thread should do with the file
+enum Request {
+    Store(Box<SavedGame>),
+    Remove,
+}
+
+/// Writes the latest state of a game to a file in the background
+pub struct Autosave {
+    path: PathBuf,
+    requests: Option<Sender<Request>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl Autosave {
+    /// Keep the autosave in a file, writing states once they are
+    /// [`DEBOUNCE`] old
+    pub fn new(path: PathBuf) -> Self {
+        Self::with_debounce(path, DEBOUNCE)
+    }
+
+    /// Keep the autosave in a file, writing states once they are `debounce` old
+    pub fn with_debounce(path: PathBuf, debounce: Duration) -> Self {
+        let (requests, received) = mpsc::channel();
+        let file = path.clone();
+        let writer = thread::spawn(move || {
+            // Each request replaces those received before it was carried out
+            while let Ok(mut request) = received.recv() {
+                let deadline = Instant::now() + debounce;
+                while let Ok(newer) =
+                    received.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                {
+                    request = newer;
+                }
+                if let Err(error) = carry_out(&file, request) {
+                    log::warn!("Cannot autosave to {}: {}", file.display(), error);
+                }
+            }
+        });
+        Self {
+            path,
+            requests: Some(requests),
+            writer: Some(writer),
+        }
+    }
+
+    /// Default location of the autosave, if a data directory exists
+    pub fn default_path() -> Option<PathBuf> {
+        profile::data_dir().map(|dir| dir.join(FILE_NAME))
+    }
+
+    /// File the autosave is kept in
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Save the state of a game soon
+    pub fn store(&self, game: SavedGame) {
+        self.send(Request::Store(Box::new(game)));
+    }
+
+    /// Remove the autosave soon, once the game ended normally
+    pub fn remove(&self) {
+        self.send(Request::Remove);
+    }
+
+    fn send(&self, request: Request) {
+        // The writer thread only stops once the autosave is dropped
+        if let Some(requests) = &self.requests {
+            let _ = requests.send(request);
+        }
+    }
+}
+
+impl Drop for Autosave {
+    /// Carry out the last request before the game exits
+    fn drop(&mut self) {
+        self.requests = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Write or remove the autosave file. The state is written to a temporary
+/// file first, so a crash while writing keeps the previous state.
+fn carry_out(path: &Path, request: Request) -> GameResult<()> {
+    match request {
+        Request::Store(game) => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let temporary = path.with_extension("toml.tmp");
+            fs::write(&temporary, toml::to_string(&*game)?)?;
+            fs::rename(temporary, path)?;
+        }
+        Request::Remove => {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+    log::trace!("Autosave updated");
+    Ok(())
+}
+
+/// The game left in an autosave file by a session that didn't end normally,
+/// `None` if there is none
+pub fn recover(path: &Path) -> GameResult<Option<SavedGame>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path)?;
+    let game = toml::from_str(&contents).map_err(|e| {
+        GameError::SaveError(format!(
+            "Corrupt autosave {}: {}",
+            path.display(),
+            e.message()
+        ))
+    })?;
+    Ok(Some(game))
+}
+
+/// Remove an autosave that is no longer wanted
+pub fn discard(path: &Path) -> GameResult<()> {
+    carry_out(path, Request::Remove)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game2048, GameEngine, GameView, MovementDirection};
+
+    #[test]
+    fn test_latest_state_is_written_and_recovered() {
+        let dir = std::env::temp_dir().join(format!("tui_2048_autosave_{}", std::process::id()));
+        let path = dir.join(FILE_NAME);
+        let mut game = Game2048::with_seed(4);
+        let autosave = Autosave::with_debounce(path.clone(), Duration::from_secs(60));
+        for direction in [MovementDirection::Left, MovementDirection::Up] {
+            game.move_in_direction(&direction);
+            autosave.store(SavedGame::new(&game, None));
+        }
+        // Dropping writes the last state without waiting for the debounce
+        drop(autosave);
+
+        let saved = recover(&path).unwrap().unwrap();
+        let resumed = saved.resume().unwrap();
+        assert_eq!(resumed.board(), game.board());
+        assert_eq!(resumed.peek_undo(), game.peek_undo());
+
+        let autosave = Autosave::with_debounce(path.clone(), Duration::ZERO);
+        autosave.remove();
+        drop(autosave);
+        assert_eq!(recover(&path).unwrap(), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
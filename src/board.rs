@@ -0,0 +1,195 @@
+//! Grid of tiles, square or rectangular.
+//!
+//! [`Board`] owns the tiles of a game and offers the geometry the game logic
+//! needs: access by row and column, iteration over the cells, and rotations
+//! so a move in any direction can be handled like a move to the left.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GameError, GameResult};
+
+/// Whether a tile can be on a board: empty (`0`) or a power of two of at
+/// least 2
+pub fn is_valid_tile(tile: u32) -> bool {
+    tile == 0 || (tile >= 2 && tile.is_power_of_two())
+}
+
+/// Grid of tiles, `0` marking an empty cell.
+///
+/// With the `serde` feature it is stored as its rows, which are checked when
+/// they are read back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "Vec<Vec<u32>>", into = "Vec<Vec<u32>>")
+)]
+pub struct Board {
+    rows: Vec<Vec<u32>>,
+}
+
+impl Board {
+    /// Empty board with `size` rows and columns
+    pub fn new(size: usize) -> Self {
+        Self {
+            rows: vec![vec![0; size]; size],
+        }
+    }
+
+    /// Empty board with `rows` rows of `columns` cells
+    pub fn with_dims(rows: usize, columns: usize) -> Self {
+        Self {
+            rows: vec![vec![0; columns]; rows],
+        }
+    }
+
+    /// Board of the given rows, which must form a rectangle of at least 2x2
+    /// valid tiles (see [`is_valid_tile`])
+    pub fn from_rows(rows: Vec<Vec<u32>>) -> GameResult<Self> {
+        let columns = rows.first().map_or(0, Vec::len);
+        if rows.len() < 2 || columns < 2 || rows.iter().any(|row| row.len() != columns) {
+            return Err(GameError::InputError(
+                "A board must be a rectangle of at least 2x2".to_string(),
+            ));
+        }
+        if let Some(&tile) = rows.iter().flatten().find(|&&tile| !is_valid_tile(tile)) {
+            return Err(GameError::InputError(format!(
+                "{} is not a valid tile",
+                tile
+            )));
+        }
+        Ok(Self { rows })
+    }
+
+    /// Board of the given rows without checking them
+    pub(crate) fn from_rows_unchecked(rows: Vec<Vec<u32>>) -> Self {
+        Self { rows }
+    }
+
+    /// Number of rows, which is the number of columns too on square boards
+    pub fn size(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Number of rows and of columns
+    pub fn dims(&self) -> (usize, usize) {
+        (self.rows.len(), self.rows.first().map_or(0, Vec::len))
+    }
+
+    /// All rows, top to bottom
+    pub fn rows(&self) -> &[Vec<u32>] {
+        &self.rows
+    }
+
+    pub fn into_rows(self) -> Vec<Vec<u32>> {
+        self.rows
+    }
+
+    /// Tile at a (row, column) position
+    pub fn get(&self, (i, j): (usize, usize)) -> u32 {
+        self.rows[i][j]
+    }
+
+    /// Replace the tile at a (row, column) position
+    pub fn set(&mut self, (i, j): (usize, usize), tile: u32) {
+        self.rows[i][j] = tile;
+    }
+
+    /// Tiles of row `i`, left to right
+    pub fn row(&self, i: usize) -> &[u32] {
+        &self.rows[i]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [u32] {
+        &mut self.rows[i]
+    }
+
+    /// Tiles of column `j`, top to bottom
+    pub fn col(&self, j: usize) -> impl Iterator<Item = u32> + '_ {
+        self.rows.iter().map(move |row| row[j])
+    }
+
+    /// Every cell with its (row, column) position, row by row
+    pub fn iter_cells(&self) -> impl Iterator<Item = ((usize, usize), u32)> + '_ {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| row.iter().enumerate().map(move |(j, &tile)| ((i, j), tile)))
+    }
+
+    /// Positions of the empty cells, row by row
+    pub fn empty_cells(&self) -> Vec<(usize, usize)> {
+        self.iter_cells()
+            .filter(|&(_, tile)| tile == 0)
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    /// Largest tile, `0` on an empty board
+    pub fn max_tile(&self) -> u32 {
+        self.rows.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// Board mirrored along the main diagonal, so rows become columns
+    pub fn transpose(&self) -> Board {
+        let (_, columns) = self.dims();
+        Self {
+            rows: (0..columns).map(|j| self.col(j).collect()).collect(),
+        }
+    }
+
+    /// Board turned a quarter clockwise, so the left column becomes the top row
+    pub fn rotate(&self) -> Board {
+        let mut rotated = self.transpose();
+        for row in &mut rotated.rows {
+            row.reverse();
+        }
+        rotated
+    }
+}
+
+impl TryFrom<Vec<Vec<u32>>> for Board {
+    type Error = GameError;
+
+    fn try_from(rows: Vec<Vec<u32>>) -> GameResult<Self> {
+        Self::from_rows(rows)
+    }
+}
+
+impl From<Board> for Vec<Vec<u32>> {
+    fn from(board: Board) -> Self {
+        board.into_rows()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_board_geometry() {
+        let board = Board::from_rows(vec![vec![2, 4], vec![0, 8]]).unwrap();
+        assert_eq!(board.row(1), &[0, 8]);
+        assert_eq!(board.col(1).collect::<Vec<_>>(), vec![4, 8]);
+        assert_eq!(board.empty_cells(), vec![(1, 0)]);
+        assert_eq!(board.max_tile(), 8);
+        assert_eq!(board.iter_cells().nth(1), Some(((0, 1), 4)));
+        assert_eq!(board.transpose().rows(), [[2, 0], [4, 8]]);
+        assert_eq!(board.rotate().rows(), [[0, 2], [8, 4]]);
+        let full_turn = (0..4).fold(board.clone(), |board, _| board.rotate());
+        assert_eq!(full_turn, board);
+
+        assert!(Board::from_rows(vec![vec![2, 4], vec![0]]).is_err());
+        assert!(Board::from_rows(vec![vec![3, 0], vec![0, 0]]).is_err());
+        assert!(Board::from_rows(vec![vec![2]]).is_err());
+        assert!(Board::from_rows(vec![vec![2], vec![4]]).is_err());
+
+        // Rows and columns of rectangles swap places when turned
+        let board = Board::from_rows(vec![vec![2, 4, 8], vec![0, 0, 16]]).unwrap();
+        assert_eq!(board.dims(), (2, 3));
+        assert_eq!(board.col(2).collect::<Vec<_>>(), vec![8, 16]);
+        assert_eq!(board.rotate().rows(), [[0, 2], [0, 4], [16, 8]]);
+        assert_eq!(Board::with_dims(3, 5).dims(), (3, 5));
+    }
+}
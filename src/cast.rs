@@ -0,0 +1,242 @@
+//! Terminal session recordings in asciinema's format.
+//!
+//! A [`CastRecorder`] turns the frames the app draws into an [asciicast v2]
+//! file: a JSON header with the terminal size, then one JSON line per frame
+//! with the seconds since the recording started and the escape sequences
+//! drawing what changed since the previous frame. The file plays back with
+//! `asciinema play` or the asciinema web player.
+//!
+//! [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::{Color, Modifier};
+use serde_json::json;
+
+use crate::error::{GameError, GameResult};
+
+/// Records frames to a writer in asciicast v2 format
+pub struct CastRecorder<W: Write = BufWriter<File>> {
+    writer: W,
+    started: Instant,
+    /// Frame recorded last, to record only the cells that changed
+    last: Option<Buffer>,
+}
+
+impl CastRecorder {
+    /// Start a recording in a new file, replacing any file at `path`
+    pub fn create(path: &Path, width: u16, height: u16) -> GameResult<Self> {
+        let file = File::create(path).map_err(|e| {
+            GameError::InputError(format!("Cannot record to {}: {}", path.display(), e))
+        })?;
+        Self::new(BufWriter::new(file), width, height)
+    }
+}
+
+impl<W: Write> CastRecorder<W> {
+    /// Start a recording of a terminal of `width` x `height` cells by
+    /// writing the header
+    pub fn new(mut writer: W, width: u16, height: u16) -> GameResult<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp,
+            "title": "2048",
+        });
+        writeln!(writer, "{}", header)?;
+        Ok(Self {
+            writer,
+            started: Instant::now(),
+            last: None,
+        })
+    }
+
+    /// Record a drawn frame.
+    ///
+    /// Frames equal to the previous one are skipped. A frame of another
+    /// size than the previous one is recorded as a resize followed by the
+    /// whole frame.
+    pub fn record(&mut self, buffer: &Buffer) -> GameResult<()> {
+        let time = self.started.elapsed().as_secs_f64();
+        let mut output = String::new();
+        let previous = match self.last.take() {
+            Some(last) if last.area == buffer.area => last,
+            last => {
+                if last.is_some() {
+                    let size = format!("{}x{}", buffer.area.width, buffer.area.height);
+                    writeln!(self.writer, "{}", json!([time, "r", size]))?;
+                }
+                // Clear the screen, then draw every cell that isn't blank
+                output.push_str("\x1b[0m\x1b[2J");
+                Buffer::empty(buffer.area)
+            }
+        };
+        output.push_str(&escape_sequences(&previous.diff(buffer)));
+        if output.is_empty() {
+            self.last = Some(previous);
+            return Ok(());
+        }
+        writeln!(self.writer, "{}", json!([time, "o", output]))?;
+        // Flushing every frame keeps the recording up to date if the game
+        // ends abruptly
+        self.writer.flush()?;
+        self.last = Some(buffer.clone());
+        Ok(())
+    }
+
+    /// Stop recording and return the writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Escape sequences drawing the changed cells of a frame, in the order
+/// [`Buffer::diff`] returns them
+fn escape_sequences(cells: &[(u16, u16, &Cell)]) -> String {
+    let mut output = String::new();
+    let mut cursor = None;
+    let mut style = None;
+    for &(x, y, cell) in cells {
+        if cursor != Some((x, y)) {
+            // Rows and columns count from 1
+            let _ = write!(output, "\x1b[{};{}H", y + 1, x + 1);
+        }
+        let cell_style = (cell.fg, cell.bg, cell.modifier);
+        if style != Some(cell_style) {
+            output.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+            style = Some(cell_style);
+        }
+        output.push_str(cell.symbol());
+        // The diff skips the cells covered by wide characters, so the next
+        // cell is only right after this one if this one is narrow
+        cursor = Some((x + 1, y));
+    }
+    output
+}
+
+/// Select Graphic Rendition sequence setting a style from scratch
+fn sgr(fg: Color, bg: Color, modifier: Modifier) -> String {
+    let mut codes = vec!["0".to_string()];
+    const MODIFIERS: [(Modifier, &str); 9] = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    codes.extend(
+        MODIFIERS
+            .iter()
+            .filter(|(flag, _)| modifier.contains(*flag))
+            .map(|(_, code)| code.to_string()),
+    );
+    codes.extend(color_code(fg, false));
+    codes.extend(color_code(bg, true));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// SGR parameters of a foreground or background color, `None` for the
+/// terminal's default
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let offset = if background { 10 } else { 0 };
+    let basic = |code: u8| Some((code + offset).to_string());
+    match color {
+        Color::Reset => None,
+        Color::Black => basic(30),
+        Color::Red => basic(31),
+        Color::Green => basic(32),
+        Color::Yellow => basic(33),
+        Color::Blue => basic(34),
+        Color::Magenta => basic(35),
+        Color::Cyan => basic(36),
+        Color::Gray => basic(37),
+        Color::DarkGray => basic(90),
+        Color::LightRed => basic(91),
+        Color::LightGreen => basic(92),
+        Color::LightYellow => basic(93),
+        Color::LightBlue => basic(94),
+        Color::LightMagenta => basic(95),
+        Color::LightCyan => basic(96),
+        Color::White => basic(97),
+        Color::Indexed(index) => Some(format!("{};5;{}", 38 + offset, index)),
+        Color::Rgb(r, g, b) => Some(format!("{};2;{};{};{}", 38 + offset, r, g, b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    fn events(recorder: CastRecorder<Vec<u8>>) -> Vec<serde_json::Value> {
+        let output = String::from_utf8(recorder.into_inner()).unwrap();
+        output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_records_header_and_changes() {
+        let mut recorder = CastRecorder::new(Vec::new(), 6, 2).unwrap();
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "2048", Style::default());
+        recorder.record(&buffer).unwrap();
+        // Nothing changed, nothing recorded
+        recorder.record(&buffer).unwrap();
+        buffer.set_string(1, 1, "x", Style::default().fg(Color::Red));
+        recorder.record(&buffer).unwrap();
+
+        let events = events(recorder);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["version"], 2);
+        assert_eq!(events[0]["width"], 6);
+        assert_eq!(events[0]["height"], 2);
+        assert_eq!(events[1][1], "o");
+        assert_eq!(events[1][2], "\x1b[0m\x1b[2J\x1b[1;1H\x1b[0m2048");
+        assert_eq!(events[2][2], "\x1b[2;2H\x1b[0;31mx");
+        assert!(events[2][0].as_f64() >= events[1][0].as_f64());
+    }
+
+    #[test]
+    fn test_resize_redraws_the_frame() {
+        let mut recorder = CastRecorder::new(Vec::new(), 4, 1).unwrap();
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buffer.set_string(0, 0, "ab", Style::default().bg(Color::Indexed(17)));
+        recorder.record(&buffer).unwrap();
+        buffer.resize(Rect::new(0, 0, 3, 2));
+        recorder.record(&buffer).unwrap();
+
+        let events = events(recorder);
+        assert_eq!(events[2][1], "r");
+        assert_eq!(events[2][2], "3x2");
+        assert_eq!(events[3][2], "\x1b[0m\x1b[2J\x1b[1;1H\x1b[0;48;5;17mab");
+    }
+
+    #[test]
+    fn test_sgr_combines_modifiers_and_colors() {
+        let modifier = Modifier::BOLD | Modifier::REVERSED;
+        assert_eq!(
+            sgr(Color::Rgb(1, 2, 3), Color::LightBlue, modifier),
+            "\x1b[0;1;7;38;2;1;2;3;104m"
+        );
+        assert_eq!(
+            sgr(Color::Reset, Color::Reset, Modifier::empty()),
+            "\x1b[0m"
+        );
+    }
+}
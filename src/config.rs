@@ -0,0 +1,256 @@
+//! Configuration module for the 2048 game.
+//!
+//! This module loads user settings (theme, key bindings, animations, tile size,
+//! board size and spawn odds) from a TOML file in the platform config
+//! directory, and saves the changes made on the settings screen back to it.
+//! With the `watch` feature the file can also be watched for changes while the
+//! game is running.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GameError, GameResult};
+use crate::game::{DEFAULT_SIZE, FOUR_CHANCE, Game2048};
+use crate::keymap::Keymap;
+use crate::profile;
+use crate::ui::TileLabels;
+use crate::ui::theme::Theme;
+
+/// Name of the config file inside the config directory
+const CONFIG_FILE: &str = "config.toml";
+
+/// Default minimum time between two moves in the same direction, in
+/// milliseconds; terminals repeat held keys about every 30 ms
+pub const DEFAULT_MOVE_INTERVAL_MS: u64 = 50;
+
+/// Default idle time before the main menu plays a demo game, in seconds
+pub const DEFAULT_DEMO_AFTER_SECS: u64 = 30;
+
+/// Default minutes of play without a break before a break is suggested
+pub const DEFAULT_BREAK_REMINDER_MINS: u64 = 60;
+
+/// User configuration loaded from the config file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Color theme for the tiles
+    pub theme: Theme,
+    /// Whether visual effects such as tile highlights are shown
+    pub animations: bool,
+    /// Whether tiles are drawn with large block digits on big enough terminals
+    pub big_tiles: bool,
+    /// How tile numbers are written: `compact` abbreviates tiles from 1024 on
+    /// (`1k`, `2k`, …), `exact` writes them out in wider cells
+    pub tile_labels: TileLabels,
+    /// Number of rows and columns of new boards, unless the mode fixes it
+    pub size: usize,
+    /// Chance that a spawned tile is a 4 instead of a 2
+    pub four_chance: f64,
+    /// Chance that a spawned tile is an 8 instead of a 2 in hard mode
+    /// (`--hard`); other games never spawn 8s
+    pub eight_chance: f64,
+    /// Minimum time between two moves in the same direction in milliseconds,
+    /// so a held key doesn't queue up moves faster than they can be seen
+    pub move_interval_ms: u64,
+    /// Most frames drawn per second, `0` for no limit. Frames are only drawn
+    /// when something shown changed, so this only matters while keys arrive
+    /// faster, e.g. when held down.
+    pub max_fps: u32,
+    /// Seconds the main menu waits for a key before playing a demo game, `0`
+    /// for no demo
+    pub demo_after_secs: u64,
+    /// Minutes of play without a break after which a break is suggested,
+    /// and again after as many more, `0` for no reminders
+    pub break_reminder_mins: u64,
+    /// Key binding overrides, mapping action names to key names
+    pub keys: HashMap<String, Vec<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            animations: true,
+            big_tiles: false,
+            tile_labels: TileLabels::default(),
+            size: DEFAULT_SIZE,
+            four_chance: FOUR_CHANCE,
+            eight_chance: 0.0,
+            move_interval_ms: DEFAULT_MOVE_INTERVAL_MS,
+            max_fps: 0,
+            demo_after_secs: DEFAULT_DEMO_AFTER_SECS,
+            break_reminder_mins: DEFAULT_BREAK_REMINDER_MINS,
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Default location of the config file, if a config directory exists
+    pub fn default_path() -> Option<PathBuf> {
+        profile::config_dir().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    /// Load the config from a file, falling back to defaults if it is missing
+    pub fn load(path: &Path) -> GameResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    /// Parse the config from TOML text
+    pub fn parse(contents: &str) -> GameResult<Self> {
+        let config: Config = toml::from_str(contents)
+            .map_err(|e| GameError::ConfigError(format!("Invalid config: {}", e.message())))?;
+        // Validate the key bindings and board settings up front so a bad file
+        // is reported as a whole
+        config.keymap()?;
+        Game2048::builder()
+            .size(config.size)
+            .four_chance(config.four_chance)
+            .eight_chance(config.eight_chance)
+            .build()?;
+        Ok(config)
+    }
+
+    /// Write the config to a file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self)
+            .map_err(|e| GameError::SerializationError(format!("Cannot encode config: {}", e)))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Build the keymap described by this config
+    pub fn keymap(&self) -> GameResult<Keymap> {
+        Keymap::with_overrides(&self.keys)
+    }
+}
+
+/// Watches the config file and reports when it changes
+#[cfg(feature = "watch")]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    // Kept alive so the OS watch stays registered
+    _watcher: notify::RecommendedWatcher,
+    events: std::sync::mpsc::Receiver<()>,
+}
+
+#[cfg(feature = "watch")]
+impl ConfigWatcher {
+    /// Start watching the given config file
+    pub fn new(path: PathBuf) -> GameResult<Self> {
+        use notify::{RecursiveMode, Watcher};
+
+        // Editors often replace the file instead of writing it in place,
+        // so watch the parent directory and filter by file name
+        let dir = path
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| GameError::ConfigError("Config path has no parent".to_string()))?;
+        fs::create_dir_all(&dir)?;
+
+        let (tx, events) = std::sync::mpsc::channel();
+        let file_name = path.file_name().map(|n| n.to_os_string());
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res {
+                let relevant = (event.kind.is_create() || event.kind.is_modify())
+                    && event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name().map(|n| n.to_os_string()) == file_name);
+                if relevant {
+                    let _ = tx.send(());
+                }
+            }
+        })
+        .map_err(|e| GameError::ConfigError(format!("Cannot watch config: {}", e)))?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| GameError::ConfigError(format!("Cannot watch config: {}", e)))?;
+
+        Ok(Self {
+            path,
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Reload the config if the file changed since the last call.
+    ///
+    /// Returns `None` when nothing changed.
+    pub fn poll(&self) -> Option<GameResult<Config>> {
+        let mut changed = false;
+        // A single save can produce several events, collapse them
+        while self.events.try_recv().is_ok() {
+            changed = true;
+        }
+        changed.then(|| Config::load(&self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            r#"
+            theme = "ocean"
+            animations = false
+
+            [keys]
+            up = ["k", "Up"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.theme, Theme::Ocean);
+        assert!(!config.animations);
+        assert_eq!(config.keys["up"], vec!["k", "Up"]);
+        assert_eq!(config.tile_labels, TileLabels::Compact);
+        let config = Config::parse("tile_labels = \"exact\"").unwrap();
+        assert_eq!(config.tile_labels, TileLabels::Exact);
+    }
+
+    #[test]
+    fn test_parse_config_rejects_unknown_action() {
+        assert!(Config::parse("[keys]\njump = [\"j\"]").is_err());
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_save_and_reload_config() {
+        let mut config = Config {
+            theme: Theme::Ember,
+            size: 5,
+            four_chance: 0.25,
+            eight_chance: 0.05,
+            ..Config::default()
+        };
+        config
+            .keys
+            .insert("undo".to_string(), vec!["Backspace".to_string()]);
+        let path =
+            std::env::temp_dir().join(format!("tui_2048_config_{}.toml", std::process::id()));
+        config.save(&path).unwrap();
+        assert_eq!(Config::load(&path).unwrap(), config);
+        fs::remove_file(path).unwrap();
+
+        assert!(Config::parse("size = 1").is_err());
+        assert!(Config::parse("four_chance = 1.5").is_err());
+        assert!(Config::parse("four_chance = 0.5\neight_chance = 0.6").is_err());
+    }
+}
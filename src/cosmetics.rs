@@ -0,0 +1,224 @@
+//! Cosmetic rewards for achievements.
+//!
+//! Border styles and color accents are unlocked by milestones such as
+//! achievements or merge chain counts. The selected ones are stored with the
+//! achievements, so they carry over to the next run.
+
+use std::fmt;
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::achievements::{Achievement, Achievements};
+use crate::i18n;
+
+/// Characters drawn around each tile
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderStyle {
+    /// Thin lines with square corners
+    #[default]
+    Square,
+    /// Thin lines with rounded corners
+    Rounded,
+    /// Double lines
+    Double,
+    /// Thick lines
+    Thick,
+}
+
+impl BorderStyle {
+    /// Top left, top right, bottom left and bottom right corners, then the
+    /// horizontal and vertical lines
+    pub fn chars(self) -> [char; 6] {
+        match self {
+            BorderStyle::Square => ['┌', '┐', '└', '┘', '─', '│'],
+            BorderStyle::Rounded => ['╭', '╮', '╰', '╯', '─', '│'],
+            BorderStyle::Double => ['╔', '╗', '╚', '╝', '═', '║'],
+            BorderStyle::Thick => ['┏', '┓', '┗', '┛', '━', '┃'],
+        }
+    }
+}
+
+/// Color of the frame around the board and of the score line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Accent {
+    /// The terminal's default color
+    #[default]
+    None,
+    Gold,
+    Emerald,
+    Crimson,
+}
+
+impl Accent {
+    /// Color of the accent, `None` for the terminal default
+    pub fn color(self) -> Option<Color> {
+        match self {
+            Accent::None => None,
+            Accent::Gold => Some(Color::Yellow),
+            Accent::Emerald => Some(Color::Green),
+            Accent::Crimson => Some(Color::Red),
+        }
+    }
+}
+
+/// What has to happen before a cosmetic can be selected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Milestone {
+    /// Available from the start
+    Start,
+    /// An achievement was unlocked
+    Achievement(Achievement),
+    /// Lines with more than one merge, over all games
+    Chains(u32),
+    /// Moves with a quad merge, over all games
+    QuadMerges(u32),
+}
+
+impl Milestone {
+    /// Whether the milestone was reached
+    pub fn is_reached(self, achievements: &Achievements) -> bool {
+        match self {
+            Milestone::Start => true,
+            Milestone::Achievement(achievement) => achievements.is_unlocked(achievement),
+            Milestone::Chains(count) => achievements.chains >= count,
+            Milestone::QuadMerges(count) => achievements.quad_merges >= count,
+        }
+    }
+}
+
+impl fmt::Display for Milestone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Milestone::Start => i18n::text("cosmetics.start").to_string(),
+            Milestone::Achievement(achievement) => {
+                i18n::format("cosmetics.unlock", &[("name", &achievement.name())])
+            }
+            Milestone::Chains(count) => i18n::format("cosmetics.chains", &[("count", count)]),
+            Milestone::QuadMerges(count) => {
+                i18n::format("cosmetics.quad_merges", &[("count", count)])
+            }
+        };
+        f.write_str(&text)
+    }
+}
+
+/// A border style or accent that can be selected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cosmetic {
+    Border(BorderStyle),
+    Accent(Accent),
+}
+
+impl Cosmetic {
+    /// All cosmetics, in the order they are listed
+    pub const ALL: [Cosmetic; 8] = [
+        Cosmetic::Border(BorderStyle::Square),
+        Cosmetic::Border(BorderStyle::Rounded),
+        Cosmetic::Border(BorderStyle::Double),
+        Cosmetic::Border(BorderStyle::Thick),
+        Cosmetic::Accent(Accent::None),
+        Cosmetic::Accent(Accent::Emerald),
+        Cosmetic::Accent(Accent::Gold),
+        Cosmetic::Accent(Accent::Crimson),
+    ];
+
+    /// Name shown on the cosmetics screen
+    pub fn name(self) -> &'static str {
+        match self {
+            Cosmetic::Border(BorderStyle::Square) => "Square border",
+            Cosmetic::Border(BorderStyle::Rounded) => "Rounded border",
+            Cosmetic::Border(BorderStyle::Double) => "Double border",
+            Cosmetic::Border(BorderStyle::Thick) => "Thick border",
+            Cosmetic::Accent(Accent::None) => "No accent",
+            Cosmetic::Accent(Accent::Emerald) => "Emerald accent",
+            Cosmetic::Accent(Accent::Gold) => "Gold accent",
+            Cosmetic::Accent(Accent::Crimson) => "Crimson accent",
+        }
+    }
+
+    /// Milestone that unlocks the cosmetic
+    pub fn milestone(self) -> Milestone {
+        match self {
+            Cosmetic::Border(BorderStyle::Square) | Cosmetic::Accent(Accent::None) => {
+                Milestone::Start
+            }
+            Cosmetic::Border(BorderStyle::Rounded) => {
+                Milestone::Achievement(Achievement::DoubleMerge)
+            }
+            Cosmetic::Border(BorderStyle::Double) => Milestone::Achievement(Achievement::QuadMerge),
+            Cosmetic::Border(BorderStyle::Thick) => Milestone::QuadMerges(10),
+            Cosmetic::Accent(Accent::Emerald) => Milestone::Chains(10),
+            Cosmetic::Accent(Accent::Gold) => Milestone::Chains(100),
+            Cosmetic::Accent(Accent::Crimson) => Milestone::QuadMerges(3),
+        }
+    }
+
+    /// Cosmetics whose milestone was reached
+    pub fn unlocked(achievements: &Achievements) -> Vec<Cosmetic> {
+        Cosmetic::ALL
+            .into_iter()
+            .filter(|cosmetic| cosmetic.milestone().is_reached(achievements))
+            .collect()
+    }
+}
+
+/// The selected border style and accent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Cosmetics {
+    pub border: BorderStyle,
+    pub accent: Accent,
+}
+
+impl Cosmetics {
+    /// Whether a cosmetic is the selected one of its kind
+    pub fn is_selected(&self, cosmetic: Cosmetic) -> bool {
+        match cosmetic {
+            Cosmetic::Border(border) => self.border == border,
+            Cosmetic::Accent(accent) => self.accent == accent,
+        }
+    }
+
+    /// Select a cosmetic, replacing the one of the same kind
+    pub fn select(&mut self, cosmetic: Cosmetic) {
+        match cosmetic {
+            Cosmetic::Border(border) => self.border = border,
+            Cosmetic::Accent(accent) => self.accent = accent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game2048, GameEngine, GameView, MovementDirection};
+
+    #[test]
+    fn test_milestones_unlock_cosmetics() {
+        let mut achievements = Achievements::default();
+        assert_eq!(
+            Cosmetic::unlocked(&achievements),
+            vec![
+                Cosmetic::Border(BorderStyle::Square),
+                Cosmetic::Accent(Accent::None)
+            ]
+        );
+
+        let board = vec![vec![2, 2, 4, 4], vec![8, 8, 16, 16], vec![0; 4], vec![0; 4]];
+        let mut game = Game2048::with_board(board, 1);
+        game.move_in_direction(&MovementDirection::Left);
+        achievements.record(game.last_move().unwrap());
+        let unlocked = Cosmetic::unlocked(&achievements);
+        assert!(unlocked.contains(&Cosmetic::Border(BorderStyle::Double)));
+        assert!(!unlocked.contains(&Cosmetic::Accent(Accent::Emerald)));
+
+        assert!(!achievements.equip(Cosmetic::Accent(Accent::Gold)));
+        assert!(achievements.equip(Cosmetic::Border(BorderStyle::Double)));
+        let cosmetics = achievements.cosmetics;
+        assert!(cosmetics.is_selected(Cosmetic::Border(BorderStyle::Double)));
+        assert!(cosmetics.is_selected(Cosmetic::Accent(Accent::None)));
+    }
+}
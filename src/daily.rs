@@ -0,0 +1,105 @@
+//! Daily challenge mode.
+//!
+//! Every player gets the same tile sequence on the same (UTC) day, because the
+//! game seed is derived from the date. Best scores are kept per day.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::date::Date;
+use crate::error::{GameError, GameResult};
+use crate::profile;
+
+/// Name of the daily scores file inside the data directory
+const DAILY_FILE: &str = "daily.toml";
+
+/// Seed for the daily challenge of the given date
+pub fn daily_seed(date: Date) -> u64 {
+    // SplitMix64 finalizer, so consecutive days get unrelated seeds
+    let mut z = (date.year as u64) * 10_000 + u64::from(date.month) * 100 + u64::from(date.day);
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Best daily challenge scores, keyed by date
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyScores {
+    best: BTreeMap<String, u64>,
+}
+
+impl DailyScores {
+    /// Default location of the daily scores file, if a data directory exists
+    pub fn default_path() -> Option<PathBuf> {
+        profile::data_dir().map(|dir| dir.join(DAILY_FILE))
+    }
+
+    /// Load the scores from a file, starting empty if it is missing
+    pub fn load(path: &Path) -> GameResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            GameError::SaveError(format!(
+                "Corrupt daily scores {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })
+    }
+
+    /// Write the scores to a file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(|e| {
+            GameError::SerializationError(format!("Cannot encode daily scores: {}", e))
+        })?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Best score for a day, if it was played
+    pub fn best(&self, date: Date) -> Option<u64> {
+        self.best.get(&date.to_string()).copied()
+    }
+
+    /// Record a score, returning `true` if it is a new best for the day
+    pub fn record(&mut self, date: Date, score: u64) -> bool {
+        let best = self.best.entry(date.to_string()).or_insert(0);
+        if score > *best {
+            *best = score;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_seed_differs_per_day() {
+        let today = Date::from_days_since_epoch(20_000);
+        let tomorrow = Date::from_days_since_epoch(20_001);
+        assert_eq!(daily_seed(today), daily_seed(today));
+        assert_ne!(daily_seed(today), daily_seed(tomorrow));
+    }
+
+    #[test]
+    fn test_record_keeps_best() {
+        let date = Date::from_days_since_epoch(20_000);
+        let mut scores = DailyScores::default();
+        assert!(scores.record(date, 500));
+        assert!(!scores.record(date, 300));
+        assert_eq!(scores.best(date), Some(500));
+    }
+}
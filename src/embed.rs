@@ -0,0 +1,190 @@
+//! Embedding the game in another ratatui application.
+//!
+//! [`EmbeddedGame`] is the game without a terminal or event loop of its own:
+//! the host application forwards key events to it and lets it draw into a
+//! part of each frame.
+//!
+//! ```no_run
+//! # use ratatui::{Frame, layout::Rect};
+//! # use crossterm::event::KeyEvent;
+//! use tui_2048::embed::{EmbeddedGame, KeyResponse};
+//! use tui_2048::prelude::*;
+//!
+//! let mut game = EmbeddedGame::new(Game2048::default(), DefaultRenderer::default());
+//! # let key: KeyEvent = todo!();
+//! # let (area, frame): (Rect, &mut Frame) = todo!();
+//! // In the host's event loop
+//! if game.handle_key(key) == KeyResponse::QuitRequested {
+//!     // e.g. close the game's pane
+//! }
+//! // In the host's draw callback
+//! game.run_embedded(area, frame);
+//! ```
+
+use std::time::Instant;
+
+use crossterm::event::{KeyEvent, KeyEventKind};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    widgets::{Block, Borders},
+};
+
+use crate::app::HIGHLIGHT_DURATION;
+use crate::config::Config;
+use crate::error::GameResult;
+use crate::game::{GameEngine, MovementDirection};
+use crate::keymap::{Action, Keymap};
+use crate::ui::GameRenderer;
+
+/// What the game did with a key event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyResponse {
+    /// The key is not bound to anything, the host may use it
+    Ignored,
+    /// The key was used by the game
+    Handled,
+    /// The player pressed the quit key; closing the game is up to the host
+    QuitRequested,
+}
+
+/// A game driven by a host application
+pub struct EmbeddedGame<G: GameEngine, R: GameRenderer> {
+    game: G,
+    renderer: R,
+    keymap: Keymap,
+    animations: bool,
+    highlight_until: Option<Instant>,
+}
+
+impl<G: GameEngine, R: GameRenderer> EmbeddedGame<G, R> {
+    /// Create an embedded game with the default key bindings
+    pub fn new(game: G, renderer: R) -> Self {
+        Self {
+            game,
+            renderer,
+            keymap: Keymap::default(),
+            animations: true,
+            highlight_until: None,
+        }
+    }
+
+    /// Apply the theme, tile label, key binding and animation settings from
+    /// a config
+    pub fn with_config(mut self, config: &Config) -> GameResult<Self> {
+        self.keymap = config.keymap()?;
+        self.renderer.set_theme(config.theme);
+        self.renderer.set_tile_labels(config.tile_labels);
+        self.animations = config.animations;
+        Ok(self)
+    }
+
+    /// The game being played
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    /// Handle a key event forwarded by the host
+    pub fn handle_key(&mut self, key: KeyEvent) -> KeyResponse {
+        if key.kind == KeyEventKind::Release {
+            return KeyResponse::Ignored;
+        }
+        let direction = match self.keymap.action(key.code) {
+            // Previews need key release events, panels and the pause a
+            // screen of their own, and snapshots and saves are left to the host
+            None
+            | Some(
+                Action::PeekUndo
+                | Action::Pause
+                | Action::ShowStats
+                | Action::ShowLeaderboard
+                | Action::ShowAchievements
+                | Action::ShowHelp
+                | Action::ShowCosmetics
+                | Action::ShowPuzzles
+                | Action::ShowSettings
+                | Action::Snapshot
+                | Action::SaveSlot
+                | Action::ShowHistory
+                | Action::ToggleHeatmap,
+            ) => return KeyResponse::Ignored,
+            Some(Action::Quit) => return KeyResponse::QuitRequested,
+            Some(Action::Undo) => {
+                self.game.undo();
+                self.highlight_until = None;
+                return KeyResponse::Handled;
+            }
+            Some(Action::Restart) => {
+                if self.game.game_over() {
+                    self.game.reset();
+                    self.highlight_until = None;
+                }
+                return KeyResponse::Handled;
+            }
+            Some(Action::MoveUp) => MovementDirection::Up,
+            Some(Action::MoveDown) => MovementDirection::Down,
+            Some(Action::MoveLeft) => MovementDirection::Left,
+            Some(Action::MoveRight) => MovementDirection::Right,
+        };
+        if self.game.move_in_direction(&direction) && self.animations {
+            self.highlight_until = Some(Instant::now() + HIGHLIGHT_DURATION);
+        }
+        KeyResponse::Handled
+    }
+
+    /// Draw the game into `area` of the host's frame. Call this every frame.
+    pub fn run_embedded(&mut self, area: Rect, frame: &mut Frame) {
+        let highlight = self
+            .highlight_until
+            .is_some_and(|until| Instant::now() < until);
+        self.renderer.set_highlight(highlight);
+
+        let title = if self.game.game_over() {
+            "2048 - Game over, press r to restart"
+        } else {
+            "2048"
+        };
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        self.renderer.render(frame, inner, &self.game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game2048, GameView};
+    use crate::ui::NoColorRenderer;
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    #[test]
+    fn test_embedded_game_handles_keys_and_draws() {
+        let board = vec![vec![2, 2, 0, 0], vec![0; 4], vec![0; 4], vec![0; 4]];
+        let mut embedded = EmbeddedGame::new(Game2048::with_board(board, 1), NoColorRenderer);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert_eq!(
+            embedded.handle_key(key(KeyCode::Left)),
+            KeyResponse::Handled
+        );
+        assert_eq!(embedded.game().board()[0][0], 4);
+        assert_eq!(
+            embedded.handle_key(key(KeyCode::F(5))),
+            KeyResponse::Ignored
+        );
+        assert_eq!(
+            embedded.handle_key(key(KeyCode::Char('q'))),
+            KeyResponse::QuitRequested
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal
+            .draw(|f| embedded.run_embedded(Rect::new(5, 2, 40, 17), f))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row: String = (5..15).map(|x| buffer[(x, 2)].symbol()).collect();
+        assert!(row.contains("2048"));
+    }
+}
@@ -17,6 +17,45 @@ pub enum GameError {
     GameStateError(String),
     /// Terminal setup errors
     TerminalError(String),
+    /// A file kept between games, like a save, the leaderboard or a replay,
+    /// is corrupt
+    SaveError(String),
+    /// The config file is invalid or can't be watched
+    ConfigError(String),
+    /// The connection to a network opponent or a spectated game failed
+    NetworkError(String),
+    /// Data couldn't be encoded to or decoded from TOML or JSON
+    SerializationError(String),
+    /// A terminal operation failed, e.g. drawing the screen or reading a key
+    Terminal {
+        /// What the game was doing, like "drawing the screen"
+        operation: &'static str,
+        source: io::Error,
+    },
+}
+
+impl GameError {
+    /// A sentence telling the player what may have gone wrong, for errors
+    /// that end the game
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            GameError::Terminal { .. } | GameError::TerminalError(_) => Some(
+                "The game needs an interactive terminal; check that input and output \
+                 aren't redirected and that the terminal is still open.",
+            ),
+            GameError::InputError(_) => Some("Run with --help to see the options."),
+            GameError::ConfigError(_) => {
+                Some("Fix or remove the config file, or pick another one with --config.")
+            }
+            GameError::SaveError(_) => {
+                Some("The file may have been damaged; move it away to start afresh.")
+            }
+            GameError::NetworkError(_) => {
+                Some("Check the address and that the other player is still connected.")
+            }
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for GameError {
@@ -26,6 +65,13 @@ impl fmt::Display for GameError {
             GameError::InputError(msg) => write!(f, "Input error: {}", msg),
             GameError::GameStateError(msg) => write!(f, "Game state error: {}", msg),
             GameError::TerminalError(msg) => write!(f, "Terminal error: {}", msg),
+            GameError::SaveError(msg) => write!(f, "Save error: {}", msg),
+            GameError::ConfigError(msg) => write!(f, "Config error: {}", msg),
+            GameError::NetworkError(msg) => write!(f, "Network error: {}", msg),
+            GameError::SerializationError(msg) => write!(f, "Serialization error: {}", msg),
+            GameError::Terminal { operation, source } => {
+                write!(f, "Terminal error while {}: {}", operation, source)
+            }
         }
     }
 }
@@ -33,7 +79,7 @@ impl fmt::Display for GameError {
 impl std::error::Error for GameError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            GameError::IoError(err) => Some(err),
+            GameError::IoError(err) | GameError::Terminal { source: err, .. } => Some(err),
             _ => None,
         }
     }
@@ -45,5 +91,66 @@ impl From<io::Error> for GameError {
     }
 }
 
+impl From<toml::de::Error> for GameError {
+    fn from(err: toml::de::Error) -> Self {
+        GameError::SerializationError(err.message().to_string())
+    }
+}
+
+impl From<toml::ser::Error> for GameError {
+    fn from(err: toml::ser::Error) -> Self {
+        GameError::SerializationError(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for GameError {
+    fn from(err: serde_json::Error) -> Self {
+        GameError::SerializationError(err.to_string())
+    }
+}
+
 /// Result type alias for Game operations
 pub type GameResult<T> = Result<T, GameError>;
+
+/// Tells which terminal operation an I/O error of crossterm or ratatui comes
+/// from, turning it into a [`GameError::Terminal`]
+pub trait TerminalContext<T> {
+    /// Name the operation that failed, like "reading a key"
+    fn context(self, operation: &'static str) -> GameResult<T>;
+}
+
+impl<T> TerminalContext<T> for io::Result<T> {
+    fn context(self, operation: &'static str) -> GameResult<T> {
+        self.map_err(|source| GameError::Terminal { operation, source })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_terminal_errors_name_the_operation() {
+        let result: io::Result<()> = Err(io::Error::new(io::ErrorKind::BrokenPipe, "pipe closed"));
+        let error = result.context("drawing the screen").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Terminal error while drawing the screen: pipe closed"
+        );
+        assert!(error.source().is_some());
+        assert!(error.hint().is_some());
+    }
+
+    #[test]
+    fn test_serialization_errors_convert() {
+        let toml_error = toml::from_str::<toml::Table>("a = ").unwrap_err();
+        assert!(matches!(
+            GameError::from(toml_error),
+            GameError::SerializationError(_)
+        ));
+        let json_error = serde_json::from_str::<u32>("x").unwrap_err();
+        let error = GameError::from(json_error);
+        assert!(error.to_string().starts_with("Serialization error: "));
+    }
+}
@@ -0,0 +1,541 @@
+//! Animations of finished games and snapshots of positions.
+//!
+//! The boards of a game, one per move, are drawn as a plain grid of tiles in
+//! the classic colors and saved as an animated SVG, which plays in any
+//! browser, or as an animated GIF for places that only take images. Both are
+//! drawn here, without a graphics library: the SVG is written as text and
+//! the GIF frames are rasterized with a small built-in digit font.
+//!
+//! Single positions are shared as snapshots in any [`SnapshotFormat`]: a
+//! text grid with [`TextSnapshot`], a PNG image with [`PngSnapshot`], or
+//! formats of other crates.
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ratatui::style::Color;
+
+use crate::error::{GameError, GameResult};
+use crate::profile;
+use crate::ui::theme::Theme;
+
+/// Time each board is shown for
+pub const FRAME_DELAY: Duration = Duration::from_millis(250);
+
+/// Time the final board is shown for before a GIF starts over
+pub const FINAL_DELAY: Duration = Duration::from_secs(3);
+
+/// Side of a tile in pixels
+const CELL: usize = 64;
+/// Space between tiles and around the grid in pixels
+const GAP: usize = 8;
+
+const BACKGROUND: (u8, u8, u8) = (187, 173, 160);
+const EMPTY_CELL: (u8, u8, u8) = (205, 193, 180);
+const DARK_TEXT: (u8, u8, u8) = (119, 110, 101);
+const LIGHT_TEXT: (u8, u8, u8) = (249, 246, 242);
+
+/// Exponent of the largest tile with a color of its own; larger tiles get
+/// its color
+const MAX_COLOR_EXPONENT: u32 = 17;
+
+/// File format of an exported game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Svg,
+    Gif,
+}
+
+impl ExportFormat {
+    /// Format named by the extension of a file
+    pub fn from_path(path: &Path) -> GameResult<Self> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        match extension.to_ascii_lowercase().as_str() {
+            "svg" => Ok(ExportFormat::Svg),
+            "gif" => Ok(ExportFormat::Gif),
+            _ => Err(GameError::InputError(format!(
+                "Cannot tell the format of {}, use a .svg or .gif file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// File extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Svg => "svg",
+            ExportFormat::Gif => "gif",
+        }
+    }
+}
+
+/// Directory where the game over screen saves its exports, if a data
+/// directory exists
+pub fn default_dir() -> Option<PathBuf> {
+    profile::data_dir().map(|dir| dir.join("exports"))
+}
+
+/// Animation of the boards in a format, as the bytes of the file
+pub fn export(boards: &[Vec<Vec<u32>>], format: ExportFormat) -> GameResult<Vec<u8>> {
+    match format {
+        ExportFormat::Svg => svg(boards).map(String::into_bytes),
+        ExportFormat::Gif => gif(boards),
+    }
+}
+
+/// Width of the image of a board with `cells` columns in pixels, or its
+/// height for `cells` rows
+fn image_size(cells: usize) -> usize {
+    cells * CELL + (cells + 1) * GAP
+}
+
+/// Top left corner of a tile in pixels
+fn tile_origin(row: usize, col: usize) -> (usize, usize) {
+    (GAP + col * (CELL + GAP), GAP + row * (CELL + GAP))
+}
+
+/// Color of a tile and of its number
+fn tile_colors(value: u32) -> ((u8, u8, u8), (u8, u8, u8)) {
+    if value == 0 {
+        return (EMPTY_CELL, DARK_TEXT);
+    }
+    let exponent = value.ilog2().clamp(1, MAX_COLOR_EXPONENT);
+    let fill = match Theme::Classic.rgb_tile_color(1 << exponent) {
+        Color::Rgb(r, g, b) => (r, g, b),
+        _ => EMPTY_CELL,
+    };
+    let text = if value <= 4 { DARK_TEXT } else { LIGHT_TEXT };
+    (fill, text)
+}
+
+/// Rows and columns of the boards, checked to be a non-empty list of
+/// boards of one size, at least 2x2 like the boards of games
+fn board_dims(boards: &[Vec<Vec<u32>>]) -> GameResult<(usize, usize)> {
+    let first = boards
+        .first()
+        .ok_or_else(|| GameError::InputError("There are no boards to export".to_string()))?;
+    let (rows, columns) = (first.len(), first.first().map_or(0, Vec::len));
+    let fits =
+        |board: &Vec<Vec<u32>>| board.len() == rows && board.iter().all(|row| row.len() == columns);
+    if rows < 2 || columns < 2 || !boards.iter().all(fits) {
+        return Err(GameError::InputError(
+            "Exported boards must be at least 2x2 and of one size".to_string(),
+        ));
+    }
+    Ok((rows, columns))
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Animated SVG showing the boards one after the other, stopping on the
+/// last one
+pub fn svg(boards: &[Vec<Vec<u32>>]) -> GameResult<String> {
+    let (rows, columns) = board_dims(boards)?;
+    let (width, height) = (image_size(columns), image_size(rows));
+    let delay = FRAME_DELAY.as_secs_f64();
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="sans-serif" font-weight="bold" text-anchor="middle">"#,
+        width, height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="{}" height="{}" rx="6" fill="{}"/>"#,
+        width,
+        height,
+        hex(BACKGROUND)
+    );
+    for row in 0..rows {
+        for col in 0..columns {
+            let (x, y) = tile_origin(row, col);
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{2}" height="{2}" rx="3" fill="{3}"/>"#,
+                x,
+                y,
+                CELL,
+                hex(EMPTY_CELL)
+            );
+        }
+    }
+
+    // Every board is a hidden group shown for its turn; the last one stays
+    for (i, board) in boards.iter().enumerate() {
+        let begin = i as f64 * delay;
+        let _ = writeln!(svg, r#"<g visibility="hidden">"#);
+        if i + 1 == boards.len() {
+            let _ = writeln!(
+                svg,
+                r#"<set attributeName="visibility" to="visible" begin="{:.2}s" fill="freeze"/>"#,
+                begin
+            );
+        } else {
+            let _ = writeln!(
+                svg,
+                r#"<set attributeName="visibility" to="visible" begin="{:.2}s" dur="{:.2}s"/>"#,
+                begin, delay
+            );
+        }
+        for (row, values) in board.iter().enumerate() {
+            for (col, &value) in values.iter().enumerate().filter(|(_, v)| **v != 0) {
+                let (x, y) = tile_origin(row, col);
+                let (fill, text) = tile_colors(value);
+                let digits = value.to_string();
+                let font_size = match digits.len() {
+                    1 | 2 => 32,
+                    3 => 26,
+                    4 => 20,
+                    _ => 15,
+                };
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{2}" height="{2}" rx="3" fill="{3}"/><text x="{4}" y="{5}" font-size="{6}" fill="{7}" dominant-baseline="central">{8}</text>"#,
+                    x,
+                    y,
+                    CELL,
+                    hex(fill),
+                    x + CELL / 2,
+                    y + CELL / 2,
+                    font_size,
+                    hex(text),
+                    digits
+                );
+            }
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// Digits 0 to 9 in a 3x5 pixel font, row by row from the top, the most
+/// significant bit being the top left pixel
+const DIGITS: [u16; 10] = [
+    0b111_101_101_101_111,
+    0b010_110_010_010_111,
+    0b111_001_111_100_111,
+    0b111_001_111_001_111,
+    0b101_101_111_001_001,
+    0b111_100_111_001_111,
+    0b111_100_111_101_111,
+    0b111_001_001_001_001,
+    0b111_101_111_101_111,
+    0b111_101_111_001_111,
+];
+
+/// Palette of the images: the background, the empty cell, the two text colors,
+/// then the tile colors by exponent
+fn palette() -> Vec<u8> {
+    let mut colors = vec![BACKGROUND, EMPTY_CELL, DARK_TEXT, LIGHT_TEXT];
+    colors.extend((1..=MAX_COLOR_EXPONENT).map(|exponent| tile_colors(1 << exponent).0));
+    colors.into_iter().flat_map(|(r, g, b)| [r, g, b]).collect()
+}
+
+/// Palette indices of a tile and of its number
+fn palette_indices(value: u32) -> (u8, u8) {
+    if value == 0 {
+        return (1, 2);
+    }
+    let exponent = value.ilog2().clamp(1, MAX_COLOR_EXPONENT);
+    let text = if value <= 4 { 2 } else { 3 };
+    (3 + exponent as u8, text)
+}
+
+/// Pixels of a board as palette indices, row by row
+fn rasterize(board: &[Vec<u32>]) -> Vec<u8> {
+    let width = image_size(board.first().map_or(0, Vec::len));
+    let mut pixels = vec![0; width * image_size(board.len())];
+    let mut fill = |x: usize, y: usize, w: usize, h: usize, index: u8| {
+        for row in y..y + h {
+            pixels[row * width + x..row * width + x + w].fill(index);
+        }
+    };
+    for (row, values) in board.iter().enumerate() {
+        for (col, &value) in values.iter().enumerate() {
+            let (x, y) = tile_origin(row, col);
+            let (tile, text) = palette_indices(value);
+            fill(x, y, CELL, CELL, tile);
+            if value == 0 {
+                continue;
+            }
+            // Digits are 3 pixels wide with a pixel between them, scaled up
+            // as far as the number fits in the tile with a margin
+            let digits = value.to_string();
+            let units = 4 * digits.len() - 1;
+            let scale = ((CELL - 16) / units).clamp(1, 6);
+            let left = x + (CELL - units * scale) / 2;
+            let top = y + (CELL - 5 * scale) / 2;
+            for (i, digit) in digits.bytes().enumerate() {
+                let glyph = DIGITS[usize::from(digit - b'0')];
+                for bit in 0..15 {
+                    if glyph & (1 << (14 - bit)) != 0 {
+                        let (gx, gy) = (bit % 3, bit / 3);
+                        fill(
+                            left + (4 * i + gx) * scale,
+                            top + gy * scale,
+                            scale,
+                            scale,
+                            text,
+                        );
+                    }
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// Animated GIF showing the boards one after the other, in a loop
+pub fn gif(boards: &[Vec<Vec<u32>>]) -> GameResult<Vec<u8>> {
+    let (rows, columns) = board_dims(boards)?;
+    let (width, height) = (image_size(columns) as u16, image_size(rows) as u16);
+    let to_io = |e: gif::EncodingError| GameError::IoError(io::Error::other(e));
+    let mut bytes = Vec::new();
+    {
+        let mut encoder =
+            gif::Encoder::new(&mut bytes, width, height, &palette()).map_err(to_io)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(to_io)?;
+        for (i, board) in boards.iter().enumerate() {
+            let delay = if i + 1 == boards.len() {
+                FINAL_DELAY
+            } else {
+                FRAME_DELAY
+            };
+            let frame = gif::Frame {
+                width,
+                height,
+                // In hundredths of a second
+                delay: (delay.as_millis() / 10) as u16,
+                buffer: rasterize(board).into(),
+                ..gif::Frame::default()
+            };
+            encoder.write_frame(&frame).map_err(to_io)?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// A position to share: the board and how far the game got
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub board: Vec<Vec<u32>>,
+    pub score: u64,
+    pub moves: u32,
+}
+
+/// A file format for snapshots of a position
+pub trait SnapshotFormat {
+    /// File extension of the format
+    fn extension(&self) -> &'static str;
+
+    /// Bytes of a file showing the position
+    fn render(&self, position: &Position) -> GameResult<Vec<u8>>;
+}
+
+/// Snapshots as a grid drawn with box characters, with the score on top
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextSnapshot;
+
+impl TextSnapshot {
+    /// The position as text, also used to copy it to the clipboard
+    pub fn text(position: &Position) -> String {
+        let width = position
+            .board
+            .iter()
+            .flatten()
+            .map(|value| value.to_string().len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let columns = position.board.first().map_or(0, Vec::len);
+        let border = |left: &str, middle: &str, right: &str| {
+            let line = vec!["─".repeat(width + 2); columns];
+            format!("{}{}{}\n", left, line.join(middle), right)
+        };
+        let mut text = format!(
+            "2048 - score {}, {} moves\n",
+            position.score, position.moves
+        );
+        text.push_str(&border("┌", "┬", "┐"));
+        for (i, row) in position.board.iter().enumerate() {
+            if i > 0 {
+                text.push_str(&border("├", "┼", "┤"));
+            }
+            let cells: Vec<String> = row
+                .iter()
+                .map(|&value| match value {
+                    0 => format!(" {:width$} ", ""),
+                    _ => format!(" {:>width$} ", value),
+                })
+                .collect();
+            text.push_str(&format!("│{}│\n", cells.join("│")));
+        }
+        text.push_str(&border("└", "┴", "┘"));
+        text
+    }
+}
+
+impl SnapshotFormat for TextSnapshot {
+    fn extension(&self) -> &'static str {
+        "txt"
+    }
+
+    fn render(&self, position: &Position) -> GameResult<Vec<u8>> {
+        Ok(Self::text(position).into_bytes())
+    }
+}
+
+/// Snapshots as PNG images of the board, drawn like the GIF frames
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PngSnapshot;
+
+impl SnapshotFormat for PngSnapshot {
+    fn extension(&self) -> &'static str {
+        "png"
+    }
+
+    fn render(&self, position: &Position) -> GameResult<Vec<u8>> {
+        let (rows, columns) = board_dims(std::slice::from_ref(&position.board))?;
+        let to_io = |e: png::EncodingError| GameError::IoError(io::Error::other(e));
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(
+            &mut bytes,
+            image_size(columns) as u32,
+            image_size(rows) as u32,
+        );
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(palette());
+        let mut writer = encoder.write_header().map_err(to_io)?;
+        writer
+            .write_image_data(&rasterize(&position.board))
+            .map_err(to_io)?;
+        writer.finish().map_err(to_io)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boards() -> Vec<Vec<Vec<u32>>> {
+        vec![
+            vec![vec![2, 0], vec![0, 2]],
+            vec![vec![4, 0], vec![2, 0]],
+            vec![vec![4, 2], vec![2048, 0]],
+        ]
+    }
+
+    #[test]
+    fn test_svg_shows_every_board_in_turn() {
+        let svg = svg(&boards()).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<g visibility=\"hidden\">").count(), 3);
+        assert!(svg.contains(r#"begin="0.50s" fill="freeze""#));
+        assert!(svg.contains(">2048</text>"));
+        // The number of tiles drawn over the empty grid
+        assert_eq!(svg.matches("</text>").count(), 2 + 2 + 3);
+    }
+
+    #[test]
+    fn test_gif_has_a_frame_per_board() {
+        let bytes = gif(&boards()).unwrap();
+        let mut decoder = gif::DecodeOptions::new();
+        decoder.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = decoder.read_info(bytes.as_slice()).unwrap();
+        let pixels = image_size(2) as u16;
+        assert_eq!((decoder.width(), decoder.height()), (pixels, pixels));
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.buffer.as_ref(), rasterize(&boards()[delays.len()]));
+            delays.push(frame.delay);
+        }
+        assert_eq!(delays, [25, 25, 300]);
+    }
+
+    #[test]
+    fn test_rasterize_draws_digits_in_the_text_color() {
+        let pixels = rasterize(&[vec![8]]);
+        // Tile color around the number, light text color inside
+        assert_eq!(pixels[image_size(1) * GAP + GAP], 3 + 3);
+        assert!(pixels.contains(&3));
+        assert!(!pixels.contains(&2));
+    }
+
+    #[test]
+    fn test_text_snapshot_draws_a_grid() {
+        let position = Position {
+            board: vec![vec![2, 0], vec![16384, 8]],
+            score: 100,
+            moves: 7,
+        };
+        let text = TextSnapshot::text(&position);
+        assert_eq!(
+            text,
+            "2048 - score 100, 7 moves\n\
+             ┌───────┬───────┐\n\
+             │     2 │       │\n\
+             ├───────┼───────┤\n\
+             │ 16384 │     8 │\n\
+             └───────┴───────┘\n"
+        );
+        assert_eq!(TextSnapshot.render(&position).unwrap(), text.into_bytes());
+    }
+
+    #[test]
+    fn test_png_snapshot_decodes_to_the_board() {
+        let position = Position {
+            board: boards()[2].clone(),
+            score: 0,
+            moves: 0,
+        };
+        let bytes = PngSnapshot.render(&position).unwrap();
+        let mut decoder = png::Decoder::new(bytes.as_slice());
+        decoder.set_transformations(png::Transformations::IDENTITY);
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(info.width as usize, image_size(2));
+        assert_eq!(pixels, rasterize(&position.board));
+
+        // Rectangular boards are as wide as their columns
+        let wide = Position {
+            board: vec![vec![2, 0, 4], vec![0, 8, 0]],
+            score: 0,
+            moves: 0,
+        };
+        let bytes = PngSnapshot.render(&wide).unwrap();
+        let info = png::Decoder::new(bytes.as_slice())
+            .read_info()
+            .unwrap()
+            .info()
+            .clone();
+        assert_eq!(
+            (info.width as usize, info.height as usize),
+            (image_size(3), image_size(2))
+        );
+        assert!(svg(&[wide.board.clone(), vec![vec![2, 0], vec![0, 2]]]).is_err());
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("game.SVG")).unwrap(),
+            ExportFormat::Svg
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("a/game.gif")).unwrap(),
+            ExportFormat::Gif
+        );
+        assert!(ExportFormat::from_path(Path::new("game.png")).is_err());
+        assert!(svg(&[]).is_err());
+        assert!(gif(&[vec![vec![2, 0]]]).is_err());
+    }
+}
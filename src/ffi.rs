@@ -0,0 +1,180 @@
+//! C ABI for the game engine.
+//!
+//! Enabled with the `ffi` feature. The header `include/tui_2048.h` is
+//! generated from this module during the build. A game is an opaque pointer
+//! created with [`tui2048_new`] and released with [`tui2048_free`]:
+//!
+//! ```c
+//! Game2048 *game = tui2048_new(42);
+//! tui2048_move(game, TUI2048_LEFT);
+//! uint32_t cells[16];
+//! tui2048_board(game, cells, 16);
+//! tui2048_free(game);
+//! ```
+
+use crate::game::{Game2048, GameEngine, GameView, MovementDirection};
+
+/// Direction value for moving up
+pub const TUI2048_UP: u32 = 0;
+/// Direction value for moving down
+pub const TUI2048_DOWN: u32 = 1;
+/// Direction value for moving left
+pub const TUI2048_LEFT: u32 = 2;
+/// Direction value for moving right
+pub const TUI2048_RIGHT: u32 = 3;
+
+/// Create a default 4x4 game whose tile spawns are determined by `seed`.
+///
+/// The game must be released with [`tui2048_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn tui2048_new(seed: u64) -> *mut Game2048 {
+    Box::into_raw(Box::new(Game2048::with_seed(seed)))
+}
+
+/// Release a game. Passing NULL does nothing.
+///
+/// # Safety
+///
+/// `game` must be NULL or a pointer returned by [`tui2048_new`] that was not
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tui2048_free(game: *mut Game2048) {
+    if !game.is_null() {
+        // SAFETY: the pointer came from Box::into_raw in tui2048_new
+        drop(unsafe { Box::from_raw(game) });
+    }
+}
+
+/// Move the tiles in a direction (`TUI2048_UP` etc.).
+///
+/// Returns whether the board changed; unknown directions change nothing.
+///
+/// # Safety
+///
+/// `game` must be a valid game pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tui2048_move(game: *mut Game2048, direction: u32) -> bool {
+    let direction = match direction {
+        TUI2048_UP => MovementDirection::Up,
+        TUI2048_DOWN => MovementDirection::Down,
+        TUI2048_LEFT => MovementDirection::Left,
+        TUI2048_RIGHT => MovementDirection::Right,
+        _ => return false,
+    };
+    // SAFETY: the caller guarantees a valid game pointer
+    unsafe { &mut *game }.move_in_direction(&direction)
+}
+
+/// Undo the last move, returning whether there was one to undo.
+///
+/// # Safety
+///
+/// `game` must be a valid game pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tui2048_undo(game: *mut Game2048) -> bool {
+    // SAFETY: the caller guarantees a valid game pointer
+    unsafe { &mut *game }.undo()
+}
+
+/// Current score.
+///
+/// # Safety
+///
+/// `game` must be a valid game pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tui2048_score(game: *const Game2048) -> u64 {
+    // SAFETY: the caller guarantees a valid game pointer
+    unsafe { &*game }.score()
+}
+
+/// Whether no move is possible anymore.
+///
+/// # Safety
+///
+/// `game` must be a valid game pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tui2048_game_over(game: *const Game2048) -> bool {
+    // SAFETY: the caller guarantees a valid game pointer
+    unsafe { &*game }.game_over()
+}
+
+/// Number of rows of the board, which is the number of columns too unless
+/// the board is rectangular.
+///
+/// # Safety
+///
+/// `game` must be a valid game pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tui2048_size(game: *const Game2048) -> usize {
+    // SAFETY: the caller guarantees a valid game pointer
+    unsafe { &*game }.size()
+}
+
+/// Number of columns of the board.
+///
+/// # Safety
+///
+/// `game` must be a valid game pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tui2048_columns(game: *const Game2048) -> usize {
+    // SAFETY: the caller guarantees a valid game pointer
+    unsafe { &*game }.dims().1
+}
+
+/// Copy the board row by row into `cells` (0 for empty cells).
+///
+/// Returns the number of cells written, or 0 if `len` is smaller than
+/// rows * columns.
+///
+/// # Safety
+///
+/// `game` must be a valid game pointer and `cells` must point to at least
+/// `len` writable values.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tui2048_board(
+    game: *const Game2048,
+    cells: *mut u32,
+    len: usize,
+) -> usize {
+    // SAFETY: the caller guarantees a valid game pointer
+    let game = unsafe { &*game };
+    let (rows, columns) = game.dims();
+    let count = rows * columns;
+    let board = game.board();
+    if cells.is_null() || len < count {
+        return 0;
+    }
+    // SAFETY: the caller guarantees `len` writable values
+    let out = unsafe { std::slice::from_raw_parts_mut(cells, count) };
+    for (cell, &value) in out.iter_mut().zip(board.iter().flatten()) {
+        *cell = value;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_api_plays_a_game() {
+        let game = tui2048_new(7);
+        unsafe {
+            assert_eq!(tui2048_size(game), 4);
+            let mut cells = [0u32; 16];
+            assert_eq!(tui2048_board(game, cells.as_mut_ptr(), 15), 0);
+            assert_eq!(tui2048_board(game, cells.as_mut_ptr(), 16), 16);
+            assert_eq!(cells.iter().filter(|&&x| x != 0).count(), 2);
+
+            assert!(!tui2048_move(game, 99));
+            let moved = [TUI2048_UP, TUI2048_LEFT, TUI2048_DOWN, TUI2048_RIGHT]
+                .into_iter()
+                .any(|direction| tui2048_move(game, direction));
+            assert!(moved);
+            assert!(tui2048_undo(game));
+            assert_eq!(tui2048_score(game), 0);
+            assert!(!tui2048_game_over(game));
+            tui2048_free(game);
+        }
+    }
+}
@@ -3,15 +3,54 @@
 //! This module provides the game logic implementation for the 2048 game,
 //! including board manipulation, scoring, movement handling, and game state tracking.
 
+use std::collections::VecDeque;
+use std::ops::{Index, IndexMut};
+use std::sync::Arc;
+
 use rand::prelude::*;
+use rand_chacha::ChaCha12Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::error::{GameError, GameResult};
+use crate::i18n;
+
+pub mod bitboard;
+#[cfg(feature = "variants")]
+pub mod cube;
+mod spawn;
 
-/// Size of the game board (4x4 grid)
-pub const SIZE: usize = 4;
-/// Maximum number of moves that can be undone
-pub const UNDO_LIMIT: usize = 10;
+pub use bitboard::BitboardGame2048;
+pub use spawn::{AlwaysTwo, CornerBiased, NoSpawn, SpawnPolicy, StandardSpawn, TileOdds};
+
+/// Default size of the game board (4x4 grid)
+pub const DEFAULT_SIZE: usize = 4;
+/// Default maximum number of moves that can be undone
+pub const DEFAULT_UNDO_LIMIT: usize = 10;
+/// Default tile value that wins the game
+pub const WIN_TILE: u32 = 2048;
+/// Default chance that a spawned tile is a 4 instead of a 2
+pub const FOUR_CHANCE: f64 = 0.1;
+/// Largest tile; two of them don't merge, as their sum wouldn't fit
+pub const MAX_TILE: u32 = 1 << 31;
+/// Default number of tiles on a new board
+pub const INITIAL_TILES: usize = 2;
+/// Most past states a game keeps for browsing its history, unless its undo
+/// limit keeps more, so long and endless games don't grow without bound
+pub const HISTORY_LIMIT: usize = 500;
+/// Number of undos per game in hard mode
+pub const HARD_MODE_UNDOS: u32 = 3;
+/// Largest combo multiplier of arcade games
+pub const MAX_MULTIPLIER: u32 = 5;
 
 /// Direction enum representing possible move directions
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum MovementDirection {
     Up,
     Down,
@@ -19,214 +58,1424 @@ pub enum MovementDirection {
     Right,
 }
 
-/// Trait defining the core game behavior
-pub trait GameEngine {
-    /// Move tiles in the specified direction
-    fn move_in_direction(&mut self, direction: &MovementDirection) -> bool;
+/// Several merges in the same row or column caused by a single move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MergeChain {
+    /// Index of the row (horizontal moves) or column (vertical moves)
+    pub line: usize,
+    /// Number of merges in the line, at least 2
+    pub merges: usize,
+}
+
+impl MergeChain {
+    /// Name of the chain size in the language of the interface
+    pub fn name(&self) -> &'static str {
+        i18n::text(match self.merges {
+            0 | 1 => "chain.single",
+            2 => "chain.double",
+            3 => "chain.triple",
+            _ => "chain.quad",
+        })
+    }
+}
+
+/// What happens to two equal tiles that merge
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum MergeRule {
+    /// They become one tile of twice the value, the regular game
+    #[default]
+    Double,
+    /// They become one tile of half the value, and two 2s vanish: the
+    /// "divide" variant, where tiles spawn large and the game is won by
+    /// getting down to a 2
+    Halve,
+}
+
+impl MergeRule {
+    /// Whether two tiles of `tile` merge: all but the [`MAX_TILE`]s do
+    /// when they double
+    pub fn can_merge(self, tile: u32) -> bool {
+        self == MergeRule::Halve || tile < MAX_TILE
+    }
+
+    /// Tile created by merging two tiles of `tile`, `0` if they vanish, and
+    /// the points scored for it
+    pub fn merge(self, tile: u32) -> (u32, u32) {
+        match self {
+            MergeRule::Double => (tile * 2, tile * 2),
+            // Both tiles are gone, the points are for what disappeared
+            MergeRule::Halve if tile == 2 => (0, tile * 2),
+            MergeRule::Halve => (tile / 2, tile),
+        }
+    }
+}
+
+/// What merging a single row or column did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeOutcome {
+    /// Points scored, the sum of the tiles created by the merges
+    pub points: u64,
+    /// Indices of the merged tiles in the compacted line
+    pub merges: Vec<usize>,
+}
+
+/// Merge equal neighbouring tiles of a line and compact it towards index 0.
+///
+/// Tiles slide one after the other onto the compacted line. A tile merges
+/// with the last tile placed if they are equal and that tile wasn't created
+/// by a merge itself, so no tile merges twice in one move.
+pub fn merge_line(line: &mut [u32]) -> MergeOutcome {
+    let mut merges = Vec::new();
+    let len = line.len();
+    let (points, _) = slide_line(line, len, MergeRule::Double, |index| merges.push(index));
+    MergeOutcome { points, merges }
+}
+
+/// Slide the first `len` tiles of a line towards index 0 in place, merging
+/// them like [`merge_line`] by `rule` without allocating.
+///
+/// `merged` is called with the index of every tile created by a merge; tiles
+/// that vanish in a merge leave their cell to the next tile.
+/// Returns the points scored and whether any tile moved.
+fn slide_line<L>(
+    line: &mut L,
+    len: usize,
+    rule: MergeRule,
+    mut merged: impl FnMut(usize),
+) -> (u64, bool)
+where
+    L: IndexMut<usize, Output = u32> + ?Sized,
+{
+    let mut points = 0;
+    let mut moved = false;
+    // Index the next tile is placed at, and whether the tile before it may
+    // still merge
+    let mut write = 0;
+    let mut mergeable = false;
+    for read in 0..len {
+        let tile = line[read];
+        if tile == 0 {
+            continue;
+        }
+        line[read] = 0;
+        if mergeable && line[write - 1] == tile && rule.can_merge(tile) {
+            let (created, scored) = rule.merge(tile);
+            line[write - 1] = created;
+            points += u64::from(scored);
+            if created == 0 {
+                write -= 1;
+            } else {
+                merged(write - 1);
+            }
+            mergeable = false;
+            moved = true;
+        } else {
+            line[write] = tile;
+            moved |= write != read;
+            write += 1;
+            mergeable = true;
+        }
+    }
+    (points, moved)
+}
+
+/// What sliding a whole board did, see [`slide_board`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Slide {
+    /// Whether any tile moved
+    pub moved: bool,
+    /// Points scored by the merges
+    pub points: u64,
+}
+
+/// Slide every tile of a board in a direction, merging equal neighbours,
+/// without spawning a tile.
+///
+/// The board is changed in place without allocating, which makes it the
+/// cheap way for searches to try moves. `merged` is called with the line (row
+/// for horizontal moves, column for vertical ones) and the position of every
+/// tile created by a merge, in board order.
+pub fn slide_board(
+    board: &mut Board,
+    direction: &MovementDirection,
+    merged: impl FnMut(usize, (usize, usize)),
+) -> Slide {
+    slide_board_by(board, direction, MergeRule::Double, merged)
+}
+
+/// Slide every tile of a board like [`slide_board`], merging equal
+/// neighbours by `rule`
+pub fn slide_board_by(
+    board: &mut Board,
+    direction: &MovementDirection,
+    rule: MergeRule,
+    mut merged: impl FnMut(usize, (usize, usize)),
+) -> Slide {
+    let dims = board.dims();
+    // Rows for horizontal moves, columns for vertical ones
+    let (lines, len) = match direction {
+        MovementDirection::Left | MovementDirection::Right => dims,
+        MovementDirection::Up | MovementDirection::Down => (dims.1, dims.0),
+    };
+    let mut slide = Slide::default();
+    for k in 0..lines {
+        let mut line = Line {
+            board: &mut *board,
+            k,
+            direction: *direction,
+        };
+        let (points, moved) = slide_line(&mut line, len, rule, |t| {
+            merged(k, line_cell(k, t, dims, direction))
+        });
+        slide.points += points;
+        slide.moved |= moved;
+    }
+    slide
+}
+
+/// Position of the `t`-th cell of line `k` of a board with `rows` rows and
+/// `columns` columns, counted from the side tiles slide towards
+fn line_cell(
+    k: usize,
+    t: usize,
+    (rows, columns): (usize, usize),
+    direction: &MovementDirection,
+) -> (usize, usize) {
+    match direction {
+        MovementDirection::Left => (k, t),
+        MovementDirection::Right => (k, columns - 1 - t),
+        MovementDirection::Up => (t, k),
+        MovementDirection::Down => (rows - 1 - t, k),
+    }
+}
+
+/// A row or column of a board, indexed from the side tiles slide towards
+struct Line<'a> {
+    board: &'a mut Board,
+    k: usize,
+    direction: MovementDirection,
+}
+
+impl Index<usize> for Line<'_> {
+    type Output = u32;
+
+    fn index(&self, t: usize) -> &u32 {
+        let (i, j) = line_cell(self.k, t, self.board.dims(), &self.direction);
+        &self.board.row(i)[j]
+    }
+}
+
+impl IndexMut<usize> for Line<'_> {
+    fn index_mut(&mut self, t: usize) -> &mut u32 {
+        let (i, j) = line_cell(self.k, t, self.board.dims(), &self.direction);
+        &mut self.board.row_mut(i)[j]
+    }
+}
+
+/// Summary of what the last move did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MoveResult {
+    /// Whether any tile moved
+    pub moved: bool,
+    /// Points scored by the move
+    pub points: u64,
+    /// Total number of merges
+    pub merges: usize,
+    /// Lines with more than one merge
+    pub chains: Vec<MergeChain>,
+    /// Tiles cleared because the move left a zen game stuck
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cleared: usize,
+    /// Combo multiplier the points were scored with in an arcade game, `0`
+    /// for moves without merges and in other games
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub multiplier: u32,
+}
+
+/// Read-only view of a game, all a renderer needs.
+///
+/// Only the board and the score must be provided; everything else has a
+/// default derived from them or describing an engine without the feature.
+pub trait GameView {
+    /// Get the current score
+    fn score(&self) -> u64;
+
+    /// Get the current board state, rows of equal length
+    fn board(&self) -> &[Vec<u32>];
+
+    /// Check if the game is over, i.e. no tile can move or merge
+    fn game_over(&self) -> bool {
+        let board = self.board();
+        let (rows, columns) = self.dims();
+        let rule = self.merge_rule();
+        for i in 0..rows {
+            for j in 0..columns {
+                if board[i][j] == 0 {
+                    return false; // Found an empty space
+                }
+                if !rule.can_merge(board[i][j]) {
+                    continue; // Too large to merge
+                }
+                if j < columns - 1 && board[i][j] == board[i][j + 1] {
+                    return false; // Found a horizontal merge
+                }
+                if i < rows - 1 && board[i][j] == board[i + 1][j] {
+                    return false; // Found a vertical merge
+                }
+            }
+        }
+        true // No moves left
+    }
+
+    /// Tile value that wins the game
+    fn win_tile(&self) -> u32 {
+        WIN_TILE
+    }
+
+    /// Check if the winning tile has been reached: a tile at least as large
+    /// as it, or as small with [`MergeRule::Halve`]
+    fn has_won(&self) -> bool {
+        let win_tile = self.win_tile();
+        let mut tiles = self.board().iter().flatten();
+        match self.merge_rule() {
+            MergeRule::Double => tiles.any(|&tile| tile >= win_tile),
+            MergeRule::Halve => tiles.any(|&tile| tile != 0 && tile <= win_tile),
+        }
+    }
+
+    /// What equal tiles merge into
+    fn merge_rule(&self) -> MergeRule {
+        MergeRule::Double
+    }
+
+    /// Tile of a regular game to 2048 that is as many merges from the
+    /// winning tile as `tile`, so renderers give the target of every game
+    /// the color of 2048. `0` stays empty.
+    fn color_tile(&self, tile: u32) -> u32 {
+        if tile == 0 {
+            return 0;
+        }
+        let exponent = match self.merge_rule() {
+            MergeRule::Double => {
+                tile.ilog2() as i32 + WIN_TILE.ilog2() as i32 - self.win_tile().ilog2() as i32
+            }
+            // Tiles count down to the winning 2 instead of up
+            MergeRule::Halve => WIN_TILE.ilog2() as i32 + 1 - tile.ilog2() as i32,
+        };
+        1 << exponent.clamp(1, 31)
+    }
+
+    /// Number of moves that led to the current board
+    fn moves(&self) -> u32 {
+        0
+    }
+
+    /// Number of rows of the board, which is the number of columns too
+    /// unless the board is rectangular (see [`GameView::dims`])
+    fn size(&self) -> usize {
+        self.board().len()
+    }
+
+    /// Number of rows and of columns of the board
+    fn dims(&self) -> (usize, usize) {
+        let board = self.board();
+        (board.len(), board.first().map_or(0, Vec::len))
+    }
+
+    /// Board and score that [`GameEngine::undo`] would restore, without
+    /// changing the game. `None` if undo isn't possible.
+    fn peek_undo(&self) -> Option<(&[Vec<u32>], u64)> {
+        None
+    }
+
+    /// Moves [`GameEngine::undo`] can take back one after another, limited
+    /// by the undo history and the undos left
+    fn undo_depth(&self) -> usize {
+        self.peek_undo().is_some() as usize
+    }
+
+    /// Whether [`GameEngine::undo`] would take back a move, e.g. to grey out
+    /// the undo key
+    fn can_undo(&self) -> bool {
+        self.undo_depth() > 0
+    }
+
+    /// Number of past states [`GameView::past_state`] can show
+    fn past_states(&self) -> usize {
+        0
+    }
+
+    /// Board and score before move `index` of the game, counted from the
+    /// oldest state the engine keeps, e.g. to browse the history of a game.
+    /// `None` if the engine doesn't keep it.
+    fn past_state(&self, _index: usize) -> Option<(&[Vec<u32>], u64)> {
+        None
+    }
+
+    /// Position and value of the tile spawned by the last move, if any
+    fn last_spawn(&self) -> Option<(usize, usize, u32)> {
+        None
+    }
+
+    /// Positions of the tiles created by merges in the last move
+    fn merged_cells(&self) -> &[(usize, usize)] {
+        &[]
+    }
+
+    /// Summary of the last move, if the engine tracks it
+    fn last_move(&self) -> Option<&MoveResult> {
+        None
+    }
+
+    /// Whether spawns adapt to the player, which makes scores incomparable
+    fn is_adaptive(&self) -> bool {
+        false
+    }
+
+    /// Odds of the tiles the next spawn draws from; custom odds make scores
+    /// incomparable
+    fn tile_odds(&self) -> TileOdds {
+        TileOdds::default()
+    }
+
+    /// Whether the game never ends, clearing tiles when the board is stuck
+    fn is_zen(&self) -> bool {
+        false
+    }
+
+    /// Multiplier the next merging move scores with in an arcade game,
+    /// `None` in other games
+    fn combo(&self) -> Option<u32> {
+        None
+    }
+
+    /// Undos still allowed in this game, `None` if they aren't counted
+    fn undos_left(&self) -> Option<u32> {
+        None
+    }
+
+    /// Whether more moves than [`DEFAULT_UNDO_LIMIT`] can be undone without
+    /// counting or penalising the undos, which makes scores incomparable
+    fn has_lenient_undo(&self) -> bool {
+        false
+    }
+
+    /// Seed of the tile spawns, if the engine is seeded, e.g. to share the
+    /// game with [`crate::share::ShareCode`]
+    fn spawn_seed(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Trait defining the core game behavior: a view that can also be played
+pub trait GameEngine: GameView {
+    /// Move tiles in the specified direction
+    fn move_in_direction(&mut self, direction: &MovementDirection) -> bool;
+
+    /// Undo the last move if possible
+    fn undo(&mut self) -> bool;
+
+    /// Start over with a new game set up like this one was: same board size
+    /// and rules, and the same tiles again if it was created with a seed
+    fn reset(&mut self);
+
+    /// Put a tile on a cell, `0` emptying it, e.g. to set up a position in a
+    /// tool or on the sandbox screen. The score and the undo history are kept
+    /// as they are.
+    ///
+    /// Engines that can't be edited return an error.
+    #[cfg(feature = "sandbox")]
+    fn set_cell(&mut self, _pos: (usize, usize), _tile: u32) -> GameResult<()> {
+        Err(GameError::GameStateError(
+            "This game can't be edited".to_string(),
+        ))
+    }
+
+    /// Go back to past state `index`, see [`GameView::past_state`], and play
+    /// on from there. The later moves are dropped, each counting as an undo.
+    ///
+    /// Engines that don't keep their history return an error.
+    fn branch(&mut self, _index: usize) -> GameResult<()> {
+        Err(GameError::GameStateError(
+            "This game doesn't keep its history".to_string(),
+        ))
+    }
+
+    /// Drop a tile on a random empty cell between moves, e.g. a junk tile
+    /// sent by the opponent in a versus game. Returns the cell it landed on,
+    /// `None` if the board is full.
+    ///
+    /// Engines that don't take tiles from outside return an error.
+    fn inject_tile(&mut self, _tile: u32) -> GameResult<Option<(usize, usize)>> {
+        Err(GameError::GameStateError(
+            "This game doesn't take tiles".to_string(),
+        ))
+    }
+
+    /// Swap the tiles of two cells between moves, e.g. with a power-up. The
+    /// score and the undo history are kept as they are, as for the other
+    /// power-ups.
+    ///
+    /// Engines without power-ups return an error, as for the others.
+    fn swap_cells(&mut self, _a: (usize, usize), _b: (usize, usize)) -> GameResult<()> {
+        Err(GameError::GameStateError(
+            "This game has no power-ups".to_string(),
+        ))
+    }
+
+    /// Remove the tile of a cell between moves
+    fn remove_cell(&mut self, _pos: (usize, usize)) -> GameResult<()> {
+        Err(GameError::GameStateError(
+            "This game has no power-ups".to_string(),
+        ))
+    }
+
+    /// Move every tile to a random cell between moves
+    fn shuffle(&mut self) -> GameResult<()> {
+        Err(GameError::GameStateError(
+            "This game has no power-ups".to_string(),
+        ))
+    }
+}
+
+/// Rules for undoing moves, see [`GameBuilder::undo_policy`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UndoPolicy {
+    /// Undos allowed per game, `None` for as many as the history holds
+    pub max_undos: Option<u32>,
+    /// Points taken from the restored score for every undo
+    pub penalty: u32,
+}
+
+impl UndoPolicy {
+    /// Hard mode: only [`HARD_MODE_UNDOS`] undos per game
+    pub fn hard() -> Self {
+        Self {
+            max_undos: Some(HARD_MODE_UNDOS),
+            penalty: 0,
+        }
+    }
+}
+
+/// Rolling estimate of how well the player is doing, used by adaptive games.
+///
+/// Players who keep many cells free get more 4s, struggling players fewer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SkillEstimate {
+    /// Exponential moving average of the empty cells after each move
+    average_empty: f64,
+    /// Chance of a 4 for an average player
+    base_chance: f64,
+}
+
+impl SkillEstimate {
+    /// Weight of the latest move in the moving average
+    const SMOOTHING: f64 = 0.2;
+    /// Empty cells at which the regular spawn odds are used
+    const NEUTRAL_EMPTY: f64 = 6.0;
+    /// Change of the 4 chance per empty cell above or below neutral
+    const STEP: f64 = 0.025;
+    /// Highest chance of a 4
+    const MAX_FOUR_CHANCE: f64 = 0.3;
+
+    fn new(base_chance: f64) -> Self {
+        Self {
+            average_empty: Self::NEUTRAL_EMPTY,
+            base_chance,
+        }
+    }
+
+    /// Account for the number of empty cells left by a move
+    fn update(&mut self, empty: usize) {
+        self.average_empty += Self::SMOOTHING * (empty as f64 - self.average_empty);
+    }
+
+    /// Chance that the next spawn is a 4
+    pub fn four_chance(&self) -> f64 {
+        (self.base_chance + (self.average_empty - Self::NEUTRAL_EMPTY) * Self::STEP)
+            .clamp(0.0, Self::MAX_FOUR_CHANCE.max(self.base_chance))
+    }
+}
+
+/// Position and counters of a game in progress, to continue it later with
+/// [`GameBuilder::resume`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Progress {
+    pub board: Vec<Vec<u32>>,
+    pub score: u64,
+    pub moves: u32,
+    /// Undos taken so far, which count against [`UndoPolicy::max_undos`]
+    pub undos_used: u32,
+    /// Boards and scores before the last moves, oldest first, so the moves
+    /// can still be undone. Entries beyond the undo limit are dropped.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub history: Vec<(Vec<Vec<u32>>, u64)>,
+}
+
+/// Settings for a new [`Game2048`], created with [`Game2048::builder`]
+#[derive(Debug, Clone)]
+pub struct GameBuilder {
+    rows: usize,
+    columns: usize,
+    seed: Option<u64>,
+    win_tile: u32,
+    four_chance: f64,
+    eight_chance: f64,
+    initial_tiles: usize,
+    undo_limit: Option<usize>,
+    undo_policy: UndoPolicy,
+    adaptive: bool,
+    zen: bool,
+    arcade: bool,
+    merge_rule: MergeRule,
+    progress: Option<Progress>,
+    spawn_policy: Arc<dyn SpawnPolicy>,
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        Self {
+            rows: DEFAULT_SIZE,
+            columns: DEFAULT_SIZE,
+            seed: None,
+            win_tile: WIN_TILE,
+            four_chance: FOUR_CHANCE,
+            eight_chance: 0.0,
+            initial_tiles: INITIAL_TILES,
+            undo_limit: Some(DEFAULT_UNDO_LIMIT),
+            undo_policy: UndoPolicy::default(),
+            adaptive: false,
+            zen: false,
+            arcade: false,
+            merge_rule: MergeRule::Double,
+            progress: None,
+            spawn_policy: standard_spawn(),
+        }
+    }
+}
+
+impl GameBuilder {
+    /// Number of rows and columns of a square board (at least 2)
+    pub fn size(mut self, size: usize) -> Self {
+        self.rows = size;
+        self.columns = size;
+        self
+    }
+
+    /// Number of rows and of columns of a rectangular board (at least 2
+    /// each), e.g. `dims(4, 6)` for four rows of six tiles
+    pub fn dims(mut self, rows: usize, columns: usize) -> Self {
+        self.rows = rows;
+        self.columns = columns;
+        self
+    }
+
+    /// Seed for the tile spawns; a random one is used if not set
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Tile value that wins the game (a power of two, at least 4); with
+    /// [`MergeRule::Halve`] the tile that spawns instead, the game being won
+    /// with a 2
+    pub fn win_tile(mut self, win_tile: u32) -> Self {
+        self.win_tile = win_tile;
+        self
+    }
+
+    /// Chance that a spawned tile is a 4 instead of a 2 (between 0 and 1)
+    pub fn four_chance(mut self, four_chance: f64) -> Self {
+        self.four_chance = four_chance;
+        self
+    }
+
+    /// Chance that a spawned tile is an 8 instead of a 2, 0 by default; the
+    /// chances of a 4 and an 8 add up to at most 1
+    pub fn eight_chance(mut self, eight_chance: f64) -> Self {
+        self.eight_chance = eight_chance;
+        self
+    }
+
+    /// Number of tiles on the new board
+    pub fn initial_tiles(mut self, initial_tiles: usize) -> Self {
+        self.initial_tiles = initial_tiles;
+        self
+    }
+
+    /// Maximum number of moves that can be undone (0 disables undo)
+    pub fn undo_limit(mut self, undo_limit: usize) -> Self {
+        self.undo_limit = Some(undo_limit);
+        self
+    }
+
+    /// Keep every move so the game can be undone back to the start
+    pub fn unlimited_undo(mut self) -> Self {
+        self.undo_limit = None;
+        self
+    }
+
+    /// Limit the undos per game or make them cost points
+    pub fn undo_policy(mut self, undo_policy: UndoPolicy) -> Self {
+        self.undo_policy = undo_policy;
+        self
+    }
+
+    /// Adapt the spawn odds to how well the player is doing
+    pub fn adaptive_difficulty(mut self) -> Self {
+        self.adaptive = true;
+        self
+    }
+
+    /// Never end the game: when a move leaves the board stuck, the smallest
+    /// tiles are cleared so play can go on
+    pub fn zen(mut self) -> Self {
+        self.zen = true;
+        self
+    }
+
+    /// Build up a combo with consecutive merging moves: the `n`-th in a row
+    /// scores `n` times its points, up to [`MAX_MULTIPLIER`]. Moves without
+    /// merges and undos break the combo.
+    pub fn arcade(mut self) -> Self {
+        self.arcade = true;
+        self
+    }
+
+    /// What equal tiles merge into, [`MergeRule::Double`] by default
+    pub fn merge_rule(mut self, merge_rule: MergeRule) -> Self {
+        self.merge_rule = merge_rule;
+        self
+    }
+
+    /// Decide where tiles spawn and what they are, [`StandardSpawn`] by
+    /// default. The initial tiles are spawned with the policy too, so games
+    /// with [`NoSpawn`] are started from a position with [`Self::resume`].
+    pub fn spawn_policy(mut self, policy: impl SpawnPolicy + 'static) -> Self {
+        self.spawn_policy = Arc::new(policy);
+        self
+    }
+
+    /// Continue a game in progress instead of starting with new tiles; the
+    /// board size is taken from the position
+    pub fn resume(mut self, progress: Progress) -> Self {
+        self.rows = progress.board.len();
+        self.columns = progress.board.first().map_or(0, Vec::len);
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Check the settings and create the game
+    pub fn build(self) -> GameResult<Game2048> {
+        let invalid = |msg: String| Err(GameError::InputError(msg));
+        if self.rows < 2 || self.columns < 2 {
+            return invalid(format!(
+                "Board size must be at least 2x2, got {}x{}",
+                self.rows, self.columns
+            ));
+        }
+        if self.win_tile < 4 || !self.win_tile.is_power_of_two() {
+            return invalid(format!(
+                "Win tile must be a power of two of at least 4, got {}",
+                self.win_tile
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.four_chance) {
+            return invalid(format!(
+                "Chance of a 4 must be between 0 and 1, got {}",
+                self.four_chance
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.eight_chance) {
+            return invalid(format!(
+                "Chance of an 8 must be between 0 and 1, got {}",
+                self.eight_chance
+            ));
+        }
+        if self.four_chance + self.eight_chance > 1.0 {
+            return invalid(format!(
+                "Chances of a 4 and an 8 add up to more than 1: {} and {}",
+                self.four_chance, self.eight_chance
+            ));
+        }
+        if let Some(progress) = &self.progress {
+            Board::from_rows(progress.board.clone())?;
+            for (board, _) in &progress.history {
+                let dims = |board: &[Vec<u32>]| (board.len(), board.first().map(Vec::len));
+                if dims(board) != dims(&progress.board) {
+                    return invalid("Undo history is for another board size".to_string());
+                }
+                Board::from_rows(board.clone())?;
+            }
+        }
+        if self.initial_tiles > self.rows * self.columns {
+            return invalid(format!(
+                "{} initial tiles don't fit on a {}x{} board",
+                self.initial_tiles, self.rows, self.columns
+            ));
+        }
+        Ok(self.build_unchecked())
+    }
+
+    /// Create the game from settings known to be valid
+    fn build_unchecked(self) -> Game2048 {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        log::debug!("New {}x{} game with seed {}", self.rows, self.columns, seed);
+        let board = Board::with_dims(self.rows, self.columns);
+        let mut game = Game2048::with_board(board.into_rows(), seed);
+        game.win_tile = self.win_tile;
+        game.four_chance = self.four_chance;
+        game.eight_chance = self.eight_chance;
+        game.undo_limit = self.undo_limit;
+        game.undo_policy = self.undo_policy;
+        game.initial_tiles = self.initial_tiles;
+        game.seeded = self.seed.is_some();
+        game.spawn_policy = self.spawn_policy;
+        game.zen = self.zen;
+        game.arcade = self.arcade;
+        game.merge_rule = self.merge_rule;
+        if self.adaptive {
+            game = game.with_adaptive_difficulty();
+        }
+        match self.progress {
+            Some(progress) => {
+                game.board = Board::from_rows_unchecked(progress.board);
+                game.score = progress.score;
+                game.moves = progress.moves;
+                game.undos_used = progress.undos_used;
+                for (board, score) in progress.history {
+                    game.save_state(Board::from_rows_unchecked(board), score);
+                }
+            }
+            None => {
+                for _ in 0..self.initial_tiles {
+                    game.spawn_tile();
+                }
+            }
+        }
+        game
+    }
+}
+
+/// Implementation of the 2048 game.
+///
+/// With the `serde` feature the whole state is serialized, including the
+/// undo history and the random generator, so a restored game spawns the same
+/// tiles as the original would have. The spawn policy is left out; restored
+/// games use [`StandardSpawn`].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Game2048 {
+    board: Board,
+    score: u64,
+    moves: u32,
+    previous_states: VecDeque<(Board, u64)>, // Store previous (board, score) pairs
+    /// Boards and scores before the last moves, oldest first, up to
+    /// [`HISTORY_LIMIT`]; the undo history keeps the last ones
+    #[cfg_attr(feature = "serde", serde(default))]
+    timeline: Vec<(Board, u64)>,
+    last_spawn: Option<(usize, usize, u32)>,
+    merged_cells: Vec<(usize, usize)>,
+    last_move: MoveResult,
+    skill: Option<SkillEstimate>,
+    win_tile: u32,
+    four_chance: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    eight_chance: f64,
+    undo_limit: Option<usize>,
+    undo_policy: UndoPolicy,
+    undos_used: u32,
+    /// Tiles a new game starts with, for [`GameEngine::reset`]
+    #[cfg_attr(feature = "serde", serde(default = "initial_tiles"))]
+    initial_tiles: usize,
+    /// Whether the seed was chosen rather than random, so a reset game
+    /// replays the same tiles
+    #[cfg_attr(feature = "serde", serde(default))]
+    seeded: bool,
+    /// Whether stuck boards are cleared instead of ending the game
+    #[cfg_attr(feature = "serde", serde(default))]
+    zen: bool,
+    /// Whether consecutive merging moves multiply the points
+    #[cfg_attr(feature = "serde", serde(default))]
+    arcade: bool,
+    /// Merging moves in a row in an arcade game
+    #[cfg_attr(feature = "serde", serde(default))]
+    streak: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    merge_rule: MergeRule,
+    #[cfg_attr(feature = "serde", serde(skip, default = "standard_spawn"))]
+    spawn_policy: Arc<dyn SpawnPolicy>,
+    seed: u64,
+    /// The generator `StdRng` currently uses, named so recorded games keep
+    /// reproducing if `StdRng` changes
+    rng: ChaCha12Rng,
+}
+
+/// Policy of games that don't choose one
+fn standard_spawn() -> Arc<dyn SpawnPolicy> {
+    Arc::new(StandardSpawn)
+}
+
+/// Initial tiles of games saved before they were kept
+#[cfg(feature = "serde")]
+fn initial_tiles() -> usize {
+    INITIAL_TILES
+}
+
+impl Game2048 {
+    /// Configure a new game, starting from the default settings
+    pub fn builder() -> GameBuilder {
+        GameBuilder::default()
+    }
+
+    /// Create a game whose tile spawns are fully determined by the seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self::builder().seed(seed).build_unchecked()
+    }
+
+    /// Create a game starting from a position, e.g. for puzzles or external
+    /// tools, with the default settings and random spawns.
+    ///
+    /// Tiles must be empty (`0`) or powers of two of at least 2. For other
+    /// settings, pass the position to [`GameBuilder::resume`] instead.
+    pub fn from_board<const N: usize>(board: [[u32; N]; N]) -> GameResult<Self> {
+        Self::builder()
+            .resume(Progress {
+                board: board.iter().map(|row| row.to_vec()).collect(),
+                score: 0,
+                moves: 0,
+                undos_used: 0,
+                history: Vec::new(),
+            })
+            .build()
+    }
+
+    /// Create a game starting from the given position without spawning
+    /// tiles or checking them, using the default settings otherwise
+    pub(crate) fn with_board(board: Vec<Vec<u32>>, seed: u64) -> Self {
+        Self {
+            board: Board::from_rows_unchecked(board),
+            score: 0,
+            moves: 0,
+            previous_states: VecDeque::new(),
+            timeline: Vec::new(),
+            last_spawn: None,
+            merged_cells: Vec::new(),
+            last_move: MoveResult::default(),
+            skill: None,
+            win_tile: WIN_TILE,
+            four_chance: FOUR_CHANCE,
+            eight_chance: 0.0,
+            undo_limit: Some(DEFAULT_UNDO_LIMIT),
+            undo_policy: UndoPolicy::default(),
+            undos_used: 0,
+            initial_tiles: INITIAL_TILES,
+            seeded: false,
+            zen: false,
+            arcade: false,
+            streak: 0,
+            merge_rule: MergeRule::Double,
+            spawn_policy: standard_spawn(),
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// The tiles of the game, with ways to inspect and transform them
+    pub fn tiles(&self) -> &Board {
+        &self.board
+    }
+
+    /// Seed used for the tile spawns of this game
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Tile the game was set up with by [`GameBuilder::win_tile`]: the one
+    /// that wins, or the one that spawns with [`MergeRule::Halve`]
+    pub fn target_tile(&self) -> u32 {
+        self.win_tile
+    }
+
+    /// Adapt the spawn odds to how well the player is doing from now on
+    pub fn with_adaptive_difficulty(mut self) -> Self {
+        self.skill = Some(SkillEstimate::new(self.four_chance));
+        self
+    }
+
+    /// Chance that the next spawned tile is a 4
+    pub fn four_chance(&self) -> f64 {
+        self.skill
+            .map_or(self.four_chance, |skill| skill.four_chance())
+    }
+
+    /// Maximum number of moves that can be undone, `None` if unlimited
+    pub fn undo_limit(&self) -> Option<usize> {
+        self.undo_limit
+    }
+
+    /// Rules for undoing moves in this game
+    pub fn undo_policy(&self) -> UndoPolicy {
+        self.undo_policy
+    }
+
+    /// Position, counters and undo history needed to continue the game later
+    pub fn progress(&self) -> Progress {
+        Progress {
+            board: self.board.rows().to_vec(),
+            score: self.score,
+            moves: self.moves,
+            undos_used: self.undos_used,
+            history: self
+                .previous_states
+                .iter()
+                .map(|(board, score)| (board.rows().to_vec(), *score))
+                .collect(),
+        }
+    }
+
+    /// Remember the state before a move so it can be undone and browsed
+    fn save_state(&mut self, board: Board, score: u64) {
+        self.timeline.push((board.clone(), score));
+        self.previous_states.push_back((board, score));
+        // Limit history size to prevent excessive memory usage
+        if let Some(limit) = self.undo_limit {
+            while self.previous_states.len() > limit {
+                self.previous_states.pop_front();
+            }
+            let kept = limit.max(HISTORY_LIMIT);
+            if self.timeline.len() > kept {
+                self.timeline.drain(..self.timeline.len() - kept);
+            }
+        }
+    }
+
+    /// Put the tile chosen by the spawn policy on the board. With
+    /// [`MergeRule::Halve`] the tiles are mirrored: 2s spawn as the target
+    /// tile and 4s as half of it.
+    fn spawn_tile(&mut self) {
+        let odds = self.tile_odds();
+        let spawn = self
+            .spawn_policy
+            .spawn(&self.board, odds, &mut self.rng)
+            .filter(|&(pos, _)| self.board.get(pos) == 0);
+        if let Some(((i, j), tile)) = spawn {
+            let tile = match self.merge_rule {
+                MergeRule::Double => tile,
+                // Shifted rather than doubled, as the target may be the
+                // largest tile
+                MergeRule::Halve => (self.win_tile >> (tile.ilog2() - 1)).max(2),
+            };
+            self.board.set((i, j), tile);
+            self.last_spawn = Some((i, j, tile));
+        }
+    }
+
+    /// Clear every tile of the smallest value on the board, or the largest
+    /// with [`MergeRule::Halve`], returning how many were cleared. A stuck
+    /// board always has two different values, so some tiles are kept.
+    fn clear_smallest_tiles(&mut self) -> usize {
+        let tiles = self
+            .board
+            .iter_cells()
+            .map(|(_, tile)| tile)
+            .filter(|&tile| tile > 0);
+        let smallest = match self.merge_rule {
+            MergeRule::Double => tiles.min(),
+            MergeRule::Halve => tiles.max(),
+        }
+        .unwrap_or(0);
+        let cells: Vec<(usize, usize)> = self
+            .board
+            .iter_cells()
+            .filter(|&(_, tile)| tile == smallest)
+            .map(|(pos, _)| pos)
+            .collect();
+        for &pos in &cells {
+            self.board.set(pos, 0);
+        }
+        if self
+            .last_spawn
+            .is_some_and(|(i, j, _)| self.board.get((i, j)) == 0)
+        {
+            self.last_spawn = None;
+        }
+        log::debug!("Zen: cleared {} tiles of {}", cells.len(), smallest);
+        cells.len()
+    }
+
+    /// Multiply the points of a move by the combo of an arcade game, building
+    /// it up if the move merged tiles and breaking it otherwise
+    fn apply_combo(&mut self, points: u64) {
+        if self.last_move.merges == 0 {
+            self.streak = 0;
+            return;
+        }
+        self.streak += 1;
+        let multiplier = self.streak.min(MAX_MULTIPLIER);
+        self.score += points * u64::from(multiplier - 1);
+        self.last_move.multiplier = multiplier;
+    }
+
+    /// Check that a cell is on the board
+    fn check_cell(&self, (i, j): (usize, usize)) -> GameResult<()> {
+        let (rows, columns) = self.board.dims();
+        if i >= rows || j >= columns {
+            return Err(GameError::InputError(format!(
+                "({}, {}) is outside the {}x{} board",
+                i, j, rows, columns
+            )));
+        }
+        Ok(())
+    }
+
+    /// Forget the highlights of the last move after the board was changed
+    /// between moves
+    fn board_edited(&mut self) {
+        self.last_spawn = None;
+        self.merged_cells.clear();
+    }
+
+    /// Count the merges of a line and note it if they form a chain
+    fn record_chain(&mut self, line: usize, merges: usize) {
+        self.last_move.merges += merges;
+        if merges >= 2 {
+            self.last_move.chains.push(MergeChain { line, merges });
+        }
+    }
+
+    /// Slide the tiles in a direction, merging equal neighbours
+    fn slide(&mut self, direction: &MovementDirection) -> bool {
+        let mut merges = Vec::new();
+        let slide = slide_board_by(&mut self.board, direction, self.merge_rule, |line, pos| {
+            merges.push((line, pos))
+        });
+        self.score += slide.points;
+        // Merges come line by line, so the count of a line is complete when
+        // the next line starts
+        let mut count = (0, 0);
+        for &(line, pos) in &merges {
+            if line != count.0 {
+                self.record_chain(count.0, count.1);
+                count = (line, 0);
+            }
+            count.1 += 1;
+            self.merged_cells.push(pos);
+        }
+        self.record_chain(count.0, count.1);
+        slide.moved
+    }
+}
+
+impl GameView for Game2048 {
+    fn score(&self) -> u64 {
+        self.score
+    }
+
+    fn board(&self) -> &[Vec<u32>] {
+        self.board.rows()
+    }
+
+    fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    fn win_tile(&self) -> u32 {
+        match self.merge_rule {
+            MergeRule::Double => self.win_tile,
+            MergeRule::Halve => 2,
+        }
+    }
+
+    fn merge_rule(&self) -> MergeRule {
+        self.merge_rule
+    }
 
-    /// Check if the game is over
-    fn game_over(&self) -> bool;
+    fn last_spawn(&self) -> Option<(usize, usize, u32)> {
+        self.last_spawn
+    }
 
-    /// Get the current score
-    fn score(&self) -> u32;
+    fn merged_cells(&self) -> &[(usize, usize)] {
+        &self.merged_cells
+    }
 
-    /// Get the current board state
-    fn board(&self) -> &[[u32; SIZE]; SIZE];
+    fn past_states(&self) -> usize {
+        self.timeline.len()
+    }
 
-    /// Undo the last move if possible
-    fn undo(&mut self) -> bool;
-}
+    fn past_state(&self, index: usize) -> Option<(&[Vec<u32>], u64)> {
+        self.timeline
+            .get(index)
+            .map(|(board, score)| (board.rows(), *score))
+    }
 
-/// Implementation of the 2048 game
-pub struct Game2048 {
-    board: [[u32; SIZE]; SIZE],
-    score: u32,
-    previous_states: Vec<([[u32; SIZE]; SIZE], u32)>, // Store previous (board, score) pairs
-}
+    fn last_move(&self) -> Option<&MoveResult> {
+        Some(&self.last_move)
+    }
 
-impl Game2048 {
-    /// Save the current game state before making changes
-    fn save_state(&mut self) {
-        self.previous_states.push((self.board, self.score));
-        // Limit history size to prevent excessive memory usage
-        if self.previous_states.len() > UNDO_LIMIT {
-            self.previous_states.remove(0);
+    fn is_adaptive(&self) -> bool {
+        self.skill.is_some()
+    }
+
+    fn tile_odds(&self) -> TileOdds {
+        TileOdds {
+            four: self.four_chance(),
+            eight: self.eight_chance,
         }
     }
 
-    fn spawn_tile(&mut self) {
-        let empty: Vec<(usize, usize)> = self
-            .board
-            .iter()
-            .enumerate()
-            .flat_map(|(i, row)| {
-                row.iter()
-                    .enumerate()
-                    .filter(|&(_, &val)| val == 0)
-                    .map(move |(j, _)| (i, j))
-            })
-            .collect();
+    fn is_zen(&self) -> bool {
+        self.zen
+    }
 
-        let mut rng = rand::rng();
-        if let Some(&(i, j)) = empty.choose(&mut rng) {
-            // 90% chance for a 2, 10% chance for a 4
-            self.board[i][j] = if rng.random_bool(0.9) { 2 } else { 4 };
-        }
+    fn combo(&self) -> Option<u32> {
+        self.arcade.then(|| (self.streak + 1).min(MAX_MULTIPLIER))
     }
 
-    fn merge(&mut self, line: &mut Vec<u32>) -> bool {
-        let mut moved = false;
-        let mut i = 0;
-        while i < line.len() {
-            if line[i] == 0 {
-                i += 1;
-                continue;
-            }
-            let mut j = i + 1;
-            while j < line.len() && line[j] == 0 {
-                j += 1;
-            }
-            if j < line.len() && line[i] == line[j] {
-                self.score += line[i];
-                line[i] *= 2;
-                line[j] = 0;
-                moved = true;
-            }
-            i += 1;
-        }
-        // Compact the line
-        let mut new_line: Vec<u32> = line.iter().filter(|&&x| x != 0).cloned().collect();
-        new_line.resize(SIZE, 0);
-        *line = new_line;
-        moved
+    fn undos_left(&self) -> Option<u32> {
+        self.undo_policy
+            .max_undos
+            .map(|max| max.saturating_sub(self.undos_used))
     }
 
-    fn move_up(&mut self) -> bool {
-        let mut moved = false;
-        for j in 0..SIZE {
-            let mut col: Vec<u32> = (0..SIZE).map(|i| self.board[i][j]).collect();
-            moved |= self.merge(&mut col);
-            for (i, &val) in col.iter().enumerate().take(SIZE) {
-                moved |= self.board[i][j] != val;
-                self.board[i][j] = val;
-            }
-        }
-        moved
+    fn has_lenient_undo(&self) -> bool {
+        self.undo_policy == UndoPolicy::default()
+            && self
+                .undo_limit
+                .is_none_or(|limit| limit > DEFAULT_UNDO_LIMIT)
     }
 
-    fn move_down(&mut self) -> bool {
-        let mut moved = false;
-        for j in 0..SIZE {
-            let mut col: Vec<u32> = (0..SIZE).map(|i| self.board[SIZE - 1 - i][j]).collect();
-            moved |= self.merge(&mut col);
-            for (i, &val) in col.iter().enumerate().take(SIZE) {
-                moved |= self.board[SIZE - 1 - i][j] != val;
-                self.board[SIZE - 1 - i][j] = val;
-            }
+    fn peek_undo(&self) -> Option<(&[Vec<u32>], u64)> {
+        if self.undos_left() == Some(0) {
+            return None;
         }
-        moved
+        self.previous_states.back().map(|(board, score)| {
+            (
+                board.rows(),
+                score.saturating_sub(u64::from(self.undo_policy.penalty)),
+            )
+        })
     }
 
-    fn move_left(&mut self) -> bool {
-        let mut moved = false;
-        for i in 0..SIZE {
-            let mut row: Vec<u32> = self.board[i].to_vec();
-            moved |= self.merge(&mut row);
-            for (j, &val) in row.iter().enumerate().take(SIZE) {
-                moved |= self.board[i][j] != val;
-                self.board[i][j] = val;
-            }
+    fn undo_depth(&self) -> usize {
+        match self.undos_left() {
+            Some(left) => self.previous_states.len().min(left as usize),
+            None => self.previous_states.len(),
         }
-        moved
     }
 
-    fn move_right(&mut self) -> bool {
-        let mut moved = false;
-        for i in 0..SIZE {
-            let mut row: Vec<u32> = self.board[i].iter().rev().cloned().collect();
-            moved |= self.merge(&mut row);
-            for (j, &val) in row.iter().enumerate().take(SIZE) {
-                moved |= self.board[i][SIZE - 1 - j] != val;
-                self.board[i][SIZE - 1 - j] = val;
-            }
-        }
-        moved
+    fn spawn_seed(&self) -> Option<u64> {
+        Some(self.seed)
     }
 }
 
 impl GameEngine for Game2048 {
     fn move_in_direction(&mut self, direction: &MovementDirection) -> bool {
-        // Save the current state before the move
-        self.save_state();
-
-        let moved = match direction {
-            MovementDirection::Up => self.move_up(),
-            MovementDirection::Down => self.move_down(),
-            MovementDirection::Left => self.move_left(),
-            MovementDirection::Right => self.move_right(),
-        };
+        // Keep the state before the move, it is only saved if the board changes
+        let board_before = self.board.clone();
+        self.merged_cells.clear();
+        self.last_move = MoveResult::default();
+        let score_before = self.score;
+
+        let moved = self.slide(direction);
+        self.last_move.moved = moved;
+        if moved && self.arcade {
+            self.apply_combo(self.score - score_before);
+        }
+        self.last_move.points = self.score - score_before;
 
         if moved {
+            self.save_state(board_before, score_before);
+            self.moves += 1;
+            if let Some(skill) = self.skill.as_mut() {
+                skill.update(self.board.empty_cells().len());
+            }
+            self.last_spawn = None;
             self.spawn_tile();
+            if self.zen && self.game_over() {
+                self.last_move.cleared = self.clear_smallest_tiles();
+            }
+            log::trace!(
+                "Moved {:?} for {} points, spawned {:?}",
+                direction,
+                self.last_move.points,
+                self.last_spawn
+            );
+        }
+        moved
+    }
+
+    fn undo(&mut self) -> bool {
+        if self.undos_left() == Some(0) {
+            return false;
+        }
+        if let Some((prev_board, prev_score)) = self.previous_states.pop_back() {
+            self.timeline.pop();
+            self.board = prev_board;
+            self.score = prev_score.saturating_sub(u64::from(self.undo_policy.penalty));
+            self.undos_used += 1;
+            self.moves = self.moves.saturating_sub(1);
+            self.streak = 0;
+            self.last_spawn = None;
+            self.merged_cells.clear();
+            self.last_move = MoveResult::default();
+            log::debug!("Undid move {}, score {}", self.moves + 1, self.score);
             true
         } else {
-            // If no tiles moved, we don't need to keep this state
-            self.previous_states.pop();
             false
         }
     }
 
-    fn game_over(&self) -> bool {
-        // Check if there are any empty spaces or possible merges
-        for i in 0..SIZE {
-            for j in 0..SIZE {
-                if self.board[i][j] == 0 {
-                    return false; // Found an empty space
-                }
-                if j < SIZE - 1 && self.board[i][j] == self.board[i][j + 1] {
-                    return false; // Found a horizontal merge
-                }
-                if i < SIZE - 1 && self.board[i][j] == self.board[i + 1][j] {
-                    return false; // Found a vertical merge
-                }
-            }
+    /// Adaptive games start over with the skill estimate of a new player
+    fn reset(&mut self) {
+        let (rows, columns) = self.dims();
+        let builder = GameBuilder {
+            rows,
+            columns,
+            seed: self.seeded.then_some(self.seed),
+            win_tile: self.win_tile,
+            four_chance: self.four_chance,
+            eight_chance: self.eight_chance,
+            initial_tiles: self.initial_tiles,
+            undo_limit: self.undo_limit,
+            undo_policy: self.undo_policy,
+            adaptive: self.skill.is_some(),
+            zen: self.zen,
+            arcade: self.arcade,
+            merge_rule: self.merge_rule,
+            progress: None,
+            spawn_policy: Arc::clone(&self.spawn_policy),
+        };
+        *self = builder.build_unchecked();
+    }
+
+    /// Going back can reach further than undo, whose history is limited, but
+    /// costs as many undos as undoing the moves one by one. The states before
+    /// the one gone back to can still be undone afterwards.
+    fn branch(&mut self, index: usize) -> GameResult<()> {
+        if index >= self.timeline.len() {
+            return Err(GameError::InputError(format!(
+                "No state {} to go back to, the game has {}",
+                index,
+                self.timeline.len()
+            )));
         }
-        true // No moves left
+        let dropped = (self.timeline.len() - index) as u32;
+        if self.undos_left().is_some_and(|left| left < dropped) {
+            return Err(GameError::GameStateError(format!(
+                "Going back {} moves takes more undos than are left",
+                dropped
+            )));
+        }
+        let (board, score) = self.timeline.split_off(index).swap_remove(0);
+        self.board = board;
+        self.score = score.saturating_sub(u64::from(self.undo_policy.penalty));
+        self.moves = self.moves.saturating_sub(dropped);
+        self.undos_used += dropped;
+        let kept = self.undo_limit.map_or(index, |limit| limit.min(index));
+        self.previous_states = self.timeline[index - kept..].iter().cloned().collect();
+        self.last_spawn = None;
+        self.merged_cells.clear();
+        self.last_move = MoveResult::default();
+        self.streak = 0;
+        log::debug!("Went back {} moves to move {}", dropped, self.moves);
+        Ok(())
     }
 
-    fn score(&self) -> u32 {
-        self.score
+    #[cfg(feature = "sandbox")]
+    fn set_cell(&mut self, (i, j): (usize, usize), tile: u32) -> GameResult<()> {
+        self.check_cell((i, j))?;
+        if !crate::board::is_valid_tile(tile) {
+            return Err(GameError::InputError(format!(
+                "{} is not a valid tile",
+                tile
+            )));
+        }
+        self.board.set((i, j), tile);
+        self.board_edited();
+        Ok(())
     }
 
-    fn board(&self) -> &[[u32; SIZE]; SIZE] {
-        &self.board
+    /// The cell is picked with the spawn generator, so seeded games see
+    /// different spawns afterwards than without the tile
+    fn inject_tile(&mut self, tile: u32) -> GameResult<Option<(usize, usize)>> {
+        if !crate::board::is_valid_tile(tile) || tile == 0 {
+            return Err(GameError::InputError(format!(
+                "{} is not a valid tile",
+                tile
+            )));
+        }
+        let Some(&pos) = self.board.empty_cells().choose(&mut self.rng) else {
+            return Ok(None);
+        };
+        self.board.set(pos, tile);
+        log::trace!("Injected {} at {:?}", tile, pos);
+        Ok(Some(pos))
     }
 
-    fn undo(&mut self) -> bool {
-        if let Some((prev_board, prev_score)) = self.previous_states.pop() {
-            self.board = prev_board;
-            self.score = prev_score;
-            true
-        } else {
-            false
+    fn swap_cells(&mut self, a: (usize, usize), b: (usize, usize)) -> GameResult<()> {
+        self.check_cell(a)?;
+        self.check_cell(b)?;
+        let tile = self.board.get(a);
+        self.board.set(a, self.board.get(b));
+        self.board.set(b, tile);
+        self.board_edited();
+        log::debug!("Swapped {:?} and {:?}", a, b);
+        Ok(())
+    }
+
+    fn remove_cell(&mut self, pos: (usize, usize)) -> GameResult<()> {
+        self.check_cell(pos)?;
+        if self.board.get(pos) == 0 {
+            return Err(GameError::InputError(format!(
+                "{:?} has no tile to remove",
+                pos
+            )));
+        }
+        self.board.set(pos, 0);
+        self.board_edited();
+        log::debug!("Removed the tile at {:?}", pos);
+        Ok(())
+    }
+
+    /// The cells are picked with the spawn generator, so seeded games shuffle
+    /// the same way every time
+    fn shuffle(&mut self) -> GameResult<()> {
+        let mut tiles: Vec<u32> = self.board.iter_cells().map(|(_, tile)| tile).collect();
+        tiles.shuffle(&mut self.rng);
+        let (_, columns) = self.board.dims();
+        for (k, tile) in tiles.into_iter().enumerate() {
+            self.board.set((k / columns, k % columns), tile);
         }
+        self.board_edited();
+        log::debug!("Shuffled the board");
+        Ok(())
     }
 }
 
 impl Default for Game2048 {
     fn default() -> Self {
-        let mut game = Self {
-            board: [[0; SIZE]; SIZE],
-            score: 0,
-            previous_states: Vec::new(),
-        };
-        game.spawn_tile();
-        game.spawn_tile();
-        game
+        Self::builder().build_unchecked()
     }
 }
 
 #[cfg(test)]
+// The first tests set up boards on a default game and check them cell by cell
+#[allow(
+    clippy::field_reassign_with_default,
+    clippy::needless_range_loop,
+    clippy::assertions_on_constants
+)]
 mod tests {
     use super::*;
 
@@ -235,28 +1484,45 @@ mod tests {
         let game = Game2048::default();
         assert_eq!(game.score(), 0);
         let empty_tiles: usize = game.board().iter().flatten().filter(|&&x| x == 0).count();
-        assert_eq!(empty_tiles, SIZE * SIZE - 2); // Two tiles should be spawned
+        assert_eq!(empty_tiles, DEFAULT_SIZE * DEFAULT_SIZE - 2); // Two tiles should be spawned
+    }
+
+    #[test]
+    fn test_same_seed_same_game() {
+        let mut a = Game2048::with_seed(42);
+        let mut b = Game2048::with_seed(42);
+        assert_eq!(a.board, b.board);
+        for direction in [MovementDirection::Left, MovementDirection::Up] {
+            a.move_in_direction(&direction);
+            b.move_in_direction(&direction);
+        }
+        assert_eq!(a.board, b.board);
     }
 
     #[test]
     fn test_move_left() {
         let mut game = Game2048::default();
-        game.board = [[2, 2, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
+        game.board = Board::from_rows_unchecked(vec![
+            vec![2, 2, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+        ]);
         game.move_in_direction(&MovementDirection::Left);
         let expected = [[4, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
         fix_gen(&mut game, &expected);
-        assert_eq!(game.board, expected);
+        assert_eq!(game.board(), expected);
     }
 
     #[test]
     fn test_move_right() {
         let mut game = Game2048::default();
-        game.board = [
-            [0, 0, 16, 64],
-            [4, 0, 4, 0],
-            [16, 0, 0, 16],
-            [2048, 0, 0, 16],
-        ];
+        game.board = Board::from_rows_unchecked(vec![
+            vec![0, 0, 16, 64],
+            vec![4, 0, 4, 0],
+            vec![16, 0, 0, 16],
+            vec![2048, 0, 0, 16],
+        ]);
         game.move_in_direction(&MovementDirection::Right);
         let expected = [
             [0, 0, 16, 64],
@@ -265,44 +1531,220 @@ mod tests {
             [0, 0, 2048, 16],
         ];
         fix_gen(&mut game, &expected);
-        assert_eq!(game.board, expected);
+        assert_eq!(game.board(), expected);
     }
 
     #[test]
     fn test_move_up() {
         let mut game = Game2048::default();
-        game.board = [[2, 0, 0, 0], [2, 0, 0, 0], [4, 0, 0, 0], [8, 0, 0, 0]];
+        game.board = Board::from_rows_unchecked(vec![
+            vec![2, 0, 0, 0],
+            vec![2, 0, 0, 0],
+            vec![4, 0, 0, 0],
+            vec![8, 0, 0, 0],
+        ]);
         game.move_in_direction(&MovementDirection::Up);
         let expected = [[4, 0, 0, 0], [4, 0, 0, 0], [8, 0, 0, 0], [0, 0, 0, 0]];
         fix_gen(&mut game, &expected);
-        assert_eq!(game.board, expected);
+        assert_eq!(game.board(), expected);
     }
 
     #[test]
     fn test_move_down() {
         let mut game = Game2048::default();
-        game.board = [[0, 0, 0, 0], [2, 0, 0, 0], [2, 0, 0, 0], [4, 0, 0, 0]];
+        game.board = Board::from_rows_unchecked(vec![
+            vec![0, 0, 0, 0],
+            vec![2, 0, 0, 0],
+            vec![2, 0, 0, 0],
+            vec![4, 0, 0, 0],
+        ]);
         game.move_in_direction(&MovementDirection::Down);
         let expected = [[0, 0, 0, 0], [0, 0, 0, 0], [4, 0, 0, 0], [4, 0, 0, 0]];
         fix_gen(&mut game, &expected);
-        assert_eq!(game.board, expected);
+        assert_eq!(game.board(), expected);
+    }
+
+    #[test]
+    fn test_merged_cells_and_last_spawn() {
+        let mut game =
+            Game2048::from_board([[0, 0, 0, 0], [0, 0, 2, 2], [0, 0, 0, 0], [0, 4, 0, 4]]).unwrap();
+        game.move_in_direction(&MovementDirection::Right);
+        assert_eq!(game.merged_cells(), &[(1, 3), (3, 3)]);
+        let (i, j, tile) = game.last_spawn().expect("A tile should be spawned");
+        assert_eq!(game.board.get((i, j)), tile);
+        assert!(tile == 2 || tile == 4);
+    }
+
+    #[test]
+    fn test_move_result_reports_chains() {
+        let mut game = Game2048::from_board([
+            [2, 2, 4, 4],
+            [8, 8, 0, 0],
+            [0; DEFAULT_SIZE],
+            [0; DEFAULT_SIZE],
+        ])
+        .unwrap();
+        game.move_in_direction(&MovementDirection::Left);
+        let result = game.last_move().unwrap();
+        assert!(result.moved);
+        assert_eq!(result.merges, 3);
+        assert_eq!(result.chains, vec![MergeChain { line: 0, merges: 2 }]);
+        assert_eq!(result.chains[0].name(), "Double");
+    }
+
+    #[test]
+    fn test_merges_score_the_created_tiles() {
+        let mut line = [2, 2, 4, 4];
+        let outcome = merge_line(&mut line);
+        assert_eq!(line, [4, 8, 0, 0]);
+        assert_eq!(outcome.points, 12);
+        assert_eq!(outcome.merges, vec![0, 1]);
+
+        let mut line = [0, 8, 2, 8];
+        assert_eq!(merge_line(&mut line), MergeOutcome::default());
+        assert_eq!(line, [8, 2, 8, 0]);
+
+        let mut game = Game2048::from_board([
+            [2, 2, 0, 0],
+            [0; DEFAULT_SIZE],
+            [0; DEFAULT_SIZE],
+            [0; DEFAULT_SIZE],
+        ])
+        .unwrap();
+        game.move_in_direction(&MovementDirection::Left);
+        assert_eq!(game.score(), 4);
+        assert_eq!(game.last_move().unwrap().points, 4);
+    }
+
+    #[test]
+    fn test_no_tile_merges_twice_in_one_move() {
+        for (before, after, merges) in [
+            ([2, 2, 2, 2], [4, 4, 0, 0], vec![0, 1]),
+            ([4, 2, 2, 0], [4, 4, 0, 0], vec![1]),
+            ([2, 2, 4, 0], [4, 4, 0, 0], vec![0]),
+            ([2, 2, 4, 8], [4, 4, 8, 0], vec![0]),
+            ([4, 4, 0, 8], [8, 8, 0, 0], vec![0]),
+            ([2, 0, 2, 2], [4, 2, 0, 0], vec![0]),
+        ] {
+            let mut line = before;
+            assert_eq!(merge_line(&mut line).merges, merges, "{:?}", before);
+            assert_eq!(line, after, "{:?}", before);
+        }
+    }
+
+    #[test]
+    fn test_scores_and_tiles_do_not_overflow() {
+        // The score grows past the largest u32
+        let mut game = Game2048::builder()
+            .seed(1)
+            .resume(Progress {
+                board: vec![vec![1 << 30, 1 << 30], vec![0, 0]],
+                score: u64::from(u32::MAX),
+                moves: 0,
+                undos_used: 0,
+                history: Vec::new(),
+            })
+            .build()
+            .unwrap();
+        assert!(game.move_in_direction(&MovementDirection::Left));
+        assert_eq!(game.score(), u64::from(u32::MAX) + (1 << 31));
+        assert_eq!(game.board()[0][0], MAX_TILE);
+
+        // The largest tiles stay apart, and a board of them is stuck
+        let mut line = [MAX_TILE, MAX_TILE, 2, 2];
+        let (points, moved) = slide_line(&mut line, 4, MergeRule::Double, |_| {});
+        assert_eq!(line, [MAX_TILE, MAX_TILE, 4, 0]);
+        assert_eq!((points, moved), (4, true));
+        let stuck = Game2048::from_board([[MAX_TILE; 2]; 2]).unwrap();
+        assert!(stuck.game_over());
+        assert!(MergeRule::Halve.can_merge(MAX_TILE));
+    }
+
+    #[test]
+    fn test_slide_board_in_every_direction() {
+        let start = Board::from_rows(vec![
+            vec![2, 2, 0, 4],
+            vec![0, 4, 4, 4],
+            vec![2, 0, 0, 2],
+            vec![8, 4, 2, 2],
+        ])
+        .unwrap();
+
+        let mut board = start.clone();
+        let mut merged = Vec::new();
+        let slide = slide_board(&mut board, &MovementDirection::Right, |line, pos| {
+            merged.push((line, pos))
+        });
+        assert_eq!((slide.moved, slide.points), (true, 4 + 8 + 4 + 4));
+        assert_eq!(
+            board.rows(),
+            [[0, 0, 4, 4], [0, 0, 4, 8], [0, 0, 0, 4], [0, 8, 4, 4]]
+        );
+        assert_eq!(merged, [(0, (0, 2)), (1, (1, 3)), (2, (2, 3)), (3, (3, 3))]);
+
+        let mut board = start.clone();
+        let slide = slide_board(&mut board, &MovementDirection::Up, |_, _| {});
+        assert_eq!((slide.moved, slide.points), (true, 4 + 8 + 8 + 4));
+        assert_eq!(
+            board.rows(),
+            [[4, 2, 4, 8], [8, 8, 2, 4], [0, 0, 0, 0], [0, 0, 0, 0]]
+        );
+
+        // Nothing moves down in a full column without neighbouring pairs
+        let mut board = Board::from_rows(vec![vec![2, 4], vec![4, 2]]).unwrap();
+        let slide = slide_board(&mut board, &MovementDirection::Down, |_, _| {});
+        assert_eq!(slide, Slide::default());
+        assert_eq!(board.rows(), [[2, 4], [4, 2]]);
+    }
+
+    #[test]
+    fn test_skill_estimate_adjusts_four_chance() {
+        let mut skill = SkillEstimate::new(FOUR_CHANCE);
+        assert_eq!(skill.four_chance(), FOUR_CHANCE);
+        for _ in 0..20 {
+            skill.update(14);
+        }
+        assert!(skill.four_chance() > FOUR_CHANCE);
+        for _ in 0..20 {
+            skill.update(0);
+        }
+        assert_eq!(skill.four_chance(), 0.0);
+    }
+
+    #[test]
+    fn test_has_won() {
+        let mut game = Game2048::from_board([
+            [1024, 1024, 0, 0],
+            [0; DEFAULT_SIZE],
+            [0; DEFAULT_SIZE],
+            [0; DEFAULT_SIZE],
+        ])
+        .unwrap();
+        assert!(!game.has_won());
+        game.move_in_direction(&MovementDirection::Left);
+        assert!(game.has_won());
     }
 
     #[test]
     fn test_game_over() {
         let mut game = Game2048::default();
-        game.board = [[2, 8, 4, 16], [8, 2, 16, 4], [32, 4, 2, 32], [2, 16, 32, 2]];
+        game.board = Board::from_rows_unchecked(vec![
+            vec![2, 8, 4, 16],
+            vec![8, 2, 16, 4],
+            vec![32, 4, 2, 32],
+            vec![2, 16, 32, 2],
+        ]);
         assert!(game.game_over());
     }
 
     fn fix_gen(game: &mut Game2048, expected: &[[u32; 4]; 4]) {
-        for i in 0..SIZE {
-            for j in 0..SIZE {
-                if game.board[i][j] != expected[i][j]
+        for i in 0..DEFAULT_SIZE {
+            for j in 0..DEFAULT_SIZE {
+                if game.board.get((i, j)) != expected[i][j]
                     && expected[i][j] == 0
-                    && (game.board[i][j] == 2 || game.board[i][j] == 4)
+                    && (game.board.get((i, j)) == 2 || game.board.get((i, j)) == 4)
                 {
-                    game.board[i][j] = 0; // Ignore generated tiles
+                    game.board.set((i, j), 0); // Ignore generated tiles
                     return;
                 }
             }
@@ -315,39 +1757,614 @@ mod tests {
         let mut game = Game2048::default();
 
         // Make a move (use a controlled scenario)
-        game.board = [[2, 2, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
-        game.save_state(); // Save the state before moving
+        game.board = Board::from_rows_unchecked(vec![
+            vec![2, 2, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+        ]);
         game.move_in_direction(&MovementDirection::Left);
+        assert_eq!(game.moves(), 1);
 
         // Undo the move
         let undo_success = game.undo();
+        assert_eq!(game.moves(), 0, "Undo should take the move back");
 
         // Verify undo was successful
         assert!(undo_success, "Undo should succeed after a move");
 
         // Verify board and score are restored
         assert_eq!(
-            game.board,
+            game.board(),
             [[2, 2, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]],
             "Board should be restored after undo"
         );
     }
 
+    #[test]
+    fn test_history_is_limited() {
+        let mut game = Game2048::default();
+        for score in 0..HISTORY_LIMIT as u64 + 10 {
+            game.save_state(game.board.clone(), score);
+        }
+        assert_eq!(game.past_states(), HISTORY_LIMIT);
+        assert_eq!(game.past_state(0).unwrap().1, 10);
+
+        // Unlimited undo keeps every state, so the history does too
+        let mut game = Game2048::builder().unlimited_undo().build().unwrap();
+        for score in 0..HISTORY_LIMIT as u64 + 10 {
+            game.save_state(game.board.clone(), score);
+        }
+        assert_eq!(game.past_states(), HISTORY_LIMIT + 10);
+    }
+
     #[test]
     fn test_undo_limit() {
         let mut game = Game2048::default();
 
         // Make more moves than the undo limit
-        for _ in 0..UNDO_LIMIT + 5 {
-            game.save_state();
+        for _ in 0..DEFAULT_UNDO_LIMIT + 5 {
+            game.save_state(game.board.clone(), game.score);
         }
 
         // We should be able to undo only up to the limit
-        for _ in 0..UNDO_LIMIT {
+        assert_eq!(game.undo_depth(), DEFAULT_UNDO_LIMIT);
+        for _ in 0..DEFAULT_UNDO_LIMIT {
             assert!(game.undo(), "Should be able to undo within the limit");
         }
 
         // Further undos should fail
+        assert!(!game.can_undo());
         assert!(!game.undo(), "Undo should fail when exceeding the limit");
     }
+
+    #[test]
+    fn test_unlimited_and_disabled_undo() {
+        let mut game = Game2048::builder().unlimited_undo().build().unwrap();
+        assert_eq!(game.undo_limit(), None);
+        assert!(game.has_lenient_undo());
+        assert!(!Game2048::default().has_lenient_undo());
+        for _ in 0..DEFAULT_UNDO_LIMIT * 10 {
+            game.save_state(game.board.clone(), game.score);
+        }
+        for _ in 0..DEFAULT_UNDO_LIMIT * 10 {
+            assert!(game.undo());
+        }
+
+        let mut game = Game2048::builder().undo_limit(0).build().unwrap();
+        let moved = [MovementDirection::Left, MovementDirection::Up]
+            .iter()
+            .any(|direction| game.move_in_direction(direction));
+        assert!(moved);
+        assert!(!game.undo(), "Undo should be disabled");
+    }
+
+    #[test]
+    fn test_undo_policy_limits_undos_and_costs_points() {
+        let policy = UndoPolicy {
+            max_undos: Some(1),
+            penalty: 4,
+        };
+        let mut game = Game2048::builder().undo_policy(policy).build().unwrap();
+        game.board = Board::from_rows_unchecked(vec![
+            vec![2, 2, 0, 0],
+            vec![0; 4],
+            vec![4, 4, 0, 0],
+            vec![0; 4],
+        ]);
+        game.score = 10;
+        assert_eq!(game.undos_left(), Some(1));
+
+        game.move_in_direction(&MovementDirection::Left);
+        let score = game.score();
+        game.move_in_direction(&MovementDirection::Right);
+        assert_eq!(game.undo_depth(), 1, "Two moves back, but one undo left");
+        let (board, peeked_score) = game.peek_undo().unwrap();
+        let board = board.to_vec();
+        assert_eq!(peeked_score, score - 4);
+        assert!(game.undo());
+        assert_eq!(game.board(), board);
+        assert_eq!(game.score(), score - 4);
+        assert_eq!(game.undos_left(), Some(0));
+        assert!(!game.can_undo());
+        assert!(!game.undo(), "The only undo was used up");
+        assert!(game.peek_undo().is_none());
+        assert_eq!(Game2048::default().undos_left(), None);
+
+        // Counted or costly undos keep even a long history ranked
+        let game = Game2048::builder()
+            .unlimited_undo()
+            .undo_policy(policy)
+            .build()
+            .unwrap();
+        assert!(!game.has_lenient_undo());
+    }
+
+    #[test]
+    fn test_builder_configures_game() {
+        let mut game = Game2048::builder()
+            .size(5)
+            .seed(3)
+            .win_tile(64)
+            .four_chance(1.0)
+            .initial_tiles(4)
+            .undo_limit(1)
+            .build()
+            .unwrap();
+        assert_eq!(game.size(), 5);
+        assert_eq!(game.win_tile(), 64);
+        assert_eq!(game.undo_limit(), Some(1));
+        let tiles: Vec<u32> = game
+            .board()
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&x| x != 0)
+            .collect();
+        assert_eq!(tiles, vec![4; 4]);
+
+        for direction in [MovementDirection::Left, MovementDirection::Right] {
+            game.move_in_direction(&direction);
+        }
+        assert!(game.undo());
+        assert!(!game.undo());
+
+        // Resetting keeps the settings and the seed
+        let first_board = Game2048::builder()
+            .size(5)
+            .seed(3)
+            .four_chance(1.0)
+            .initial_tiles(4)
+            .build()
+            .unwrap()
+            .board()
+            .to_vec();
+        game.reset();
+        assert_eq!(game.board(), first_board);
+        assert_eq!((game.moves(), game.score()), (0, 0));
+        assert_eq!(game.win_tile(), 64);
+        assert_eq!(game.undo_limit(), Some(1));
+        assert!(!game.can_undo());
+    }
+
+    #[test]
+    fn test_spawn_policies() {
+        let mut game = Game2048::builder()
+            .seed(5)
+            .four_chance(1.0)
+            .spawn_policy(AlwaysTwo)
+            .build()
+            .unwrap();
+        assert_eq!(game.board().iter().flatten().sum::<u32>(), 4);
+        while !game.move_in_direction(&MovementDirection::Left) {
+            game.move_in_direction(&MovementDirection::Up);
+        }
+        let (i, j, tile) = game.last_spawn().unwrap();
+        assert_eq!((game.board()[i][j], tile), (2, 2));
+
+        // Without spawns only merges change the board
+        let mut game = Game2048::builder()
+            .spawn_policy(NoSpawn)
+            .resume(Progress {
+                board: vec![vec![2, 2], vec![0, 0]],
+                score: 0,
+                moves: 0,
+                undos_used: 0,
+                history: Vec::new(),
+            })
+            .build()
+            .unwrap();
+        assert!(game.move_in_direction(&MovementDirection::Left));
+        assert_eq!(game.board(), [[4, 0], [0, 0]]);
+        assert_eq!(game.last_spawn(), None);
+    }
+
+    #[test]
+    fn test_divide_games_halve_tiles_down_to_the_win() {
+        let game = Game2048::builder()
+            .seed(3)
+            .win_tile(1024)
+            .merge_rule(MergeRule::Halve)
+            .spawn_policy(AlwaysTwo)
+            .build()
+            .unwrap();
+        // Tiles spawn as the target
+        assert!(game.board().iter().flatten().all(|&t| t == 0 || t == 1024));
+        assert_eq!(game.win_tile(), 2);
+        assert_eq!(game.target_tile(), 1024);
+
+        let mut line =
+            Board::from_rows(vec![vec![8, 8, 2, 2], vec![0; 4], vec![0; 4], vec![0; 4]]).unwrap();
+        let slide = slide_board_by(
+            &mut line,
+            &MovementDirection::Left,
+            MergeRule::Halve,
+            |_, _| {},
+        );
+        // Two 2s vanish, letting nothing take their place
+        assert_eq!(line.rows()[0], [4, 0, 0, 0]);
+        assert_eq!(slide.points, 8 + 4);
+
+        let mut game = Game2048::builder()
+            .merge_rule(MergeRule::Halve)
+            .spawn_policy(NoSpawn)
+            .resume(Progress {
+                board: vec![vec![4, 4], vec![0, 8]],
+                score: 0,
+                moves: 0,
+                undos_used: 0,
+                history: Vec::new(),
+            })
+            .build()
+            .unwrap();
+        assert!(!game.has_won());
+        assert!(game.move_in_direction(&MovementDirection::Left));
+        assert_eq!(game.board(), [[2, 0], [8, 0]]);
+        assert_eq!(game.merged_cells(), [(0, 0)]);
+        assert!(game.has_won());
+    }
+
+    #[test]
+    fn test_colors_follow_the_target() {
+        let regular = Game2048::with_seed(1);
+        assert_eq!(regular.color_tile(0), 0);
+        assert_eq!(regular.color_tile(2), 2);
+        assert_eq!(regular.color_tile(2048), 2048);
+
+        let easy = Game2048::builder().win_tile(1024).build().unwrap();
+        assert_eq!(easy.color_tile(1024), 2048);
+        assert_eq!(easy.color_tile(2), 4);
+        let hard = Game2048::builder().win_tile(8192).build().unwrap();
+        assert_eq!(hard.color_tile(8192), 2048);
+        assert_eq!(hard.color_tile(16), 4);
+        // Tiles too small to shift keep the smallest color
+        assert_eq!(hard.color_tile(4), 2);
+
+        let divide = Game2048::builder()
+            .merge_rule(MergeRule::Halve)
+            .build()
+            .unwrap();
+        assert_eq!(divide.color_tile(2048), 2);
+        assert_eq!(divide.color_tile(2), 2048);
+    }
+
+    #[test]
+    fn test_rectangular_boards() {
+        let mut game = Game2048::builder().dims(3, 5).seed(8).build().unwrap();
+        assert_eq!(game.dims(), (3, 5));
+        assert_eq!(
+            game.board().iter().flatten().filter(|&&t| t != 0).count(),
+            2
+        );
+        game.reset();
+        assert_eq!(game.dims(), (3, 5));
+
+        // Rows are longer than columns, so both kinds of lines must slide
+        let resume = |board: Vec<Vec<u32>>| {
+            Game2048::builder()
+                .spawn_policy(NoSpawn)
+                .resume(Progress {
+                    board,
+                    score: 0,
+                    moves: 0,
+                    undos_used: 0,
+                    history: Vec::new(),
+                })
+                .build()
+                .unwrap()
+        };
+        let mut game = resume(vec![vec![2, 2, 4], vec![0, 0, 4]]);
+        assert!(game.move_in_direction(&MovementDirection::Right));
+        assert_eq!(game.board(), [[0, 4, 4], [0, 0, 4]]);
+        assert!(game.move_in_direction(&MovementDirection::Down));
+        assert_eq!(game.board(), [[0, 0, 0], [0, 4, 8]]);
+        assert_eq!(game.score(), 12);
+        assert!(!game.game_over());
+        assert!(game.swap_cells((0, 0), (1, 2)).is_ok());
+        assert!(game.remove_cell((2, 1)).is_err());
+
+        assert!(resume(vec![vec![2, 4, 2], vec![4, 2, 4]]).game_over());
+        assert!(!resume(vec![vec![2, 4, 2], vec![4, 2, 2]]).game_over());
+        assert!(Game2048::builder().dims(2, 1).build().is_err());
+    }
+
+    #[test]
+    fn test_eights_spawn_with_their_chance() {
+        let game = Game2048::builder()
+            .four_chance(0.0)
+            .eight_chance(1.0)
+            .seed(4)
+            .build()
+            .unwrap();
+        let tiles: Vec<u32> = game.board().iter().flatten().copied().collect();
+        assert_eq!(tiles.iter().filter(|&&tile| tile == 8).count(), 2);
+        assert_eq!(
+            tiles.iter().filter(|&&tile| tile != 0 && tile != 8).count(),
+            0
+        );
+        assert_eq!(game.tile_odds().eight, 1.0);
+
+        // The odds outlive a restart
+        let mut game = game;
+        game.reset();
+        assert_eq!(game.tile_odds().eight, 1.0);
+
+        // Only games with the usual odds are ranked
+        assert_ne!(game.tile_odds(), TileOdds::default());
+        assert_eq!(Game2048::default().tile_odds(), TileOdds::default());
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_settings() {
+        assert!(Game2048::builder().size(1).build().is_err());
+        assert!(Game2048::builder().win_tile(100).build().is_err());
+        assert!(Game2048::builder().four_chance(1.5).build().is_err());
+        assert!(Game2048::builder().eight_chance(-0.1).build().is_err());
+        assert!(
+            Game2048::builder()
+                .four_chance(0.5)
+                .eight_chance(0.6)
+                .build()
+                .is_err()
+        );
+        assert!(
+            Game2048::builder()
+                .size(2)
+                .initial_tiles(5)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_divide_game_may_target_the_largest_tile() {
+        // 2s spawn as the target and 4s as half of it, without overflowing
+        for (four_chance, spawned) in [(0.0, MAX_TILE), (1.0, MAX_TILE / 2)] {
+            let game = Game2048::builder()
+                .merge_rule(MergeRule::Halve)
+                .win_tile(MAX_TILE)
+                .four_chance(four_chance)
+                .seed(5)
+                .build()
+                .unwrap();
+            let tiles: Vec<u32> = game.board().iter().flatten().copied().collect();
+            assert_eq!(tiles.iter().filter(|&&tile| tile == spawned).count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_from_board_validates_tiles() {
+        let game = Game2048::from_board([[2, 0, 0], [0, 4, 0], [0, 0, 2048]]).unwrap();
+        assert_eq!(game.size(), 3);
+        assert_eq!(game.board(), [[2, 0, 0], [0, 4, 0], [0, 0, 2048]]);
+        assert_eq!(game.moves(), 0);
+        assert!(Game2048::from_board([[2, 3], [0, 0]]).is_err());
+        assert!(Game2048::from_board([[1, 0], [0, 0]]).is_err());
+        assert!(Game2048::from_board([[2]]).is_err());
+    }
+
+    #[cfg(feature = "sandbox")]
+    #[test]
+    fn test_set_cell() {
+        let mut game = Game2048::from_board([[2, 0], [0, 0]]).unwrap();
+        game.set_cell((1, 1), 8).unwrap();
+        game.set_cell((0, 0), 0).unwrap();
+        assert_eq!(game.board(), [[0, 0], [0, 8]]);
+        assert!(game.set_cell((2, 0), 2).is_err());
+        assert!(game.set_cell((0, 0), 6).is_err());
+    }
+
+    #[test]
+    fn test_zen_game_clears_the_smallest_tiles_instead_of_ending() {
+        let board = vec![vec![2, 4, 2], vec![4, 8, 4], vec![16, 32, 0]];
+        let progress = |board: &Vec<Vec<u32>>| Progress {
+            board: board.clone(),
+            score: 0,
+            moves: 0,
+            undos_used: 0,
+            history: Vec::new(),
+        };
+        // With this seed a 2 fills the last cell, leaving the board stuck
+        let mut zen = Game2048::builder()
+            .seed(1)
+            .zen()
+            .resume(progress(&board))
+            .build()
+            .unwrap();
+        assert!(zen.move_in_direction(&MovementDirection::Right));
+        assert!(zen.is_zen());
+        assert!(!zen.game_over());
+        assert_eq!(zen.last_move().unwrap().cleared, 3);
+        assert_eq!(zen.board(), [[0, 4, 0], [4, 8, 4], [0, 16, 32]]);
+
+        let mut regular = Game2048::builder()
+            .seed(1)
+            .resume(progress(&board))
+            .build()
+            .unwrap();
+        regular.move_in_direction(&MovementDirection::Right);
+        assert!(regular.game_over());
+        assert_eq!(regular.last_move().unwrap().cleared, 0);
+    }
+
+    #[test]
+    fn test_arcade_combo_multiplies_merging_moves() {
+        let progress = Progress {
+            board: vec![
+                vec![2, 2, 0, 0],
+                vec![2, 2, 0, 0],
+                vec![8, 8, 0, 0],
+                vec![0, 0, 0, 0],
+            ],
+            score: 0,
+            moves: 0,
+            undos_used: 0,
+            history: Vec::new(),
+        };
+        let mut game = Game2048::builder()
+            .arcade()
+            .spawn_policy(NoSpawn)
+            .resume(progress)
+            .build()
+            .unwrap();
+        assert_eq!(game.combo(), Some(1));
+        assert!(game.move_in_direction(&MovementDirection::Left));
+        assert_eq!(game.last_move().unwrap().multiplier, 1);
+        assert_eq!(game.score(), 4 + 4 + 16);
+        // The second merging move in a row scores double
+        assert!(game.move_in_direction(&MovementDirection::Down));
+        assert_eq!(game.board()[2][0], 8);
+        assert_eq!(game.last_move().unwrap().multiplier, 2);
+        assert_eq!(game.last_move().unwrap().points, 2 * 8);
+        assert_eq!(game.score(), 24 + 2 * 8);
+        assert_eq!(game.combo(), Some(3));
+        // A move without merges breaks the combo
+        assert!(game.move_in_direction(&MovementDirection::Right));
+        assert_eq!(game.last_move().unwrap().multiplier, 0);
+        assert_eq!(game.combo(), Some(1));
+        assert_eq!(Game2048::with_seed(1).combo(), None);
+    }
+
+    #[test]
+    fn test_power_ups_edit_the_board() {
+        let mut game = Game2048::from_board([[2, 0], [8, 4]]).unwrap();
+        game.swap_cells((0, 0), (1, 1)).unwrap();
+        assert_eq!(game.board(), [[4, 0], [8, 2]]);
+        game.remove_cell((1, 0)).unwrap();
+        assert_eq!(game.board(), [[4, 0], [0, 2]]);
+        assert!(game.remove_cell((1, 0)).is_err());
+        assert!(game.swap_cells((0, 0), (2, 0)).is_err());
+
+        let mut game = Game2048::with_seed(8);
+        let mut tiles: Vec<u32> = game.board().iter().flatten().copied().collect();
+        game.shuffle().unwrap();
+        let mut shuffled: Vec<u32> = game.board().iter().flatten().copied().collect();
+        tiles.sort();
+        shuffled.sort();
+        assert_eq!(tiles, shuffled);
+        assert_eq!(game.score(), 0);
+        assert_eq!(game.last_spawn(), None);
+    }
+
+    #[test]
+    fn test_branch_goes_back_beyond_the_undo_limit() {
+        let mut game = Game2048::builder().seed(6).undo_limit(2).build().unwrap();
+        let mut states = vec![(game.board().to_vec(), game.score())];
+        let directions = [MovementDirection::Left, MovementDirection::Up];
+        while game.moves() < 6 {
+            for direction in &directions {
+                if game.move_in_direction(direction) {
+                    states.push((game.board().to_vec(), game.score()));
+                }
+            }
+        }
+        let moves = game.moves() as usize;
+        assert_eq!(game.past_states(), moves);
+        let (board, score) = game.past_state(1).unwrap();
+        assert_eq!((board.to_vec(), score), states[1]);
+
+        // Going back further than undo reaches, then undoing within the limit
+        game.branch(3).unwrap();
+        assert_eq!((game.board().to_vec(), game.score()), states[3]);
+        assert_eq!(game.moves(), 3);
+        assert_eq!(game.past_states(), 3);
+        assert_eq!(game.undos_left(), None);
+        assert!(game.undo());
+        assert!(game.undo());
+        assert!(!game.undo());
+        assert_eq!(game.board(), states[1].0);
+        assert!(game.branch(1).is_err());
+
+        let mut game = Game2048::builder()
+            .seed(6)
+            .undo_policy(UndoPolicy::hard())
+            .build()
+            .unwrap();
+        while game.moves() <= HARD_MODE_UNDOS {
+            game.move_in_direction(&MovementDirection::Left);
+            game.move_in_direction(&MovementDirection::Up);
+        }
+        assert!(game.branch(0).is_err());
+        game.branch(game.past_states() - 2).unwrap();
+        assert_eq!(game.undos_left(), Some(HARD_MODE_UNDOS - 2));
+    }
+
+    #[test]
+    fn test_inject_tile_fills_empty_cells() {
+        let mut game = Game2048::from_board([[2, 0], [4, 8]]).unwrap();
+        assert_eq!(game.inject_tile(2).unwrap(), Some((0, 1)));
+        assert_eq!(game.board(), [[2, 2], [4, 8]]);
+        assert_eq!(game.inject_tile(2).unwrap(), None);
+        assert!(game.inject_tile(3).is_err());
+        assert_eq!(game.score(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip_keeps_spawns() {
+        let mut game = Game2048::builder()
+            .seed(9)
+            .adaptive_difficulty()
+            .build()
+            .unwrap();
+        game.move_in_direction(&MovementDirection::Left);
+        game.move_in_direction(&MovementDirection::Up);
+        let json = serde_json::to_string(&game).unwrap();
+        let mut restored: Game2048 = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.progress(), game.progress());
+        assert_eq!(restored.peek_undo(), game.peek_undo());
+
+        // The generator continues where it left off
+        for direction in [MovementDirection::Down, MovementDirection::Right] {
+            game.move_in_direction(&direction);
+            restored.move_in_direction(&direction);
+        }
+        assert_eq!(restored.board(), game.board());
+        assert_eq!(restored.four_chance(), game.four_chance());
+
+        let direction: MovementDirection = serde_json::from_str("\"left\"").unwrap();
+        assert_eq!(direction, MovementDirection::Left);
+        assert!(serde_json::from_str::<Board>("[[3, 0], [0, 0]]").is_err());
+    }
+
+    #[test]
+    fn test_resume_continues_progress() {
+        let mut game = Game2048::builder()
+            .seed(5)
+            .undo_policy(UndoPolicy::hard())
+            .build()
+            .unwrap();
+        while game.moves() < 3 {
+            let moved = [MovementDirection::Left, MovementDirection::Up]
+                .iter()
+                .any(|direction| game.move_in_direction(direction));
+            assert!(moved);
+        }
+        assert!(game.undo());
+
+        let resumed = Game2048::builder()
+            .undo_policy(UndoPolicy::hard())
+            .resume(game.progress())
+            .build()
+            .unwrap();
+        assert_eq!(resumed.board(), game.board());
+        assert_eq!(resumed.score(), game.score());
+        assert_eq!(resumed.moves(), 2);
+        assert_eq!(resumed.undos_left(), Some(HARD_MODE_UNDOS - 1));
+        // The undo history is kept
+        assert_eq!(resumed.peek_undo(), game.peek_undo());
+        assert!(resumed.peek_undo().is_some());
+
+        let broken = Progress {
+            board: vec![vec![3, 0], vec![0, 0]],
+            score: 0,
+            moves: 0,
+            undos_used: 0,
+            history: Vec::new(),
+        };
+        assert!(Game2048::builder().resume(broken).build().is_err());
+    }
 }
+
+#[cfg(test)]
+mod proptests;
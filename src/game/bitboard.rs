@@ -0,0 +1,459 @@
+//! 4x4 game packed into a single `u64`, for searches and benchmarks.
+//!
+//! Every cell takes 4 bits holding the exponent of its tile (`0` for an
+//! empty cell, `1` for a 2, up to `15` for a 32768). Row `i` is in bits
+//! `16 * i..16 * i + 16` with column `j` in its `j`-th nibble, so a whole
+//! row is a `u16` and a move is a lookup per row in tables computed once.
+//! Vertical moves transpose the board, move its rows and transpose it back.
+
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+use rand::prelude::*;
+use rand_chacha::ChaCha12Rng;
+
+use super::{DEFAULT_UNDO_LIMIT, GameEngine, GameView, INITIAL_TILES, MovementDirection, TileOdds};
+use crate::board::{Board, is_valid_tile};
+use crate::error::{GameError, GameResult};
+
+/// Rows and columns of a bitboard
+pub const BITBOARD_SIZE: usize = 4;
+/// Largest tile a bitboard holds; two of them don't merge
+pub const MAX_BITBOARD_TILE: u32 = 1 << 15;
+
+/// Rows after a move and the points it scores, by the `u16` of the row
+struct Tables {
+    left: Vec<u16>,
+    left_points: Vec<u32>,
+    right: Vec<u16>,
+    right_points: Vec<u32>,
+}
+
+/// Nibbles of a row in reverse order
+fn reverse_row(row: u16) -> u16 {
+    (row >> 12) | ((row >> 4) & 0x00F0) | ((row << 4) & 0x0F00) | (row << 12)
+}
+
+/// A row moved left and the points scored, merging like
+/// [`merge_line`](super::merge_line)
+fn move_row_left(row: u16) -> (u16, u32) {
+    let mut cells = [0u16; 4];
+    let mut points = 0;
+    let mut write = 0;
+    let mut mergeable = false;
+    for j in 0..4 {
+        let exponent = (row >> (4 * j)) & 0xF;
+        if exponent == 0 {
+            continue;
+        }
+        if mergeable && cells[write - 1] == exponent && exponent < 15 {
+            cells[write - 1] += 1;
+            points += 1 << cells[write - 1];
+            mergeable = false;
+        } else {
+            cells[write] = exponent;
+            write += 1;
+            mergeable = true;
+        }
+    }
+    let moved = cells
+        .iter()
+        .enumerate()
+        .fold(0, |row, (j, &exponent)| row | (exponent << (4 * j)));
+    (moved, points)
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let (left, left_points) = (0..=u16::MAX).map(move_row_left).unzip();
+        let (right, right_points) = (0..=u16::MAX)
+            .map(|row| {
+                let (moved, points) = move_row_left(reverse_row(row));
+                (reverse_row(moved), points)
+            })
+            .unzip();
+        Tables {
+            left,
+            left_points,
+            right,
+            right_points,
+        }
+    })
+}
+
+/// Board mirrored along the main diagonal, so rows become columns
+pub fn transpose(board: u64) -> u64 {
+    // Swap the off-diagonal nibbles of each 2x2 block, then the off-diagonal
+    // 2x2 blocks
+    let a1 = board & 0xF0F0_0F0F_F0F0_0F0F;
+    let a2 = board & 0x0000_F0F0_0000_F0F0;
+    let a3 = board & 0x0F0F_0000_0F0F_0000;
+    let a = a1 | (a2 << 12) | (a3 >> 12);
+    let b1 = a & 0xFF00_FF00_00FF_00FF;
+    let b2 = a & 0x00FF_00FF_0000_0000;
+    let b3 = a & 0x0000_0000_FF00_FF00;
+    b1 | (b2 >> 24) | (b3 << 24)
+}
+
+/// Slide a packed board in a direction without spawning a tile, returning
+/// the board after the move and the points it scores. The board is
+/// unchanged if nothing can move.
+pub fn slide_bits(board: u64, direction: &MovementDirection) -> (u64, u64) {
+    let tables = tables();
+    let vertical = matches!(direction, MovementDirection::Up | MovementDirection::Down);
+    let (moves, points) = match direction {
+        MovementDirection::Left | MovementDirection::Up => (&tables.left, &tables.left_points),
+        MovementDirection::Right | MovementDirection::Down => (&tables.right, &tables.right_points),
+    };
+    let rows = if vertical { transpose(board) } else { board };
+    let mut moved = 0;
+    let mut total = 0;
+    for i in 0..4 {
+        let row = usize::from((rows >> (16 * i)) as u16);
+        moved |= u64::from(moves[row]) << (16 * i);
+        total += u64::from(points[row]);
+    }
+    (if vertical { transpose(moved) } else { moved }, total)
+}
+
+/// Exponent of a tile, checking it fits in a bitboard
+fn exponent(tile: u32) -> GameResult<u64> {
+    if !is_valid_tile(tile) || tile > MAX_BITBOARD_TILE {
+        return Err(GameError::InputError(format!(
+            "{} is not a tile a bitboard can hold",
+            tile
+        )));
+    }
+    Ok(if tile == 0 {
+        0
+    } else {
+        u64::from(tile.trailing_zeros())
+    })
+}
+
+/// Put a 2, or a 4 with the default chance, on a random empty cell of a
+/// packed board, drawing from `rng` like [`StandardSpawn`](super::StandardSpawn).
+///
+/// Returns the new board with the row, column and value of the tile, `None`
+/// if the board is full.
+pub fn spawn_bits<R: Rng + ?Sized>(board: u64, rng: &mut R) -> Option<(u64, (usize, usize, u32))> {
+    let mut empty = [(0, 0); BITBOARD_SIZE * BITBOARD_SIZE];
+    let mut count = 0;
+    for i in 0..BITBOARD_SIZE {
+        for j in 0..BITBOARD_SIZE {
+            if bits_tile(board, (i, j)) == 0 {
+                empty[count] = (i, j);
+                count += 1;
+            }
+        }
+    }
+    let &(i, j) = empty[..count].choose(rng)?;
+    let tile = TileOdds::default().sample(rng);
+    let exponent = u64::from(tile.trailing_zeros());
+    Some((board | exponent << (16 * i + 4 * j), (i, j, tile)))
+}
+
+/// Tile at a (row, column) position of a packed board
+pub fn bits_tile(board: u64, (i, j): (usize, usize)) -> u32 {
+    match (board >> (16 * i + 4 * j)) & 0xF {
+        0 => 0,
+        exponent => 1 << exponent,
+    }
+}
+
+/// Pack a 4x4 board of tiles up to [`MAX_BITBOARD_TILE`]
+pub fn pack(board: &Board) -> GameResult<u64> {
+    let (rows, columns) = board.dims();
+    if (rows, columns) != (BITBOARD_SIZE, BITBOARD_SIZE) {
+        return Err(GameError::InputError(format!(
+            "A bitboard is {}x{}, got a {}x{} board",
+            BITBOARD_SIZE, BITBOARD_SIZE, rows, columns
+        )));
+    }
+    board.iter_cells().try_fold(0, |bits, ((i, j), tile)| {
+        Ok(bits | exponent(tile)? << (16 * i + 4 * j))
+    })
+}
+
+/// Tiles of a packed board
+pub fn unpack(board: u64) -> Board {
+    Board::from_rows_unchecked(
+        (0..BITBOARD_SIZE)
+            .map(|i| {
+                (0..BITBOARD_SIZE)
+                    .map(|j| bits_tile(board, (i, j)))
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+/// The regular 4x4 game on a packed board.
+///
+/// It plays like a [`Game2048`](super::Game2048) with the default settings
+/// and spawns the same tiles for the same seed, but keeps only what a search
+/// needs: the tiles, the score, the move count and a short undo history.
+/// Tiles stop at [`MAX_BITBOARD_TILE`].
+#[derive(Debug, Clone)]
+pub struct BitboardGame2048 {
+    board: u64,
+    score: u64,
+    moves: u32,
+    previous_states: VecDeque<(u64, u64)>,
+    last_spawn: Option<(usize, usize, u32)>,
+    /// The board unpacked for [`GameView::board`], updated after every change
+    rows: Vec<Vec<u32>>,
+    seed: u64,
+    /// Whether the seed was chosen rather than random, so a reset game
+    /// replays the same tiles
+    seeded: bool,
+    rng: ChaCha12Rng,
+}
+
+impl BitboardGame2048 {
+    /// Create a game whose tile spawns are fully determined by the seed
+    pub fn with_seed(seed: u64) -> Self {
+        let mut game = Self::from_bits(0, seed);
+        for _ in 0..INITIAL_TILES {
+            game.spawn_tile();
+        }
+        game.sync_rows();
+        game
+    }
+
+    /// Create a game starting from a 4x4 position, with random spawns
+    pub fn from_board(board: &Board) -> GameResult<Self> {
+        let mut game = Self::from_bits(pack(board)?, rand::random());
+        game.seeded = false;
+        Ok(game)
+    }
+
+    /// Create a game starting from a packed position
+    pub fn from_bits(board: u64, seed: u64) -> Self {
+        let mut game = Self {
+            board,
+            score: 0,
+            moves: 0,
+            previous_states: VecDeque::new(),
+            last_spawn: None,
+            rows: vec![vec![0; BITBOARD_SIZE]; BITBOARD_SIZE],
+            seed,
+            seeded: true,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        };
+        game.sync_rows();
+        game
+    }
+
+    /// The packed board
+    pub fn bits(&self) -> u64 {
+        self.board
+    }
+
+    /// Seed the game was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Put a 2, or a 4 with the default chance, on a random empty cell
+    fn spawn_tile(&mut self) {
+        if let Some((board, spawn)) = spawn_bits(self.board, &mut self.rng) {
+            self.board = board;
+            self.last_spawn = Some(spawn);
+        }
+    }
+
+    /// Unpack the board into the rows shown by [`GameView::board`]
+    fn sync_rows(&mut self) {
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = bits_tile(self.board, (i, j));
+            }
+        }
+    }
+}
+
+impl GameView for BitboardGame2048 {
+    fn score(&self) -> u64 {
+        self.score
+    }
+
+    fn board(&self) -> &[Vec<u32>] {
+        &self.rows
+    }
+
+    fn game_over(&self) -> bool {
+        [
+            MovementDirection::Up,
+            MovementDirection::Down,
+            MovementDirection::Left,
+            MovementDirection::Right,
+        ]
+        .iter()
+        .all(|direction| slide_bits(self.board, direction).0 == self.board)
+    }
+
+    fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    fn size(&self) -> usize {
+        BITBOARD_SIZE
+    }
+
+    fn last_spawn(&self) -> Option<(usize, usize, u32)> {
+        self.last_spawn
+    }
+
+    fn spawn_seed(&self) -> Option<u64> {
+        Some(self.seed)
+    }
+}
+
+impl GameEngine for BitboardGame2048 {
+    fn move_in_direction(&mut self, direction: &MovementDirection) -> bool {
+        let (board, points) = slide_bits(self.board, direction);
+        if board == self.board {
+            return false;
+        }
+        self.previous_states.push_back((self.board, self.score));
+        if self.previous_states.len() > DEFAULT_UNDO_LIMIT {
+            self.previous_states.pop_front();
+        }
+        self.board = board;
+        self.score += points;
+        self.moves += 1;
+        self.last_spawn = None;
+        self.spawn_tile();
+        self.sync_rows();
+        true
+    }
+
+    fn undo(&mut self) -> bool {
+        let Some((board, score)) = self.previous_states.pop_back() else {
+            return false;
+        };
+        self.board = board;
+        self.score = score;
+        self.moves = self.moves.saturating_sub(1);
+        self.last_spawn = None;
+        self.sync_rows();
+        true
+    }
+
+    fn reset(&mut self) {
+        *self = if self.seeded {
+            Self::with_seed(self.seed)
+        } else {
+            Self::default()
+        };
+    }
+}
+
+impl Default for BitboardGame2048 {
+    fn default() -> Self {
+        let mut game = Self::with_seed(rand::random());
+        game.seeded = false;
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game2048, slide_board};
+
+    const DIRECTIONS: [MovementDirection; 4] = [
+        MovementDirection::Up,
+        MovementDirection::Down,
+        MovementDirection::Left,
+        MovementDirection::Right,
+    ];
+
+    /// Random 4x4 board with tiles up to 2048
+    fn random_board(rng: &mut impl Rng) -> Board {
+        Board::from_rows(
+            (0..4)
+                .map(|_| {
+                    (0..4)
+                        .map(|_| match rng.random_range(0..=11) {
+                            0 => 0,
+                            exponent => 1 << exponent,
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_pack_and_transpose() {
+        let board = Board::from_rows(vec![
+            vec![2, 4, 8, 16],
+            vec![0, 0, 0, 32768],
+            vec![0, 2, 0, 0],
+            vec![1024, 0, 0, 2048],
+        ])
+        .unwrap();
+        let bits = pack(&board).unwrap();
+        assert_eq!(unpack(bits), board);
+        assert_eq!(bits_tile(bits, (1, 3)), 32768);
+        assert_eq!(unpack(transpose(bits)), board.transpose());
+
+        assert!(pack(&Board::new(3)).is_err());
+        let mut large = Board::new(4);
+        large.set((0, 0), 65536);
+        assert!(pack(&large).is_err());
+    }
+
+    #[test]
+    fn test_slides_match_the_array_implementation() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..500 {
+            let board = random_board(&mut rng);
+            for direction in &DIRECTIONS {
+                let mut expected = board.clone();
+                let slide = slide_board(&mut expected, direction, |_, _| {});
+                let (bits, points) = slide_bits(pack(&board).unwrap(), direction);
+                assert_eq!(unpack(bits), expected, "{:?} {:?}", board, direction);
+                assert_eq!(points, slide.points, "{:?} {:?}", board, direction);
+            }
+        }
+    }
+
+    #[test]
+    fn test_largest_tiles_do_not_merge() {
+        let mut board = Board::new(4);
+        board.set((0, 0), MAX_BITBOARD_TILE);
+        board.set((0, 1), MAX_BITBOARD_TILE);
+        let bits = pack(&board).unwrap();
+        assert_eq!(slide_bits(bits, &MovementDirection::Left), (bits, 0));
+    }
+
+    #[test]
+    fn test_games_match_the_array_implementation() {
+        for seed in 0..20 {
+            let mut game = Game2048::with_seed(seed);
+            let mut bitboard = BitboardGame2048::with_seed(seed);
+            let mut rng = StdRng::seed_from_u64(seed);
+            assert_eq!(bitboard.board(), game.board());
+            while !game.game_over() {
+                let direction = DIRECTIONS[rng.random_range(0..4)];
+                assert_eq!(
+                    bitboard.move_in_direction(&direction),
+                    game.move_in_direction(&direction)
+                );
+                if rng.random_bool(0.05) {
+                    assert_eq!(bitboard.undo(), game.undo());
+                }
+                assert_eq!(bitboard.board(), game.board());
+                assert_eq!(bitboard.score(), game.score());
+                assert_eq!(bitboard.moves(), game.moves());
+                assert_eq!(bitboard.last_spawn(), game.last_spawn());
+                assert_eq!(bitboard.game_over(), game.game_over());
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Experimental 3D variant, played on a cube of tiles such as 3x3x3.
+//!
+//! The cube is a stack of square layers as large as the cube is deep. Besides
+//! the four directions of the regular game, tiles slide forward to the first
+//! layer and back to the last one. Every line of cells along the direction
+//! is slid like a row of a board, so merges score as they do in 2D.
+
+use std::ops::{Index, IndexMut};
+
+use rand::prelude::*;
+use rand_chacha::ChaCha12Rng;
+
+use super::spawn::TileOdds;
+use super::{INITIAL_TILES, MergeRule, slide_line};
+use crate::board::Board;
+use crate::error::{GameError, GameResult};
+
+/// Default number of cells along each edge of the cube
+pub const DEFAULT_CUBE_SIZE: usize = 3;
+
+/// Position of a cell: layer, row and column
+pub type Coord = (usize, usize, usize);
+
+/// Direction tiles slide in on a cube
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction3D {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// Towards the first layer
+    Forward,
+    /// Towards the last layer
+    Back,
+}
+
+impl Direction3D {
+    /// All six directions
+    pub const ALL: [Direction3D; 6] = [
+        Direction3D::Left,
+        Direction3D::Right,
+        Direction3D::Up,
+        Direction3D::Down,
+        Direction3D::Forward,
+        Direction3D::Back,
+    ];
+
+    /// Axis the tiles move along (0 for layers, 1 for rows, 2 for columns)
+    /// and whether they move towards the start of it
+    fn axis(self) -> (usize, bool) {
+        match self {
+            Direction3D::Forward => (0, true),
+            Direction3D::Back => (0, false),
+            Direction3D::Up => (1, true),
+            Direction3D::Down => (1, false),
+            Direction3D::Left => (2, true),
+            Direction3D::Right => (2, false),
+        }
+    }
+}
+
+/// Cube of tiles, `0` marking an empty cell, stored as its layers
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CubeBoard {
+    layers: Vec<Board>,
+}
+
+impl CubeBoard {
+    /// Empty cube with `size` cells along each edge
+    pub fn new(size: usize) -> Self {
+        Self {
+            layers: (0..size).map(|_| Board::new(size)).collect(),
+        }
+    }
+
+    /// Cube of the given layers, front to back, which must be squares of
+    /// valid tiles with as many rows as there are layers
+    pub fn from_layers(layers: Vec<Vec<Vec<u32>>>) -> GameResult<Self> {
+        let size = layers.len();
+        let layers = layers
+            .into_iter()
+            .map(Board::from_rows)
+            .collect::<GameResult<Vec<_>>>()?;
+        if layers.iter().any(|layer| layer.dims() != (size, size)) {
+            return Err(GameError::InputError(
+                "A cube must have as many layers as rows and columns".to_string(),
+            ));
+        }
+        Ok(Self { layers })
+    }
+
+    /// Number of cells along each edge
+    pub fn size(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// All layers, front to back
+    pub fn layers(&self) -> &[Board] {
+        &self.layers
+    }
+
+    /// Tile at a position
+    pub fn get(&self, (l, i, j): Coord) -> u32 {
+        self.layers[l].get((i, j))
+    }
+
+    /// Replace the tile at a position
+    pub fn set(&mut self, (l, i, j): Coord, tile: u32) {
+        self.layers[l].set((i, j), tile);
+    }
+
+    /// Positions of the empty cells, layer by layer
+    pub fn empty_cells(&self) -> Vec<Coord> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(l, layer)| layer.empty_cells().into_iter().map(move |(i, j)| (l, i, j)))
+            .collect()
+    }
+
+    /// Largest tile, `0` on an empty cube
+    pub fn max_tile(&self) -> u32 {
+        self.layers.iter().map(Board::max_tile).max().unwrap_or(0)
+    }
+}
+
+/// Position of the `t`-th cell of line `k` of a cube with `size` cells along
+/// each edge, counted from the side tiles slide towards. The lines along an
+/// axis are numbered by the two other coordinates.
+fn line_cell(k: usize, t: usize, size: usize, direction: Direction3D) -> Coord {
+    let (axis, to_start) = direction.axis();
+    let along = if to_start { t } else { size - 1 - t };
+    let (a, b) = (k / size, k % size);
+    match axis {
+        0 => (along, a, b),
+        1 => (a, along, b),
+        _ => (a, b, along),
+    }
+}
+
+/// A line of cells of a cube, indexed from the side tiles slide towards
+struct Line<'a> {
+    cube: &'a mut CubeBoard,
+    k: usize,
+    direction: Direction3D,
+}
+
+impl Index<usize> for Line<'_> {
+    type Output = u32;
+
+    fn index(&self, t: usize) -> &u32 {
+        let (l, i, j) = line_cell(self.k, t, self.cube.size(), self.direction);
+        &self.cube.layers[l].row(i)[j]
+    }
+}
+
+impl IndexMut<usize> for Line<'_> {
+    fn index_mut(&mut self, t: usize) -> &mut u32 {
+        let (l, i, j) = line_cell(self.k, t, self.cube.size(), self.direction);
+        &mut self.cube.layers[l].row_mut(i)[j]
+    }
+}
+
+/// Slide every tile of a cube in a direction, merging equal neighbours,
+/// without spawning a tile. Returns the points scored and whether any tile
+/// moved.
+pub fn slide_cube(cube: &mut CubeBoard, direction: Direction3D) -> (u64, bool) {
+    let size = cube.size();
+    let mut points = 0;
+    let mut moved = false;
+    for k in 0..size * size {
+        let mut line = Line {
+            cube: &mut *cube,
+            k,
+            direction,
+        };
+        let (line_points, line_moved) = slide_line(&mut line, size, MergeRule::Double, |_| {});
+        points += line_points;
+        moved |= line_moved;
+    }
+    (points, moved)
+}
+
+/// A game of 2048 on a cube
+#[derive(Debug, Clone)]
+pub struct Cube2048 {
+    board: CubeBoard,
+    score: u64,
+    moves: u32,
+    odds: TileOdds,
+    rng: ChaCha12Rng,
+    last_spawn: Option<Coord>,
+}
+
+impl Cube2048 {
+    /// New game on a cube with `size` cells along each edge (at least 2),
+    /// with two tiles spawned from the seed
+    pub fn new(size: usize, seed: u64) -> GameResult<Self> {
+        if size < 2 {
+            return Err(GameError::InputError(format!(
+                "Cube size must be at least 2, got {}",
+                size
+            )));
+        }
+        Ok(Self::resume(CubeBoard::new(size), seed).with_tiles(INITIAL_TILES))
+    }
+
+    /// Game continuing from a cube, spawning from the seed
+    pub fn resume(board: CubeBoard, seed: u64) -> Self {
+        Self {
+            board,
+            score: 0,
+            moves: 0,
+            odds: TileOdds::default(),
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            last_spawn: None,
+        }
+    }
+
+    fn with_tiles(mut self, count: usize) -> Self {
+        for _ in 0..count {
+            self.spawn();
+        }
+        self
+    }
+
+    pub fn board(&self) -> &CubeBoard {
+        &self.board
+    }
+
+    pub fn score(&self) -> u64 {
+        self.score
+    }
+
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    /// Where the last tile spawned, if one did since the game started
+    pub fn last_spawn(&self) -> Option<Coord> {
+        self.last_spawn
+    }
+
+    /// Move the tiles in a direction and spawn a tile if any moved
+    pub fn move_in_direction(&mut self, direction: Direction3D) -> bool {
+        let (points, moved) = slide_cube(&mut self.board, direction);
+        if moved {
+            self.score += points;
+            self.moves += 1;
+            self.spawn();
+        }
+        moved
+    }
+
+    /// Whether no move in any of the six directions changes the cube
+    pub fn game_over(&self) -> bool {
+        Direction3D::ALL
+            .iter()
+            .all(|&direction| !slide_cube(&mut self.board.clone(), direction).1)
+    }
+
+    /// Put a random tile on a random empty cell
+    fn spawn(&mut self) {
+        let Some(&pos) = self.board.empty_cells().choose(&mut self.rng) else {
+            return;
+        };
+        let tile = self.odds.sample(&mut self.rng);
+        self.board.set(pos, tile);
+        self.last_spawn = Some(pos);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiles_slide_along_all_three_axes() {
+        let mut cube = CubeBoard::new(2);
+        cube.set((1, 1, 1), 2);
+        cube.set((0, 1, 1), 2);
+        assert_eq!(slide_cube(&mut cube, Direction3D::Forward), (4, true));
+        assert_eq!(cube.get((0, 1, 1)), 4);
+        assert_eq!(cube.get((1, 1, 1)), 0);
+
+        assert_eq!(slide_cube(&mut cube, Direction3D::Up), (0, true));
+        assert_eq!(slide_cube(&mut cube, Direction3D::Left), (0, true));
+        assert_eq!(cube.get((0, 0, 0)), 4);
+        assert_eq!(slide_cube(&mut cube, Direction3D::Left), (0, false));
+        assert_eq!(slide_cube(&mut cube, Direction3D::Back), (0, true));
+        assert_eq!(cube.get((1, 0, 0)), 4);
+    }
+
+    #[test]
+    fn test_cube_game() {
+        let mut game = Cube2048::new(DEFAULT_CUBE_SIZE, 7).unwrap();
+        assert_eq!(game.board().empty_cells().len(), 27 - 2);
+        let moved = Direction3D::ALL
+            .iter()
+            .any(|&direction| game.move_in_direction(direction));
+        assert!(moved);
+        assert_eq!(game.moves(), 1);
+        assert_eq!(game.board().empty_cells().len(), 27 - 3);
+        assert!(Cube2048::new(1, 7).is_err());
+
+        // A full cube without equal neighbours along any axis is stuck
+        let stuck = CubeBoard::from_layers(vec![
+            vec![vec![2, 4], vec![4, 2]],
+            vec![vec![4, 2], vec![2, 4]],
+        ])
+        .unwrap();
+        assert!(Cube2048::resume(stuck.clone(), 1).game_over());
+        let mut layers: Vec<Vec<Vec<u32>>> = stuck
+            .layers()
+            .iter()
+            .map(|layer| layer.rows().to_vec())
+            .collect();
+        layers[1][1][1] = 2;
+        let open = CubeBoard::from_layers(layers).unwrap();
+        assert!(!Cube2048::resume(open, 1).game_over());
+        assert!(CubeBoard::from_layers(vec![vec![vec![2, 4], vec![4, 2]]]).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Property tests of the engine invariants on random boards and moves.
+
+use proptest::prelude::*;
+
+use super::*;
+
+const DIRECTIONS: [MovementDirection; 4] = [
+    MovementDirection::Up,
+    MovementDirection::Down,
+    MovementDirection::Left,
+    MovementDirection::Right,
+];
+
+/// Square boards of 2x2 to 5x5 with mostly small tiles and some empty cells
+fn boards() -> impl Strategy<Value = Vec<Vec<u32>>> {
+    let tiles = prop_oneof![
+        2 => Just(0u32),
+        3 => (1u32..=6).prop_map(|exponent| 1 << exponent),
+        1 => (7u32..=12).prop_map(|exponent| 1 << exponent),
+    ];
+    (2usize..=5).prop_flat_map(move |size| {
+        prop::collection::vec(prop::collection::vec(tiles.clone(), size), size)
+    })
+}
+
+fn directions() -> impl Strategy<Value = Vec<MovementDirection>> {
+    prop::collection::vec(prop::sample::select(DIRECTIONS.to_vec()), 1..40)
+}
+
+fn tile_sum(game: &Game2048) -> u64 {
+    game.board()
+        .iter()
+        .flatten()
+        .map(|&tile| u64::from(tile))
+        .sum()
+}
+
+proptest! {
+    #[test]
+    fn moves_conserve_tiles_and_raise_the_score(
+        board in boards(),
+        seed in any::<u64>(),
+        moves in directions(),
+    ) {
+        let mut game = Game2048::with_board(board, seed);
+        for direction in &moves {
+            let sum = tile_sum(&game);
+            let score = game.score();
+            let moved = game.move_in_direction(direction);
+
+            // Merges keep the sum, only the spawned tile adds to it
+            let spawned = game.last_spawn().filter(|_| moved).map_or(0, |(_, _, tile)| tile);
+            prop_assert!(!moved || spawned == 2 || spawned == 4);
+            prop_assert_eq!(tile_sum(&game), sum + u64::from(spawned));
+
+            let result = game.last_move().unwrap();
+            prop_assert_eq!(result.moved, moved);
+            prop_assert_eq!(game.score(), score + result.points);
+            prop_assert_eq!(result.merges, game.merged_cells().len());
+        }
+    }
+
+    #[test]
+    fn undo_restores_the_state_before_a_move(
+        board in boards(),
+        seed in any::<u64>(),
+        moves in directions(),
+    ) {
+        let mut game = Game2048::with_board(board, seed);
+        game.undo_limit = None;
+        let mut states = vec![(game.board().to_vec(), game.score(), game.moves())];
+        for direction in &moves {
+            if game.move_in_direction(direction) {
+                states.push((game.board().to_vec(), game.score(), game.moves()));
+            }
+        }
+        states.pop();
+        while let Some((board, score, moves)) = states.pop() {
+            prop_assert!(game.undo());
+            prop_assert_eq!(game.board(), board.as_slice());
+            prop_assert_eq!(game.score(), score);
+            prop_assert_eq!(game.moves(), moves);
+        }
+        prop_assert!(!game.undo());
+    }
+
+    #[test]
+    fn nothing_moves_when_the_game_is_over(board in boards(), seed in any::<u64>()) {
+        let mut game = Game2048::with_board(board, seed);
+        let over = game.game_over();
+        let before = game.board().to_vec();
+        let moved = DIRECTIONS.iter().any(|direction| {
+            let mut probe = game.clone();
+            probe.move_in_direction(direction)
+        });
+        // An empty board is not over, but there is nothing to move either
+        let empty = before.iter().flatten().all(|&tile| tile == 0);
+        prop_assert_eq!(moved, !over && !empty);
+        if over {
+            for direction in &DIRECTIONS {
+                prop_assert!(!game.move_in_direction(direction));
+                prop_assert_eq!(game.board(), before.as_slice());
+            }
+        }
+    }
+}
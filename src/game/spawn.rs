@@ -0,0 +1,219 @@
+//! Policies for the tile spawned after each move.
+//!
+//! Regular games spawn a 2 or a 4 on a random empty cell, with the odds of
+//! [`TileOdds`]; hard games may spawn 8s too. Variants, practice modes and
+//! training scenarios can place tiles differently, or not at all, by passing
+//! another [`SpawnPolicy`] to [`GameBuilder::spawn_policy`].
+//!
+//! [`GameBuilder::spawn_policy`]: super::GameBuilder::spawn_policy
+
+use std::fmt;
+
+use rand::prelude::*;
+
+use super::FOUR_CHANCE;
+use crate::board::Board;
+
+/// Chances of the tiles that spawn: a 4 or an 8, and a 2 otherwise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileOdds {
+    /// Chance of a 4
+    pub four: f64,
+    /// Chance of an 8
+    pub eight: f64,
+}
+
+impl Default for TileOdds {
+    /// The regular odds: a 4 in 10% of the spawns, never an 8
+    fn default() -> Self {
+        Self {
+            four: FOUR_CHANCE,
+            eight: 0.0,
+        }
+    }
+}
+
+impl TileOdds {
+    /// Chance of a 2, what the 4 and the 8 leave
+    pub fn two(&self) -> f64 {
+        (1.0 - self.four - self.eight).clamp(0.0, 1.0)
+    }
+
+    /// Draw a tile with these odds. A 2 is drawn first and an 8 only when
+    /// it can spawn, so games without 8s draw as they always did and games
+    /// with the same seed stay identical.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u32 {
+        if rng.random_bool(self.two()) {
+            2
+        } else if self.eight > 0.0
+            && rng.random_bool((self.eight / (self.four + self.eight)).clamp(0.0, 1.0))
+        {
+            8
+        } else {
+            4
+        }
+    }
+}
+
+/// Where the next tile appears and what it is
+pub trait SpawnPolicy: fmt::Debug + Send + Sync {
+    /// Cell and value of the tile to put on the board, `None` to spawn
+    /// nothing.
+    ///
+    /// `odds` are the tile odds the game would use, whose chance of a 4
+    /// adaptive games change as they go. Drawing only from `rng` keeps games
+    /// with the same seed identical.
+    fn spawn(
+        &self,
+        board: &Board,
+        odds: TileOdds,
+        rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), u32)>;
+}
+
+/// The regular rules: a random empty cell gets a tile drawn with the game's
+/// odds (a 4 in 10% of the spawns and a 2 otherwise by default)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StandardSpawn;
+
+impl SpawnPolicy for StandardSpawn {
+    fn spawn(
+        &self,
+        board: &Board,
+        odds: TileOdds,
+        rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), u32)> {
+        let &pos = board.empty_cells().choose(rng)?;
+        Some((pos, odds.sample(rng)))
+    }
+}
+
+/// A random empty cell always gets a 2
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AlwaysTwo;
+
+impl SpawnPolicy for AlwaysTwo {
+    fn spawn(
+        &self,
+        board: &Board,
+        _odds: TileOdds,
+        rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), u32)> {
+        board.empty_cells().choose(rng).map(|&pos| (pos, 2))
+    }
+}
+
+/// Tiles like the regular rules, but cells near the corners are preferred:
+/// each empty cell is twice as likely as one a step further from its
+/// nearest corner
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CornerBiased;
+
+impl SpawnPolicy for CornerBiased {
+    fn spawn(
+        &self,
+        board: &Board,
+        odds: TileOdds,
+        rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), u32)> {
+        let (rows, columns) = board.dims();
+        let (last_row, last_col) = (rows - 1, columns - 1);
+        let last = last_row.max(last_col);
+        let weight = |&(i, j): &(usize, usize)| {
+            let steps = i.min(last_row - i) + j.min(last_col - j);
+            1u32 << (last - steps.min(last))
+        };
+        let &pos = board.empty_cells().choose_weighted(rng, weight).ok()?;
+        Some((pos, odds.sample(rng)))
+    }
+}
+
+/// Nothing spawns, so only merges change the board
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoSpawn;
+
+impl SpawnPolicy for NoSpawn {
+    fn spawn(
+        &self,
+        _board: &Board,
+        _odds: TileOdds,
+        _rng: &mut dyn RngCore,
+    ) -> Option<((usize, usize), u32)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_policies_fill_empty_cells() {
+        let board = Board::from_rows(vec![vec![2, 0, 0], vec![0, 4, 0], vec![8, 0, 16]]).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let fours = TileOdds {
+            four: 1.0,
+            eight: 0.0,
+        };
+        let odds = TileOdds::default();
+        for _ in 0..50 {
+            let (pos, tile) = StandardSpawn.spawn(&board, fours, &mut rng).unwrap();
+            assert_eq!((board.get(pos), tile), (0, 4));
+            let (pos, tile) = AlwaysTwo.spawn(&board, fours, &mut rng).unwrap();
+            assert_eq!((board.get(pos), tile), (0, 2));
+            let (pos, _) = CornerBiased.spawn(&board, odds, &mut rng).unwrap();
+            assert_eq!(board.get(pos), 0);
+        }
+        assert_eq!(NoSpawn.spawn(&board, odds, &mut rng), None);
+
+        let full = Board::from_rows(vec![vec![2, 4], vec![8, 16]]).unwrap();
+        assert_eq!(StandardSpawn.spawn(&full, odds, &mut rng), None);
+        assert_eq!(CornerBiased.spawn(&full, odds, &mut rng), None);
+    }
+
+    #[test]
+    fn test_corner_biased_prefers_corners() {
+        let board = Board::new(4);
+        let mut rng = StdRng::seed_from_u64(1);
+        let corners = (0..1000)
+            .filter_map(|_| CornerBiased.spawn(&board, TileOdds::default(), &mut rng))
+            .filter(|&((i, j), _)| (i == 0 || i == 3) && (j == 0 || j == 3))
+            .count();
+        // The corners are 4 of the 16 cells but hold 32 of the total weight of 72
+        assert!(corners > 350, "{corners} corner spawns");
+    }
+
+    #[test]
+    fn test_tiles_follow_the_odds() {
+        let odds = TileOdds {
+            four: 0.2,
+            eight: 0.1,
+        };
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut counts = [0; 3];
+        for _ in 0..10_000 {
+            match odds.sample(&mut rng) {
+                2 => counts[0] += 1,
+                4 => counts[1] += 1,
+                8 => counts[2] += 1,
+                tile => panic!("spawned a {tile}"),
+            }
+        }
+        assert!((6500..7500).contains(&counts[0]), "{counts:?}");
+        assert!((1500..2500).contains(&counts[1]), "{counts:?}");
+        assert!((500..1500).contains(&counts[2]), "{counts:?}");
+
+        // Without 8s the tiles are drawn as by a single coin flip
+        let mut a = StdRng::seed_from_u64(9);
+        let mut b = StdRng::seed_from_u64(9);
+        for _ in 0..100 {
+            let coin = if b.random_bool(1.0 - FOUR_CHANCE) {
+                2
+            } else {
+                4
+            };
+            assert_eq!(TileOdds::default().sample(&mut a), coin);
+        }
+    }
+}
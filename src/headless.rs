@@ -0,0 +1,523 @@
+//! Non-interactive game runs.
+//!
+//! This module backs the subcommands that play without a terminal UI, so the
+//! binary can be used from shell scripts and CI. Each run ends with an
+//! [`ExitStatus`] that maps to a documented process exit code.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use rand::prelude::*;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::ai::{self, ExpectimaxConfig, MctsConfig, TranspositionTable};
+use crate::error::{GameError, GameResult};
+use crate::game::{Game2048, GameEngine, GameView, MovementDirection};
+use crate::replay::Replay;
+
+/// Exit codes of the non-interactive subcommands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// The run completed successfully (exit code 0)
+    Success,
+    /// Any other error, such as bad arguments or IO failures (exit code 1)
+    Failure,
+    /// The given starting position is not a valid board (exit code 2)
+    InvalidPosition,
+    /// The bot did not reach its target tile (exit code 3)
+    TargetNotReached,
+    /// At least one stored replay no longer reproduces (exit code 4)
+    ReplayMismatch,
+}
+
+impl ExitStatus {
+    /// Process exit code for this status
+    pub fn code(self) -> i32 {
+        match self {
+            ExitStatus::Success => 0,
+            ExitStatus::Failure => 1,
+            ExitStatus::InvalidPosition => 2,
+            ExitStatus::TargetNotReached => 3,
+            ExitStatus::ReplayMismatch => 4,
+        }
+    }
+}
+
+/// Parse a board position such as `"2 2 0 0/0 4 0 0/0 0 0 0/0 0 0 8"`.
+///
+/// Rows are separated by `/`, cells by spaces or commas. The board must be
+/// square with at least 2 rows, and every cell must be empty (0) or a power
+/// of two of at least 2.
+pub fn parse_position(text: &str) -> GameResult<Vec<Vec<u32>>> {
+    let invalid = |msg: String| GameError::InputError(format!("Invalid position: {}", msg));
+    let rows: Vec<&str> = text.trim().split('/').collect();
+    let size = rows.len();
+    if size < 2 {
+        return Err(invalid("expected at least 2 rows".to_string()));
+    }
+
+    let mut board = vec![vec![0; size]; size];
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<&str> = row
+            .split([' ', ','])
+            .filter(|cell| !cell.is_empty())
+            .collect();
+        if cells.len() != size {
+            return Err(invalid(format!(
+                "row {} has {} cells, expected {}",
+                i + 1,
+                cells.len(),
+                size
+            )));
+        }
+        for (j, cell) in cells.iter().enumerate() {
+            let value: u32 = cell
+                .parse()
+                .map_err(|_| invalid(format!("'{}' is not a number", cell)))?;
+            if value == 1 || (value != 0 && !value.is_power_of_two()) {
+                return Err(invalid(format!("{} is not a valid tile", value)));
+            }
+            board[i][j] = value;
+        }
+    }
+    Ok(board)
+}
+
+/// Parse a position (see [`parse_position`]) and start a game from it
+pub fn game_from_position(text: &str, seed: u64) -> GameResult<Game2048> {
+    parse_position(text).map(|board| Game2048::with_board(board, seed))
+}
+
+/// Summary of a bot run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BotReport {
+    pub moves: usize,
+    pub score: u64,
+    pub max_tile: u32,
+    pub reached_target: bool,
+}
+
+impl BotReport {
+    /// Exit status for the run
+    pub fn status(&self) -> ExitStatus {
+        if self.reached_target {
+            ExitStatus::Success
+        } else {
+            ExitStatus::TargetNotReached
+        }
+    }
+}
+
+/// Let the greedy bot play until it reaches the target tile, gets stuck, or
+/// runs out of moves
+pub fn run_bot(game: &mut Game2048, target: u32, max_moves: Option<usize>) -> BotReport {
+    let max_tile = |game: &Game2048| game.tiles().max_tile();
+    let mut moves = 0;
+
+    while max_tile(game) < target && max_moves.is_none_or(|max| moves < max) {
+        let Some(direction) = ai::greedy_move(game) else {
+            break;
+        };
+        game.move_in_direction(&direction);
+        moves += 1;
+    }
+
+    BotReport {
+        moves,
+        score: game.score(),
+        max_tile: max_tile(game),
+        reached_target: max_tile(game) >= target,
+    }
+}
+
+/// Parse a move script such as `"LLURD"`.
+///
+/// Every move is one letter, `L`, `R`, `U` or `D` in either case. Whitespace
+/// and commas between moves are ignored, as is the rest of a line after `#`.
+pub fn parse_moves(text: &str) -> GameResult<Vec<MovementDirection>> {
+    let mut moves = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        for c in line.chars().filter(|&c| !c.is_whitespace() && c != ',') {
+            let direction = match c.to_ascii_uppercase() {
+                'L' => MovementDirection::Left,
+                'R' => MovementDirection::Right,
+                'U' => MovementDirection::Up,
+                'D' => MovementDirection::Down,
+                _ => {
+                    return Err(GameError::InputError(format!(
+                        "Invalid move '{}' on line {}, expected L, R, U or D",
+                        c,
+                        number + 1
+                    )));
+                }
+            };
+            moves.push(direction);
+        }
+    }
+    Ok(moves)
+}
+
+/// Final state of a scripted run, printed as JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScriptReport {
+    pub board: Vec<Vec<u32>>,
+    pub score: u64,
+    /// Moves that changed the board
+    pub moves: u32,
+    /// Moves of the script that changed nothing, e.g. after the game was over
+    pub skipped: usize,
+    pub max_tile: u32,
+    pub game_over: bool,
+}
+
+impl ScriptReport {
+    /// The report on a single line
+    pub fn to_json(&self) -> GameResult<String> {
+        serde_json::to_string(self)
+            .map_err(|e| GameError::SerializationError(format!("Cannot encode report: {}", e)))
+    }
+}
+
+/// Play the moves of a script in order, skipping the ones that don't move
+/// anything
+pub fn run_script(game: &mut Game2048, moves: &[MovementDirection]) -> ScriptReport {
+    let skipped = moves
+        .iter()
+        .filter(|direction| !game.move_in_direction(direction))
+        .count();
+    ScriptReport {
+        board: game.board().to_vec(),
+        score: game.score(),
+        moves: game.moves(),
+        skipped,
+        max_tile: game.tiles().max_tile(),
+        game_over: game.game_over(),
+    }
+}
+
+/// Strategy playing the games of [`run_solve`] and [`run_simulation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solver {
+    /// [`ai::random_move`]
+    Random,
+    /// [`ai::greedy_move`]
+    Greedy,
+    /// [`ai::expectimax_move`] with its settings
+    Expectimax(ExpectimaxConfig),
+    /// [`ai::mcts_move`] with its settings
+    Mcts(MctsConfig),
+}
+
+impl Solver {
+    /// Solver with default settings by its command line name, `random`,
+    /// `greedy`, `expectimax` or `mcts`
+    pub fn from_name(name: &str) -> GameResult<Self> {
+        match name {
+            "random" => Ok(Solver::Random),
+            "greedy" => Ok(Solver::Greedy),
+            "expectimax" => Ok(Solver::Expectimax(ExpectimaxConfig::default())),
+            "mcts" => Ok(Solver::Mcts(MctsConfig::default())),
+            _ => Err(GameError::InputError(format!(
+                "Unknown strategy '{}', expected random, greedy, expectimax or mcts",
+                name
+            ))),
+        }
+    }
+
+    /// Command line name of the solver
+    pub fn name(&self) -> &'static str {
+        match self {
+            Solver::Random => "random",
+            Solver::Greedy => "greedy",
+            Solver::Expectimax(_) => "expectimax",
+            Solver::Mcts(_) => "mcts",
+        }
+    }
+
+    /// Play a game to the end from the given seed, which seeds both the
+    /// tile spawns and the solver's own random choices
+    fn play(&self, seed: u64) -> Game2048 {
+        let mut game = Game2048::with_seed(seed);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let table = TranspositionTable::default();
+        loop {
+            let direction = match self {
+                Solver::Random => ai::random_move(&game, &mut rng),
+                Solver::Greedy => ai::greedy_move(&game),
+                Solver::Expectimax(config) => ai::expectimax_move_with(&game, config, &table),
+                Solver::Mcts(config) => ai::mcts_move(&game, config, &mut rng),
+            };
+            let Some(direction) = direction else {
+                return game;
+            };
+            game.move_in_direction(&direction);
+        }
+    }
+}
+
+/// Results of the games played by a solver
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolveReport {
+    /// Final score of every game, in the order they were played
+    pub scores: Vec<u64>,
+    /// Number of games by the largest tile they ended with
+    pub max_tiles: BTreeMap<u32, usize>,
+}
+
+impl SolveReport {
+    /// Report of finished games
+    fn from_games(games: impl IntoIterator<Item = Game2048>) -> Self {
+        let mut report = SolveReport::default();
+        for game in games {
+            report.scores.push(game.score());
+            *report.max_tiles.entry(game.tiles().max_tile()).or_default() += 1;
+        }
+        report
+    }
+
+    /// Mean final score, `0` without games
+    pub fn average_score(&self) -> f64 {
+        if self.scores.is_empty() {
+            return 0.0;
+        }
+        self.scores.iter().map(|&score| score as f64).sum::<f64>() / self.scores.len() as f64
+    }
+
+    /// Middle final score, the mean of the two middle ones for an even
+    /// number of games, `0` without games
+    pub fn median_score(&self) -> f64 {
+        let mut scores = self.scores.clone();
+        scores.sort_unstable();
+        let middle = scores.len() / 2;
+        match scores.len() {
+            0 => 0.0,
+            len if len % 2 == 0 => (scores[middle - 1] as f64 + scores[middle] as f64) / 2.0,
+            _ => scores[middle] as f64,
+        }
+    }
+
+    /// Share of the games that reached a tile, between 0 and 1
+    pub fn reach_rate(&self, tile: u32) -> f64 {
+        let reached: usize = self.max_tiles.range(tile..).map(|(_, count)| count).sum();
+        reached as f64 / self.scores.len().max(1) as f64
+    }
+}
+
+/// Let a solver play `games` games to the end, one after the other. Game `i`
+/// uses seed `seed + i`, so runs with the same arguments give the same
+/// report.
+pub fn run_solve(solver: Solver, games: usize, seed: u64) -> SolveReport {
+    SolveReport::from_games((0..games).map(|i| solver.play(seed.wrapping_add(i as u64))))
+}
+
+/// Play the games of [`run_solve`] on `threads` threads. The report is the
+/// same as with a single thread.
+pub fn run_simulation(
+    solver: Solver,
+    games: usize,
+    seed: u64,
+    threads: usize,
+) -> GameResult<SolveReport> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|e| GameError::GameStateError(format!("Cannot start threads: {}", e)))?;
+    let games: Vec<Game2048> = pool.install(|| {
+        (0..games)
+            .into_par_iter()
+            .map(|i| solver.play(seed.wrapping_add(i as u64)))
+            .collect()
+    });
+    Ok(SolveReport::from_games(games))
+}
+
+/// Result of verifying a directory of replays
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// Number of replay files checked
+    pub checked: usize,
+    /// Files that could not be read or no longer reproduce, with the reason
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+impl VerifyReport {
+    /// Exit status for the run
+    pub fn status(&self) -> ExitStatus {
+        if self.failures.is_empty() {
+            ExitStatus::Success
+        } else {
+            ExitStatus::ReplayMismatch
+        }
+    }
+}
+
+/// Re-simulate every replay (`*.toml`) in a directory, spread over all CPUs
+pub fn verify_all(dir: &Path) -> GameResult<VerifyReport> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = paths.len().div_ceil(threads).max(1);
+    let mut failures: Vec<(PathBuf, String)> = thread::scope(|scope| {
+        let workers: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|path| verify_file(path))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect()
+    });
+    failures.sort();
+
+    Ok(VerifyReport {
+        checked: paths.len(),
+        failures,
+    })
+}
+
+/// Verify one replay file, returning the reason if it fails
+fn verify_file(path: &Path) -> Option<(PathBuf, String)> {
+    let reason = match Replay::load(path) {
+        Ok(Some(replay)) => replay.verify().err()?.to_string(),
+        Ok(None) => "file disappeared".to_string(),
+        Err(e) => e.to_string(),
+    };
+    Some((path.to_path_buf(), reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_position() {
+        let board = parse_position("2 2 0 0/0,4,0,0/0 0 0 0/0 0 0 8").unwrap();
+        assert_eq!(board[0], [2, 2, 0, 0]);
+        assert_eq!(board[1], [0, 4, 0, 0]);
+        assert_eq!(board[3], [0, 0, 0, 8]);
+    }
+
+    #[test]
+    fn test_parse_position_rejects_invalid_tiles() {
+        assert!(parse_position("3 0 0 0/0 0 0 0/0 0 0 0/0 0 0 0").is_err());
+        assert!(parse_position("1 0 0 0/0 0 0 0/0 0 0 0/0 0 0 0").is_err());
+        assert!(parse_position("2 0 0/0 0 0 0/0 0 0 0/0 0 0 0").is_err());
+        assert!(parse_position("0 0 0 0/0 0 0 0/0 0 0 0").is_err());
+    }
+
+    #[test]
+    fn test_parse_moves() {
+        use MovementDirection::*;
+        assert_eq!(parse_moves("LLURD").unwrap(), [Left, Left, Up, Right, Down]);
+        assert_eq!(
+            parse_moves("l, r # first\n\n  u d\n# done").unwrap(),
+            [Left, Right, Up, Down]
+        );
+        assert!(parse_moves("LX").is_err());
+    }
+
+    #[test]
+    fn test_run_script() {
+        let mut game = game_from_position("2 2 0/0 0 0/0 0 0", 1).unwrap();
+        // Up moves nothing on this board
+        let moves = parse_moves("UL").unwrap();
+        let report = run_script(&mut game, &moves);
+        assert_eq!(report.moves, 1);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.score, 4);
+        assert_eq!(report.board[0][0], 4);
+        assert_eq!(report.max_tile, 4);
+        assert!(!report.game_over);
+        let json = report.to_json().unwrap();
+        assert!(json.starts_with(r#"{"board":[[4,"#));
+        assert!(
+            json.ends_with(r#""score":4,"moves":1,"skipped":1,"max_tile":4,"game_over":false}"#)
+        );
+    }
+
+    #[test]
+    fn test_bot_reaches_small_target() {
+        let mut game = Game2048::with_seed(7);
+        let report = run_bot(&mut game, 64, None);
+        assert!(report.reached_target);
+        assert_eq!(report.status(), ExitStatus::Success);
+    }
+
+    #[test]
+    fn test_verify_all_reports_broken_replays() {
+        let dir = std::env::temp_dir().join(format!("tui_2048_verify_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut replay = Replay::new(3);
+        replay.save(&dir.join("good.toml")).unwrap();
+        replay.score = 100;
+        replay.save(&dir.join("bad.toml")).unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let report = verify_all(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.failures.len(), 1);
+        assert!(report.failures[0].0.ends_with("bad.toml"));
+        assert_eq!(report.status(), ExitStatus::ReplayMismatch);
+    }
+
+    #[test]
+    fn test_bot_reports_missed_target() {
+        let mut game = Game2048::with_seed(7);
+        let report = run_bot(&mut game, 2048, Some(5));
+        assert_eq!(report.moves, 5);
+        assert_eq!(report.status(), ExitStatus::TargetNotReached);
+    }
+
+    #[test]
+    fn test_solve_reports_scores_and_tiles() {
+        assert!(Solver::from_name("minimax").is_err());
+        let solver = Solver::from_name("greedy").unwrap();
+        let report = run_solve(solver, 3, 11);
+        assert_eq!(report.scores.len(), 3);
+        assert_eq!(report.max_tiles.values().sum::<usize>(), 3);
+        assert_eq!(run_solve(solver, 3, 11), report);
+
+        let mcts = Solver::Mcts(MctsConfig {
+            rollouts: 4,
+            time_budget: None,
+        });
+        let report = run_solve(mcts, 1, 11);
+        assert!(report.average_score() > 0.0);
+    }
+
+    #[test]
+    fn test_simulation_matches_serial_runs() {
+        let solver = Solver::from_name("random").unwrap();
+        let report = run_simulation(solver, 8, 4, 3).unwrap();
+        assert_eq!(report, run_solve(solver, 8, 4));
+        assert_eq!(report.reach_rate(2), 1.0);
+        assert_eq!(report.reach_rate(1 << 20), 0.0);
+
+        let report = SolveReport {
+            scores: vec![40, 10, 30, 20],
+            max_tiles: BTreeMap::from([(4, 2), (8, 2)]),
+        };
+        assert_eq!(report.average_score(), 25.0);
+        assert_eq!(report.median_score(), 25.0);
+        assert_eq!(report.reach_rate(8), 0.5);
+    }
+}
@@ -0,0 +1,119 @@
+//! Board quality heuristics.
+//!
+//! These measures describe how well organized a board is. The automatic
+//! players rank moves with them, and the game over screen reports them so
+//! players can compare their boards with what the bot aims for.
+
+/// Number of empty cells
+pub fn empty_cells(board: &[Vec<u32>]) -> usize {
+    board.iter().flatten().filter(|&&x| x == 0).count()
+}
+
+/// How consistently tiles increase or decrease along rows and columns, in
+/// percent (higher is better).
+///
+/// Empty cells are skipped. For every line the direction that fits more
+/// neighbouring pairs counts, so a snake of decreasing tiles scores 100.
+pub fn monotonicity(board: &[Vec<u32>]) -> u32 {
+    let mut consistent = 0;
+    let mut pairs = 0;
+    for line in lines(board) {
+        let tiles: Vec<u32> = line.into_iter().filter(|&x| x != 0).collect();
+        let increasing = tiles.windows(2).filter(|w| w[0] <= w[1]).count();
+        let decreasing = tiles.windows(2).filter(|w| w[0] >= w[1]).count();
+        consistent += increasing.max(decreasing);
+        pairs += tiles.len().saturating_sub(1);
+    }
+    percent(consistent, pairs, 100)
+}
+
+/// Share of neighbouring tiles that are more than one merge apart, in percent
+/// (lower is better).
+///
+/// Such tiles can't be combined soon and split the board into pieces.
+pub fn fragmentation(board: &[Vec<u32>]) -> u32 {
+    let mut far = 0;
+    let mut pairs = 0;
+    for line in lines(board) {
+        for pair in line.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if a == 0 || b == 0 {
+                continue;
+            }
+            pairs += 1;
+            if a.max(b) / a.min(b) > 2 {
+                far += 1;
+            }
+        }
+    }
+    percent(far, pairs, 0)
+}
+
+/// All rows followed by all columns
+fn lines(board: &[Vec<u32>]) -> impl Iterator<Item = Vec<u32>> + '_ {
+    let width = board.first().map_or(0, Vec::len);
+    let columns = (0..width).map(move |j| board.iter().map(|row| row[j]).collect());
+    board.iter().cloned().chain(columns)
+}
+
+/// `part` of `total` in percent, or `empty` if there is nothing to measure
+fn percent(part: usize, total: usize, empty: u32) -> u32 {
+    (part * 100).checked_div(total).map_or(empty, |p| p as u32)
+}
+
+/// All heuristics for one board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardMetrics {
+    pub empty: usize,
+    pub monotonicity: u32,
+    pub fragmentation: u32,
+}
+
+impl BoardMetrics {
+    /// Measure a board
+    pub fn of(board: &[Vec<u32>]) -> Self {
+        Self {
+            empty: empty_cells(board),
+            monotonicity: monotonicity(board),
+            fragmentation: fragmentation(board),
+        }
+    }
+
+    /// Average of several measurements, or `None` if there are none
+    pub fn average(samples: &[BoardMetrics]) -> Option<Self> {
+        let n = samples.len();
+        if n == 0 {
+            return None;
+        }
+        Some(Self {
+            empty: samples.iter().map(|m| m.empty).sum::<usize>() / n,
+            monotonicity: samples.iter().map(|m| m.monotonicity).sum::<u32>() / n as u32,
+            fragmentation: samples.iter().map(|m| m.fragmentation).sum::<u32>() / n as u32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snake_board_is_monotonic() {
+        let board = vec![
+            vec![1024, 512, 256, 128],
+            vec![8, 16, 32, 64],
+            vec![4, 2, 0, 0],
+            vec![2, 0, 0, 0],
+        ];
+        assert_eq!(monotonicity(&board), 100);
+        assert_eq!(empty_cells(&board), 5);
+    }
+
+    #[test]
+    fn test_fragmentation_counts_distant_neighbours() {
+        let board = vec![vec![2, 4], vec![32, 0]];
+        // 2-4 can merge soon, 2-32 cannot
+        assert_eq!(fragmentation(&board), 50);
+        assert_eq!(fragmentation(&vec![vec![0; 4]; 4]), 0);
+    }
+}
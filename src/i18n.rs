@@ -0,0 +1,216 @@
+//! Translations of the user interface.
+//!
+//! The texts of every language are kept in a TOML file in `src/i18n`, one
+//! table per part of the interface, and built into the binary. A text is
+//! looked up by its table and name, e.g. `game_over.title`; words in braces
+//! such as `{score}` are filled in by [`format`]. Texts missing from a
+//! language fall back to English.
+//!
+//! The language is picked once at startup with [`set_language`], usually from
+//! the locale (see [`Language::detect`]).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::{GameError, GameResult};
+
+/// Languages the interface is translated to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    /// All languages, in the order of their bundles
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// ISO 639-1 code of the language, as in `--lang` and the locale
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    /// Language of a code such as `de` or a locale such as `de_AT.UTF-8`
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|language| language.code() == code)
+    }
+
+    /// Language of the locale in the environment, English if it names none
+    /// of the translations
+    pub fn detect() -> Self {
+        Self::from_env(|name| std::env::var(name).ok())
+    }
+
+    /// Language of the locale in the variables returned by `var`. The first
+    /// locale variable that is set decides, like in the C library.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .find_map(|name| var(name).filter(|value| !value.is_empty()))
+            .and_then(|locale| Self::from_code(&locale))
+            .unwrap_or_default()
+    }
+
+    /// Contents of the language's TOML file
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("i18n/en.toml"),
+            Language::German => include_str!("i18n/de.toml"),
+        }
+    }
+}
+
+/// The texts of a language, by `table.name`
+#[derive(Debug, Default)]
+pub struct Bundle {
+    texts: HashMap<String, String>,
+}
+
+impl Bundle {
+    /// Read the texts from a TOML file of tables of strings
+    pub fn parse(source: &str) -> GameResult<Self> {
+        let tables: HashMap<String, HashMap<String, String>> = toml::from_str(source)
+            .map_err(|e| GameError::InputError(format!("Invalid translations: {}", e.message())))?;
+        let texts = tables
+            .into_iter()
+            .flat_map(|(table, texts)| {
+                texts
+                    .into_iter()
+                    .map(move |(name, text)| (format!("{}.{}", table, name), text))
+            })
+            .collect();
+        Ok(Self { texts })
+    }
+
+    /// The bundle built into the game for a language
+    pub fn builtin(language: Language) -> Self {
+        // The files are checked by the tests
+        Self::parse(language.source()).unwrap_or_default()
+    }
+
+    /// The text with the given key, if the bundle has it
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.texts.get(key).map(String::as_str)
+    }
+}
+
+/// Index in [`Language::ALL`] of the language of the interface
+static LANGUAGE: AtomicUsize = AtomicUsize::new(0);
+
+/// Bundles of all languages, in the order of [`Language::ALL`]
+fn bundles() -> &'static [Bundle] {
+    static BUNDLES: OnceLock<Vec<Bundle>> = OnceLock::new();
+    BUNDLES.get_or_init(|| Language::ALL.into_iter().map(Bundle::builtin).collect())
+}
+
+/// Switch the interface to a language
+pub fn set_language(language: Language) {
+    let index = Language::ALL.iter().position(|&l| l == language);
+    LANGUAGE.store(index.unwrap_or_default(), Ordering::Relaxed);
+}
+
+/// Language of the interface
+pub fn language() -> Language {
+    Language::ALL[LANGUAGE.load(Ordering::Relaxed)]
+}
+
+/// Text with the given key in the language of the interface, or in English
+/// if it isn't translated. Unknown keys are returned as they are, so a typo
+/// shows up on screen.
+pub fn text(key: &'static str) -> &'static str {
+    let bundles = bundles();
+    bundles[LANGUAGE.load(Ordering::Relaxed)]
+        .get(key)
+        .or_else(|| bundles[0].get(key))
+        .unwrap_or(key)
+}
+
+/// [`text`] with the placeholders named in `args` filled in
+pub fn format(key: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    fill(text(key), args)
+}
+
+/// Replace every `{name}` in a text with the value of `name` in `args`
+fn fill(text: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut filled = text.to_string();
+    for (name, value) in args {
+        filled = filled.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Names in braces in a text
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn test_translations_are_complete() {
+        let english = Bundle::parse(Language::English.source()).unwrap();
+        for language in Language::ALL {
+            let bundle = Bundle::parse(language.source()).unwrap();
+            for (key, text) in &english.texts {
+                let translation = bundle
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{} lacks {}", language.code(), key));
+                assert_eq!(placeholders(translation), placeholders(text), "{}", key);
+            }
+            assert_eq!(bundle.texts.len(), english.texts.len());
+        }
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        let german = Bundle::builtin(Language::German);
+        let text = german.get("game_over.final_score").unwrap();
+        assert_eq!(fill(text, &[("score", &1234)]), "Endstand: 1234");
+        assert_eq!(
+            fill("{a} and {b}, {a}", &[("a", &1), ("b", &"two")]),
+            "1 and two, 1"
+        );
+    }
+
+    #[test]
+    fn test_language_from_locale() {
+        let env = |vars: &'static [(&str, &str)]| {
+            Language::from_env(move |name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            })
+        };
+        assert_eq!(env(&[("LANG", "de_AT.UTF-8")]), Language::German);
+        assert_eq!(
+            env(&[("LANG", "de_DE"), ("LC_ALL", "C")]),
+            Language::English
+        );
+        assert_eq!(env(&[("LANG", "de"), ("LC_ALL", "")]), Language::German);
+        assert_eq!(env(&[("LANG", "fr_FR.UTF-8")]), Language::English);
+        assert_eq!(env(&[]), Language::English);
+        assert_eq!(Language::from_code("DE"), Some(Language::German));
+        assert_eq!(Language::from_code("xx"), None);
+    }
+}
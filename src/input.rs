@@ -0,0 +1,193 @@
+//! Sources of terminal events for the app.
+//!
+//! The app reads its input through [`InputSource`], so it can be driven by
+//! the terminal ([`TerminalInput`], or [`ThreadedInput`] reading it on a
+//! background thread), by a fixed list of events ([`ScriptedInput`]), e.g.
+//! to test the whole app loop without a terminal, or by events sent from
+//! other threads ([`ChannelInput`]).
+
+use std::collections::VecDeque;
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+
+use crate::error::{GameError, GameResult, TerminalContext};
+
+/// Something the app can wait on for events
+pub trait InputSource {
+    /// Wait up to `timeout` for the next event, `None` if none arrived
+    fn next_event(&mut self, timeout: Duration) -> GameResult<Option<Event>>;
+
+    /// Drop the events that arrived but weren't read yet, e.g. keys pressed
+    /// for a screen that was replaced in the meantime. Sources whose events
+    /// are all intended, such as scripts, keep them.
+    fn discard_pending(&mut self) -> GameResult<()> {
+        Ok(())
+    }
+}
+
+/// Events of the terminal, read with crossterm
+#[derive(Debug, Default)]
+pub struct TerminalInput;
+
+impl InputSource for TerminalInput {
+    fn next_event(&mut self, timeout: Duration) -> GameResult<Option<Event>> {
+        if !event::poll(timeout).context("waiting for input")? {
+            return Ok(None);
+        }
+        Ok(Some(event::read().context("reading input")?))
+    }
+
+    fn discard_pending(&mut self) -> GameResult<()> {
+        while event::poll(Duration::ZERO).context("waiting for input")? {
+            event::read().context("reading input")?;
+        }
+        Ok(())
+    }
+}
+
+/// Events of the terminal, read on a background thread.
+///
+/// Reading blocks the thread instead of the app, which waits on a channel and
+/// wakes up as soon as an event arrives or its next frame is due. The thread
+/// is started by the first read, and ends with the first event read after the
+/// input is dropped.
+#[derive(Debug, Default)]
+pub struct ThreadedInput {
+    events: Option<Receiver<io::Result<Event>>>,
+}
+
+impl ThreadedInput {
+    /// Start reading terminal events on a new thread
+    fn spawn() -> Receiver<io::Result<Event>> {
+        let (sender, events) = mpsc::channel();
+        thread::spawn(move || {
+            loop {
+                let event = event::read();
+                let failed = event.is_err();
+                if sender.send(event).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        events
+    }
+}
+
+impl InputSource for ThreadedInput {
+    fn next_event(&mut self, timeout: Duration) -> GameResult<Option<Event>> {
+        let events = self.events.get_or_insert_with(Self::spawn);
+        match events.recv_timeout(timeout) {
+            Ok(event) => Ok(Some(event.context("reading input")?)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(GameError::InputError(
+                "The terminal input thread stopped".to_string(),
+            )),
+        }
+    }
+
+    fn discard_pending(&mut self) -> GameResult<()> {
+        if let Some(events) = &self.events {
+            while events.try_recv().is_ok() {}
+        }
+        Ok(())
+    }
+}
+
+/// A fixed sequence of events, returned without waiting.
+///
+/// Once every event was returned, reading fails with an input error, so an
+/// app driven by a script that ran out stops instead of waiting forever.
+#[derive(Debug, Default)]
+pub struct ScriptedInput {
+    events: VecDeque<Event>,
+}
+
+impl ScriptedInput {
+    /// Script of the given events
+    pub fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+
+    /// Script of key presses without modifiers
+    pub fn keys(codes: impl IntoIterator<Item = KeyCode>) -> Self {
+        Self::new(
+            codes
+                .into_iter()
+                .map(|code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE))),
+        )
+    }
+
+    /// Number of events not read yet
+    pub fn remaining(&self) -> usize {
+        self.events.len()
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn next_event(&mut self, _timeout: Duration) -> GameResult<Option<Event>> {
+        match self.events.pop_front() {
+            Some(event) => Ok(Some(event)),
+            None => Err(GameError::InputError("The input script ended".to_string())),
+        }
+    }
+}
+
+/// Events sent through a channel, e.g. by a test or a host application
+/// feeding the app from another thread.
+///
+/// Reading waits for the next event like the terminal would; once every
+/// sender is dropped and the events sent are read, it fails with an input
+/// error.
+#[derive(Debug)]
+pub struct ChannelInput {
+    events: Receiver<Event>,
+}
+
+impl ChannelInput {
+    /// Input reading the events sent with the returned sender
+    pub fn new() -> (Sender<Event>, Self) {
+        let (sender, events) = mpsc::channel();
+        (sender, Self { events })
+    }
+}
+
+impl InputSource for ChannelInput {
+    fn next_event(&mut self, timeout: Duration) -> GameResult<Option<Event>> {
+        match self.events.recv_timeout(timeout) {
+            Ok(event) => Ok(Some(event)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(GameError::InputError(
+                "Every sender of the input channel was dropped".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_input_waits_for_events_until_the_senders_are_gone() {
+        let (sender, mut input) = ChannelInput::new();
+        let timeout = Duration::from_millis(1);
+        assert_eq!(input.next_event(timeout).unwrap(), None);
+
+        let key = Event::Key(KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+        let sent = key.clone();
+        thread::spawn(move || sender.send(sent).unwrap())
+            .join()
+            .unwrap();
+        assert_eq!(input.next_event(timeout).unwrap(), Some(key));
+        assert!(matches!(
+            input.next_event(timeout),
+            Err(GameError::InputError(_))
+        ));
+    }
+}
@@ -0,0 +1,687 @@
+//! Key binding module.
+//!
+//! This module maps terminal key codes to game actions. The default bindings
+//! can be overridden from the `[keys]` table of the config file.
+//!
+//! Besides single keys, actions can be bound to chords of two keys pressed
+//! one after the other, such as `g` then `s` for the statistics. The first
+//! key of a chord waits up to [`CHORD_TIMEOUT`] for the second one.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+
+use crate::error::{GameError, GameResult};
+use crate::game::MovementDirection;
+
+/// How long the second key of a chord is awaited
+pub const CHORD_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Actions that can be triggered by a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Undo,
+    /// Show the board before the last move while the key is held
+    PeekUndo,
+    /// Hide the board and stop the clock until the next key press
+    Pause,
+    Restart,
+    Quit,
+    /// Show statistics about the current game
+    ShowStats,
+    /// Show the top scores
+    ShowLeaderboard,
+    /// Show unlocked achievements
+    ShowAchievements,
+    /// Show the key bindings
+    ShowHelp,
+    /// Choose border styles and accents unlocked by achievements
+    ShowCosmetics,
+    /// Choose a puzzle to play in puzzle mode
+    ShowPuzzles,
+    /// Change the settings while playing
+    ShowSettings,
+    /// Copy the board to the clipboard and save it to files
+    Snapshot,
+    /// Save the game to a named save slot
+    SaveSlot,
+    /// Browse the past boards of the game and go back to one
+    ShowHistory,
+    /// Shade the cells by how often tiles spawned there, then by how often
+    /// they merged there, then not at all
+    ToggleHeatmap,
+}
+
+impl Action {
+    /// All actions, in the order they are listed in help texts
+    pub const ALL: [Action; 20] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Undo,
+        Action::PeekUndo,
+        Action::Pause,
+        Action::Restart,
+        Action::Quit,
+        Action::ShowStats,
+        Action::ShowLeaderboard,
+        Action::ShowAchievements,
+        Action::ShowHelp,
+        Action::ShowCosmetics,
+        Action::ShowPuzzles,
+        Action::ShowSettings,
+        Action::Snapshot,
+        Action::SaveSlot,
+        Action::ShowHistory,
+        Action::ToggleHeatmap,
+    ];
+
+    /// Name of the action as used in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "up",
+            Action::MoveDown => "down",
+            Action::MoveLeft => "left",
+            Action::MoveRight => "right",
+            Action::Undo => "undo",
+            Action::PeekUndo => "peek",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+            Action::Quit => "quit",
+            Action::ShowStats => "stats",
+            Action::ShowLeaderboard => "leaderboard",
+            Action::ShowAchievements => "achievements",
+            Action::ShowHelp => "help",
+            Action::ShowCosmetics => "cosmetics",
+            Action::ShowPuzzles => "puzzles",
+            Action::ShowSettings => "settings",
+            Action::Snapshot => "snapshot",
+            Action::SaveSlot => "save",
+            Action::ShowHistory => "history",
+            Action::ToggleHeatmap => "heatmap",
+        }
+    }
+
+    /// Look up an action by its config file name
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|a| a.name() == name)
+    }
+
+    /// Direction of a move action
+    pub fn direction(self) -> Option<MovementDirection> {
+        match self {
+            Action::MoveUp => Some(MovementDirection::Up),
+            Action::MoveDown => Some(MovementDirection::Down),
+            Action::MoveLeft => Some(MovementDirection::Left),
+            Action::MoveRight => Some(MovementDirection::Right),
+            _ => None,
+        }
+    }
+
+    /// Whether the game can't be played properly without a key for this
+    /// action. Moving, restarting and quitting are required.
+    pub fn is_required(self) -> bool {
+        matches!(
+            self,
+            Action::MoveUp
+                | Action::MoveDown
+                | Action::MoveLeft
+                | Action::MoveRight
+                | Action::Restart
+                | Action::Quit
+        )
+    }
+}
+
+/// A single key or a chord of two keys that triggers an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyBinding {
+    Key(KeyCode),
+    Chord(KeyCode, KeyCode),
+}
+
+impl KeyBinding {
+    /// Parse a binding from the config file: a key name ("w", "Up", ...) or
+    /// two key names separated by a space for a chord ("g s")
+    pub fn parse(text: &str) -> GameResult<Self> {
+        let keys: Vec<&str> = text.split_whitespace().collect();
+        match keys[..] {
+            [key] => Ok(KeyBinding::Key(parse_key(key)?)),
+            [first, second] => Ok(KeyBinding::Chord(parse_key(first)?, parse_key(second)?)),
+            _ => Err(GameError::InputError(format!(
+                "Invalid key binding '{}', expected one key or a chord of two",
+                text
+            ))),
+        }
+    }
+
+    /// First key of the binding
+    fn first(self) -> KeyCode {
+        match self {
+            KeyBinding::Key(key) | KeyBinding::Chord(key, _) => key,
+        }
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyBinding::Key(key) => write!(f, "{}", key_name(*key)),
+            KeyBinding::Chord(first, second) => {
+                write!(f, "{} {}", key_name(*first), key_name(*second))
+            }
+        }
+    }
+}
+
+/// A mistake in the key binding overrides that the game works around
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapIssue {
+    /// A key is bound to two actions; it only triggers the first one
+    Conflict {
+        key: KeyBinding,
+        used: Action,
+        ignored: Action,
+    },
+    /// A single key also starts a chord; it only starts the chord
+    StartsChord { key: KeyCode, ignored: Action },
+    /// A required action has no key; the free default keys are used instead
+    Unbound {
+        action: Action,
+        restored: Vec<KeyBinding>,
+    },
+}
+
+impl KeymapIssue {
+    /// Change to the `[keys]` table that resolves the issue
+    pub fn suggestion(&self, keymap: &Keymap) -> String {
+        match self {
+            KeymapIssue::Conflict { ignored, .. } | KeymapIssue::StartsChord { ignored, .. } => {
+                let mut keys = keymap.keys(*ignored);
+                if keys.is_empty() {
+                    keys.extend(keymap.free_key());
+                }
+                format!(
+                    "Use other keys for {}, e.g. {}",
+                    ignored.name(),
+                    binding(*ignored, &keys)
+                )
+            }
+            KeymapIssue::Unbound { action, restored } => {
+                let keys = if restored.is_empty() {
+                    keymap.free_key().into_iter().collect()
+                } else {
+                    restored.clone()
+                };
+                format!(
+                    "Add a key for {}, e.g. {}",
+                    action.name(),
+                    binding(*action, &keys)
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for KeymapIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapIssue::StartsChord { key, ignored } => write!(
+                f,
+                "{} is bound to {} but also starts a chord; it only starts the chord",
+                key_name(*key),
+                ignored.name()
+            ),
+            KeymapIssue::Conflict { key, used, ignored } => write!(
+                f,
+                "{} is bound to both {} and {}; it only triggers {}",
+                key,
+                used.name(),
+                ignored.name(),
+                used.name()
+            ),
+            KeymapIssue::Unbound { action, restored } if restored.is_empty() => write!(
+                f,
+                "{} has no key and its default keys are taken",
+                action.name()
+            ),
+            KeymapIssue::Unbound { action, restored } => write!(
+                f,
+                "{} has no key; using {} instead",
+                action.name(),
+                restored
+                    .iter()
+                    .map(|key| key.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// A `[keys]` table entry binding an action to keys
+fn binding(action: Action, keys: &[KeyBinding]) -> String {
+    let keys: Vec<String> = keys.iter().map(|key| format!("\"{}\"", key)).collect();
+    format!("{} = [{}]", action.name(), keys.join(", "))
+}
+
+/// First key of a chord, waiting for the second one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingChord {
+    pub prefix: KeyCode,
+    pub started: Instant,
+}
+
+impl PendingChord {
+    /// Time left to press the second key
+    pub fn remaining(&self) -> Duration {
+        CHORD_TIMEOUT.saturating_sub(self.started.elapsed())
+    }
+}
+
+/// What a key press means, see [`Keymap::press`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyInput {
+    /// The key, or the chord it completed, triggers an action
+    Action(Action),
+    /// The key started a chord
+    ChordStarted,
+    /// The key isn't bound, or doesn't complete the pending chord
+    Unbound,
+}
+
+/// Mapping from key codes to actions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: HashMap<KeyBinding, Action>,
+}
+
+impl Keymap {
+    /// Build a keymap from the defaults with the given overrides applied.
+    ///
+    /// Each override replaces all default keys of its action. Conflicts and
+    /// unbound actions are resolved as described by [`Keymap::diagnose`].
+    pub fn with_overrides(overrides: &HashMap<String, Vec<String>>) -> GameResult<Self> {
+        Self::diagnose(overrides).map(|(keymap, _)| keymap)
+    }
+
+    /// Build a keymap like [`Keymap::with_overrides`] and report the mistakes
+    /// that had to be worked around.
+    ///
+    /// A key bound to several actions triggers the one overridden first (in
+    /// the order of [`Action::ALL`]), and an override always wins over a
+    /// default key. A single key that also starts a chord only starts the
+    /// chord. Required actions left without a key get their free default keys
+    /// back. Unknown action or key names are still errors.
+    pub fn diagnose(
+        overrides: &HashMap<String, Vec<String>>,
+    ) -> GameResult<(Self, Vec<KeymapIssue>)> {
+        if let Some(name) = overrides
+            .keys()
+            .find(|name| Action::from_name(name).is_none())
+        {
+            return Err(GameError::InputError(format!("Unknown action: {}", name)));
+        }
+        let defaults = Keymap::default();
+        let mut keymap = Keymap::default();
+        let mut issues = vec![];
+
+        let overridden: Vec<(Action, &Vec<String>)> = Action::ALL
+            .iter()
+            .filter_map(|&action| overrides.get(action.name()).map(|keys| (action, keys)))
+            .collect();
+        for (action, _) in &overridden {
+            keymap.bindings.retain(|_, a| a != action);
+        }
+        for &(action, keys) in &overridden {
+            for key in keys {
+                let key = KeyBinding::parse(key)?;
+                let previous = match keymap.bindings.get(&key) {
+                    // Keys taken by earlier overrides stay with them
+                    Some(&used) if overridden.iter().any(|&(a, _)| a == used) => {
+                        if used != action {
+                            issues.push(KeymapIssue::Conflict {
+                                key,
+                                used,
+                                ignored: action,
+                            });
+                        }
+                        continue;
+                    }
+                    previous => previous.copied(),
+                };
+                keymap.bindings.insert(key, action);
+                if let Some(ignored) = previous {
+                    issues.push(KeymapIssue::Conflict {
+                        key,
+                        used: action,
+                        ignored,
+                    });
+                }
+            }
+        }
+
+        // Single keys that start a chord would make the chord unreachable
+        let mut shadowed: Vec<(KeyCode, Action)> = keymap
+            .bindings
+            .iter()
+            .filter_map(|(&key, &ignored)| match key {
+                KeyBinding::Key(code) if !keymap.chord_options(code).is_empty() => {
+                    Some((code, ignored))
+                }
+                _ => None,
+            })
+            .collect();
+        shadowed.sort_by_key(|&(key, _)| key_name(key));
+        for (key, ignored) in shadowed {
+            keymap.bindings.remove(&KeyBinding::Key(key));
+            issues.push(KeymapIssue::StartsChord { key, ignored });
+        }
+
+        for action in Action::ALL {
+            if !action.is_required() || !keymap.keys(action).is_empty() {
+                continue;
+            }
+            let restored: Vec<KeyBinding> = defaults
+                .keys(action)
+                .into_iter()
+                .filter(|&key| keymap.is_free(key))
+                .collect();
+            for &key in &restored {
+                keymap.bindings.insert(key, action);
+            }
+            issues.push(KeymapIssue::Unbound { action, restored });
+        }
+        Ok((keymap, issues))
+    }
+
+    /// Get the action bound to a single key, if any
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.bindings.get(&KeyBinding::Key(key)).copied()
+    }
+
+    /// Resolve a key press, taking chords into account.
+    ///
+    /// `pending` holds the first key of a chord between presses. An expired
+    /// chord is dropped and the key is resolved on its own.
+    pub fn press(&self, pending: &mut Option<PendingChord>, key: KeyCode) -> KeyInput {
+        if let Some(chord) = pending.take().filter(|chord| !chord.remaining().is_zero()) {
+            return self
+                .bindings
+                .get(&KeyBinding::Chord(chord.prefix, key))
+                .map_or(KeyInput::Unbound, |&action| KeyInput::Action(action));
+        }
+        if !self.chord_options(key).is_empty() {
+            *pending = Some(PendingChord {
+                prefix: key,
+                started: Instant::now(),
+            });
+            return KeyInput::ChordStarted;
+        }
+        self.action(key).map_or(KeyInput::Unbound, KeyInput::Action)
+    }
+
+    /// Second keys completing a chord that starts with `prefix`, with their
+    /// actions in the order of [`Action::ALL`]
+    pub fn chord_options(&self, prefix: KeyCode) -> Vec<(KeyCode, Action)> {
+        let mut options: Vec<(KeyCode, Action)> = self
+            .bindings
+            .iter()
+            .filter_map(|(&key, &action)| match key {
+                KeyBinding::Chord(first, second) if first == prefix => Some((second, action)),
+                _ => None,
+            })
+            .collect();
+        options.sort_by_key(|&(key, action)| (action_index(action), key_name(key)));
+        options
+    }
+
+    /// Keys and chords bound to an action, sorted by name
+    pub fn keys(&self, action: Action) -> Vec<KeyBinding> {
+        let mut keys: Vec<KeyBinding> = self
+            .bindings
+            .iter()
+            .filter(|&(_, a)| *a == action)
+            .map(|(&key, _)| key)
+            .collect();
+        keys.sort_by_key(|key| key.to_string());
+        keys
+    }
+
+    /// Whether a binding can be added without clashing with existing ones
+    fn is_free(&self, key: KeyBinding) -> bool {
+        !self.bindings.keys().any(|&bound| match (bound, key) {
+            (KeyBinding::Key(a), KeyBinding::Key(b)) => a == b,
+            (KeyBinding::Chord(..), KeyBinding::Chord(..)) => bound == key,
+            _ => bound.first() == key.first(),
+        })
+    }
+
+    /// A letter or function key that isn't bound yet
+    fn free_key(&self) -> Option<KeyBinding> {
+        ('a'..='z')
+            .map(KeyCode::Char)
+            .chain((1..=12).map(KeyCode::F))
+            .map(KeyBinding::Key)
+            .find(|&key| self.is_free(key))
+    }
+}
+
+/// Position of an action in [`Action::ALL`]
+fn action_index(action: Action) -> usize {
+    Action::ALL
+        .iter()
+        .position(|&a| a == action)
+        .unwrap_or(usize::MAX)
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let g = KeyCode::Char('g');
+        let bindings = [
+            (KeyBinding::Key(KeyCode::Up), Action::MoveUp),
+            (KeyBinding::Key(KeyCode::Char('w')), Action::MoveUp),
+            (KeyBinding::Key(KeyCode::Down), Action::MoveDown),
+            (KeyBinding::Key(KeyCode::Char('s')), Action::MoveDown),
+            (KeyBinding::Key(KeyCode::Left), Action::MoveLeft),
+            (KeyBinding::Key(KeyCode::Char('a')), Action::MoveLeft),
+            (KeyBinding::Key(KeyCode::Right), Action::MoveRight),
+            (KeyBinding::Key(KeyCode::Char('d')), Action::MoveRight),
+            (KeyBinding::Key(KeyCode::Char('u')), Action::Undo),
+            (KeyBinding::Key(KeyCode::Char('z')), Action::Undo),
+            (KeyBinding::Key(KeyCode::Char('p')), Action::PeekUndo),
+            (KeyBinding::Key(KeyCode::Char(' ')), Action::Pause),
+            (KeyBinding::Key(KeyCode::Char('r')), Action::Restart),
+            (KeyBinding::Key(KeyCode::Char('q')), Action::Quit),
+            (KeyBinding::Key(KeyCode::Esc), Action::Quit),
+            (KeyBinding::Key(KeyCode::Char('x')), Action::Snapshot),
+            (KeyBinding::Chord(g, KeyCode::Char('s')), Action::ShowStats),
+            (
+                KeyBinding::Chord(g, KeyCode::Char('l')),
+                Action::ShowLeaderboard,
+            ),
+            (
+                KeyBinding::Chord(g, KeyCode::Char('a')),
+                Action::ShowAchievements,
+            ),
+            (KeyBinding::Chord(g, KeyCode::Char('?')), Action::ShowHelp),
+            (
+                KeyBinding::Chord(g, KeyCode::Char('c')),
+                Action::ShowCosmetics,
+            ),
+            (
+                KeyBinding::Chord(g, KeyCode::Char('p')),
+                Action::ShowPuzzles,
+            ),
+            (
+                KeyBinding::Chord(g, KeyCode::Char('o')),
+                Action::ShowSettings,
+            ),
+            (KeyBinding::Chord(g, KeyCode::Char('w')), Action::SaveSlot),
+            (
+                KeyBinding::Chord(g, KeyCode::Char('h')),
+                Action::ShowHistory,
+            ),
+            (
+                KeyBinding::Chord(g, KeyCode::Char('m')),
+                Action::ToggleHeatmap,
+            ),
+        ];
+        Self {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+/// Parse a key name from the config file ("w", "Up", "Esc", "F5", ...)
+pub fn parse_key(name: &str) -> GameResult<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    let code = match name.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "esc" | "escape" => KeyCode::Esc,
+        "enter" | "return" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "space" => KeyCode::Char(' '),
+        other => match other.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+            _ => return Err(GameError::InputError(format!("Unknown key: {}", name))),
+        },
+    };
+    Ok(code)
+}
+
+/// Name of a key as accepted by [`parse_key`]
+pub fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::Backspace => "Backspace".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("w").unwrap(), KeyCode::Char('w'));
+        assert_eq!(parse_key("Up").unwrap(), KeyCode::Up);
+        assert_eq!(parse_key("F5").unwrap(), KeyCode::F(5));
+        assert!(parse_key("Hyper").is_err());
+        assert_eq!(
+            KeyBinding::parse("g Up").unwrap(),
+            KeyBinding::Chord(KeyCode::Char('g'), KeyCode::Up)
+        );
+        assert!(KeyBinding::parse("g g g").is_err());
+    }
+
+    #[test]
+    fn test_override_replaces_default_keys() {
+        let overrides = HashMap::from([("up".to_string(), vec!["k".to_string()])]);
+        let keymap = Keymap::with_overrides(&overrides).unwrap();
+        assert_eq!(keymap.action(KeyCode::Char('k')), Some(Action::MoveUp));
+        assert_eq!(keymap.action(KeyCode::Char('w')), None);
+        assert_eq!(keymap.action(KeyCode::Down), Some(Action::MoveDown));
+    }
+
+    #[test]
+    fn test_chords() {
+        let keymap = Keymap::default();
+        let mut pending = None;
+        assert_eq!(
+            keymap.press(&mut pending, KeyCode::Char('g')),
+            KeyInput::ChordStarted
+        );
+        assert_eq!(keymap.chord_options(KeyCode::Char('g')).len(), 10);
+        assert_eq!(
+            keymap.press(&mut pending, KeyCode::Char('s')),
+            KeyInput::Action(Action::ShowStats)
+        );
+        assert!(pending.is_none());
+        // Without a pending chord, s moves down again
+        assert_eq!(
+            keymap.press(&mut pending, KeyCode::Char('s')),
+            KeyInput::Action(Action::MoveDown)
+        );
+
+        keymap.press(&mut pending, KeyCode::Char('g'));
+        assert_eq!(
+            keymap.press(&mut pending, KeyCode::Char('x')),
+            KeyInput::Unbound
+        );
+
+        pending = Some(PendingChord {
+            prefix: KeyCode::Char('g'),
+            started: Instant::now() - CHORD_TIMEOUT,
+        });
+        assert_eq!(
+            keymap.press(&mut pending, KeyCode::Char('a')),
+            KeyInput::Action(Action::MoveLeft),
+            "An expired chord is dropped"
+        );
+    }
+
+    #[test]
+    fn test_diagnose_conflicts_and_unbound_actions() {
+        let key = |c| KeyBinding::Key(KeyCode::Char(c));
+        let overrides = HashMap::from([
+            ("undo".to_string(), vec!["w".to_string(), "q".to_string()]),
+            ("restart".to_string(), vec!["q".to_string()]),
+            ("quit".to_string(), vec![]),
+            ("peek".to_string(), vec!["g".to_string()]),
+        ]);
+        let (keymap, issues) = Keymap::diagnose(&overrides).unwrap();
+        assert_eq!(
+            issues,
+            vec![
+                KeymapIssue::Conflict {
+                    key: key('w'),
+                    used: Action::Undo,
+                    ignored: Action::MoveUp
+                },
+                KeymapIssue::Conflict {
+                    key: key('q'),
+                    used: Action::Undo,
+                    ignored: Action::Restart
+                },
+                KeymapIssue::StartsChord {
+                    key: KeyCode::Char('g'),
+                    ignored: Action::PeekUndo
+                },
+                KeymapIssue::Unbound {
+                    action: Action::Restart,
+                    restored: vec![key('r')]
+                },
+                KeymapIssue::Unbound {
+                    action: Action::Quit,
+                    restored: vec![KeyBinding::Key(KeyCode::Esc)]
+                },
+            ]
+        );
+        assert_eq!(keymap.action(KeyCode::Char('q')), Some(Action::Undo));
+        assert_eq!(keymap.action(KeyCode::Esc), Some(Action::Quit));
+        assert_eq!(keymap.action(KeyCode::Char('g')), None);
+        assert_eq!(
+            issues[4].suggestion(&keymap),
+            "Add a key for quit, e.g. quit = [\"Esc\"]"
+        );
+        assert!(Keymap::diagnose(&HashMap::new()).unwrap().1.is_empty());
+    }
+}
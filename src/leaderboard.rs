@@ -0,0 +1,132 @@
+//! Local leaderboard for the 2048 game.
+//!
+//! This module keeps the best scores achieved on this machine together with
+//! the player name, and stores them as a TOML file in the data directory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::date::Date;
+use crate::error::{GameError, GameResult};
+use crate::profile;
+
+/// Number of entries kept on the leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Maximum length of a player name
+pub const MAX_NAME_LEN: usize = 16;
+
+/// Name of the leaderboard file inside the data directory
+const LEADERBOARD_FILE: &str = "leaderboard.toml";
+
+/// A single leaderboard entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub score: u64,
+    pub date: Date,
+    pub max_tile: u32,
+    pub board_size: usize,
+}
+
+/// Top scores, sorted from best to worst
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Leaderboard {
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    /// Default location of the leaderboard file, if a data directory exists
+    pub fn default_path() -> Option<PathBuf> {
+        profile::data_dir().map(|dir| dir.join(LEADERBOARD_FILE))
+    }
+
+    /// Load the leaderboard from a file, starting empty if it is missing
+    pub fn load(path: &Path) -> GameResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            GameError::SaveError(format!(
+                "Corrupt leaderboard {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })
+    }
+
+    /// Write the leaderboard to a file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(|e| {
+            GameError::SerializationError(format!("Cannot encode leaderboard: {}", e))
+        })?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Entries from best to worst
+    pub fn entries(&self) -> &[LeaderboardEntry] {
+        &self.entries
+    }
+
+    /// Check if a score would make it onto the leaderboard
+    pub fn qualifies(&self, score: u64) -> bool {
+        score > 0
+            && (self.entries.len() < LEADERBOARD_SIZE
+                || self.entries.last().is_some_and(|e| score > e.score))
+    }
+
+    /// Insert an entry, returning its rank (0-based) if it made the cut
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        // Earlier entries win ties
+        let rank = self.entries.partition_point(|e| e.score >= entry.score);
+        self.entries.insert(rank, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            name: name.to_string(),
+            score,
+            date: Date::from_days_since_epoch(0),
+            max_tile: 2048,
+            board_size: 4,
+        }
+    }
+
+    #[test]
+    fn test_insert_keeps_top_scores_sorted() {
+        let mut board = Leaderboard::default();
+        for score in 1..=LEADERBOARD_SIZE as u64 {
+            board.insert(entry("a", score * 100));
+        }
+        assert!(!board.qualifies(100));
+        assert_eq!(board.insert(entry("b", 550)), Some(5));
+        assert_eq!(board.entries().len(), LEADERBOARD_SIZE);
+        assert_eq!(board.entries()[0].score, 1000);
+        assert_eq!(board.entries().last().unwrap().score, 200);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut board = Leaderboard::default();
+        board.insert(entry("alice", 1234));
+        let text = toml::to_string(&board).unwrap();
+        assert_eq!(toml::from_str::<Leaderboard>(&text).unwrap(), board);
+    }
+}
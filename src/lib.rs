@@ -6,17 +6,91 @@
 
 // Game module containing the core game logic
 pub mod game;
+// Square grid of tiles with iterators and rotations
+pub mod board;
 // UI module for handling rendering
 pub mod ui;
 // App module for handling application flow
 pub mod app;
+// Facade for embedding the game in other ratatui applications
+pub mod embed;
 // Error handling module
 pub mod error;
+// Log file of what the game does
+pub mod logging;
+// Configuration file handling
+pub mod config;
+// Translations of the user interface
+pub mod i18n;
+// Terminal and scripted input sources
+pub mod input;
+// Key binding module
+pub mod keymap;
+// Calendar date helpers
+pub mod date;
+// Local high score table
+pub mod leaderboard;
+// Daily challenge seeds and scores
+pub mod daily;
+// Automatic players
+pub mod ai;
+// Board quality measures shared by the bot and the game over report
+pub mod heuristics;
+// Non-interactive runs and their exit codes
+pub mod headless;
+// Game recordings and ghost replays
+pub mod replay;
+// Terminal session recordings in asciinema's format
+pub mod cast;
+// Animated SVG and GIF exports of finished games
+pub mod export;
+// Share codes for positions
+pub mod share;
+// Network race mode
+pub mod net;
+// Achievements and merge chain counters
+pub mod achievements;
+// Border styles and accents unlocked by achievements
+pub mod cosmetics;
+// Statistics collected during a game
+pub mod stats;
+// Live game broadcasts for spectators
+pub mod spectate;
+// Curated puzzles and their completion
+pub mod puzzles;
+// Unfinished games kept for resuming
+pub mod saves;
+// Autosave after every move, for crash recovery
+pub mod autosave;
+// Startup picker for unfinished games
+pub mod resume;
+// Main menu shown at startup
+pub mod menu;
+// Named profiles with directories of their own
+pub mod profile;
+// First-run tutorial
+pub mod tutorial;
+// Terminal setup and panic-safe restoration
+pub mod terminal;
+// C ABI for the engine
+#[cfg(feature = "ffi")]
+pub mod ffi;
+// Python bindings for the engine
+#[cfg(feature = "python")]
+pub mod python;
+// HTTP API for the engine
+#[cfg(feature = "http")]
+pub mod server;
 
 /// Reexported types to provide a cleaner API
 pub mod prelude {
-    pub use crate::app::App;
+    pub use crate::app::{App, AppOutcome};
+    pub use crate::board::Board;
+    pub use crate::config::Config;
     pub use crate::error::{GameError, GameResult};
-    pub use crate::game::{Game2048, GameEngine, MovementDirection};
+    pub use crate::game::{Game2048, GameEngine, GameView, MoveResult, MovementDirection};
+    pub use crate::keymap::{Action, Keymap};
+    pub use crate::leaderboard::{Leaderboard, LeaderboardEntry};
+    pub use crate::ui::theme::Theme;
     pub use crate::ui::{DefaultRenderer, GameRenderer, NoColorRenderer};
-}
\ No newline at end of file
+}
@@ -0,0 +1,233 @@
+//! Log of what the game does, written to a file.
+//!
+//! The terminal belongs to the UI, so the messages of the `log` macros used
+//! throughout the crate are never printed; [`init`] sends them to `game.log`
+//! in the log directory instead. Once the file grows past its size limit it
+//! is renamed to `game.log.1`, older files move one number up and the oldest
+//! is removed.
+//!
+//! The level is picked with `--log-level` or the `TUI_2048_LOG` variable,
+//! e.g. `TUI_2048_LOG=debug` to follow every move and AI search.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::date::Date;
+use crate::error::{GameError, GameResult};
+
+/// Size after which the log file is rotated
+pub const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Rotated files kept next to the current one
+pub const KEEP_FILES: usize = 3;
+
+/// Level used unless another one is picked
+pub const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// Environment variable with the level of the log
+pub const LEVEL_VAR: &str = "TUI_2048_LOG";
+
+/// Name of the current log file
+const FILE_NAME: &str = "game.log";
+
+/// Default directory of the logs, if a data directory exists
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("tui_2048").join("logs"))
+}
+
+/// Level named like `warn` or `debug`, `off` to turn the log off
+pub fn parse_level(name: &str) -> GameResult<LevelFilter> {
+    name.parse().map_err(|_| {
+        GameError::InputError(format!(
+            "Unknown log level '{}', expected off, error, warn, info, debug or trace",
+            name
+        ))
+    })
+}
+
+/// Level from [`LEVEL_VAR`], [`DEFAULT_LEVEL`] if it isn't set
+pub fn level_from_env() -> GameResult<LevelFilter> {
+    match std::env::var(LEVEL_VAR) {
+        Ok(name) if !name.is_empty() => parse_level(&name),
+        _ => Ok(DEFAULT_LEVEL),
+    }
+}
+
+/// A log file that is rotated once it grows too large
+#[derive(Debug)]
+pub struct RotatingFile {
+    dir: PathBuf,
+    file: File,
+    /// Bytes in the current file
+    written: u64,
+    max_size: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    /// Append to the log file in `dir`, creating the directory if needed.
+    /// The file is rotated after `max_size` bytes, keeping `keep` old files.
+    pub fn open(dir: &Path, max_size: u64, keep: usize) -> GameResult<Self> {
+        fs::create_dir_all(dir)?;
+        let file = Self::open_current(dir)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file,
+            written,
+            max_size,
+            keep,
+        })
+    }
+
+    fn open_current(dir: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(FILE_NAME))
+    }
+
+    /// Path of the current file, or of the `n`th rotated one
+    pub fn path(dir: &Path, n: usize) -> PathBuf {
+        match n {
+            0 => dir.join(FILE_NAME),
+            n => dir.join(format!("{}.{}", FILE_NAME, n)),
+        }
+    }
+
+    /// Append a line, rotating the files first if it doesn't fit
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.written > 0 && self.written + len > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.written += len;
+        Ok(())
+    }
+
+    /// Move every file one number up, dropping the oldest, and start a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep == 0 {
+            fs::remove_file(Self::path(&self.dir, 0))?;
+        } else {
+            for n in (0..self.keep).rev() {
+                let from = Self::path(&self.dir, n);
+                if from.exists() {
+                    fs::rename(from, Self::path(&self.dir, n + 1))?;
+                }
+            }
+        }
+        self.file = Self::open_current(&self.dir)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+/// Logger writing the records to a [`RotatingFile`]
+struct FileLogger {
+    level: LevelFilter,
+    file: Mutex<RotatingFile>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}: {}",
+            timestamp(SystemTime::now()),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        // A log that can't be written is dropped rather than shown in the UI
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_line(&line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+/// Send the records of the `log` macros up to `level` to the log file in
+/// `dir`. Does nothing for [`LevelFilter::Off`]; fails if the file can't be
+/// opened or a logger is set already.
+pub fn init(dir: &Path, level: LevelFilter) -> GameResult<()> {
+    if level == LevelFilter::Off {
+        return Ok(());
+    }
+    let file = RotatingFile::open(dir, MAX_FILE_SIZE, KEEP_FILES)?;
+    log::set_boxed_logger(Box::new(FileLogger {
+        level,
+        file: Mutex::new(file),
+    }))
+    .map_err(|e| GameError::TerminalError(format!("Cannot start the log: {}", e)))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// UTC date and time of day with milliseconds, as written in the log
+fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let date = Date::from_days_since_epoch((secs / 86_400) as i64);
+    let of_day = secs % 86_400;
+    format!(
+        "{} {:02}:{:02}:{:02}.{:03}",
+        date,
+        of_day / 3600,
+        of_day / 60 % 60,
+        of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_files_rotate_past_the_size_limit() {
+        let dir = std::env::temp_dir().join(format!("tui_2048_logs_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut file = RotatingFile::open(&dir, 20, 2).unwrap();
+        for line in ["first line", "second line", "third line", "fourth line"] {
+            file.write_line(line).unwrap();
+        }
+        let read = |n| fs::read_to_string(RotatingFile::path(&dir, n)).unwrap();
+        assert_eq!(read(0), "fourth line\n");
+        assert_eq!(read(1), "third line\n");
+        assert_eq!(read(2), "second line\n");
+        assert!(!RotatingFile::path(&dir, 3).exists());
+
+        // Appending to an existing file counts its size
+        let file = RotatingFile::open(&dir, 20, 2).unwrap();
+        assert_eq!(file.written, 12);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_timestamp_and_levels() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_792_162_983_042);
+        assert_eq!(timestamp(time), "2026-10-16 15:03:03.042");
+        assert_eq!(parse_level("DEBUG").unwrap(), LevelFilter::Debug);
+        assert_eq!(parse_level("off").unwrap(), LevelFilter::Off);
+        assert!(parse_level("loud").is_err());
+    }
+}
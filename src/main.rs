@@ -4,30 +4,365 @@
 //! It handles command line arguments, sets up the terminal environment,
 //! and initializes the game components.
 
-use ratatui::{Terminal, backend::CrosstermBackend};
+use std::fs;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use tui_2048::app::App;
+use log::LevelFilter;
+
+use tui_2048::achievements::{Achievement, Achievements};
+use tui_2048::ai::{ExpectimaxConfig, MctsConfig};
+use tui_2048::app::{App, AppOutcome};
+use tui_2048::autosave::{self, Autosave};
+use tui_2048::config::Config;
+use tui_2048::daily::{DailyScores, daily_seed};
+use tui_2048::date::Date;
 use tui_2048::error::{GameError, GameResult};
-use tui_2048::game::Game2048;
-use tui_2048::ui::{DefaultRenderer, NoColorRenderer};
+use tui_2048::export::{self, ExportFormat, PngSnapshot};
+use tui_2048::game::{
+    DEFAULT_SIZE, DEFAULT_UNDO_LIMIT, Game2048, GameBuilder, GameView, HARD_MODE_UNDOS, MergeRule,
+    UndoPolicy, WIN_TILE,
+};
+use tui_2048::headless::{
+    ExitStatus, SolveReport, Solver, game_from_position, parse_moves, run_bot, run_script,
+    run_simulation, run_solve, verify_all,
+};
+use tui_2048::i18n::{self, Language};
+use tui_2048::leaderboard::Leaderboard;
+use tui_2048::logging;
+use tui_2048::menu::{self, Menu, MenuItem};
+use tui_2048::net::NetSession;
+use tui_2048::profile;
+use tui_2048::puzzles::{self, Puzzle, PuzzleProgress};
+use tui_2048::replay::Replay;
+use tui_2048::resume::{self, Choice};
+use tui_2048::saves::{Mode, SavedGame, Saves};
+use tui_2048::share::ShareCode;
+use tui_2048::spectate::{self, Broadcaster, GameEvent};
+use tui_2048::stats::{self, PlayTime};
+use tui_2048::terminal;
+use tui_2048::tutorial::Onboarding;
+use tui_2048::ui::ascii::AsciiRenderer;
+use tui_2048::ui::big::BigTileRenderer;
+use tui_2048::ui::capabilities::{Capabilities, ColorDepth};
+use tui_2048::ui::{DefaultRenderer, GameRenderer, NoColorRenderer, mini_board};
+
+/// Options selected on the command line
+struct Options {
+    /// Colors to draw with, detected from the terminal unless overridden
+    colors: ColorDepth,
+    /// Draw tiles with large block digits
+    big_tiles: bool,
+    /// Draw with ASCII characters only, detected from the terminal unless
+    /// overridden
+    ascii: bool,
+    /// Config file given with `--config`, instead of the one of the profile
+    config_file: Option<PathBuf>,
+    /// Named profile to keep the config and records in
+    profile: Option<String>,
+    /// Date of the daily challenge being played
+    daily_date: Option<Date>,
+    /// Fixed seed for the tile spawns
+    seed: Option<u64>,
+    ghost: bool,
+    /// Two players on one keyboard, each with a board of their own
+    versus: bool,
+    /// Adapt the spawn odds to the player's skill
+    adaptive: bool,
+    /// Clear stuck boards instead of ending the game
+    zen: bool,
+    /// Multiply the points of merging moves in a row
+    arcade: bool,
+    /// Charge power-ups with merges
+    power_ups: bool,
+    /// Grade every move against the AI
+    trainer: bool,
+    /// Tile that wins the game, or spawns with `divide`
+    win_tile: u32,
+    /// Halve equal tiles instead of doubling them
+    divide: bool,
+    /// Number of rows and of columns of the board, overriding the settings
+    size: Option<(usize, usize)>,
+    /// Number of moves that can be undone, `None` for unlimited
+    undo_limit: Option<usize>,
+    /// Undos per game and their cost
+    undo_policy: UndoPolicy,
+    /// Address to wait for a network opponent on
+    host: Option<String>,
+    /// Address of a network opponent to join
+    join: Option<String>,
+    /// Address to serve the game to spectators on
+    spectate: Option<String>,
+    /// Play the built-in puzzles instead of a regular game
+    puzzles: bool,
+    /// Show the tutorial even if it was seen before
+    tutorial: bool,
+    /// Show the main menu before the game, unless a mode was picked
+    menu: bool,
+    /// Open the settings screen when the game starts
+    settings: bool,
+    /// File with moves to play without the terminal UI, `-` for stdin
+    script: Option<PathBuf>,
+    /// asciinema file to record the session to
+    record: Option<PathBuf>,
+    /// Level of the messages written to the log file
+    log_level: LevelFilter,
+    /// Save board snapshots as PNG images too
+    snapshot_png: bool,
+    /// Shared position to play on
+    import: Option<ShareCode>,
+    /// Set up the board on the sandbox screen before playing
+    #[cfg(feature = "sandbox")]
+    sandbox: bool,
+}
+
+impl Options {
+    /// Config file to use, if there is one
+    fn config_path(&self) -> Option<PathBuf> {
+        self.config_file.clone().or_else(Config::default_path)
+    }
+
+    /// Load the config, falling back to the defaults without a config file
+    fn load_config(&self) -> GameResult<Config> {
+        match self.config_path() {
+            Some(path) => Config::load(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Whether the game is played in hard mode, which may spawn 8s
+    fn hard(&self) -> bool {
+        self.undo_policy.max_undos == Some(HARD_MODE_UNDOS)
+    }
+
+    /// Whether the board is set up on the sandbox screen
+    #[cfg(feature = "sandbox")]
+    fn sandbox(&self) -> bool {
+        self.sandbox
+    }
+
+    #[cfg(not(feature = "sandbox"))]
+    fn sandbox(&self) -> bool {
+        false
+    }
+}
+
+fn main() {
+    if let Err(error) = run() {
+        report_error(&error);
+        std::process::exit(1);
+    }
+}
+
+/// Tell the player why the game stopped, once the terminal is usable again
+fn report_error(error: &GameError) {
+    log::error!("Stopped: {}", error);
+    // The app restores the terminal when it is dropped; this catches errors
+    // that ended a menu or the app without its guard
+    if terminal::is_active() {
+        let _ = terminal::restore();
+    }
+    eprintln!("Error: {}", error);
+    if let Some(hint) = error.hint() {
+        eprintln!("{}", hint);
+    }
+    if log::log_enabled!(log::Level::Error)
+        && let Some(dir) = logging::default_dir()
+    {
+        eprintln!("The log in {} may tell more.", dir.display());
+    }
+}
 
-fn main() -> GameResult<()> {
+/// Parse the command line and run the game or a subcommand
+fn run() -> GameResult<()> {
     let args = std::env::args().collect::<Vec<String>>();
-    let mut use_color = true;
+
+    // Subcommands log at the level of the environment, the game parses
+    // --log-level first
+    if args.get(1).is_some_and(|arg| !arg.starts_with('-')) {
+        start_log(logging::level_from_env()?);
+    }
+
+    if args.get(1).map(String::as_str) == Some("bot") {
+        let status = run_bot_command(&args[2..]);
+        std::process::exit(status.code());
+    }
+    if args.get(1).map(String::as_str) == Some("solve") {
+        let status = run_solve_command(&args[2..]);
+        std::process::exit(status.code());
+    }
+    if args.get(1).map(String::as_str) == Some("simulate") {
+        let status = run_simulate_command(&args[2..]);
+        std::process::exit(status.code());
+    }
+    if args.get(1).map(String::as_str) == Some("verify-all") {
+        let status = run_verify_command(&args[2..]);
+        std::process::exit(status.code());
+    }
+    if args.get(1).map(String::as_str) == Some("watch") {
+        return run_watch_command(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("export") {
+        return run_export_command(&args[2..]);
+    }
+    #[cfg(feature = "http")]
+    if args.get(1).map(String::as_str) == Some("serve") {
+        return run_serve_command(&args[2..]);
+    }
+    let capabilities = Capabilities::detect();
+    // The locale picks the language, unless --lang overrides it
+    i18n::set_language(Language::detect());
+    let mut options = Options {
+        colors: capabilities.colors,
+        big_tiles: false,
+        ascii: !capabilities.unicode,
+        config_file: None,
+        profile: None,
+        daily_date: None,
+        seed: None,
+        ghost: false,
+        versus: false,
+        adaptive: false,
+        zen: false,
+        arcade: false,
+        power_ups: false,
+        trainer: false,
+        win_tile: WIN_TILE,
+        divide: false,
+        size: None,
+        undo_limit: Some(DEFAULT_UNDO_LIMIT),
+        undo_policy: UndoPolicy::default(),
+        host: None,
+        join: None,
+        spectate: None,
+        puzzles: false,
+        tutorial: false,
+        menu: true,
+        settings: false,
+        script: None,
+        record: None,
+        log_level: logging::level_from_env()?,
+        snapshot_png: false,
+        import: None,
+        #[cfg(feature = "sandbox")]
+        sandbox: false,
+    };
 
     // Parse command line arguments
-    for arg in &args[1..] {
+    let mut args_iter = args[1..].iter();
+    while let Some(arg) = args_iter.next() {
         match arg.as_str() {
             "--help" | "-h" => {
                 println!("Usage: tui_2048 [OPTIONS]");
+                println!("       tui_2048 bot [BOT OPTIONS]");
+                println!("       tui_2048 solve [SOLVE OPTIONS]");
+                println!("       tui_2048 simulate [SIMULATE OPTIONS]");
+                println!("       tui_2048 watch <ADDR>");
+                println!("       tui_2048 verify-all [DIR]");
+                println!("       tui_2048 export <REPLAY> <FILE.svg|FILE.gif>");
+                #[cfg(feature = "http")]
+                println!("       tui_2048 serve [ADDR]");
                 println!("Options:");
                 println!("  --help,     -h      Show this help message");
                 println!("  --version,  -v      Show version information");
+                println!(
+                    "  --color             Use colors even if the terminal seems to lack them"
+                );
                 println!("  --no-color          Run the game without colors");
+                println!("  --big-tiles         Draw tiles with large digits on big terminals");
+                println!("  --ascii             Draw with ASCII characters only");
+                println!(
+                    "  --unicode           Draw box characters even if the locale is not UTF-8"
+                );
+                println!("                      (colors and Unicode are detected from NO_COLOR,");
+                println!("                      COLORTERM, TERM and the locale by default)");
+                println!("  --config <PATH>     Use a different config file");
+                println!(
+                    "  --profile <NAME>    Keep settings and records in a profile of their own"
+                );
+                println!("  --lang <CODE>       Language of the interface: en or de");
+                println!("                      (default: from LC_ALL, LC_MESSAGES or LANG)");
+                println!("  --daily             Play today's daily challenge");
+                println!("  --puzzles           Solve puzzles with a move limit");
+                #[cfg(feature = "sandbox")]
+                println!("  --sandbox           Place tiles anywhere, then play from there");
+                println!("  --tutorial          Show the tutorial again");
+                println!("  --no-menu           Start a game right away");
+                println!("  --import <CODE>     Play on from a position shared with x");
+                println!("  --seed <N>          Use a fixed seed for tile spawns");
+                println!("  --ghost             Race against your best game on the same seed");
+                println!("  --versus            Two players on one keyboard: WASD against arrows");
+                println!("  --adaptive          Adapt tile spawns to your skill (unranked)");
+                println!(
+                    "  --zen               Endless game: stuck boards lose their smallest tiles"
+                );
+                println!("  --arcade            Merging moves in a row multiply points (unranked)");
+                println!(
+                    "  --power-ups         Merges charge swaps, removals and shuffles (unranked)"
+                );
+                println!("  --trainer           Grade every move against the AI (4x4, unranked)");
+                println!("  --target <TILE>     Tile that wins: 1024, 2048, 8192 or 16384");
+                println!("  --divide            Equal tiles halve; start from the target, reach 2");
+                println!(
+                    "  --size <N|RxC>      Play on an NxN board, or one of R rows and C columns"
+                );
+                println!("                      (default: the settings, 4)");
+                println!("  --undo-limit <N>    Moves that can be undone (default 10,");
+                println!("                      0 disables undo, `unlimited` keeps all)");
+                println!(
+                    "  --hard              Only 3 undos per game, and 8s may spawn (see config)"
+                );
+                println!("  --undo-penalty <N>  Every undo costs N points");
+                println!("  --host <ADDR>       Wait for a network opponent (e.g. 0.0.0.0:2048)");
+                println!("  --join <ADDR>       Race against a network opponent");
+                println!("  --spectate <ADDR>   Let others watch with `tui_2048 watch <ADDR>`");
+                println!(
+                    "  --script <FILE>     Play moves such as LLURD from a file (- for stdin)"
+                );
+                println!("                      and print the final board as JSON");
+                println!("  --record <FILE>     Record the session to an asciinema .cast file");
+                println!("  --snapshot-png      Save board snapshots (x) as PNG images too");
+                println!("  --log-level <LEVEL> off, error, warn, info, debug or trace (default");
+                println!("                      info, or TUI_2048_LOG); F12 shows debug info");
+                println!("\nBot options (non-interactive, exit code 0 = target reached):");
+                println!("  --target <TILE>     Tile the bot must reach (default 2048)");
+                println!("  --board <POSITION>  Start position, rows separated by '/'");
+                println!("                      (exit code 2 if invalid)");
+                println!("  --seed <N>          Seed for tile spawns");
+                println!("  --max-moves <N>     Give up after N moves (exit code 3)");
+                println!("\nSolve options (play whole games and report the results):");
+                println!("  --algo <NAME>       random, greedy, expectimax or mcts (default mcts)");
+                println!("  --games <N>         Games to play (default 10)");
+                println!("  --rollouts <N>      Random games per MCTS move (default 200)");
+                println!("  --depth <N>         Moves expectimax looks ahead (default 3)");
+                println!("  --time-ms <N>       Time per move in milliseconds; expectimax then");
+                println!("                      settles for a shallower search");
+                println!("  --seed <N>          Seed of the first game");
+                println!("\nSimulate options (like solve, on several threads):");
+                println!(
+                    "  --strategy <NAME>   random, greedy, expectimax or mcts (default greedy)"
+                );
+                println!("  --games <N>         Games to play (default 100)");
+                println!("  --threads <N>       Threads to play on (default: one per CPU)");
+                println!("  --seed <N>          Seed of the first game");
+                println!("\nverify-all re-simulates the replays in DIR (default: the ghost");
+                println!("directory) and exits with code 4 if any no longer reproduce.");
+                println!("\nexport draws a replay, e.g. a ghost, as an animated SVG or GIF.");
+                #[cfg(feature = "http")]
+                println!(
+                    "\nserve answers the HTTP API on ADDR (default {}).",
+                    DEFAULT_SERVE_ADDR
+                );
                 println!("\n----------------------- in-game controls ------------------------");
                 println!("  Arrow keys or WASD to move tiles");
                 println!("  U or Z to undo a move");
+                println!("  Hold P to see the board before the last move");
+                println!("  G then S, L, A or ? for stats, leaderboard, achievements or help");
+                println!("  G then C to choose border styles and accents unlocked by achievements");
+                println!("  G then O to change the settings");
+                #[cfg(feature = "sandbox")]
+                println!("  In the sandbox, +/- change the tile under the cursor, R edits again");
                 println!("  R to restart after game over");
                 println!("  Q or Esc to quit the game");
                 return Ok(());
@@ -36,67 +371,1186 @@ fn main() -> GameResult<()> {
                 println!("tui_2048 version 1.0.0");
                 return Ok(());
             }
+            "--color" => {
+                options.colors = options.colors.max(ColorDepth::Basic);
+            }
             "--no-color" => {
-                use_color = false;
+                options.colors = ColorDepth::None;
+            }
+            "--big-tiles" => {
+                options.big_tiles = true;
+            }
+            "--snapshot-png" => {
+                options.snapshot_png = true;
+            }
+            "--ascii" => {
+                options.ascii = true;
+            }
+            "--unicode" => {
+                options.ascii = false;
+            }
+            "--daily" => {
+                let today = Date::today();
+                options.daily_date = Some(today);
+                options.seed = Some(daily_seed(today));
+            }
+            "--seed" => match args_iter.next().and_then(|seed| seed.parse().ok()) {
+                Some(seed) => options.seed = Some(seed),
+                None => {
+                    return Err(GameError::InputError(
+                        "--seed requires a number".to_string(),
+                    ));
+                }
+            },
+            "--ghost" => {
+                options.ghost = true;
+            }
+            "--versus" => {
+                options.versus = true;
             }
+            "--puzzles" => {
+                options.puzzles = true;
+            }
+            #[cfg(feature = "sandbox")]
+            "--sandbox" => {
+                options.sandbox = true;
+            }
+            "--tutorial" => {
+                options.tutorial = true;
+            }
+            "--no-menu" => {
+                options.menu = false;
+            }
+            "--adaptive" => {
+                options.adaptive = true;
+            }
+            "--zen" => {
+                options.zen = true;
+            }
+            "--target" => match args_iter.next().and_then(|tile| tile.parse().ok()) {
+                Some(tile @ (1024 | 2048 | 8192 | 16384)) => options.win_tile = tile,
+                _ => {
+                    return Err(GameError::InputError(
+                        "--target requires 1024, 2048, 8192 or 16384".to_string(),
+                    ));
+                }
+            },
+            "--arcade" => {
+                options.arcade = true;
+            }
+            "--power-ups" => {
+                options.power_ups = true;
+            }
+            "--trainer" => {
+                options.trainer = true;
+            }
+            "--divide" => {
+                options.divide = true;
+            }
+            "--hard" => {
+                options.undo_policy.max_undos = Some(HARD_MODE_UNDOS);
+            }
+            "--undo-penalty" => match args_iter.next().and_then(|points| points.parse().ok()) {
+                Some(points) => options.undo_policy.penalty = points,
+                None => {
+                    return Err(GameError::InputError(
+                        "--undo-penalty requires a number".to_string(),
+                    ));
+                }
+            },
+            "--size" => match args_iter.next().and_then(|size| parse_board_size(size)) {
+                Some(size) => options.size = Some(size),
+                None => {
+                    return Err(GameError::InputError(
+                        "--size requires a number or rows and columns like 4x6".to_string(),
+                    ));
+                }
+            },
+            "--undo-limit" => {
+                options.undo_limit = match args_iter.next().map(String::as_str) {
+                    Some("unlimited") => None,
+                    limit => Some(limit.and_then(|limit| limit.parse().ok()).ok_or_else(|| {
+                        GameError::InputError(
+                            "--undo-limit requires a number or `unlimited`".to_string(),
+                        )
+                    })?),
+                };
+            }
+            "--host" | "--join" | "--spectate" => match args_iter.next() {
+                Some(addr) if arg == "--host" => options.host = Some(addr.clone()),
+                Some(addr) if arg == "--join" => options.join = Some(addr.clone()),
+                Some(addr) => options.spectate = Some(addr.clone()),
+                None => {
+                    return Err(GameError::InputError(format!(
+                        "{} requires an address",
+                        arg
+                    )));
+                }
+            },
+            "--script" => match args_iter.next() {
+                Some(path) => options.script = Some(PathBuf::from(path)),
+                None => {
+                    return Err(GameError::InputError(
+                        "--script requires a file or `-` for stdin".to_string(),
+                    ));
+                }
+            },
+            "--import" => match args_iter.next() {
+                Some(code) => options.import = Some(ShareCode::decode(code)?),
+                None => {
+                    return Err(GameError::InputError(
+                        "--import requires a share code".to_string(),
+                    ));
+                }
+            },
+            "--log-level" => match args_iter.next() {
+                Some(name) => options.log_level = logging::parse_level(name)?,
+                None => {
+                    return Err(GameError::InputError(
+                        "--log-level requires a level".to_string(),
+                    ));
+                }
+            },
+            "--record" => match args_iter.next() {
+                Some(path) => options.record = Some(PathBuf::from(path)),
+                None => {
+                    return Err(GameError::InputError(
+                        "--record requires a file".to_string(),
+                    ));
+                }
+            },
+            "--lang" => match args_iter
+                .next()
+                .map(|code| (code, Language::from_code(code)))
+            {
+                Some((_, Some(language))) => i18n::set_language(language),
+                Some((code, None)) => {
+                    let codes: Vec<&str> = Language::ALL.iter().map(|l| l.code()).collect();
+                    return Err(GameError::InputError(format!(
+                        "Unknown language {}, available: {}",
+                        code,
+                        codes.join(", ")
+                    )));
+                }
+                None => {
+                    return Err(GameError::InputError(
+                        "--lang requires a language code".to_string(),
+                    ));
+                }
+            },
+            "--config" => match args_iter.next() {
+                Some(path) => options.config_file = Some(PathBuf::from(path)),
+                None => {
+                    return Err(GameError::InputError(
+                        "--config requires a path".to_string(),
+                    ));
+                }
+            },
+            "--profile" => match args_iter.next() {
+                Some(name) => {
+                    profile::validate_name(name)?;
+                    options.profile = Some(name.clone());
+                }
+                None => {
+                    return Err(GameError::InputError(
+                        "--profile requires a name".to_string(),
+                    ));
+                }
+            },
             _ => {
-                eprintln!("Unknown option: {}", arg);
-                return Err(GameError::InputError(format!("Invalid argument: {}", arg)));
+                return Err(GameError::InputError(format!("Unknown option: {}", arg)));
+            }
+        }
+    }
+
+    if options.ghost && options.seed.is_none() {
+        return Err(GameError::InputError(
+            "--ghost requires --seed or --daily".to_string(),
+        ));
+    }
+
+    if options
+        .size
+        .is_some_and(|size| size != (DEFAULT_SIZE, DEFAULT_SIZE))
+        && (options.daily_date.is_some() || options.ghost)
+    {
+        return Err(GameError::InputError(
+            "--daily and --ghost are played on the default board size".to_string(),
+        ));
+    }
+    if (options.puzzles || options.sandbox())
+        && (options.daily_date.is_some()
+            || options.ghost
+            || options.host.is_some()
+            || options.join.is_some())
+    {
+        return Err(GameError::InputError(
+            "--puzzles and --sandbox can't be combined with --daily, --ghost, --host or --join"
+                .to_string(),
+        ));
+    }
+    if options.import.is_some()
+        && (options.daily_date.is_some()
+            || options.ghost
+            || options.puzzles
+            || options.sandbox()
+            || options.host.is_some()
+            || options.join.is_some())
+    {
+        return Err(GameError::InputError(
+            "--import can't be combined with other modes".to_string(),
+        ));
+    }
+    if options.versus
+        && (options.divide
+            || options.power_ups
+            || options.trainer
+            || options.daily_date.is_some()
+            || options.ghost
+            || options.puzzles
+            || options.sandbox()
+            || options.import.is_some()
+            || options.host.is_some()
+            || options.join.is_some())
+    {
+        return Err(GameError::InputError(
+            "--versus can't be combined with other modes".to_string(),
+        ));
+    }
+    if options.zen
+        && (options.daily_date.is_some()
+            || options.ghost
+            || options.puzzles
+            || options.host.is_some()
+            || options.join.is_some())
+    {
+        return Err(GameError::InputError(
+            "--zen can't be combined with --daily, --ghost, --puzzles, --host or --join"
+                .to_string(),
+        ));
+    }
+    if (options.arcade
+        || options.power_ups
+        || options.trainer
+        || options.divide
+        || options.win_tile != WIN_TILE)
+        && (options.daily_date.is_some()
+            || options.ghost
+            || options.puzzles
+            || options.host.is_some()
+            || options.join.is_some())
+    {
+        return Err(GameError::InputError(
+            "--arcade, --power-ups, --trainer, --target and --divide can't be combined with --daily, --ghost, --puzzles, --host or --join"
+                .to_string(),
+        ));
+    }
+    // Shared positions continue with their own seed and board size
+    if let Some(code) = &options.import {
+        options.seed = Some(code.seed);
+        options.size = Some((code.board.len(), code.board[0].len()));
+    }
+    // Replays of ghosts are verified with the regular undo rules
+    if options.ghost && options.undo_policy != UndoPolicy::default() {
+        return Err(GameError::InputError(
+            "--ghost can't be combined with --hard or --undo-penalty".to_string(),
+        ));
+    }
+    start_log(options.log_level);
+    log::info!("Starting tui_2048 {}", env!("CARGO_PKG_VERSION"));
+    if let Some(name) = &options.profile {
+        profile::create(name)?;
+        profile::set_active(Some(name.clone()))?;
+        log::info!("Using profile {}", name);
+    }
+    let mut config = options.load_config()?;
+    // Report invalid game settings before the terminal is taken over
+    game_builder(&options, &config).build()?;
+
+    if let Some(path) = &options.script {
+        let status = run_script_command(path, game_builder(&options, &config));
+        std::process::exit(status.code());
+    }
+
+    // Connect before the terminal is taken over, so progress can be printed
+    let session = if let Some(addr) = &options.host {
+        let seed = *options.seed.get_or_insert_with(rand::random);
+        println!("Waiting for an opponent on {}...", addr);
+        Some(NetSession::host(addr.as_str(), seed)?)
+    } else if let Some(addr) = &options.join {
+        println!("Connecting to {}...", addr);
+        let (session, seed) = NetSession::join(addr.as_str())?;
+        options.seed = Some(seed);
+        Some(session)
+    } else {
+        None
+    };
+
+    // The menu picks the mode, unless one was picked on the command line
+    let show_menu = options.menu
+        && session.is_none()
+        && options.daily_date.is_none()
+        && !options.ghost
+        && !options.puzzles
+        && !options.sandbox()
+        && options.import.is_none()
+        && options.spectate.is_none()
+        && !options.versus;
+    // Switching the profile in the menu loads its games and records and
+    // shows the menu again
+    let (saves, mut games, mut recovered, autosave_path, mut resumed, pick_saved_game) = loop {
+        // Unfinished games are kept unless the game depends on other players, a
+        // ghost, a puzzle, power-ups, the trainer or a position set up in the
+        // sandbox
+        let mut saves = if session.is_none()
+            && !options.versus
+            && !options.power_ups
+            && !options.trainer
+            && !options.ghost
+            && !options.puzzles
+            && !options.sandbox()
+        {
+            Saves::default_dir().map(Saves::new)
+        } else {
+            None
+        };
+        let mut games = match &saves {
+            Some(saves) => saves.list()?,
+            None => Vec::new(),
+        };
+        // A game left behind by a crash is offered first, instead of the menu
+        let autosave_path =
+            Autosave::default_path().filter(|_| saves.is_some() && options.import.is_none());
+        let recovered = autosave_path.as_deref().and_then(recover_autosave);
+        let show_menu = show_menu && recovered.is_none();
+        let recovered = match recovered {
+            Some(game) => {
+                games.insert(0, game);
+                true
+            }
+            None => false,
+        };
+        let resumed = match &options.import {
+            Some(code) => Some(code.game(game_builder(&options, &config))?),
+            None => None,
+        };
+        // Without the menu, unfinished games are offered right away
+        let mut pick_saved_game = !show_menu && resumed.is_none() && !games.is_empty();
+        if show_menu {
+            let mut menu = Menu::new(!games.is_empty(), menu_stats()?)
+                .with_profiles(profile::list()?, profile::active().as_deref());
+            if config.demo_after_secs > 0 {
+                menu = menu.with_demo(Duration::from_secs(config.demo_after_secs), config.theme);
+            }
+            match menu::show(&mut menu, options.ascii)? {
+                MenuItem::NewGame | MenuItem::Stats => {}
+                MenuItem::Settings => options.settings = true,
+                MenuItem::Continue => pick_saved_game = true,
+                MenuItem::DailyChallenge => {
+                    let today = Date::today();
+                    options.daily_date = Some(today);
+                    options.seed = Some(daily_seed(today));
+                    options.size = None;
+                }
+                MenuItem::Puzzles => {
+                    options.puzzles = true;
+                    saves = None;
+                }
+                MenuItem::Zen => options.zen = true,
+                MenuItem::Variant => {
+                    let variant = menu.variant();
+                    options.win_tile = variant.win_tile();
+                    options.divide = variant.merge_rule() == MergeRule::Halve;
+                }
+                MenuItem::Profile => {
+                    // The menu is shown again with the records of the profile
+                    profile::set_active(menu.profile().map(str::to_string))?;
+                    log::info!("Switched to profile {:?}", profile::active());
+                    config = options.load_config()?;
+                    game_builder(&options, &config).build()?;
+                    continue;
+                }
+                MenuItem::Quit => return Ok(()),
             }
         }
+        break (
+            saves,
+            games,
+            recovered,
+            autosave_path,
+            resumed,
+            pick_saved_game,
+        );
+    };
+    if pick_saved_game {
+        let choice = resume::pick(
+            &mut games,
+            &mut recovered,
+            options.ascii,
+            |game, autosaved| match (autosaved, &autosave_path, &saves) {
+                (true, Some(path), _) => autosave::discard(path),
+                (false, _, Some(saves)) => saves.delete(game),
+                _ => Ok(()),
+            },
+        )?;
+        // Picking another game discards the recovered one; quitting keeps it
+        // for the next launch
+        if recovered
+            && !matches!(choice, Choice::Resume(0) | Choice::Quit)
+            && let Some(path) = &autosave_path
+        {
+            autosave::discard(path)?;
+        }
+        match choice {
+            Choice::Resume(index) => {
+                use_mode(&mut options, &games[index].mode);
+                resumed = Some(games[index].resume()?);
+            }
+            Choice::NewGame => {}
+            Choice::Quit => return Ok(()),
+        }
     }
 
     // Use a closure to ensure terminal cleanup even in case of errors
-    run_app(use_color)
-}
-
-/// Run the application with proper terminal setup and cleanup
-fn run_app(use_color: bool) -> GameResult<()> {
-    // Setup terminal
-    let mut stdout = io::stdout();
-    crossterm::terminal::enable_raw_mode()?;
-    let backend = CrosstermBackend::new(&mut stdout);
-    let mut terminal = Terminal::new(backend)?;
-    terminal.clear()?;
-    terminal.hide_cursor()?;
-
-    // Create game components
-    let game = Game2048::default();
-
-    // Use a result variable to store the application outcome
-    let result = {
-        // Create and run the app with the appropriate renderer
-        if use_color {
-            let renderer = DefaultRenderer;
-            let mut app = App::new(game, renderer, terminal);
-            app.run()
+    match run_app(options, &config, session, resumed, saves)? {
+        // Conventional exit status for termination by SIGINT
+        AppOutcome::Interrupted => std::process::exit(130),
+        _ => Ok(()),
+    }
+}
+
+/// The game left in the autosave by a session that didn't end normally. A
+/// corrupt autosave is logged and removed, as there is nothing to offer.
+fn recover_autosave(path: &Path) -> Option<SavedGame> {
+    match autosave::recover(path) {
+        Ok(game) => game,
+        Err(error) => {
+            log::warn!("{}", error);
+            let _ = autosave::discard(path);
+            None
+        }
+    }
+}
+
+/// Write the log to the log file in the data directory. A log that can't be
+/// opened is reported without stopping the game.
+fn start_log(level: LevelFilter) {
+    let Some(dir) = logging::default_dir() else {
+        return;
+    };
+    if let Err(error) = logging::init(&dir, level) {
+        eprintln!("Warning: {}", error);
+    }
+}
+
+/// Lines of the stats page of the main menu: the records kept across games
+fn menu_stats() -> GameResult<Vec<String>> {
+    let mut lines = vec![];
+    if let Some(path) = Leaderboard::default_path() {
+        let leaderboard = Leaderboard::load(&path)?;
+        match leaderboard.entries().first() {
+            Some(best) => lines.push(i18n::format(
+                "menu.best_score",
+                &[("score", &best.score), ("name", &best.name)],
+            )),
+            None => lines.push(i18n::text("menu.no_best_score").to_string()),
+        }
+    }
+    if let Some(path) = DailyScores::default_path() {
+        let best = DailyScores::load(&path)?.best(Date::today());
+        lines.push(match best {
+            Some(score) => i18n::format("menu.daily", &[("score", &score)]),
+            None => i18n::text("menu.daily_not_played").to_string(),
+        });
+    }
+    if let Some(path) = Achievements::default_path() {
+        let achievements = Achievements::load(&path)?;
+        let unlocked = Achievement::ALL
+            .iter()
+            .filter(|&&achievement| achievements.is_unlocked(achievement))
+            .count();
+        lines.push(i18n::format(
+            "menu.achievements",
+            &[("unlocked", &unlocked), ("total", &Achievement::ALL.len())],
+        ));
+        lines.push(i18n::format(
+            "menu.merge_chains",
+            &[("count", &achievements.chains)],
+        ));
+    }
+    if let Some(path) = PuzzleProgress::default_path() {
+        let solved = PuzzleProgress::load(&path)?.solved_count();
+        lines.push(i18n::format(
+            "menu.puzzles_solved",
+            &[("solved", &solved), ("total", &puzzles::builtin().len())],
+        ));
+    }
+    if let Some(path) = PlayTime::default_path() {
+        let play_time = PlayTime::load(&path)?;
+        lines.push(i18n::format(
+            "menu.play_time",
+            &[
+                (
+                    "total",
+                    &stats::hours_and_minutes(Duration::from_secs(play_time.total_secs)),
+                ),
+                (
+                    "longest",
+                    &stats::hours_and_minutes(Duration::from_secs(play_time.longest_session_secs)),
+                ),
+            ],
+        ));
+    }
+    Ok(lines)
+}
+
+/// Run the greedy bot without a terminal UI and report the result
+fn run_bot_command(args: &[String]) -> ExitStatus {
+    let mut target = WIN_TILE;
+    let mut board = None;
+    let mut seed = rand::random();
+    let mut max_moves = None;
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let Some(value) = args_iter.next() else {
+            eprintln!("{} requires a value", arg);
+            return ExitStatus::Failure;
+        };
+        let parsed = match arg.as_str() {
+            "--target" => value.parse().map(|v| target = v).is_ok(),
+            "--seed" => value.parse().map(|v| seed = v).is_ok(),
+            "--max-moves" => value.parse().map(|v| max_moves = Some(v)).is_ok(),
+            "--board" => {
+                board = Some(value.as_str());
+                true
+            }
+            _ => {
+                eprintln!("Unknown bot option: {}", arg);
+                return ExitStatus::Failure;
+            }
+        };
+        if !parsed {
+            eprintln!("Invalid value for {}: {}", arg, value);
+            return ExitStatus::Failure;
+        }
+    }
+
+    let mut game = match board {
+        Some(board) => match game_from_position(board, seed) {
+            Ok(game) => game,
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitStatus::InvalidPosition;
+            }
+        },
+        None => Game2048::with_seed(seed),
+    };
+    let report = run_bot(&mut game, target, max_moves);
+    println!(
+        "{} {} after {} moves (score {}, best tile {})",
+        if report.reached_target {
+            "Reached"
         } else {
-            let renderer = NoColorRenderer;
-            let mut app = App::new(game, renderer, terminal);
-            app.run()
+            "Did not reach"
+        },
+        target,
+        report.moves,
+        report.score,
+        report.max_tile
+    );
+    report.status()
+}
+
+/// Let a solver play whole games and print the average score and how often
+/// each tile was the largest
+fn run_solve_command(args: &[String]) -> ExitStatus {
+    let mut algo = "mcts".to_string();
+    let mut games = 10;
+    let mut mcts = MctsConfig::default();
+    let mut expectimax = ExpectimaxConfig::default();
+    let mut seed = rand::random();
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let Some(value) = args_iter.next() else {
+            eprintln!("{} requires a value", arg);
+            return ExitStatus::Failure;
+        };
+        let parsed = match arg.as_str() {
+            "--algo" => {
+                algo = value.clone();
+                true
+            }
+            "--games" => value.parse().map(|v| games = v).is_ok(),
+            "--rollouts" => value.parse().map(|v| mcts.rollouts = v).is_ok(),
+            "--depth" => value.parse().map(|v| expectimax.depth = v).is_ok(),
+            "--time-ms" => value
+                .parse()
+                .map(|v| {
+                    mcts.time_budget = Some(Duration::from_millis(v));
+                    expectimax.time_budget = mcts.time_budget;
+                })
+                .is_ok(),
+            "--seed" => value.parse().map(|v| seed = v).is_ok(),
+            _ => {
+                eprintln!("Unknown solve option: {}", arg);
+                return ExitStatus::Failure;
+            }
+        };
+        if !parsed {
+            eprintln!("Invalid value for {}: {}", arg, value);
+            return ExitStatus::Failure;
+        }
+    }
+
+    let solver = match Solver::from_name(&algo) {
+        Ok(Solver::Mcts(_)) => Solver::Mcts(mcts),
+        Ok(Solver::Expectimax(_)) => Solver::Expectimax(expectimax),
+        Ok(solver) => solver,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitStatus::Failure;
+        }
+    };
+    let report = run_solve(solver, games, seed);
+    println!(
+        "Played {} games with {}: average score {:.1}",
+        report.scores.len(),
+        solver.name(),
+        report.average_score()
+    );
+    print_max_tiles(&report);
+    ExitStatus::Success
+}
+
+/// Let a strategy play many games on several threads and print statistics
+/// of the results
+fn run_simulate_command(args: &[String]) -> ExitStatus {
+    let mut strategy = "greedy".to_string();
+    let mut games = 100;
+    let mut threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut seed = rand::random();
+
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let Some(value) = args_iter.next() else {
+            eprintln!("{} requires a value", arg);
+            return ExitStatus::Failure;
+        };
+        let parsed = match arg.as_str() {
+            "--strategy" => {
+                strategy = value.clone();
+                true
+            }
+            "--games" => value.parse().map(|v| games = v).is_ok(),
+            "--threads" => value.parse().map(|v| threads = v).is_ok(),
+            "--seed" => value.parse().map(|v| seed = v).is_ok(),
+            _ => {
+                eprintln!("Unknown simulate option: {}", arg);
+                return ExitStatus::Failure;
+            }
+        };
+        if !parsed {
+            eprintln!("Invalid value for {}: {}", arg, value);
+            return ExitStatus::Failure;
+        }
+    }
+
+    let report = Solver::from_name(&strategy)
+        .and_then(|solver| run_simulation(solver, games, seed, threads));
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitStatus::Failure;
         }
     };
+    println!(
+        "Simulated {} games with {} on {} threads",
+        report.scores.len(),
+        strategy,
+        threads
+    );
+    println!("Mean score:   {:.1}", report.average_score());
+    println!("Median score: {:.1}", report.median_score());
+    println!(
+        "Reached {}: {:.1}%",
+        WIN_TILE,
+        report.reach_rate(WIN_TILE) * 100.0
+    );
+    print_max_tiles(&report);
+    ExitStatus::Success
+}
+
+/// Print how often each tile was the largest, with a bar per tile
+fn print_max_tiles(report: &SolveReport) {
+    const BAR_WIDTH: usize = 40;
+    let games = report.scores.len().max(1);
+    println!("Largest tile:");
+    for (tile, &count) in report.max_tiles.iter().rev() {
+        println!(
+            "  {:>6} {:<width$} {:>4} ({:.1}%)",
+            tile,
+            "#".repeat((count * BAR_WIDTH).div_ceil(games)),
+            count,
+            count as f64 * 100.0 / games as f64,
+            width = BAR_WIDTH
+        );
+    }
+}
 
-    // Ensure terminal state is restored even if there was an error
-    let cleanup_result = restore_terminal(&mut stdout);
+/// Play the moves of a script and print the final state as JSON
+fn run_script_command(path: &Path, builder: GameBuilder) -> ExitStatus {
+    let text = if path == Path::new("-") {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(path)
+    };
+    let result = text
+        .map_err(GameError::from)
+        .and_then(|text| parse_moves(&text))
+        .and_then(|moves| {
+            let mut game = builder.build()?;
+            run_script(&mut game, &moves).to_json()
+        });
+    match result {
+        Ok(json) => {
+            println!("{}", json);
+            ExitStatus::Success
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitStatus::Failure
+        }
+    }
+}
 
-    // Combine results, prioritizing the application result
-    match (result, cleanup_result) {
-        (Ok(_), Ok(_)) => Ok(()),
-        (Err(e), _) => Err(e),
-        (_, Err(e)) => Err(e),
+/// Re-simulate stored replays and report the ones that no longer reproduce
+fn run_verify_command(args: &[String]) -> ExitStatus {
+    let dir = match args {
+        [] => match Replay::ghost_dir() {
+            Some(dir) => dir,
+            None => {
+                eprintln!("No data directory found, pass the replay directory");
+                return ExitStatus::Failure;
+            }
+        },
+        [dir] => PathBuf::from(dir),
+        _ => {
+            eprintln!("verify-all takes at most one directory");
+            return ExitStatus::Failure;
+        }
+    };
+    let report = match verify_all(&dir) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Cannot read {}: {}", dir.display(), e);
+            return ExitStatus::Failure;
+        }
+    };
+    for (path, reason) in &report.failures {
+        println!("FAIL {}: {}", path.display(), reason);
     }
+    println!(
+        "Verified {} replays, {} failed",
+        report.checked,
+        report.failures.len()
+    );
+    report.status()
+}
+
+/// Draw the boards of a replay file as an animation
+fn run_export_command(args: &[String]) -> GameResult<()> {
+    let [replay, output] = args else {
+        return Err(GameError::InputError(
+            "export requires a replay file and an output file".to_string(),
+        ));
+    };
+    let format = ExportFormat::from_path(Path::new(output))?;
+    let replay = Replay::load(Path::new(replay))?
+        .ok_or_else(|| GameError::InputError(format!("No replay found at {}", replay)))?;
+    let boards = replay.boards();
+    fs::write(output, export::export(&boards, format)?)?;
+    println!("Exported {} moves to {}", boards.len() - 1, output);
+    Ok(())
 }
 
-/// Restore terminal to its original state
-fn restore_terminal(stdout: &mut io::Stdout) -> GameResult<()> {
-    crossterm::terminal::disable_raw_mode()?;
+/// Address the HTTP API listens on unless another is given
+#[cfg(feature = "http")]
+const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:2048";
 
-    // We need to create another terminal instance to show the cursor
-    // since the original one is moved into the app
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    terminal.show_cursor()?;
+#[cfg(feature = "http")]
+fn run_serve_command(args: &[String]) -> GameResult<()> {
+    let addr = match args {
+        [] => DEFAULT_SERVE_ADDR,
+        [addr] => addr.as_str(),
+        _ => {
+            return Err(GameError::InputError(
+                "serve takes at most one address".to_string(),
+            ));
+        }
+    };
+    println!("Serving the 2048 API on http://{}", addr);
+    tui_2048::server::serve(addr)
+}
 
+/// Print the games broadcast by another player until they quit
+fn run_watch_command(args: &[String]) -> GameResult<()> {
+    let [addr] = args else {
+        return Err(GameError::InputError(
+            "watch requires exactly one address".to_string(),
+        ));
+    };
+    let mut last_move = String::new();
+    spectate::watch(addr.as_str(), |event| match event {
+        GameEvent::Move { step, points } => {
+            last_move = format!("Last move: {:?} (+{})", step, points);
+        }
+        GameEvent::State {
+            score,
+            game_over,
+            board,
+        } => {
+            // Clear the screen and redraw from the top left corner
+            print!("\x1b[2J\x1b[H");
+            println!("Watching {} - score {}\n", addr, score);
+            for line in mini_board(&board) {
+                println!("{}", line);
+            }
+            println!("\n{}", last_move);
+            if game_over {
+                println!("Game over");
+            }
+        }
+    })?;
+    println!("The game has ended");
     Ok(())
 }
+
+/// Play with the rules of a saved game
+fn use_mode(options: &mut Options, mode: &Mode) {
+    options.size = Some(mode.dims());
+    options.undo_limit = mode.undo_limit;
+    options.undo_policy = UndoPolicy {
+        max_undos: mode.max_undos,
+        penalty: mode.undo_penalty,
+    };
+    options.adaptive = mode.adaptive;
+    options.zen = mode.zen;
+    options.arcade = mode.arcade;
+    options.win_tile = mode.win_tile;
+    options.divide = mode.divide;
+    options.daily_date = mode.daily;
+    options.seed = mode.daily.map(daily_seed);
+}
+
+/// Run the application; the app sets up the terminal and restores it when dropped
+fn run_app(
+    options: Options,
+    config: &Config,
+    session: Option<NetSession>,
+    resumed: Option<Game2048>,
+    saves: Option<Saves>,
+) -> GameResult<AppOutcome> {
+    let game = match resumed {
+        Some(game) => game,
+        None => game_builder(&options, config).build()?,
+    };
+
+    // Create and run the app with the appropriate renderer
+    let launch = Launch {
+        game,
+        config,
+        options: &options,
+        session,
+        saves,
+    };
+    // Big tiles are drawn with block characters, so ASCII takes precedence
+    let big_tiles = options.big_tiles || config.big_tiles;
+    let color = options.colors != ColorDepth::None;
+    match (color, options.ascii, big_tiles) {
+        (true, true, _) => launch.start(AsciiRenderer::default()),
+        (false, true, _) => launch.start(AsciiRenderer::without_color()),
+        (true, false, true) => launch.start(BigTileRenderer::default()),
+        (false, false, true) => launch.start(BigTileRenderer::without_color()),
+        (true, false, false) => launch.start(DefaultRenderer::default()),
+        (false, false, false) => launch.start(NoColorRenderer),
+    }
+}
+
+/// Everything needed to play a game, apart from the renderer
+struct Launch<'a> {
+    game: Game2048,
+    config: &'a Config,
+    options: &'a Options,
+    session: Option<NetSession>,
+    saves: Option<Saves>,
+}
+
+impl Launch<'_> {
+    /// Set up the terminal for a renderer matching its colors, then play
+    fn start<R: GameRenderer>(self, mut renderer: R) -> GameResult<AppOutcome> {
+        renderer.set_color_depth(self.options.colors);
+        let app = App::init(self.game, renderer)?;
+        play(app, self.config, self.options, self.session, self.saves)
+    }
+}
+
+/// Play until the player quits, then keep the last game if it is unfinished
+fn play<R>(
+    app: App<Game2048, R>,
+    config: &Config,
+    options: &Options,
+    session: Option<NetSession>,
+    saves: Option<Saves>,
+) -> GameResult<AppOutcome>
+where
+    R: GameRenderer,
+{
+    let mut app = setup_app(app, config, options, session)?;
+    let daily = options.daily_date;
+    if let Some(saves) = &saves {
+        app = app.with_save_slots(saves.clone(), move |game| SavedGame::new(game, daily));
+        // The autosave outlives a crash, so the game can be recovered; games
+        // that end normally are saved below instead
+        if let Some(path) = Autosave::default_path() {
+            app = app.with_autosave(Autosave::new(path), move |game| SavedGame::new(game, daily));
+        }
+    }
+    #[cfg(feature = "tokio")]
+    let outcome = run_until_quit_async(&mut app)?;
+    #[cfg(not(feature = "tokio"))]
+    let outcome = run_until_quit(&mut app)?;
+    app.discard_autosave();
+    let game = app.game();
+    let saved = SavedGame::new(game, options.daily_date);
+    let (finished, started) = (game.game_over(), game.moves() > 0);
+    // Restore the terminal before printing
+    drop(app);
+
+    if let Some(saves) = saves {
+        if finished {
+            saves.remove(&saved.mode)?;
+        } else if started {
+            saves.save(&saved)?;
+            println!("Game saved, start tui_2048 again to resume it");
+        }
+    }
+    Ok(outcome)
+}
+
+/// Board size given as `N` for a square or as `RxC` for rows and columns
+fn parse_board_size(text: &str) -> Option<(usize, usize)> {
+    match text.split_once('x') {
+        Some((rows, columns)) => Some((rows.parse().ok()?, columns.parse().ok()?)),
+        None => text.parse().ok().map(|size| (size, size)),
+    }
+}
+
+/// Game settings chosen on the command line, or else in the config
+fn game_builder(options: &Options, config: &Config) -> GameBuilder {
+    let mut builder = Game2048::builder();
+    // Daily challenges and ghosts are compared with other games on the default board
+    if options.daily_date.is_none() && !options.ghost {
+        let (rows, columns) = options.size.unwrap_or((config.size, config.size));
+        builder = builder.dims(rows, columns).four_chance(config.four_chance);
+        if options.hard() {
+            builder = builder.eight_chance(config.eight_chance);
+        }
+    }
+    builder = match options.undo_limit {
+        Some(limit) => builder.undo_limit(limit),
+        None => builder.unlimited_undo(),
+    };
+    if let Some(seed) = options.seed {
+        builder = builder.seed(seed);
+    }
+    builder = builder.undo_policy(options.undo_policy);
+    if options.adaptive {
+        builder = builder.adaptive_difficulty();
+    }
+    if options.zen {
+        builder = builder.zen();
+    }
+    if options.arcade {
+        builder = builder.arcade();
+    }
+    if options.divide {
+        builder = builder.merge_rule(MergeRule::Halve);
+    }
+    builder.win_tile(options.win_tile)
+}
+
+/// Keep playing new games until the player quits
+#[cfg(not(feature = "tokio"))]
+fn run_until_quit<G, R>(app: &mut App<G, R>) -> GameResult<AppOutcome>
+where
+    G: tui_2048::game::GameEngine,
+    R: GameRenderer,
+{
+    loop {
+        match app.run()? {
+            AppOutcome::Restarted => continue,
+            outcome => return Ok(outcome),
+        }
+    }
+}
+
+/// Keep playing new games until the player quits, awaiting events on a
+/// single-threaded runtime
+#[cfg(feature = "tokio")]
+fn run_until_quit_async<G, R>(app: &mut App<G, R>) -> GameResult<AppOutcome>
+where
+    G: tui_2048::game::GameEngine,
+    R: GameRenderer,
+{
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()?;
+    runtime.block_on(async {
+        loop {
+            match app.run_async().await? {
+                AppOutcome::Restarted => continue,
+                outcome => return Ok(outcome),
+            }
+        }
+    })
+}
+
+/// Apply the config and attach score tracking and optional config watching to the app
+fn setup_app<R>(
+    app: App<Game2048, R>,
+    config: &Config,
+    options: &Options,
+    session: Option<NetSession>,
+) -> GameResult<App<Game2048, R>>
+where
+    R: GameRenderer,
+{
+    let mut app = app.with_config(config)?;
+    let racing = session.is_some() || options.versus;
+    if let Some(session) = session {
+        app = app.with_opponent(session);
+    }
+    if options.versus {
+        app = app.with_versus(game_builder(options, config).build()?);
+    }
+    if options.power_ups {
+        app = app.with_power_ups();
+    }
+    if options.trainer {
+        app = app.with_trainer();
+    }
+    if let Some(addr) = &options.spectate {
+        app = app.with_spectators(Broadcaster::bind(addr.as_str())?);
+    }
+    if let Some(path) = &options.record {
+        app = app.with_recording(path)?;
+    }
+    if let Some(dir) = export::default_dir() {
+        app = app.with_export_dir(dir);
+    }
+    if options.snapshot_png {
+        app = app.with_snapshot_format(PngSnapshot);
+    }
+    // Restarts keep the settings, and replay the same tile sequence if seeded.
+    // The settings were checked before the app started, and the settings
+    // screen only offers valid ones.
+    let builder = game_builder(options, config);
+    let board = (config.size, config.four_chance, config.eight_chance);
+    let fixed_board = options.daily_date.is_some() || options.ghost;
+    let hard = options.hard();
+    app = app.with_configured_game_factory(move |config| {
+        let mut builder = builder.clone();
+        // Changes on the settings screen replace the board of the command line
+        // or the resumed game
+        if !fixed_board && (config.size, config.four_chance, config.eight_chance) != board {
+            builder = builder.size(config.size).four_chance(config.four_chance);
+            if hard {
+                builder = builder.eight_chance(config.eight_chance);
+            }
+        }
+        builder.build().unwrap_or_default()
+    });
+    if let Some(path) = options.config_path() {
+        app = app.with_config_path(path);
+    }
+    if let Some(seed) = options.seed
+        && options.ghost
+        && let Some(dir) = Replay::ghost_dir()
+    {
+        app = app.with_ghost(seed, dir)?;
+    }
+    if let Some(path) = Achievements::default_path() {
+        app = app.with_achievements(path)?;
+    }
+    if let Some(path) = PlayTime::default_path() {
+        app = app.with_play_time(path)?;
+    }
+    // The tutorial is shown on the first launch, and not in games against others
+    // or when the settings were opened from the menu
+    if let Some(path) = Onboarding::default_path()
+        && !options.puzzles
+        && !options.sandbox()
+        && !options.settings
+        && !racing
+        && (options.tutorial || !Onboarding::load(&path)?.tutorial_seen)
+    {
+        app = app.with_tutorial(path);
+    }
+    if options.puzzles {
+        let path = PuzzleProgress::default_path().ok_or_else(|| {
+            GameError::InputError("--puzzles needs a data directory to record progress".to_string())
+        })?;
+        // Puzzles aren't ranked
+        return watch_config(
+            app.with_puzzles(puzzles::builtin(), path, Puzzle::game)?,
+            options.config_path(),
+        );
+    }
+    // Sandbox games aren't ranked
+    #[cfg(feature = "sandbox")]
+    if options.sandbox {
+        return watch_config(app.with_sandbox(), options.config_path());
+    }
+    let app = match options.daily_date {
+        // Daily games are ranked separately from the regular leaderboard
+        Some(date) => match DailyScores::default_path() {
+            Some(path) => app.with_daily(date, path)?,
+            None => app,
+        },
+        None => match Leaderboard::default_path() {
+            Some(path) => app.with_leaderboard(path)?,
+            None => app,
+        },
+    };
+    let app = if options.settings {
+        app.with_settings_open()
+    } else {
+        app
+    };
+    watch_config(app, options.config_path())
+}
+
+/// Enable config hot-reloading when the `watch` feature is compiled in
+#[cfg(feature = "watch")]
+fn watch_config<G, R>(app: App<G, R>, path: Option<PathBuf>) -> GameResult<App<G, R>>
+where
+    G: tui_2048::game::GameEngine,
+    R: GameRenderer,
+{
+    match path {
+        Some(path) => app.watch_config(path),
+        None => Ok(app),
+    }
+}
+
+#[cfg(not(feature = "watch"))]
+fn watch_config<G, R>(app: App<G, R>, _path: Option<PathBuf>) -> GameResult<App<G, R>>
+where
+    G: tui_2048::game::GameEngine,
+    R: GameRenderer,
+{
+    Ok(app)
+}
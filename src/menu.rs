@@ -0,0 +1,581 @@
+//! Main menu, shown at startup before a game begins.
+//!
+//! The menu lists what can be played and leads to an overview of the
+//! records. Choices that leave the menu are returned to the caller, which
+//! sets the game up.
+//!
+//! A menu left alone for a while can play a demo game next to the list, the
+//! bot making a move every few hundred milliseconds, until a key is pressed.
+//!
+//! Once there are named profiles, the menu also switches between them; the
+//! caller then loads the records of the new profile and shows the menu again.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::{
+    Frame, Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+
+use crate::ai;
+use crate::error::{GameResult, TerminalContext};
+use crate::game::{Game2048, GameEngine, GameView, MergeRule, WIN_TILE};
+use crate::i18n;
+use crate::terminal::TerminalGuard;
+use crate::ui::ascii::to_ascii;
+use crate::ui::theme::Theme;
+use crate::ui::{DefaultRenderer, GameRenderer};
+
+/// Time between two moves of the demo game
+const DEMO_MOVE_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Width of the entry list
+const LIST_WIDTH: u16 = 24;
+
+/// An entry of the main menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    NewGame,
+    /// Resume an unfinished game
+    Continue,
+    DailyChallenge,
+    Puzzles,
+    /// A game that never ends, see [`crate::game::GameBuilder::zen`]
+    Zen,
+    /// A game with another target or merge rule, chosen with the left and
+    /// right keys, see [`Menu::variant`]
+    Variant,
+    /// Overview of the records kept across games
+    Stats,
+    /// The settings screen of the game
+    Settings,
+    /// Switch to the profile chosen with the left and right keys, see
+    /// [`Menu::profile`]
+    Profile,
+    Quit,
+}
+
+impl MenuItem {
+    /// All entries, in the order they are listed
+    pub const ALL: [MenuItem; 10] = [
+        MenuItem::NewGame,
+        MenuItem::Continue,
+        MenuItem::DailyChallenge,
+        MenuItem::Puzzles,
+        MenuItem::Zen,
+        MenuItem::Variant,
+        MenuItem::Stats,
+        MenuItem::Settings,
+        MenuItem::Profile,
+        MenuItem::Quit,
+    ];
+
+    /// Label shown in the menu
+    pub fn label(self) -> &'static str {
+        match self {
+            MenuItem::NewGame => i18n::text("menu.new_game"),
+            MenuItem::Continue => i18n::text("menu.continue"),
+            MenuItem::DailyChallenge => i18n::text("menu.daily_challenge"),
+            MenuItem::Puzzles => i18n::text("menu.puzzles"),
+            MenuItem::Zen => i18n::text("menu.zen"),
+            MenuItem::Variant => i18n::text("menu.variant"),
+            MenuItem::Stats => i18n::text("menu.stats"),
+            MenuItem::Settings => i18n::text("menu.settings"),
+            MenuItem::Profile => i18n::text("menu.profile"),
+            MenuItem::Quit => i18n::text("menu.quit"),
+        }
+    }
+
+    /// Whether the entry opens a page of the menu instead of leaving it
+    fn is_page(self) -> bool {
+        matches!(self, MenuItem::Stats)
+    }
+}
+
+/// Game offered by the Variant entry of the menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// Won with a 1024
+    Easy,
+    /// Won with an 8192
+    Hard,
+    /// Won with a 16384
+    Expert,
+    /// Tiles spawn as 2048s and halve, see [`MergeRule::Halve`]
+    Divide,
+}
+
+impl Variant {
+    /// All variants, in the order they are offered
+    pub const ALL: [Variant; 4] = [
+        Variant::Easy,
+        Variant::Hard,
+        Variant::Expert,
+        Variant::Divide,
+    ];
+
+    /// Tile the game is set up with, see
+    /// [`GameBuilder::win_tile`](crate::game::GameBuilder::win_tile)
+    pub fn win_tile(self) -> u32 {
+        match self {
+            Variant::Easy => 1024,
+            Variant::Hard => 8192,
+            Variant::Expert => 16384,
+            Variant::Divide => WIN_TILE,
+        }
+    }
+
+    /// What equal tiles merge into
+    pub fn merge_rule(self) -> MergeRule {
+        match self {
+            Variant::Divide => MergeRule::Halve,
+            _ => MergeRule::Double,
+        }
+    }
+
+    /// Name shown in the menu
+    pub fn label(self) -> String {
+        match self {
+            Variant::Divide => i18n::text("menu.divide").to_string(),
+            _ => self.win_tile().to_string(),
+        }
+    }
+}
+
+/// The main menu with its selection and the page opened from it
+pub struct Menu {
+    items: Vec<MenuItem>,
+    state: ListState,
+    /// Index in [`Variant::ALL`] of the variant offered
+    variant: usize,
+    /// Named profiles to switch between
+    profiles: Vec<String>,
+    /// Index of the profile offered, `0` for the default profile and the
+    /// ones after it for [`Menu::profiles`]
+    profile: usize,
+    /// Lines of the stats page
+    stats: Vec<String>,
+    /// Page shown instead of the list
+    page: Option<MenuItem>,
+    /// How long the menu waits for a key before the demo starts, if at all
+    demo_after: Option<Duration>,
+    /// When the last key was pressed, or the menu was opened
+    idle_since: Instant,
+    demo: Option<Demo>,
+    renderer: DefaultRenderer,
+}
+
+/// Game played by the bot while the menu is idle
+struct Demo {
+    game: Game2048,
+    next_move: Instant,
+}
+
+impl Menu {
+    /// Menu with all entries, leaving out Continue unless `can_continue` and
+    /// Profile until there are profiles, see [`Menu::with_profiles`].
+    ///
+    /// `stats` are the lines of the stats page.
+    pub fn new(can_continue: bool, stats: Vec<String>) -> Self {
+        let items = MenuItem::ALL
+            .into_iter()
+            .filter(|&item| item != MenuItem::Continue || can_continue)
+            .filter(|&item| item != MenuItem::Profile)
+            .collect();
+        Self {
+            items,
+            // Continuing is the likely choice if there is a game to continue
+            state: ListState::default().with_selected(Some(usize::from(can_continue))),
+            variant: 0,
+            profiles: Vec::new(),
+            profile: 0,
+            stats,
+            page: None,
+            demo_after: None,
+            idle_since: Instant::now(),
+            demo: None,
+            renderer: DefaultRenderer::default(),
+        }
+    }
+
+    /// Play a demo game in the given theme once no key was pressed for
+    /// `idle`
+    pub fn with_demo(mut self, idle: Duration, theme: Theme) -> Self {
+        self.demo_after = Some(idle);
+        self.renderer = DefaultRenderer::new(theme);
+        self
+    }
+
+    /// List the Profile entry to switch between the default profile and
+    /// the named `profiles`, offering the `active` one first. Without named
+    /// profiles the entry stays hidden.
+    pub fn with_profiles(mut self, profiles: Vec<String>, active: Option<&str>) -> Self {
+        if profiles.is_empty() {
+            return self;
+        }
+        self.profile = active
+            .and_then(|active| profiles.iter().position(|name| name == active))
+            .map_or(0, |index| index + 1);
+        self.profiles = profiles;
+        let quit = self.items.len() - 1;
+        self.items.insert(quit, MenuItem::Profile);
+        self
+    }
+
+    /// The profile the Profile entry switches to, `None` for the default one
+    pub fn profile(&self) -> Option<&str> {
+        self.profile
+            .checked_sub(1)
+            .map(|index| self.profiles[index].as_str())
+    }
+
+    /// Whether the demo game is shown
+    pub fn demo_running(&self) -> bool {
+        self.demo.is_some()
+    }
+
+    /// Start the demo once the menu was idle long enough and make its next
+    /// move when it is due; a demo without moves starts over.
+    ///
+    /// Returns how long to wait for a key before the next call is due, `None`
+    /// if nothing happens until a key is pressed.
+    pub fn tick(&mut self) -> Option<Duration> {
+        let after = self.demo_after?;
+        let now = Instant::now();
+        let demo = match &mut self.demo {
+            Some(demo) => demo,
+            // Pages are read, not idled on
+            None if self.page.is_some() => return None,
+            None => {
+                let idle = now.duration_since(self.idle_since);
+                if idle < after {
+                    return Some(after - idle);
+                }
+                self.demo.insert(Demo {
+                    game: Game2048::default(),
+                    next_move: now + DEMO_MOVE_INTERVAL,
+                })
+            }
+        };
+        if now >= demo.next_move {
+            match ai::greedy_move(&demo.game) {
+                Some(direction) => {
+                    demo.game.move_in_direction(&direction);
+                }
+                None => demo.game.reset(),
+            }
+            demo.next_move = now + DEMO_MOVE_INTERVAL;
+        }
+        Some(demo.next_move - now)
+    }
+
+    /// The selected entry
+    pub fn selected(&self) -> MenuItem {
+        self.items[self.state.selected().unwrap_or(0)]
+    }
+
+    /// The variant the Variant entry starts
+    pub fn variant(&self) -> Variant {
+        Variant::ALL[self.variant]
+    }
+
+    /// Text of an entry in the list
+    fn item_text(&self, item: MenuItem) -> String {
+        match item {
+            MenuItem::Variant => format!("{} < {} >", item.label(), self.variant().label()),
+            MenuItem::Profile => format!(
+                "{} < {} >",
+                item.label(),
+                self.profile()
+                    .unwrap_or_else(|| i18n::text("menu.default_profile"))
+            ),
+            _ => item.label().to_string(),
+        }
+    }
+
+    /// Handle a key press, returning the entry the player left the menu with
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<MenuItem> {
+        self.idle_since = Instant::now();
+        // The demo is ended by any key, like pages
+        if self.demo.take().is_some() {
+            return None;
+        }
+        // Pages are closed by any key
+        if self.page.take().is_some() {
+            return None;
+        }
+        let last = self.items.len() - 1;
+        let selected = self.state.selected().unwrap_or(0);
+        match code {
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('w') => {
+                self.state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Char('s') => {
+                self.state.select(Some((selected + 1).min(last)));
+            }
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Char('a')
+                if self.selected() == MenuItem::Variant =>
+            {
+                self.variant = (self.variant + Variant::ALL.len() - 1) % Variant::ALL.len();
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char('d')
+                if self.selected() == MenuItem::Variant =>
+            {
+                self.variant = (self.variant + 1) % Variant::ALL.len();
+            }
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Char('a')
+                if self.selected() == MenuItem::Profile =>
+            {
+                let count = self.profiles.len() + 1;
+                self.profile = (self.profile + count - 1) % count;
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char('d')
+                if self.selected() == MenuItem::Profile =>
+            {
+                self.profile = (self.profile + 1) % (self.profiles.len() + 1);
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let item = self.selected();
+                if item.is_page() {
+                    self.page = Some(item);
+                } else {
+                    return Some(item);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return Some(MenuItem::Quit),
+            _ => {}
+        }
+        None
+    }
+
+    /// Draw the list, or the page opened from it
+    pub fn draw(&mut self, f: &mut Frame) {
+        let area = f.area();
+        if self.page == Some(MenuItem::Stats) {
+            let lines: Vec<Line> = self
+                .stats
+                .iter()
+                .map(|line| Line::from(line.clone()))
+                .collect();
+            f.render_widget(
+                Paragraph::new(lines).block(
+                    Block::default()
+                        .title(i18n::text("menu.stats_title"))
+                        .borders(Borders::ALL),
+                ),
+                area,
+            );
+            return;
+        }
+
+        let block = Block::default()
+            .title(i18n::text("menu.title"))
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let height = self.items.len() as u16;
+        let [_, list_area, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(height),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+        let [_, list_area, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(LIST_WIDTH),
+            Constraint::Fill(1),
+        ])
+        .areas(list_area);
+        let list_area = match &self.demo {
+            Some(demo) => self.draw_demo(f, inner, demo),
+            None => list_area,
+        };
+        let list = List::new(
+            self.items
+                .iter()
+                .map(|&item| ListItem::new(self.item_text(item))),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+
+    /// Draw the demo board left of where the list goes, returning the area
+    /// of the list
+    fn draw_demo(&self, f: &mut Frame, area: Rect, demo: &Demo) -> Rect {
+        let (rows, columns) = demo.game.dims();
+        let board_width = self.renderer.board_width(columns);
+        // A status line above three lines per row
+        let board_height = 1 + 3 * rows as u16;
+        let [_, board_area, _, list_area, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(board_width),
+            Constraint::Length(4),
+            Constraint::Length(LIST_WIDTH),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        let [_, board_area, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(board_height),
+            Constraint::Fill(1),
+        ])
+        .areas(board_area);
+        self.renderer.render(f, board_area, &demo.game);
+        let [_, list_area, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(self.items.len() as u16),
+            Constraint::Fill(1),
+        ])
+        .areas(list_area);
+        list_area
+    }
+}
+
+/// Show the menu until the player picks an entry that leaves it.
+///
+/// Sets up the terminal while the menu is shown and restores it afterwards.
+/// With `ascii`, the menu is drawn with ASCII characters only.
+pub fn show(menu: &mut Menu, ascii: bool) -> GameResult<MenuItem> {
+    let guard = TerminalGuard::enter()?;
+    let mut terminal =
+        Terminal::new(CrosstermBackend::new(io::stdout())).context("opening the terminal")?;
+    terminal.clear().context("clearing the screen")?;
+
+    let choice = loop {
+        let timeout = menu.tick();
+        terminal
+            .draw(|f| {
+                menu.draw(f);
+                if ascii {
+                    to_ascii(f.buffer_mut());
+                }
+            })
+            .context("drawing the screen")?;
+        if let Some(timeout) = timeout
+            && !event::poll(timeout).context("reading input")?
+        {
+            continue;
+        }
+        let Event::Key(key) = event::read().context("reading input")? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            break MenuItem::Quit;
+        }
+        if let Some(item) = menu.handle_key(key.code) {
+            break item;
+        }
+    };
+    guard.restore()?;
+    Ok(choice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn test_menu_navigation_and_pages() {
+        let mut menu = Menu::new(false, vec!["Best score: 2048".to_string()]);
+        assert_eq!(menu.selected(), MenuItem::NewGame);
+        menu.handle_key(KeyCode::Down);
+        assert_eq!(menu.selected(), MenuItem::DailyChallenge);
+        menu.handle_key(KeyCode::Up);
+        menu.handle_key(KeyCode::Up);
+        assert_eq!(menu.handle_key(KeyCode::Enter), Some(MenuItem::NewGame));
+
+        // Stats opens a page, closed by the next key without moving
+        for _ in 0..5 {
+            menu.handle_key(KeyCode::Down);
+        }
+        assert_eq!(menu.selected(), MenuItem::Stats);
+        assert_eq!(menu.handle_key(KeyCode::Enter), None);
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        terminal.draw(|f| menu.draw(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..40).map(|x| buffer[(x, 1)].symbol()).collect();
+        assert!(row.contains("Best score: 2048"));
+        assert_eq!(menu.handle_key(KeyCode::Down), None);
+        assert_eq!(menu.selected(), MenuItem::Stats);
+
+        // Left and right change the variant while it is selected
+        menu.handle_key(KeyCode::Up);
+        assert_eq!(menu.selected(), MenuItem::Variant);
+        assert_eq!(menu.variant(), Variant::Easy);
+        menu.handle_key(KeyCode::Left);
+        assert_eq!(menu.variant(), Variant::Divide);
+        menu.handle_key(KeyCode::Right);
+        menu.handle_key(KeyCode::Right);
+        assert_eq!(menu.variant(), Variant::Hard);
+        terminal.draw(|f| menu.draw(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Variant < 8192 >"));
+        assert_eq!(menu.handle_key(KeyCode::Enter), Some(MenuItem::Variant));
+        menu.handle_key(KeyCode::Down);
+        assert_eq!(menu.handle_key(KeyCode::Char('q')), Some(MenuItem::Quit));
+
+        // With a saved game, Continue is listed and selected
+        let menu = Menu::new(true, vec![]);
+        assert_eq!(menu.selected(), MenuItem::Continue);
+    }
+
+    #[test]
+    fn test_profile_entry() {
+        let menu = Menu::new(false, vec![]).with_profiles(Vec::new(), None);
+        assert!(!menu.items.contains(&MenuItem::Profile));
+
+        let profiles = vec!["alice".to_string(), "bob".to_string()];
+        let mut menu = Menu::new(false, vec![]).with_profiles(profiles, Some("bob"));
+        assert_eq!(menu.profile(), Some("bob"));
+        for _ in 0..menu.items.len() - 2 {
+            menu.handle_key(KeyCode::Down);
+        }
+        assert_eq!(menu.selected(), MenuItem::Profile);
+        menu.handle_key(KeyCode::Right);
+        assert_eq!(menu.profile(), None);
+        assert_eq!(menu.item_text(MenuItem::Profile), "Profile < default >");
+        menu.handle_key(KeyCode::Right);
+        assert_eq!(menu.profile(), Some("alice"));
+        menu.handle_key(KeyCode::Left);
+        menu.handle_key(KeyCode::Left);
+        assert_eq!(menu.profile(), Some("bob"));
+        assert_eq!(menu.handle_key(KeyCode::Enter), Some(MenuItem::Profile));
+    }
+
+    #[test]
+    fn test_demo_starts_when_idle_and_ends_with_any_key() {
+        let mut menu = Menu::new(false, vec![]).with_demo(Duration::ZERO, Theme::default());
+        assert!(!menu.demo_running());
+        assert_eq!(menu.tick(), Some(DEMO_MOVE_INTERVAL));
+        assert!(menu.demo_running());
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|f| menu.draw(f)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Score: 0"));
+        assert!(screen.contains(MenuItem::NewGame.label()));
+
+        // The key only ends the demo, without picking the entry
+        assert_eq!(menu.handle_key(KeyCode::Enter), None);
+        assert!(!menu.demo_running());
+        assert_eq!(menu.handle_key(KeyCode::Enter), Some(MenuItem::NewGame));
+    }
+}
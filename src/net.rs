@@ -0,0 +1,242 @@
+//! Network race mode.
+//!
+//! Two players race on the same seed over a plain TCP connection. The host
+//! picks the seed and sends it when the other player joins; afterwards both
+//! sides send their score and board whenever they change.
+//!
+//! The protocol is line based text:
+//!
+//! ```text
+//! SEED <seed>
+//! STATE <score> <0|1 game over> <cell>,<cell>,...
+//! ```
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::error::{GameError, GameResult};
+
+/// How often the current state is re-sent even if nothing changed
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Last known state of the opponent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteState {
+    pub score: u64,
+    pub game_over: bool,
+    /// Square board, possibly of a different size than ours
+    pub board: Vec<Vec<u32>>,
+}
+
+impl RemoteState {
+    /// Encode as a protocol line (without newline)
+    fn encode(&self) -> String {
+        let cells: Vec<String> = self.board.iter().flatten().map(u32::to_string).collect();
+        format!(
+            "STATE {} {} {}",
+            self.score,
+            u8::from(self.game_over),
+            cells.join(",")
+        )
+    }
+
+    /// Decode a `STATE` protocol line
+    fn decode(line: &str) -> GameResult<Self> {
+        let invalid = || GameError::NetworkError(format!("Invalid message: {}", line));
+        let mut parts = line.split_whitespace();
+        if parts.next() != Some("STATE") {
+            return Err(invalid());
+        }
+        let score = parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(invalid)?;
+        let game_over = match parts.next() {
+            Some("0") => false,
+            Some("1") => true,
+            _ => return Err(invalid()),
+        };
+        let cells: Vec<u32> = parts
+            .next()
+            .ok_or_else(invalid)?
+            .split(',')
+            .map(|c| c.parse().map_err(|_| invalid()))
+            .collect::<GameResult<_>>()?;
+        let size = cells.len().isqrt();
+        if size < 2 || size * size != cells.len() {
+            return Err(invalid());
+        }
+        let board = cells.chunks(size).map(<[u32]>::to_vec).collect();
+        Ok(Self {
+            score,
+            game_over,
+            board,
+        })
+    }
+}
+
+/// Network error saying what failed
+fn network(what: &str, error: io::Error) -> GameError {
+    GameError::NetworkError(format!("{}: {}", what, error))
+}
+
+/// A connection to the opponent
+pub struct NetSession {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    last_sent: Option<(String, Instant)>,
+    connected: bool,
+}
+
+impl NetSession {
+    /// Wait for an opponent to connect and send them the seed
+    pub fn host(addr: impl ToSocketAddrs, seed: u64) -> GameResult<Self> {
+        let listener = TcpListener::bind(addr).map_err(|e| network("Cannot listen", e))?;
+        let (mut stream, _) = listener
+            .accept()
+            .map_err(|e| network("Cannot accept the opponent", e))?;
+        writeln!(stream, "SEED {}", seed).map_err(|e| network("Cannot send the seed", e))?;
+        Self::new(stream)
+    }
+
+    /// Connect to a host and receive the seed of the race
+    pub fn join(addr: impl ToSocketAddrs) -> GameResult<(Self, u64)> {
+        let mut stream = TcpStream::connect(addr).map_err(|e| network("Cannot connect", e))?;
+        // Read byte by byte so no state message after the handshake is lost
+        let mut line = String::new();
+        let mut byte = [0; 1];
+        while stream
+            .read(&mut byte)
+            .map_err(|e| network("Cannot receive the seed", e))?
+            == 1
+            && byte[0] != b'\n'
+        {
+            line.push(byte[0] as char);
+        }
+        let seed = line
+            .trim()
+            .strip_prefix("SEED ")
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| {
+                GameError::NetworkError(format!("Unexpected handshake: {}", line.trim()))
+            })?;
+        Ok((Self::new(stream)?, seed))
+    }
+
+    fn new(stream: TcpStream) -> GameResult<Self> {
+        stream
+            .set_nonblocking(true)
+            .and_then(|()| stream.set_nodelay(true))
+            .map_err(|e| network("Cannot set up the connection", e))?;
+        Ok(Self {
+            stream,
+            buffer: Vec::new(),
+            last_sent: None,
+            connected: true,
+        })
+    }
+
+    /// Whether the opponent is still connected
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Send our state if it changed or the heartbeat interval passed
+    pub fn send_state(&mut self, state: &RemoteState) -> GameResult<()> {
+        if !self.connected {
+            return Ok(());
+        }
+        let line = state.encode();
+        let due = self
+            .last_sent
+            .as_ref()
+            .is_none_or(|(last, at)| *last != line || at.elapsed() >= HEARTBEAT_INTERVAL);
+        if due {
+            if let Err(e) = writeln!(self.stream, "{}", line) {
+                // Dropping a heartbeat on a full socket buffer is harmless
+                if e.kind() != io::ErrorKind::WouldBlock {
+                    self.connected = false;
+                    return Err(network("Cannot send the state", e));
+                }
+            }
+            self.last_sent = Some((line, Instant::now()));
+        }
+        Ok(())
+    }
+
+    /// Read pending messages without blocking, returning the newest state
+    pub fn poll(&mut self) -> GameResult<Option<RemoteState>> {
+        let mut chunk = [0; 1024];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    self.connected = false;
+                    break;
+                }
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    self.connected = false;
+                    return Err(network("Cannot receive the state", e));
+                }
+            }
+        }
+
+        let mut latest = None;
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            latest = Some(RemoteState::decode(line.trim())?);
+        }
+        Ok(latest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_state_round_trip() {
+        let state = RemoteState {
+            score: 1234,
+            game_over: true,
+            board: vec![vec![2, 4, 8], vec![0; 3], vec![2048, 0, 2]],
+        };
+        assert_eq!(RemoteState::decode(&state.encode()).unwrap(), state);
+        assert!(RemoteState::decode("STATE 1 0 2,4").is_err());
+    }
+
+    #[test]
+    fn test_host_and_join_exchange_states() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let host = thread::spawn(move || NetSession::host(addr, 77).unwrap());
+        let (mut client, seed) = loop {
+            if let Ok(joined) = NetSession::join(addr) {
+                break joined;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        let mut host = host.join().unwrap();
+        assert_eq!(seed, 77);
+
+        let state = RemoteState {
+            score: 8,
+            game_over: false,
+            board: vec![vec![4, 4, 0, 0], vec![0; 4], vec![0; 4], vec![0; 4]],
+        };
+        client.send_state(&state).unwrap();
+        let received = loop {
+            if let Some(received) = host.poll().unwrap() {
+                break received;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(received, state);
+    }
+}
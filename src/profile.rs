@@ -0,0 +1,152 @@
+//! Named profiles for players sharing a machine.
+//!
+//! Everything the game keeps about a player (the config, leaderboard,
+//! achievements, saves, ghosts and so on) lives in the directories of the
+//! active profile. Without a profile these are the top-level directories, as
+//! before profiles existed; a named profile gets directories of its own in
+//! `profiles/<name>` inside them. The log file is shared by all profiles.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::error::{GameError, GameResult};
+
+/// Directory of the named profiles inside the top-level directories
+const PROFILES_DIR: &str = "profiles";
+
+/// Longest profile name
+const MAX_NAME_LEN: usize = 32;
+
+/// Name of the active profile, `None` for the default one
+static ACTIVE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Check that a profile name can be used as a directory name: 1 to 32
+/// letters, digits, `-` and `_`
+pub fn validate_name(name: &str) -> GameResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(GameError::InputError(format!(
+            "Invalid profile name {:?}: use up to {} letters, digits, - and _",
+            name, MAX_NAME_LEN
+        )))
+    }
+}
+
+/// Switch to a named profile, or back to the default one with `None`
+pub fn set_active(name: Option<String>) -> GameResult<()> {
+    if let Some(name) = &name {
+        validate_name(name)?;
+    }
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = name;
+    Ok(())
+}
+
+/// Name of the active profile, `None` for the default one
+pub fn active() -> Option<String> {
+    ACTIVE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Directory of a profile inside a top-level directory
+fn within(base: &Path, profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => base.join(PROFILES_DIR).join(name),
+        None => base.to_path_buf(),
+    }
+}
+
+/// Top-level data directory shared by all profiles, if one exists
+fn base_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("tui_2048"))
+}
+
+/// Data directory of the active profile, if a data directory exists
+pub fn data_dir() -> Option<PathBuf> {
+    base_data_dir().map(|dir| within(&dir, active().as_deref()))
+}
+
+/// Config directory of the active profile, if a config directory exists
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| within(&dir.join("tui_2048"), active().as_deref()))
+}
+
+/// Create the data directory of a named profile, so it is listed from now on
+pub fn create(name: &str) -> GameResult<()> {
+    validate_name(name)?;
+    if let Some(dir) = base_data_dir() {
+        fs::create_dir_all(within(&dir, Some(name)))?;
+    }
+    Ok(())
+}
+
+/// Names of the named profiles, sorted
+pub fn list() -> GameResult<Vec<String>> {
+    match base_data_dir() {
+        Some(dir) => list_in(&dir),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Names of the named profiles in a top-level data directory, sorted.
+/// Entries that aren't directories with a valid name are skipped.
+fn list_in(base: &Path) -> GameResult<Vec<String>> {
+    let dir = base.join(PROFILES_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(name) = entry.file_name().to_str()
+            && validate_name(name).is_ok()
+        {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_names() {
+        for name in ["alice", "Bob_2", "grand-pa", "Zoë"] {
+            assert!(validate_name(name).is_ok(), "{name}");
+        }
+        for name in ["", "..", "a/b", "with space", &"x".repeat(33)] {
+            assert!(validate_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_profiles_have_directories_of_their_own() {
+        let base = std::env::temp_dir().join(format!("tui_2048_profiles_{}", std::process::id()));
+        assert_eq!(within(&base, None), base);
+        assert_eq!(
+            within(&base, Some("alice")),
+            base.join("profiles").join("alice")
+        );
+        assert!(list_in(&base).unwrap().is_empty());
+
+        for name in ["bob", "alice"] {
+            fs::create_dir_all(within(&base, Some(name))).unwrap();
+        }
+        fs::write(base.join("profiles").join("notes.txt"), "").unwrap();
+        fs::create_dir_all(base.join("profiles").join("not valid")).unwrap();
+        let names = list_in(&base).unwrap();
+        fs::remove_dir_all(&base).unwrap();
+        assert_eq!(names, ["alice", "bob"]);
+    }
+}
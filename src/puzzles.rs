@@ -0,0 +1,262 @@
+//! Puzzle mode with curated starting positions.
+//!
+//! A puzzle starts from a fixed board and is solved by creating a target tile
+//! within a number of moves. The built-in puzzles are embedded from
+//! `puzzles.toml`; the best solution of each is kept in a file in the data
+//! directory.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::error::{GameError, GameResult};
+use crate::game::{Game2048, GameView, Progress};
+use crate::i18n;
+use crate::profile;
+
+/// Name of the puzzle progress file inside the data directory
+const PUZZLES_FILE: &str = "puzzles.toml";
+
+/// Puzzles shipped with the game
+const BUILTIN_PUZZLES: &str = include_str!("puzzles.toml");
+
+/// A starting position and the tile to reach from it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub name: String,
+    pub board: Vec<Vec<u32>>,
+    /// Tile that solves the puzzle
+    pub target: u32,
+    /// Moves allowed to create the target tile
+    pub max_moves: u32,
+    /// Seed of the tiles spawned after each move
+    pub seed: u64,
+}
+
+/// How a puzzle attempt stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuzzleStatus {
+    Playing,
+    /// The target tile was created within the allowed moves
+    Solved,
+    /// The moves ran out or the board filled up before the target was reached
+    Failed,
+}
+
+/// File layout of a puzzle collection
+#[derive(Deserialize)]
+struct PuzzleFile {
+    puzzles: Vec<Puzzle>,
+}
+
+impl Puzzle {
+    /// Fresh attempt at the puzzle
+    pub fn game(&self) -> GameResult<Game2048> {
+        Game2048::builder()
+            .seed(self.seed)
+            .resume(Progress {
+                board: self.board.clone(),
+                score: 0,
+                moves: 0,
+                undos_used: 0,
+                history: Vec::new(),
+            })
+            .build()
+    }
+
+    /// Where an attempt at the puzzle stands
+    pub fn status(&self, game: &dyn GameView) -> PuzzleStatus {
+        let max_tile = game.board().iter().flatten().copied().max().unwrap_or(0);
+        if max_tile >= self.target && game.moves() <= self.max_moves {
+            PuzzleStatus::Solved
+        } else if game.moves() >= self.max_moves || game.game_over() {
+            PuzzleStatus::Failed
+        } else {
+            PuzzleStatus::Playing
+        }
+    }
+
+    /// Check that the board is valid and the target can be created on it
+    fn validate(&self) -> GameResult<()> {
+        let board = Board::from_rows(self.board.clone())?;
+        if self.target < 4 || !self.target.is_power_of_two() {
+            return Err(GameError::InputError(format!(
+                "Puzzle {:?}: target must be a power of two of at least 4, got {}",
+                self.name, self.target
+            )));
+        }
+        if board.max_tile() >= self.target || self.max_moves == 0 {
+            return Err(GameError::InputError(format!(
+                "Puzzle {:?} is solved before the first move",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Puzzle {
+    /// The goal, e.g. "Reach 256 in 12 moves"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let key = if self.max_moves == 1 {
+            "puzzles.goal_one"
+        } else {
+            "puzzles.goal"
+        };
+        let goal = i18n::format(key, &[("target", &self.target), ("moves", &self.max_moves)]);
+        f.write_str(&goal)
+    }
+}
+
+/// Parse and check a collection of puzzles in the format of `puzzles.toml`
+pub fn parse(contents: &str) -> GameResult<Vec<Puzzle>> {
+    let file: PuzzleFile = toml::from_str(contents)
+        .map_err(|e| GameError::InputError(format!("Invalid puzzles: {}", e.message())))?;
+    for puzzle in &file.puzzles {
+        puzzle.validate()?;
+    }
+    Ok(file.puzzles)
+}
+
+/// The puzzles shipped with the game
+pub fn builtin() -> Vec<Puzzle> {
+    parse(BUILTIN_PUZZLES).expect("built-in puzzles are valid")
+}
+
+/// Fewest moves each puzzle was solved in, by puzzle name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PuzzleProgress {
+    solved: BTreeMap<String, u32>,
+}
+
+impl PuzzleProgress {
+    /// Default location of the progress file, if a data directory exists
+    pub fn default_path() -> Option<PathBuf> {
+        profile::data_dir().map(|dir| dir.join(PUZZLES_FILE))
+    }
+
+    /// Load the progress from a file, starting empty if it is missing
+    pub fn load(path: &Path) -> GameResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            GameError::SaveError(format!(
+                "Corrupt puzzle progress {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })
+    }
+
+    /// Write the progress to a file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(|e| {
+            GameError::SerializationError(format!("Cannot encode puzzle progress: {}", e))
+        })?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Fewest moves the puzzle was solved in, if it was solved
+    pub fn best(&self, puzzle: &Puzzle) -> Option<u32> {
+        self.solved.get(&puzzle.name).copied()
+    }
+
+    /// Number of puzzles solved at least once
+    pub fn solved_count(&self) -> usize {
+        self.solved.len()
+    }
+
+    /// Record a solution, returning whether it beat the previous best
+    pub fn record(&mut self, puzzle: &Puzzle, moves: u32) -> bool {
+        match self.solved.get(&puzzle.name) {
+            Some(&best) if best <= moves => false,
+            _ => {
+                self.solved.insert(puzzle.name.clone(), moves);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEngine, MovementDirection};
+
+    const DIRECTIONS: [MovementDirection; 4] = [
+        MovementDirection::Up,
+        MovementDirection::Down,
+        MovementDirection::Left,
+        MovementDirection::Right,
+    ];
+
+    /// Whether the puzzle can still be solved from this attempt
+    fn solvable(puzzle: &Puzzle, game: &Game2048) -> bool {
+        match puzzle.status(game) {
+            PuzzleStatus::Solved => true,
+            PuzzleStatus::Failed => false,
+            PuzzleStatus::Playing => DIRECTIONS.iter().any(|direction| {
+                let mut next = game.clone();
+                next.move_in_direction(direction) && solvable(puzzle, &next)
+            }),
+        }
+    }
+
+    #[test]
+    fn test_builtin_puzzles_are_solvable() {
+        let puzzles = builtin();
+        assert!(!puzzles.is_empty());
+        for puzzle in &puzzles {
+            let game = puzzle.game().unwrap();
+            assert_eq!(puzzle.status(&game), PuzzleStatus::Playing);
+            assert!(solvable(puzzle, &game), "{} cannot be solved", puzzle.name);
+        }
+    }
+
+    #[test]
+    fn test_puzzle_status_and_progress() {
+        let puzzles = parse(
+            "[[puzzles]]\nname = \"Pair\"\nboard = [[2, 2], [0, 0]]\n\
+             target = 4\nmax_moves = 1\nseed = 1\n",
+        )
+        .unwrap();
+        let puzzle = &puzzles[0];
+        assert_eq!(puzzle.to_string(), "Reach 4 in 1 move");
+
+        let mut game = puzzle.game().unwrap();
+        game.move_in_direction(&MovementDirection::Down);
+        assert_eq!(puzzle.status(&game), PuzzleStatus::Failed);
+        game.undo();
+        game.move_in_direction(&MovementDirection::Left);
+        assert_eq!(puzzle.status(&game), PuzzleStatus::Solved);
+
+        let mut progress = PuzzleProgress::default();
+        assert!(progress.record(puzzle, 3));
+        assert!(progress.record(puzzle, 1));
+        assert!(!progress.record(puzzle, 2));
+        assert_eq!(progress.best(puzzle), Some(1));
+
+        let path =
+            std::env::temp_dir().join(format!("tui_2048_puzzles_{}.toml", std::process::id()));
+        progress.save(&path).unwrap();
+        assert_eq!(PuzzleProgress::load(&path).unwrap(), progress);
+        fs::remove_file(path).unwrap();
+
+        // Already solved and impossible targets are rejected
+        let solved = "[[puzzles]]\nname = \"Done\"\nboard = [[4, 0], [0, 0]]\n\
+                      target = 4\nmax_moves = 1\nseed = 1\n";
+        assert!(parse(solved).is_err());
+        assert!(parse(&solved.replace("target = 4", "target = 12")).is_err());
+    }
+}
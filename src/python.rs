@@ -0,0 +1,336 @@
+//! Python bindings for the game engine.
+//!
+//! Enabled with the `python` feature. The module `tui_2048` exposes the game,
+//! the built-in move policies and batch simulation, so strategies can be
+//! analyzed from Python while the rules stay in this crate:
+//!
+//! ```python
+//! import tui_2048
+//!
+//! game = tui_2048.Game(seed=42)
+//! game.move("left")
+//! results = tui_2048.simulate("greedy", games=100, seed=0)
+//! ```
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::ai::{self, DIRECTIONS};
+use crate::error::GameError;
+use crate::game::{Game2048, GameEngine, GameView, MovementDirection};
+
+impl From<GameError> for PyErr {
+    fn from(err: GameError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// Parse a direction name as used by the config file ("up", "left", ...)
+fn parse_direction(name: &str) -> PyResult<MovementDirection> {
+    match name {
+        "up" => Ok(MovementDirection::Up),
+        "down" => Ok(MovementDirection::Down),
+        "left" => Ok(MovementDirection::Left),
+        "right" => Ok(MovementDirection::Right),
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown direction '{}', expected up, down, left or right",
+            name
+        ))),
+    }
+}
+
+/// Name of a direction as accepted by [`parse_direction`]
+fn direction_name(direction: MovementDirection) -> &'static str {
+    match direction {
+        MovementDirection::Up => "up",
+        MovementDirection::Down => "down",
+        MovementDirection::Left => "left",
+        MovementDirection::Right => "right",
+    }
+}
+
+fn max_tile(game: &Game2048) -> u32 {
+    game.tiles().max_tile()
+}
+
+/// A game of 2048
+#[pyclass(name = "Game")]
+#[derive(Clone)]
+struct PyGame {
+    game: Game2048,
+}
+
+#[pymethods]
+impl PyGame {
+    /// Create a game; `undo_limit=None` keeps every move
+    #[new]
+    #[pyo3(signature = (seed=None, size=4, undo_limit=Some(10)))]
+    fn new(seed: Option<u64>, size: usize, undo_limit: Option<usize>) -> PyResult<Self> {
+        let mut builder = Game2048::builder().size(size);
+        if let Some(seed) = seed {
+            builder = builder.seed(seed);
+        }
+        builder = match undo_limit {
+            Some(limit) => builder.undo_limit(limit),
+            None => builder.unlimited_undo(),
+        };
+        Ok(Self {
+            game: builder.build()?,
+        })
+    }
+
+    /// Move the tiles, returning whether the board changed
+    #[pyo3(name = "move")]
+    fn move_in_direction(&mut self, direction: &str) -> PyResult<bool> {
+        Ok(self.game.move_in_direction(&parse_direction(direction)?))
+    }
+
+    /// Undo the last move, returning whether there was one to undo
+    fn undo(&mut self) -> bool {
+        self.game.undo()
+    }
+
+    /// Directions that change the board
+    fn legal_moves(&self) -> Vec<&'static str> {
+        DIRECTIONS
+            .iter()
+            .filter(|direction| self.game.clone().move_in_direction(direction))
+            .map(|&direction| direction_name(direction))
+            .collect()
+    }
+
+    /// Independent copy of the game, including its random state
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    /// Rows of the board, 0 for empty cells
+    #[getter]
+    fn board(&self) -> Vec<Vec<u32>> {
+        self.game.board().to_vec()
+    }
+
+    #[getter]
+    fn score(&self) -> u64 {
+        self.game.score()
+    }
+
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.game.seed()
+    }
+
+    #[getter]
+    fn max_tile(&self) -> u32 {
+        max_tile(&self.game)
+    }
+
+    #[getter]
+    fn game_over(&self) -> bool {
+        self.game.game_over()
+    }
+
+    #[getter]
+    fn has_won(&self) -> bool {
+        self.game.has_won()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Game(score={}, max_tile={}, game_over={})",
+            self.game.score(),
+            max_tile(&self.game),
+            if self.game.game_over() {
+                "True"
+            } else {
+                "False"
+            }
+        )
+    }
+}
+
+/// Move picked by the greedy bot, or None if no move is possible
+#[pyfunction]
+fn greedy_move(game: &PyGame) -> Option<&'static str> {
+    ai::greedy_move(&game.game).map(direction_name)
+}
+
+/// Policies implemented in Rust, which run without holding the GIL
+#[derive(Clone, Copy)]
+enum Policy {
+    Greedy,
+    Random,
+}
+
+impl Policy {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "greedy" => Ok(Policy::Greedy),
+            "random" => Ok(Policy::Random),
+            _ => Err(PyValueError::new_err(format!(
+                "Unknown policy '{}', expected greedy or random",
+                name
+            ))),
+        }
+    }
+
+    fn pick(self, game: &Game2048, rng: &mut StdRng) -> Option<MovementDirection> {
+        match self {
+            Policy::Greedy => ai::greedy_move(game),
+            Policy::Random => DIRECTIONS
+                .iter()
+                .copied()
+                .filter(|direction| game.clone().move_in_direction(direction))
+                .choose(rng),
+        }
+    }
+}
+
+/// Outcome of one simulated game
+struct Outcome {
+    seed: u64,
+    moves: usize,
+    score: u64,
+    max_tile: u32,
+}
+
+/// Play a game until `pick` returns no move or the move limit is reached
+fn play(
+    game: &mut Game2048,
+    max_moves: Option<usize>,
+    mut pick: impl FnMut(&Game2048) -> PyResult<Option<MovementDirection>>,
+) -> PyResult<Outcome> {
+    let mut moves = 0;
+    while max_moves.is_none_or(|max| moves < max) {
+        let Some(direction) = pick(game)? else {
+            break;
+        };
+        if !game.move_in_direction(&direction) {
+            break;
+        }
+        moves += 1;
+    }
+    Ok(Outcome {
+        seed: game.seed(),
+        moves,
+        score: game.score(),
+        max_tile: max_tile(game),
+    })
+}
+
+/// Play `games` games with a policy and return one dict per game.
+///
+/// `policy` is "greedy", "random" or a callable that gets a `Game` and
+/// returns a direction name, or None to stop. Game `i` uses seed `seed + i`.
+/// A game ends when it is over, the policy stops or picks a move that does
+/// not change the board, or after `max_moves` moves.
+#[pyfunction]
+#[pyo3(signature = (policy, games, seed=0, size=4, max_moves=None))]
+fn simulate<'py>(
+    py: Python<'py>,
+    policy: &Bound<'py, PyAny>,
+    games: usize,
+    seed: u64,
+    size: usize,
+    max_moves: Option<usize>,
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let new_game = |index: u64| {
+        Game2048::builder()
+            .size(size)
+            .seed(seed.wrapping_add(index))
+            .build()
+    };
+
+    let outcomes = if let Ok(name) = policy.extract::<&str>() {
+        let policy = Policy::parse(name)?;
+        py.allow_threads(|| {
+            (0..games as u64)
+                .map(|index| {
+                    let mut game = new_game(index)?;
+                    let mut rng = StdRng::seed_from_u64(game.seed());
+                    play(&mut game, max_moves, |game| Ok(policy.pick(game, &mut rng)))
+                })
+                .collect::<PyResult<Vec<_>>>()
+        })?
+    } else {
+        (0..games as u64)
+            .map(|index| {
+                let mut game = new_game(index)?;
+                play(&mut game, max_moves, |game| {
+                    if game.game_over() {
+                        return Ok(None);
+                    }
+                    let choice = policy.call1((PyGame { game: game.clone() },))?;
+                    choice
+                        .extract::<Option<String>>()?
+                        .map(|name| parse_direction(&name))
+                        .transpose()
+                })
+            })
+            .collect::<PyResult<Vec<_>>>()?
+    };
+
+    outcomes
+        .into_iter()
+        .map(|outcome| {
+            let dict = PyDict::new(py);
+            dict.set_item("seed", outcome.seed)?;
+            dict.set_item("moves", outcome.moves)?;
+            dict.set_item("score", outcome.score)?;
+            dict.set_item("max_tile", outcome.max_tile)?;
+            Ok(dict)
+        })
+        .collect()
+}
+
+/// Python module `tui_2048`
+#[pymodule]
+fn tui_2048(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyGame>()?;
+    module.add_function(wrap_pyfunction!(greedy_move, module)?)?;
+    module.add_function(wrap_pyfunction!(simulate, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulate_from_python() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "tui_2048").unwrap();
+            tui_2048(&module).unwrap();
+
+            let game = module.getattr("Game").unwrap().call1((Some(3),)).unwrap();
+            assert!(game.call_method0("legal_moves").unwrap().len().unwrap() > 0);
+            assert!(game.call_method1("move", ("sideways",)).is_err());
+
+            let simulate = module.getattr("simulate").unwrap();
+            let greedy: Vec<Bound<PyDict>> =
+                simulate.call1(("greedy", 3)).unwrap().extract().unwrap();
+            assert_eq!(greedy.len(), 3);
+            let again: Vec<Bound<PyDict>> =
+                simulate.call1(("greedy", 3)).unwrap().extract().unwrap();
+            let score = |dict: &Bound<PyDict>| -> u64 {
+                dict.get_item("score").unwrap().unwrap().extract().unwrap()
+            };
+            assert_eq!(score(&greedy[2]), score(&again[2]));
+
+            // A Python policy stops its game when it returns None
+            let stop = py.eval(c"lambda game: None", None, None).unwrap();
+            let results: Vec<Bound<PyDict>> = simulate.call1((stop, 1)).unwrap().extract().unwrap();
+            let moves: usize = results[0]
+                .get_item("moves")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(moves, 0);
+        });
+    }
+}
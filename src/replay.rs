@@ -0,0 +1,397 @@
+//! Game recordings and ghost replays.
+//!
+//! A game is fully determined by its seed and the sequence of moves and
+//! undos, so a [`Replay`] only needs those. Replays are used to race
+//! against a "ghost" of the best previous game on the same seed.
+//!
+//! Recordings also keep an audit log of every tile the random number
+//! generator spawned. [`verify_replay`] re-simulates the game from the seed
+//! and checks every spawn and the final score, so a leaderboard can tell a
+//! legitimately generated game from one with made-up tiles.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GameError, GameResult};
+use crate::game::{Game2048, GameEngine, GameView, MovementDirection};
+use crate::profile;
+
+/// A single recorded player action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayStep {
+    Up,
+    Down,
+    Left,
+    Right,
+    Undo,
+}
+
+impl From<MovementDirection> for ReplayStep {
+    fn from(direction: MovementDirection) -> Self {
+        match direction {
+            MovementDirection::Up => ReplayStep::Up,
+            MovementDirection::Down => ReplayStep::Down,
+            MovementDirection::Left => ReplayStep::Left,
+            MovementDirection::Right => ReplayStep::Right,
+        }
+    }
+}
+
+impl ReplayStep {
+    /// Apply this step to a game
+    pub fn apply(self, game: &mut impl GameEngine) -> bool {
+        match self {
+            ReplayStep::Up => game.move_in_direction(&MovementDirection::Up),
+            ReplayStep::Down => game.move_in_direction(&MovementDirection::Down),
+            ReplayStep::Left => game.move_in_direction(&MovementDirection::Left),
+            ReplayStep::Right => game.move_in_direction(&MovementDirection::Right),
+            ReplayStep::Undo => game.undo(),
+        }
+    }
+}
+
+/// Why a replay no longer reproduces the recorded game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The step at this index did not change the board
+    StepRejected(usize),
+    /// The replayed game ended with a different score
+    Score { recorded: u64, replayed: u64 },
+    /// The tile logged after this many steps is not the one the seed spawns
+    Spawn { step: usize },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::StepRejected(index) => write!(f, "step {} has no effect", index + 1),
+            Mismatch::Score { recorded, replayed } => {
+                write!(f, "recorded score {} but replayed {}", recorded, replayed)
+            }
+            Mismatch::Spawn { step: 0 } => write!(f, "the starting tiles differ"),
+            Mismatch::Spawn { step } => {
+                write!(f, "the tile spawned after step {} differs", step)
+            }
+        }
+    }
+}
+
+/// A tile spawned by the random number generator, as logged in a replay
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpawnRecord {
+    /// Steps taken before the tile spawned, `0` for the starting tiles
+    pub step: usize,
+    pub row: usize,
+    pub column: usize,
+    pub tile: u32,
+}
+
+/// Recording of a game: its seed, every action that changed the board and
+/// the tiles spawned along the way
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub steps: Vec<ReplayStep>,
+    pub score: u64,
+    /// Audit log of the spawns; empty in recordings made before it existed,
+    /// which are verified by their score alone
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spawns: Vec<SpawnRecord>,
+}
+
+impl Replay {
+    /// Start an empty recording for a seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            steps: Vec::new(),
+            score: 0,
+            spawns: Vec::new(),
+        }
+    }
+
+    /// Clear the recording and log the tiles a new game starts with
+    pub fn begin(&mut self, game: &impl GameView) {
+        self.steps.clear();
+        self.score = 0;
+        self.spawns.clear();
+        for (row, tiles) in game.board().iter().enumerate() {
+            for (column, &tile) in tiles.iter().enumerate() {
+                if tile != 0 {
+                    self.spawns.push(SpawnRecord {
+                        step: 0,
+                        row,
+                        column,
+                        tile,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Record a step that changed the board of `game`, and the tile it
+    /// spawned
+    pub fn record(&mut self, step: ReplayStep, game: &impl GameView) {
+        self.steps.push(step);
+        if step == ReplayStep::Undo {
+            return;
+        }
+        if let Some((row, column, tile)) = game.last_spawn() {
+            self.spawns.push(SpawnRecord {
+                step: self.steps.len(),
+                row,
+                column,
+                tile,
+            });
+        }
+    }
+
+    /// Directory where ghost replays are kept, if a data directory exists
+    pub fn ghost_dir() -> Option<PathBuf> {
+        profile::data_dir().map(|dir| dir.join("ghosts"))
+    }
+
+    /// File name of the ghost replay for a seed inside the ghost directory
+    pub fn ghost_file(seed: u64) -> String {
+        format!("{:016x}.toml", seed)
+    }
+
+    /// Re-simulate the game and check that it spawns the logged tiles and
+    /// still ends with the recorded score
+    pub fn verify(&self) -> Result<(), Mismatch> {
+        let mut game = Game2048::with_seed(self.seed);
+        let mut replayed = Replay::new(self.seed);
+        replayed.begin(&game);
+        for (index, &step) in self.steps.iter().enumerate() {
+            if !step.apply(&mut game) {
+                return Err(Mismatch::StepRejected(index));
+            }
+            replayed.record(step, &game);
+        }
+        if !self.spawns.is_empty() && self.spawns != replayed.spawns {
+            // The first spawn where the logs part, or the first one missing
+            // from the shorter log
+            let agreed = self
+                .spawns
+                .iter()
+                .zip(&replayed.spawns)
+                .take_while(|(recorded, replayed)| recorded == replayed)
+                .count();
+            let step = [self.spawns.get(agreed), replayed.spawns.get(agreed)]
+                .into_iter()
+                .flatten()
+                .map(|spawn| spawn.step)
+                .min()
+                .unwrap_or(0);
+            return Err(Mismatch::Spawn { step });
+        }
+        if game.score() != self.score {
+            return Err(Mismatch::Score {
+                recorded: self.score,
+                replayed: game.score(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Boards of the replayed game from the start, one after every move;
+    /// an undo drops the board of the move it took back. Stops at a step
+    /// that no longer applies.
+    pub fn boards(&self) -> Vec<Vec<Vec<u32>>> {
+        let mut game = Game2048::with_seed(self.seed);
+        let mut boards = vec![game.board().to_vec()];
+        for step in &self.steps {
+            if !step.apply(&mut game) {
+                break;
+            }
+            match step {
+                ReplayStep::Undo => {
+                    boards.pop();
+                }
+                _ => boards.push(game.board().to_vec()),
+            }
+        }
+        boards
+    }
+
+    /// Load a replay from a file, returning `None` if it does not exist
+    pub fn load(path: &Path) -> GameResult<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map(Some).map_err(|e| {
+            GameError::SaveError(format!(
+                "Corrupt replay {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })
+    }
+
+    /// Write the replay to a file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self)
+            .map_err(|e| GameError::SerializationError(format!("Cannot encode replay: {}", e)))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+/// Whether a recorded game was legitimately generated: re-simulated from its
+/// seed, every step applies, every logged tile is the one the seed spawns and
+/// the game ends with the recorded score
+pub fn verify_replay(replay: &Replay) -> bool {
+    replay.verify().is_ok()
+}
+
+/// A replayed game that advances in step with the player
+pub struct Ghost {
+    replay: Replay,
+    game: Game2048,
+    position: usize,
+}
+
+impl Ghost {
+    /// Start replaying from the beginning
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            game: Game2048::with_seed(replay.seed),
+            replay,
+            position: 0,
+        }
+    }
+
+    /// The ghost's game at its current position
+    pub fn game(&self) -> &Game2048 {
+        &self.game
+    }
+
+    /// Final score of the recorded game
+    pub fn final_score(&self) -> u64 {
+        self.replay.score
+    }
+
+    /// Whether every recorded step has been replayed
+    pub fn finished(&self) -> bool {
+        self.position >= self.replay.steps.len()
+    }
+
+    /// Replay steps until the ghost has made `moves` moves (or ran out)
+    pub fn advance_to(&mut self, moves: usize) {
+        while self.position < moves && !self.finished() {
+            self.replay.steps[self.position].apply(&mut self.game);
+            self.position += 1;
+        }
+    }
+
+    /// Rewind to the start of the recording
+    pub fn restart(&mut self) {
+        self.game = Game2048::with_seed(self.replay.seed);
+        self.position = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ghost_reproduces_recorded_game() {
+        let mut game = Game2048::with_seed(99);
+        let mut replay = Replay::new(99);
+        for step in [
+            ReplayStep::Left,
+            ReplayStep::Up,
+            ReplayStep::Undo,
+            ReplayStep::Right,
+            ReplayStep::Down,
+        ] {
+            if step.apply(&mut game) {
+                replay.steps.push(step);
+            }
+        }
+        replay.score = game.score();
+
+        assert_eq!(replay.verify(), Ok(()));
+        let mut ghost = Ghost::new(replay.clone());
+        ghost.advance_to(replay.steps.len());
+        assert!(ghost.finished());
+        assert_eq!(ghost.game().board(), game.board());
+        assert_eq!(ghost.game().score(), game.score());
+
+        // The undone move leaves no board behind
+        let boards = replay.boards();
+        assert_eq!(boards.len(), 1 + replay.steps.len() - 2);
+        assert_eq!(boards[0], Game2048::with_seed(99).board());
+        assert_eq!(boards.last().unwrap(), &game.board());
+    }
+
+    #[test]
+    fn test_spawn_log_is_verified() {
+        let mut game = Game2048::with_seed(42);
+        let mut replay = Replay::new(42);
+        replay.begin(&game);
+        assert_eq!(replay.spawns.len(), 2);
+        for step in [
+            ReplayStep::Left,
+            ReplayStep::Undo,
+            ReplayStep::Up,
+            ReplayStep::Right,
+        ] {
+            if step.apply(&mut game) {
+                replay.record(step, &game);
+            }
+        }
+        replay.score = game.score();
+        // Two starting tiles and one for each move; the undo spawns nothing
+        let moves = replay
+            .steps
+            .iter()
+            .filter(|&&step| step != ReplayStep::Undo)
+            .count();
+        assert_eq!(replay.spawns.len(), 2 + moves);
+        assert!(verify_replay(&replay));
+
+        // A tile the seed didn't spawn is caught even if the score matches
+        let mut forged = replay.clone();
+        let last = forged.spawns.last_mut().unwrap();
+        last.tile = if last.tile == 2 { 4 } else { 2 };
+        let step = last.step;
+        assert_eq!(forged.verify(), Err(Mismatch::Spawn { step }));
+        assert!(!verify_replay(&forged));
+        let mut forged = replay.clone();
+        forged.spawns[0].row = (forged.spawns[0].row + 1) % 4;
+        assert_eq!(forged.verify(), Err(Mismatch::Spawn { step: 0 }));
+        let mut truncated = replay.clone();
+        truncated.spawns.pop();
+        assert_eq!(truncated.verify(), Err(Mismatch::Spawn { step }));
+
+        // Recordings without a log are checked by their score alone
+        let mut unlogged = replay.clone();
+        unlogged.spawns.clear();
+        assert!(verify_replay(&unlogged));
+        let toml = toml::to_string(&unlogged).unwrap();
+        assert!(!toml.contains("spawns"));
+        let logged: Replay = toml::from_str(&toml::to_string(&replay).unwrap()).unwrap();
+        assert_eq!(logged, replay);
+    }
+
+    #[test]
+    fn test_verify_detects_mismatches() {
+        let mut replay = Replay::new(5);
+        replay.score = 1;
+        assert!(matches!(replay.verify(), Err(Mismatch::Score { .. })));
+
+        // Two undos in a row: the second one has nothing left to undo
+        replay.steps = vec![ReplayStep::Undo];
+        assert_eq!(replay.verify(), Err(Mismatch::StepRejected(0)));
+    }
+}
@@ -0,0 +1,178 @@
+//! Picker for unfinished games, shown at startup.
+//!
+//! Every saved game and save slot is listed with a thumbnail of its board,
+//! its rules and when it was created, so the player can continue any of them,
+//! delete the ones no longer wanted or start the game selected on the command
+//! line instead.
+
+use std::io;
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::{
+    Frame, Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::error::{GameResult, TerminalContext};
+use crate::i18n;
+use crate::saves::SavedGame;
+use crate::terminal::TerminalGuard;
+use crate::ui::ascii::to_ascii;
+use crate::ui::mini_board;
+
+/// What the player picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    /// Continue the saved game at this index
+    Resume(usize),
+    /// Start the game selected on the command line
+    NewGame,
+    /// Leave without playing
+    Quit,
+}
+
+/// Let the player pick one of the saved games.
+///
+/// Sets up the terminal while the picker is shown and restores it afterwards.
+/// With `recovered`, the first game is the autosave of a session that didn't
+/// end normally and is marked as such. With `ascii`, the picker is drawn with
+/// ASCII characters only.
+///
+/// Games the player deletes are passed to `delete`, along with whether it is
+/// the recovered one, and removed from `games` unless that fails.
+pub fn pick(
+    games: &mut Vec<SavedGame>,
+    recovered: &mut bool,
+    ascii: bool,
+    mut delete: impl FnMut(&SavedGame, bool) -> GameResult<()>,
+) -> GameResult<Choice> {
+    let guard = TerminalGuard::enter()?;
+    let mut terminal =
+        Terminal::new(CrosstermBackend::new(io::stdout())).context("opening the terminal")?;
+    terminal.clear().context("clearing the screen")?;
+
+    // The entry after the last game starts a new game
+    let mut cursor = 0;
+    let choice = loop {
+        terminal
+            .draw(|f| {
+                draw(f, games, cursor, *recovered);
+                if ascii {
+                    to_ascii(f.buffer_mut());
+                }
+            })
+            .context("drawing the screen")?;
+        let Event::Key(key) = event::read().context("reading input")? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Choice::Quit;
+            }
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('w') => {
+                cursor = cursor.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Char('s') => {
+                cursor = (cursor + 1).min(games.len());
+            }
+            KeyCode::Enter if cursor < games.len() => break Choice::Resume(cursor),
+            KeyCode::Delete | KeyCode::Char('d') if cursor < games.len() => {
+                let is_recovered = *recovered && cursor == 0;
+                match delete(&games[cursor], is_recovered) {
+                    Ok(()) => {
+                        games.remove(cursor);
+                        *recovered &= !is_recovered;
+                    }
+                    Err(error) => log::warn!("Cannot delete the saved game: {}", error),
+                }
+            }
+            KeyCode::Enter | KeyCode::Char('n') => break Choice::NewGame,
+            KeyCode::Esc | KeyCode::Char('q') => break Choice::Quit,
+            _ => {}
+        }
+    };
+    guard.restore()?;
+    Ok(choice)
+}
+
+/// Draw the saved games as cards, scrolled so the selected one is visible
+fn draw(f: &mut Frame, games: &[SavedGame], cursor: usize, recovered: bool) {
+    let block = Block::default()
+        .title(i18n::text("resume.title"))
+        .borders(Borders::ALL);
+    let area = block.inner(f.area());
+    f.render_widget(block, f.area());
+
+    let heights: Vec<u16> = games
+        .iter()
+        .map(|game| game.board.len() as u16 + 2)
+        .chain([3])
+        .collect();
+    // Skip cards from the top until the selected one fits
+    let mut first = 0;
+    while first < cursor && heights[first..=cursor].iter().sum::<u16>() > area.height {
+        first += 1;
+    }
+
+    let constraints = heights[first..]
+        .iter()
+        .map(|&height| Constraint::Length(height));
+    let areas = Layout::vertical(constraints).split(area);
+    for (index, &card) in (first..).zip(areas.iter()) {
+        let style = if index == cursor {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        match games.get(index) {
+            Some(game) => {
+                let mode = game.mode.to_string();
+                let title = match &game.slot {
+                    _ if recovered && index == 0 => {
+                        i18n::format("resume.recovered", &[("mode", &mode)])
+                    }
+                    Some(name) => i18n::format("resume.slot", &[("name", name), ("mode", &mode)]),
+                    None => mode,
+                };
+                draw_card(f, card, game, title, style)
+            }
+            None => f.render_widget(
+                Paragraph::new(i18n::text("resume.new_game"))
+                    .block(Block::default().borders(Borders::ALL).border_style(style)),
+                card,
+            ),
+        }
+    }
+}
+
+/// One saved game: its board on the left, rules and progress on the right
+fn draw_card(f: &mut Frame, area: Rect, game: &SavedGame, title: String, style: Style) {
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(style);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let thumbnail_width = game.board.len() as u16 * 5 + 2;
+    let [thumbnail, details] =
+        Layout::horizontal([Constraint::Length(thumbnail_width), Constraint::Min(0)]).areas(inner);
+    f.render_widget(Paragraph::new(mini_board(&game.board)), thumbnail);
+    let lines = vec![
+        Line::from(i18n::format("resume.score", &[("score", &game.score)])),
+        Line::from(i18n::format("resume.moves", &[("moves", &game.moves)])),
+        Line::from(i18n::format(
+            "resume.created",
+            &[("date", &game.created_on())],
+        )),
+        Line::from(i18n::format("resume.saved", &[("date", &game.saved_on())])),
+    ];
+    f.render_widget(Paragraph::new(lines), details);
+}
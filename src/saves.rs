@@ -0,0 +1,450 @@
+//! Unfinished games, kept so they can be resumed.
+//!
+//! A game that is quit before it is over is saved to the data directory, one
+//! file per mode, so an unfinished daily challenge and an unfinished 5x5 game
+//! can both be resumed later. Finishing a game removes the save of its mode.
+//!
+//! Games can also be saved to named slots while playing, kept in the `slots`
+//! subdirectory. Saving to a slot again replaces the game in it, but keeps the
+//! time the slot was created.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::date::Date;
+use crate::error::{GameError, GameResult};
+use crate::game::{Game2048, GameBuilder, GameView, MergeRule, Progress, UndoPolicy, WIN_TILE};
+use crate::profile;
+
+/// Longest name of a save slot
+pub const MAX_SLOT_NAME_LEN: usize = 24;
+
+/// Subdirectory of the save slots
+const SLOTS_DIR: &str = "slots";
+
+/// Rules a game was started with, which a resumed game keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mode {
+    /// Number of rows, and of columns unless they are given
+    pub size: usize,
+    /// Number of columns of a rectangular board, `None` for a square one
+    #[serde(default)]
+    pub columns: Option<usize>,
+    /// Number of moves that can be undone, `None` for unlimited
+    pub undo_limit: Option<usize>,
+    pub max_undos: Option<u32>,
+    pub undo_penalty: u32,
+    pub adaptive: bool,
+    /// Whether stuck boards are cleared instead of ending the game
+    #[serde(default)]
+    pub zen: bool,
+    /// Whether merging moves in a row multiply the points
+    #[serde(default)]
+    pub arcade: bool,
+    /// Tile that wins the game, or that spawns in divide games
+    #[serde(default = "default_win_tile")]
+    pub win_tile: u32,
+    /// Whether equal tiles halve instead of doubling
+    #[serde(default)]
+    pub divide: bool,
+    /// Date of the daily challenge, if it is one
+    pub daily: Option<Date>,
+}
+
+impl Mode {
+    /// Rules of an existing game
+    pub fn of(game: &Game2048, daily: Option<Date>) -> Self {
+        let policy = game.undo_policy();
+        let (rows, columns) = game.dims();
+        Self {
+            size: rows,
+            columns: (columns != rows).then_some(columns),
+            undo_limit: game.undo_limit(),
+            max_undos: policy.max_undos,
+            undo_penalty: policy.penalty,
+            adaptive: game.is_adaptive(),
+            zen: game.is_zen(),
+            arcade: game.combo().is_some(),
+            win_tile: game.target_tile(),
+            divide: game.merge_rule() == MergeRule::Halve,
+            daily,
+        }
+    }
+
+    /// Number of rows and of columns of the board
+    pub fn dims(&self) -> (usize, usize) {
+        (self.size, self.columns.unwrap_or(self.size))
+    }
+
+    /// Builder for a game with these rules
+    pub fn builder(&self) -> GameBuilder {
+        let (rows, columns) = self.dims();
+        let mut builder = Game2048::builder().dims(rows, columns);
+        builder = match self.undo_limit {
+            Some(limit) => builder.undo_limit(limit),
+            None => builder.unlimited_undo(),
+        };
+        builder = builder.undo_policy(UndoPolicy {
+            max_undos: self.max_undos,
+            penalty: self.undo_penalty,
+        });
+        if self.adaptive {
+            builder = builder.adaptive_difficulty();
+        }
+        if self.zen {
+            builder = builder.zen();
+        }
+        if self.arcade {
+            builder = builder.arcade();
+        }
+        if self.divide {
+            builder = builder.merge_rule(MergeRule::Halve);
+        }
+        builder.win_tile(self.win_tile)
+    }
+
+    /// File name of the save for this mode
+    fn file_name(&self) -> String {
+        let mut name = match self.daily {
+            Some(date) => format!("daily-{}", date),
+            None => format!("{}x{}", self.size, self.dims().1),
+        };
+        match self.undo_limit {
+            Some(limit) => name.push_str(&format!("-undo{}", limit)),
+            None => name.push_str("-undo-unlimited"),
+        }
+        if let Some(max) = self.max_undos {
+            name.push_str(&format!("-max{}", max));
+        }
+        if self.undo_penalty > 0 {
+            name.push_str(&format!("-penalty{}", self.undo_penalty));
+        }
+        if self.adaptive {
+            name.push_str("-adaptive");
+        }
+        if self.zen {
+            name.push_str("-zen");
+        }
+        if self.arcade {
+            name.push_str("-arcade");
+        }
+        if self.win_tile != WIN_TILE {
+            name.push_str(&format!("-target{}", self.win_tile));
+        }
+        if self.divide {
+            name.push_str("-divide");
+        }
+        name + ".toml"
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(date) = self.daily {
+            write!(f, "Daily {}, ", date)?;
+        }
+        write!(f, "{}x{}", self.size, self.dims().1)?;
+        if let Some(max) = self.max_undos {
+            write!(f, ", {} undos", max)?;
+        } else if self.undo_limit == Some(0) {
+            write!(f, ", no undo")?;
+        }
+        if self.undo_penalty > 0 {
+            write!(f, ", undo costs {}", self.undo_penalty)?;
+        }
+        if self.adaptive {
+            write!(f, ", adaptive")?;
+        }
+        if self.zen {
+            write!(f, ", zen")?;
+        }
+        if self.arcade {
+            write!(f, ", arcade")?;
+        }
+        if self.divide {
+            write!(f, ", divide from {}", self.win_tile)?;
+        } else if self.win_tile != WIN_TILE {
+            write!(f, ", target {}", self.win_tile)?;
+        }
+        Ok(())
+    }
+}
+
+/// Target of saves from before it could be chosen
+fn default_win_tile() -> u32 {
+    WIN_TILE
+}
+
+/// An unfinished game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub mode: Mode,
+    pub board: Vec<Vec<u32>>,
+    pub score: u64,
+    pub moves: u32,
+    pub undos_used: u32,
+    /// Boards and scores before the last moves, which can still be undone
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<(Vec<Vec<u32>>, u64)>,
+    /// Seconds since the Unix epoch when the game was saved
+    pub saved_at: u64,
+    /// Seconds since the Unix epoch when the game was first saved, 0 for
+    /// saves from before this was recorded
+    #[serde(default)]
+    pub created_at: u64,
+    /// Name of the save slot, `None` for the save of the mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<String>,
+}
+
+impl SavedGame {
+    /// Capture a game with the rules it was started with
+    pub fn new(game: &Game2048, daily: Option<Date>) -> Self {
+        let progress = game.progress();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self {
+            mode: Mode::of(game, daily),
+            board: progress.board,
+            score: progress.score,
+            moves: progress.moves,
+            undos_used: progress.undos_used,
+            history: progress.history,
+            saved_at: now,
+            created_at: now,
+            slot: None,
+        }
+    }
+
+    /// The same game saved in the slot `name`
+    pub fn in_slot(mut self, name: &str) -> Self {
+        self.slot = Some(name.to_string());
+        self
+    }
+
+    /// Day the game was saved
+    pub fn saved_on(&self) -> Date {
+        Date::from_days_since_epoch((self.saved_at / 86_400) as i64)
+    }
+
+    /// Day the game was first saved, or the last time if that isn't known
+    pub fn created_on(&self) -> Date {
+        let created_at = if self.created_at > 0 {
+            self.created_at
+        } else {
+            self.saved_at
+        };
+        Date::from_days_since_epoch((created_at / 86_400) as i64)
+    }
+
+    /// Continue the game with its undo history and new random tile spawns
+    pub fn resume(&self) -> GameResult<Game2048> {
+        self.mode
+            .builder()
+            .resume(Progress {
+                board: self.board.clone(),
+                score: self.score,
+                moves: self.moves,
+                undos_used: self.undos_used,
+                history: self.history.clone(),
+            })
+            .build()
+    }
+}
+
+/// Directory with one saved game per mode and the save slots
+#[derive(Debug, Clone)]
+pub struct Saves {
+    dir: PathBuf,
+}
+
+impl Saves {
+    /// Use saves in a directory, which is created when the first game is saved
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Default directory of the saves, if a data directory exists
+    pub fn default_dir() -> Option<PathBuf> {
+        profile::data_dir().map(|dir| dir.join("saves"))
+    }
+
+    /// Saved games and slots, the most recent first. Corrupt saves are
+    /// skipped.
+    pub fn list(&self) -> GameResult<Vec<SavedGame>> {
+        let mut games = Vec::new();
+        for dir in [self.dir.clone(), self.dir.join(SLOTS_DIR)] {
+            if !dir.exists() {
+                continue;
+            }
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "toml")
+                    && let Ok(game) = Self::load(&path)
+                {
+                    games.push(game);
+                }
+            }
+        }
+        games.sort_by_key(|game| std::cmp::Reverse(game.saved_at));
+        Ok(games)
+    }
+
+    /// File a game is saved in: its slot, or else the save of its mode
+    fn path(&self, game: &SavedGame) -> PathBuf {
+        match &game.slot {
+            Some(name) => self.dir.join(SLOTS_DIR).join(slot_file_name(name)),
+            None => self.dir.join(game.mode.file_name()),
+        }
+    }
+
+    fn load(path: &Path) -> GameResult<SavedGame> {
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            GameError::SaveError(format!(
+                "Corrupt saved game {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })
+    }
+
+    /// Save a game, replacing the earlier save of the same mode, or the game
+    /// in the same slot for games in a slot
+    pub fn save(&self, game: &SavedGame) -> GameResult<()> {
+        let path = self.path(game);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut game = game.clone();
+        if game.slot.is_some()
+            && let Ok(earlier) = Self::load(&path)
+            && earlier.created_at > 0
+        {
+            game.created_at = earlier.created_at;
+        }
+        let contents = toml::to_string(&game).map_err(|e| {
+            GameError::SerializationError(format!("Cannot encode saved game: {}", e))
+        })?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Delete a saved game or slot
+    pub fn delete(&self, game: &SavedGame) -> GameResult<()> {
+        let path = self.path(game);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the save of a mode, if there is one
+    pub fn remove(&self, mode: &Mode) -> GameResult<()> {
+        let path = self.dir.join(mode.file_name());
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// File name of a slot, with the characters that can't be used in file
+/// names replaced
+fn slot_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("slot-{}.toml", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEngine, MovementDirection};
+
+    #[test]
+    fn test_one_save_per_mode() {
+        let dir = std::env::temp_dir().join(format!("tui_2048_saves_{}", std::process::id()));
+        let saves = Saves::new(dir.clone());
+        assert!(saves.list().unwrap().is_empty());
+
+        let mut classic = Game2048::with_seed(1);
+        let moved = [MovementDirection::Left, MovementDirection::Up]
+            .iter()
+            .any(|direction| classic.move_in_direction(direction));
+        assert!(moved);
+        let daily = Date::from_days_since_epoch(20_000);
+        saves.save(&SavedGame::new(&classic, None)).unwrap();
+        saves.save(&SavedGame::new(&classic, None)).unwrap();
+        saves
+            .save(&SavedGame::new(&Game2048::with_seed(2), Some(daily)))
+            .unwrap();
+
+        let games = saves.list().unwrap();
+        assert_eq!(games.len(), 2);
+        let saved = games.iter().find(|game| game.mode.daily.is_none()).unwrap();
+        assert_eq!(saved.mode.to_string(), "4x4");
+        let resumed = saved.resume().unwrap();
+        assert_eq!(resumed.board(), classic.board());
+        assert_eq!(resumed.moves(), 1);
+
+        saves.remove(&saved.mode).unwrap();
+        assert_eq!(saves.list().unwrap()[0].mode.daily, Some(daily));
+        fs::remove_dir_all(dir).unwrap();
+
+        // Rectangular boards keep their columns
+        let wide = Game2048::builder().dims(3, 5).seed(4).build().unwrap();
+        let mode = Mode::of(&wide, None);
+        assert_eq!(mode.to_string(), "3x5");
+        assert_eq!(mode.builder().build().unwrap().dims(), (3, 5));
+    }
+
+    #[test]
+    fn test_named_slots() {
+        let dir = std::env::temp_dir().join(format!("tui_2048_slots_{}", std::process::id()));
+        let saves = Saves::new(dir.clone());
+        let game = Game2048::with_seed(3);
+        let mut first = SavedGame::new(&game, None).in_slot("before the 2048/merge");
+        first.created_at = 86_400;
+        saves.save(&first).unwrap();
+        saves
+            .save(&SavedGame::new(&game, None).in_slot("other"))
+            .unwrap();
+        saves.save(&SavedGame::new(&game, None)).unwrap();
+        assert_eq!(saves.list().unwrap().len(), 3);
+
+        // Saving to a slot again keeps the date it was created
+        let resaved = SavedGame::new(&game, None).in_slot("before the 2048/merge");
+        saves.save(&resaved).unwrap();
+        let games = saves.list().unwrap();
+        assert_eq!(games.len(), 3);
+        let slot = games.iter().find(|game| game.slot == resaved.slot).unwrap();
+        assert_eq!(slot.created_on(), Date::from_days_since_epoch(1));
+        assert_eq!(slot.saved_at, resaved.saved_at);
+
+        saves.delete(slot).unwrap();
+        let names: Vec<_> = saves
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|game| game.slot)
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&Some("other".to_string())));
+        assert!(names.contains(&None));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
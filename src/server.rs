@@ -0,0 +1,289 @@
+//! HTTP API for the engine.
+//!
+//! `tui_2048 serve` runs the game engine behind a small JSON API, so
+//! frontends in any language can play without reimplementing the rules:
+//!
+//! ```text
+//! POST /game            {"size": 4, "seed": 1}   -> the new game
+//! POST /game/{id}/move  {"direction": "left"}    -> the game after the move
+//! GET  /game/{id}                                -> the game
+//! ```
+//!
+//! Every field of the body of `POST /game` is optional, as is the body.
+//! Games are kept in memory; when more than [`MAX_GAMES`] exist, the oldest
+//! one is dropped. Errors are answered with a status code and
+//! `{"error": "<message>"}`.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GameError, GameResult};
+use crate::game::{Game2048, GameEngine, GameView, MovementDirection};
+
+/// Games kept at most; creating another drops the oldest
+pub const MAX_GAMES: usize = 1000;
+
+/// Games being played, by id
+#[derive(Default)]
+struct Games {
+    next_id: u64,
+    games: BTreeMap<u64, Game2048>,
+}
+
+type SharedGames = Arc<Mutex<Games>>;
+
+/// Settings of a new game, all optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewGame {
+    pub size: Option<usize>,
+    pub seed: Option<u64>,
+    pub win_tile: Option<u32>,
+    pub four_chance: Option<f64>,
+}
+
+/// Body of a move request
+#[derive(Debug, Deserialize)]
+pub struct MoveRequest {
+    /// `up`, `down`, `left` or `right`
+    pub direction: String,
+}
+
+/// A game as the API returns it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameState {
+    pub id: u64,
+    pub board: Vec<Vec<u32>>,
+    pub score: u64,
+    pub moves: u32,
+    pub max_tile: u32,
+    pub won: bool,
+    pub game_over: bool,
+}
+
+impl GameState {
+    fn new(id: u64, game: &Game2048) -> Self {
+        Self {
+            id,
+            board: game.board().to_vec(),
+            score: game.score(),
+            moves: game.moves(),
+            max_tile: game.tiles().max_tile(),
+            won: game.has_won(),
+            game_over: game.game_over(),
+        }
+    }
+}
+
+/// Answer to a move: the game afterwards and what the move did
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MoveResponse {
+    #[serde(flatten)]
+    pub game: GameState,
+    /// Whether any tile moved; a move that changes nothing isn't counted
+    pub moved: bool,
+    /// Points scored by the move
+    pub points: u64,
+}
+
+/// An error answered with a status code and a JSON message
+#[derive(Debug)]
+pub struct ApiError(StatusCode, String);
+
+impl From<GameError> for ApiError {
+    fn from(err: GameError) -> Self {
+        ApiError(StatusCode::BAD_REQUEST, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Body {
+            error: String,
+        }
+        (self.0, Json(Body { error: self.1 })).into_response()
+    }
+}
+
+/// Parse a direction name ("up", "left", ...)
+fn parse_direction(name: &str) -> Result<MovementDirection, ApiError> {
+    match name {
+        "up" => Ok(MovementDirection::Up),
+        "down" => Ok(MovementDirection::Down),
+        "left" => Ok(MovementDirection::Left),
+        "right" => Ok(MovementDirection::Right),
+        _ => Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown direction '{}', expected up, down, left or right",
+                name
+            ),
+        )),
+    }
+}
+
+fn not_found(id: u64) -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, format!("No game with id {}", id))
+}
+
+fn lock(games: &SharedGames) -> std::sync::MutexGuard<'_, Games> {
+    // A panic while holding the lock leaves the games as they were between
+    // two requests, so they are still usable
+    games
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+async fn create_game(
+    State(games): State<SharedGames>,
+    body: Option<Json<NewGame>>,
+) -> Result<(StatusCode, Json<GameState>), ApiError> {
+    let settings = body.map(|Json(settings)| settings).unwrap_or_default();
+    let mut builder = Game2048::builder();
+    if let Some(size) = settings.size {
+        builder = builder.size(size);
+    }
+    if let Some(seed) = settings.seed {
+        builder = builder.seed(seed);
+    }
+    if let Some(win_tile) = settings.win_tile {
+        builder = builder.win_tile(win_tile);
+    }
+    if let Some(four_chance) = settings.four_chance {
+        builder = builder.four_chance(four_chance);
+    }
+    let game = builder.build()?;
+
+    let mut games = lock(&games);
+    games.next_id += 1;
+    let id = games.next_id;
+    let state = GameState::new(id, &game);
+    games.games.insert(id, game);
+    while games.games.len() > MAX_GAMES {
+        games.games.pop_first();
+    }
+    Ok((StatusCode::CREATED, Json(state)))
+}
+
+async fn get_game(
+    State(games): State<SharedGames>,
+    Path(id): Path<u64>,
+) -> Result<Json<GameState>, ApiError> {
+    let games = lock(&games);
+    let game = games.games.get(&id).ok_or_else(|| not_found(id))?;
+    Ok(Json(GameState::new(id, game)))
+}
+
+async fn make_move(
+    State(games): State<SharedGames>,
+    Path(id): Path<u64>,
+    Json(request): Json<MoveRequest>,
+) -> Result<Json<MoveResponse>, ApiError> {
+    let direction = parse_direction(&request.direction)?;
+    let mut games = lock(&games);
+    let game = games.games.get_mut(&id).ok_or_else(|| not_found(id))?;
+    let score = game.score();
+    let moved = game.move_in_direction(&direction);
+    Ok(Json(MoveResponse {
+        game: GameState::new(id, game),
+        moved,
+        points: game.score() - score,
+    }))
+}
+
+/// Routes of the API, with an empty set of games
+pub fn router() -> Router {
+    Router::new()
+        .route("/game", post(create_game))
+        .route("/game/{id}", get(get_game))
+        .route("/game/{id}/move", post(make_move))
+        .with_state(SharedGames::default())
+}
+
+/// Serve the API on an address such as `127.0.0.1:2048` until the process
+/// ends
+pub fn serve(addr: &str) -> GameResult<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(listener, router()).await?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_game(seed: u64) -> Option<Json<NewGame>> {
+        Some(Json(NewGame {
+            seed: Some(seed),
+            ..NewGame::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_create_move_and_get() {
+        let games = SharedGames::default();
+        let (status, Json(created)) = create_game(State(games.clone()), new_game(1))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.id, 1);
+        assert_eq!(created.board, Game2048::with_seed(1).board());
+
+        let mut expected = Game2048::with_seed(1);
+        let moved = expected.move_in_direction(&MovementDirection::Left);
+        let request = MoveRequest {
+            direction: "left".to_string(),
+        };
+        let Json(response) = make_move(State(games.clone()), Path(1), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(response.moved, moved);
+        assert_eq!(response.game.board, expected.board());
+
+        let Json(state) = get_game(State(games), Path(1)).await.unwrap();
+        assert_eq!(state, response.game);
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let games = SharedGames::default();
+        let Err(ApiError(status, _)) = get_game(State(games.clone()), Path(7)).await else {
+            panic!("found a game that wasn't created");
+        };
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let settings = Some(Json(NewGame {
+            size: Some(1),
+            ..NewGame::default()
+        }));
+        let Err(ApiError(status, message)) = create_game(State(games.clone()), settings).await
+        else {
+            panic!("created a 1x1 game");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains("at least 2"), "{message}");
+
+        let (status, _) = create_game(State(games.clone()), None).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        let request = MoveRequest {
+            direction: "sideways".to_string(),
+        };
+        let Err(ApiError(status, _)) = make_move(State(games), Path(1), Json(request)).await else {
+            panic!("made an unknown move");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
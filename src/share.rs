@@ -0,0 +1,230 @@
+//! Share codes for positions.
+//!
+//! A [`ShareCode`] packs a board, its score and move count, and the seed of
+//! the tile spawns into a short string of URL-safe base64, so a position can
+//! be pasted into a chat and played on with `tui_2048 --import <code>`. Two
+//! players importing the same code get the same tiles afterwards.
+//!
+//! The bytes are a version, the number of rows, the seed, score and moves in
+//! big endian, the exponent of every tile row by row (0 for empty cells) and
+//! a checksum catching most typos. The number of columns follows from the
+//! number of tiles. Scores that fit in 4 bytes are stored in 4 bytes as in
+//! version 1, keeping most codes short and readable by older versions.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+use crate::error::{GameError, GameResult};
+use crate::game::{Game2048, GameBuilder, GameView, Progress};
+
+/// Version of the encoding, the first byte of every code
+const VERSION: u8 = 2;
+
+/// Version of the codes storing the score in 4 bytes
+const VERSION_U32_SCORE: u8 = 1;
+
+/// Bytes before the tiles of a version: version, rows, seed, score and moves
+fn header_len(version: u8) -> usize {
+    let score_len = if version == VERSION_U32_SCORE { 4 } else { 8 };
+    2 + 8 + score_len + 4
+}
+
+/// A position and the seed to continue it with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareCode {
+    pub board: Vec<Vec<u32>>,
+    pub score: u64,
+    pub moves: u32,
+    pub seed: u64,
+}
+
+impl ShareCode {
+    /// Share code of the current position of a game, if its tile spawns
+    /// are seeded
+    pub fn of(game: &impl GameView) -> Option<Self> {
+        Some(Self {
+            board: game.board().to_vec(),
+            score: game.score(),
+            moves: game.moves(),
+            seed: game.spawn_seed()?,
+        })
+    }
+
+    /// Encode the position as a base64 string
+    pub fn encode(&self) -> String {
+        let short_score = u32::try_from(self.score).ok();
+        let version = if short_score.is_some() {
+            VERSION_U32_SCORE
+        } else {
+            VERSION
+        };
+        let mut bytes = vec![version, self.board.len() as u8];
+        bytes.extend(self.seed.to_be_bytes());
+        match short_score {
+            Some(score) => bytes.extend(score.to_be_bytes()),
+            None => bytes.extend(self.score.to_be_bytes()),
+        }
+        bytes.extend(self.moves.to_be_bytes());
+        bytes.extend(
+            self.board
+                .iter()
+                .flatten()
+                .map(|&tile| if tile == 0 { 0 } else { tile.ilog2() as u8 }),
+        );
+        bytes.push(checksum(&bytes));
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decode a string made by [`ShareCode::encode`]
+    pub fn decode(code: &str) -> GameResult<Self> {
+        let invalid =
+            |reason: &str| GameError::InputError(format!("Invalid share code: {}", reason));
+        let bytes = URL_SAFE_NO_PAD
+            .decode(code.trim())
+            .map_err(|_| invalid("not base64"))?;
+        let (&sum, bytes) = bytes.split_last().ok_or_else(|| invalid("empty"))?;
+        if checksum(bytes) != sum {
+            return Err(invalid(
+                "the checksum doesn't match, was it copied completely?",
+            ));
+        }
+        let version = *bytes.first().ok_or_else(|| invalid("empty"))?;
+        if version != VERSION && version != VERSION_U32_SCORE {
+            return Err(invalid("made by another version"));
+        }
+        let header_len = header_len(version);
+        if bytes.len() < header_len {
+            return Err(invalid("made by another version"));
+        }
+        let rows = usize::from(bytes[1]);
+        let tiles = &bytes[header_len..];
+        if rows == 0 || tiles.is_empty() || tiles.len() % rows != 0 {
+            return Err(invalid("the board is cut off"));
+        }
+        if tiles.iter().any(|&exponent| exponent >= 32) {
+            return Err(invalid("a tile is too large"));
+        }
+        let number = |range: std::ops::Range<usize>| -> u64 {
+            bytes[range]
+                .iter()
+                .fold(0, |number, &byte| number << 8 | u64::from(byte))
+        };
+        Ok(Self {
+            board: tiles
+                .chunks(tiles.len() / rows)
+                .map(|row| {
+                    row.iter()
+                        .map(|&exponent| if exponent == 0 { 0 } else { 1 << exponent })
+                        .collect()
+                })
+                .collect(),
+            seed: number(2..10),
+            score: number(10..header_len - 4),
+            moves: number(header_len - 4..header_len) as u32,
+        })
+    }
+
+    /// Game continuing the position with the seed of the code and the other
+    /// settings of a builder
+    pub fn game(&self, builder: GameBuilder) -> GameResult<Game2048> {
+        builder
+            .seed(self.seed)
+            .resume(Progress {
+                board: self.board.clone(),
+                score: self.score,
+                moves: self.moves,
+                undos_used: 0,
+                history: Vec::new(),
+            })
+            .build()
+    }
+}
+
+/// Checksum of the bytes of a code
+fn checksum(bytes: &[u8]) -> u8 {
+    // Weighting by position also catches swapped bytes
+    bytes.iter().enumerate().fold(0u8, |sum, (i, &byte)| {
+        sum.wrapping_add(byte.wrapping_mul(i as u8 | 1))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEngine, MovementDirection};
+
+    #[test]
+    fn test_round_trip() {
+        let mut game = Game2048::with_seed(77);
+        for direction in [MovementDirection::Left, MovementDirection::Up] {
+            game.move_in_direction(&direction);
+        }
+        let code = ShareCode::of(&game).unwrap();
+        let encoded = code.encode();
+        assert!(encoded.len() < 50, "{encoded}");
+        assert_eq!(ShareCode::decode(&encoded).unwrap(), code);
+
+        let large = ShareCode {
+            board: vec![vec![1 << 17, 0, 2], vec![0; 3], vec![4, 8, 1 << 31]],
+            score: u64::from(u32::MAX) + 1,
+            moves: 12345,
+            seed: u64::MAX - 1,
+        };
+        assert_eq!(ShareCode::decode(&large.encode()).unwrap(), large);
+
+        let wide = ShareCode {
+            board: vec![vec![2, 0, 0, 4, 0, 0], vec![0; 6]],
+            score: 0,
+            moves: 0,
+            seed: 3,
+        };
+        let decoded = ShareCode::decode(&wide.encode()).unwrap();
+        assert_eq!(decoded, wide);
+        let game = decoded.game(Game2048::builder()).unwrap();
+        assert_eq!(game.dims(), (2, 6));
+    }
+
+    #[test]
+    fn test_scores_past_u32_take_eight_bytes() {
+        let version = |code: &ShareCode| URL_SAFE_NO_PAD.decode(code.encode()).unwrap()[0];
+        let mut code = ShareCode {
+            board: vec![vec![2, 0], vec![0, 4]],
+            score: u64::from(u32::MAX),
+            moves: 5,
+            seed: 9,
+        };
+        assert_eq!(version(&code), VERSION_U32_SCORE);
+        assert_eq!(ShareCode::decode(&code.encode()).unwrap(), code);
+        code.score += 1;
+        assert_eq!(version(&code), VERSION);
+        assert_eq!(ShareCode::decode(&code.encode()).unwrap(), code);
+    }
+
+    #[test]
+    fn test_imported_games_continue_alike() {
+        let mut game = Game2048::with_seed(5);
+        game.move_in_direction(&MovementDirection::Right);
+        let code = ShareCode::decode(&ShareCode::of(&game).unwrap().encode()).unwrap();
+
+        let mut first = code.game(Game2048::builder()).unwrap();
+        let mut second = code.game(Game2048::builder()).unwrap();
+        assert_eq!(first.board(), game.board());
+        assert_eq!((first.score(), first.moves()), (game.score(), game.moves()));
+        first.move_in_direction(&MovementDirection::Left);
+        second.move_in_direction(&MovementDirection::Left);
+        assert_eq!(first.board(), second.board());
+    }
+
+    #[test]
+    fn test_rejects_damaged_codes() {
+        let code = ShareCode::of(&Game2048::with_seed(1)).unwrap().encode();
+        assert!(ShareCode::decode("not a code!").is_err());
+        assert!(ShareCode::decode("").is_err());
+        assert!(ShareCode::decode(&code[..code.len() - 4]).is_err());
+
+        // A typo is noticed
+        let mut typo = code.clone().into_bytes();
+        typo[10] = if typo[10] == b'A' { b'B' } else { b'A' };
+        assert!(ShareCode::decode(&String::from_utf8(typo).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! Spectator mode.
+//!
+//! A running game can publish its state on a local TCP port so other
+//! terminals can watch it live. Every message is one JSON encoded
+//! [`GameEvent`] per line, which makes the stream easy to consume from
+//! scripts or a small web bridge as well.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GameError, GameResult};
+use crate::replay::ReplayStep;
+
+/// How often the publisher thread checks for new spectators while idle
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An event published to spectators
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// The full current state of the game
+    State {
+        score: u64,
+        game_over: bool,
+        board: Vec<Vec<u32>>,
+    },
+    /// The player made a move or undid one
+    Move { step: ReplayStep, points: u64 },
+}
+
+impl GameEvent {
+    /// Encode as a protocol line (without newline)
+    pub fn encode(&self) -> GameResult<String> {
+        serde_json::to_string(self)
+            .map_err(|e| GameError::SerializationError(format!("Cannot encode event: {}", e)))
+    }
+
+    /// Decode a protocol line
+    pub fn decode(line: &str) -> GameResult<Self> {
+        serde_json::from_str(line)
+            .map_err(|e| GameError::NetworkError(format!("Invalid event: {}", e)))
+    }
+}
+
+/// Publishes game events to every connected spectator.
+///
+/// Connections are accepted and written to on background threads, so
+/// publishing never blocks the game. Dropping the broadcaster disconnects
+/// all spectators.
+pub struct Broadcaster {
+    sender: Sender<GameEvent>,
+    addr: SocketAddr,
+}
+
+impl Broadcaster {
+    /// Listen for spectators on an address
+    pub fn bind(addr: impl ToSocketAddrs) -> GameResult<Self> {
+        let serve =
+            |e: io::Error| GameError::NetworkError(format!("Cannot serve spectators: {}", e));
+        let listener = TcpListener::bind(addr).map_err(serve)?;
+        let addr = listener.local_addr().map_err(serve)?;
+        let (sender, events) = mpsc::channel::<GameEvent>();
+        let (spectator_sender, new_spectators) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if spectator_sender.send(stream).is_err() {
+                    break;
+                }
+            }
+        });
+
+        thread::spawn(move || {
+            let mut spectators: Vec<TcpStream> = vec![];
+            // New spectators get the latest state right away
+            let mut last_state: Option<String> = None;
+            loop {
+                for mut stream in new_spectators.try_iter() {
+                    let greeted = last_state
+                        .as_ref()
+                        .is_none_or(|line| writeln!(stream, "{}", line).is_ok());
+                    if greeted {
+                        spectators.push(stream);
+                    }
+                }
+
+                let event = match events.recv_timeout(ACCEPT_INTERVAL) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                let Ok(line) = event.encode() else {
+                    continue;
+                };
+                if matches!(event, GameEvent::State { .. }) {
+                    if last_state.as_ref() == Some(&line) {
+                        continue;
+                    }
+                    last_state = Some(line.clone());
+                }
+                spectators.retain_mut(|stream| writeln!(stream, "{}", line).is_ok());
+            }
+        });
+
+        Ok(Self { sender, addr })
+    }
+
+    /// Address spectators connect to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Send an event to all spectators. Unchanged states are not resent.
+    pub fn publish(&self, event: GameEvent) {
+        // The publisher thread only stops when the broadcaster is dropped
+        let _ = self.sender.send(event);
+    }
+}
+
+/// Connect to a broadcasting game and call `on_event` for every event until
+/// the game ends the connection
+pub fn watch(addr: impl ToSocketAddrs, mut on_event: impl FnMut(GameEvent)) -> GameResult<()> {
+    let network = |e: io::Error| GameError::NetworkError(format!("Cannot watch the game: {}", e));
+    let stream = TcpStream::connect(addr).map_err(network)?;
+    for line in BufReader::new(stream).lines() {
+        on_event(GameEvent::decode(&line.map_err(network)?)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_round_trip() {
+        let event = GameEvent::Move {
+            step: ReplayStep::Left,
+            points: 8,
+        };
+        let line = event.encode().unwrap();
+        assert_eq!(line, r#"{"type":"move","step":"left","points":8}"#);
+        assert_eq!(GameEvent::decode(&line).unwrap(), event);
+    }
+
+    #[test]
+    fn test_spectator_receives_latest_state() {
+        let broadcaster = Broadcaster::bind("127.0.0.1:0").unwrap();
+        let state = GameEvent::State {
+            score: 4,
+            game_over: false,
+            board: vec![vec![4, 0, 0, 0], vec![0; 4], vec![0; 4], vec![0, 0, 0, 2]],
+        };
+        broadcaster.publish(state.clone());
+        let addr = broadcaster.local_addr();
+        let mut events = vec![];
+        let spectator = thread::spawn(move || {
+            let _ = watch(addr, |event| events.push(event));
+            events
+        });
+        // Wait until the publisher has greeted the spectator
+        thread::sleep(ACCEPT_INTERVAL * 3);
+        drop(broadcaster);
+
+        assert_eq!(spectator.join().unwrap(), vec![state]);
+    }
+}
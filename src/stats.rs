@@ -0,0 +1,304 @@
+//! Statistics collected while a game is played, where tiles spawned and
+//! merged during the session, and the play time kept across games in a file
+//! in the data directory.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GameError, GameResult};
+use crate::game::GameView;
+use crate::profile;
+
+/// Name of the play time file inside the data directory
+const PLAY_TIME_FILE: &str = "play_time.toml";
+
+/// Number of scores kept in a [`ScoreHistory`] by default
+pub const SCORE_HISTORY_SIZE: usize = 512;
+
+/// Score after each move, keeping only the most recent moves
+#[derive(Debug, Clone)]
+pub struct ScoreHistory {
+    scores: VecDeque<u64>,
+    capacity: usize,
+    /// Number of the move whose score is the oldest one kept, starting at 1
+    first_move: usize,
+}
+
+impl Default for ScoreHistory {
+    fn default() -> Self {
+        Self::with_capacity(SCORE_HISTORY_SIZE)
+    }
+}
+
+impl ScoreHistory {
+    /// History keeping the scores of the last `capacity` moves (at least 1)
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            scores: VecDeque::with_capacity(capacity),
+            capacity,
+            first_move: 1,
+        }
+    }
+
+    /// Record the score after a move, dropping the oldest one if full
+    pub fn push(&mut self, score: u64) {
+        if self.scores.len() == self.capacity {
+            self.scores.pop_front();
+            self.first_move += 1;
+        }
+        self.scores.push_back(score);
+    }
+
+    /// Forget the score of the last move, after it was undone
+    pub fn pop(&mut self) {
+        self.scores.pop_back();
+    }
+
+    /// Forget all scores, for a new game
+    pub fn clear(&mut self) {
+        self.scores.clear();
+        self.first_move = 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// The scores kept, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = u64> + ExactSizeIterator + '_ {
+        self.scores.iter().copied()
+    }
+
+    /// The last `count` scores, oldest first
+    pub fn recent(&self, count: usize) -> impl Iterator<Item = u64> + '_ {
+        self.iter().skip(self.len().saturating_sub(count))
+    }
+
+    /// (move number, score) pairs for plotting
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        (self.first_move..)
+            .zip(self.iter())
+            .map(|(number, score)| (number as f64, score as f64))
+            .collect()
+    }
+}
+
+/// What a [`Heatmap`] shows the counts of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeatmapKind {
+    /// Tiles spawned on a cell
+    Spawns,
+    /// Tiles created by merges on a cell
+    Merges,
+}
+
+/// How often tiles spawned and merged on every cell, over the games of a
+/// session on boards of the same size
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Heatmap {
+    spawns: Vec<Vec<u32>>,
+    merges: Vec<Vec<u32>>,
+}
+
+impl Heatmap {
+    /// Count the tile spawned and the merges of the last move of a game.
+    /// The counts start over when the board has a different size than the
+    /// boards counted so far.
+    pub fn record(&mut self, game: &dyn GameView) {
+        let (rows, columns) = game.dims();
+        if (self.spawns.len(), self.spawns.first().map_or(0, Vec::len)) != (rows, columns) {
+            self.spawns = vec![vec![0; columns]; rows];
+            self.merges = vec![vec![0; columns]; rows];
+        }
+        if let Some((i, j, _)) = game.last_spawn() {
+            self.spawns[i][j] += 1;
+        }
+        for &(i, j) in game.merged_cells() {
+            self.merges[i][j] += 1;
+        }
+    }
+
+    /// Count of every cell, row by row
+    pub fn counts(&self, kind: HeatmapKind) -> &[Vec<u32>] {
+        match kind {
+            HeatmapKind::Spawns => &self.spawns,
+            HeatmapKind::Merges => &self.merges,
+        }
+    }
+
+    /// Count of every cell relative to the highest one, from 0 to 1; all 0
+    /// while nothing was counted
+    pub fn levels(&self, kind: HeatmapKind) -> Vec<Vec<f64>> {
+        let counts = self.counts(kind);
+        let max = counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+        counts
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&count| f64::from(count) / f64::from(max))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Time spent playing, over all games
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayTime {
+    /// Seconds played in total
+    pub total_secs: u64,
+    /// Seconds of the longest stretch played without a break
+    pub longest_session_secs: u64,
+}
+
+impl PlayTime {
+    /// Default location of the play time file, if a data directory exists
+    pub fn default_path() -> Option<PathBuf> {
+        profile::data_dir().map(|dir| dir.join(PLAY_TIME_FILE))
+    }
+
+    /// Load the play time from a file, starting at zero if it is missing
+    pub fn load(path: &Path) -> GameResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            GameError::SaveError(format!(
+                "Corrupt play time {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })
+    }
+
+    /// Write the play time to a file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(|e| {
+            GameError::SerializationError(format!("Cannot encode play time: {}", e))
+        })?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Add time played during a session that has lasted `session` so far
+    pub fn record(&mut self, played: Duration, session: Duration) {
+        self.total_secs += played.as_secs();
+        self.longest_session_secs = self.longest_session_secs.max(session.as_secs());
+    }
+}
+
+/// Format a duration in whole minutes, e.g. `45 min`, `1 h` or `2 h 5 min`
+pub fn hours_and_minutes(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{} min", minutes),
+        (hours, 0) => format!("{} h", hours),
+        (hours, minutes) => format!("{} h {} min", hours, minutes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded_and_follows_undo() {
+        let mut history = ScoreHistory::with_capacity(3);
+        for score in [4, 8, 16, 20] {
+            history.push(score);
+        }
+        assert_eq!(history.iter().collect::<Vec<_>>(), vec![8, 16, 20]);
+        assert_eq!(history.recent(2).collect::<Vec<_>>(), vec![16, 20]);
+        assert_eq!(history.points()[0], (2.0, 8.0));
+
+        history.pop();
+        assert_eq!(history.points(), vec![(2.0, 8.0), (3.0, 16.0)]);
+        history.clear();
+        assert!(history.is_empty());
+        history.push(4);
+        assert_eq!(history.points(), vec![(1.0, 4.0)]);
+    }
+
+    #[test]
+    fn test_heatmap_counts_spawns_and_merges() {
+        use crate::game::{Game2048, GameEngine, MovementDirection, Progress};
+
+        let mut game = Game2048::builder()
+            .seed(3)
+            .resume(Progress {
+                board: vec![vec![2, 2, 0], vec![0, 0, 0], vec![0, 0, 0]],
+                score: 0,
+                moves: 0,
+                undos_used: 0,
+                history: Vec::new(),
+            })
+            .build()
+            .unwrap();
+        let mut heatmap = Heatmap::default();
+        assert!(game.move_in_direction(&MovementDirection::Left));
+        heatmap.record(&game);
+        assert_eq!(heatmap.counts(HeatmapKind::Merges)[0], [1, 0, 0]);
+        assert_eq!(
+            heatmap
+                .counts(HeatmapKind::Spawns)
+                .iter()
+                .flatten()
+                .sum::<u32>(),
+            1
+        );
+        let levels = heatmap.levels(HeatmapKind::Merges);
+        assert_eq!(levels[0], [1.0, 0.0, 0.0]);
+        assert_eq!(levels[1], [0.0; 3]);
+
+        // A board of another size starts the counts over
+        heatmap.record(&Game2048::builder().size(4).seed(1).build().unwrap());
+        assert_eq!(heatmap.counts(HeatmapKind::Merges).len(), 4);
+        assert!(
+            heatmap
+                .levels(HeatmapKind::Merges)
+                .iter()
+                .flatten()
+                .all(|&level| level == 0.0)
+        );
+    }
+
+    #[test]
+    fn test_play_time_adds_up_and_round_trips() {
+        let mut play_time = PlayTime::default();
+        play_time.record(Duration::from_secs(90), Duration::from_secs(90));
+        play_time.record(Duration::from_secs(30), Duration::from_secs(40));
+        assert_eq!(play_time.total_secs, 120);
+        assert_eq!(play_time.longest_session_secs, 90);
+
+        let path =
+            std::env::temp_dir().join(format!("tui_2048_play_time_{}.toml", std::process::id()));
+        assert_eq!(PlayTime::load(&path).unwrap(), PlayTime::default());
+        play_time.save(&path).unwrap();
+        let loaded = PlayTime::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, play_time);
+        assert_eq!(
+            hours_and_minutes(Duration::from_secs(45 * 60 + 59)),
+            "45 min"
+        );
+        assert_eq!(hours_and_minutes(Duration::from_secs(3600)), "1 h");
+        assert_eq!(
+            hours_and_minutes(Duration::from_secs(2 * 3600 + 5 * 60)),
+            "2 h 5 min"
+        );
+    }
+}
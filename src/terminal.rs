@@ -0,0 +1,153 @@
+//! Terminal setup and restoration.
+//!
+//! The game puts the terminal into raw mode, switches to the alternate screen
+//! and hides the cursor. If that state leaks, for example because of a panic,
+//! the shell is unusable until it is reset, so restoring it is tied to a guard
+//! value and a panic hook.
+//!
+//! Terminals supporting the kitty keyboard protocol are also asked to report
+//! key releases, which the game uses for hold-to-preview interactions.
+//!
+//! The game draws to standard output unless it is set up on standard error
+//! with [`TerminalGuard::enter_on`], leaving standard output to be piped.
+
+use std::io::{self, Write};
+use std::panic;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crossterm::event::{
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::{cursor, execute, terminal};
+
+use crate::error::{GameResult, TerminalContext};
+
+/// Whether the terminal was asked to report key releases
+static KEY_RELEASES: AtomicBool = AtomicBool::new(false);
+
+/// Whether the terminal is in game mode and needs restoring
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the game draws to standard error instead of standard output
+static ON_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Stream the game draws to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Output {
+    #[default]
+    Stdout,
+    /// Standard error, e.g. while standard output is piped to another program
+    Stderr,
+}
+
+impl Output {
+    /// Handle writing to the stream
+    pub fn writer(self) -> Box<dyn Write + Send> {
+        match self {
+            Output::Stdout => Box::new(io::stdout()),
+            Output::Stderr => Box::new(io::stderr()),
+        }
+    }
+}
+
+/// Stream the game draws to, as set up by the last [`TerminalGuard`]
+pub fn output() -> Output {
+    if ON_STDERR.load(Ordering::Relaxed) {
+        Output::Stderr
+    } else {
+        Output::Stdout
+    }
+}
+
+/// Whether the terminal was put in game mode and not restored since
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Whether key release events are delivered, so keys can be held
+pub fn reports_key_releases() -> bool {
+    KEY_RELEASES.load(Ordering::Relaxed)
+}
+
+/// Leave raw mode and the alternate screen and show the cursor again
+pub fn restore() -> GameResult<()> {
+    ACTIVE.store(false, Ordering::Relaxed);
+    let mut out = output().writer();
+    if KEY_RELEASES.swap(false, Ordering::Relaxed) {
+        execute!(out, PopKeyboardEnhancementFlags).context("turning off key release events")?;
+    }
+    terminal::disable_raw_mode().context("leaving raw mode")?;
+    execute!(out, terminal::LeaveAlternateScreen, cursor::Show)
+        .context("leaving the alternate screen")?;
+    Ok(())
+}
+
+/// Restore the terminal before the panic message is printed.
+///
+/// The previous hook still runs afterwards, so the message stays readable
+/// instead of being mangled by raw mode. Installing it more than once has no
+/// effect.
+pub fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            previous(info);
+        }));
+    });
+}
+
+/// Keeps the terminal in game mode while alive.
+///
+/// Dropping the guard restores the terminal, including on early returns and
+/// while unwinding from a panic. Use [`TerminalGuard::restore`] to see
+/// whether restoring worked.
+pub struct TerminalGuard {
+    restored: bool,
+}
+
+impl TerminalGuard {
+    /// Enter raw mode and the alternate screen, hide the cursor, ask for key
+    /// release events if supported and install the panic hook
+    pub fn enter() -> GameResult<Self> {
+        Self::enter_on(Output::Stdout)
+    }
+
+    /// Like [`TerminalGuard::enter`], drawing to `output`
+    pub fn enter_on(output: Output) -> GameResult<Self> {
+        install_panic_hook();
+        ON_STDERR.store(output == Output::Stderr, Ordering::Relaxed);
+        let mut out = output.writer();
+        terminal::enable_raw_mode().context("entering raw mode")?;
+        ACTIVE.store(true, Ordering::Relaxed);
+        // From here on the guard undoes the raw mode even if hiding fails
+        let guard = Self { restored: false };
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide)
+            .context("switching to the alternate screen")?;
+        if terminal::supports_keyboard_enhancement().unwrap_or(false) {
+            execute!(
+                out,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )
+            .context("asking for key release events")?;
+            KEY_RELEASES.store(true, Ordering::Relaxed);
+        }
+        Ok(guard)
+    }
+
+    /// Restore the terminal now, reporting errors
+    pub fn restore(mut self) -> GameResult<()> {
+        self.restored = true;
+        restore()
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if !self.restored {
+            let _ = restore();
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! First-run tutorial explaining the controls.
+//!
+//! The tutorial walks through a few steps, each one waiting for the player to
+//! try what it explains. Whether it was seen is kept in a file in the data
+//! directory, so it is only shown on the first launch.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GameError, GameResult};
+use crate::game::MovementDirection;
+use crate::i18n;
+use crate::keymap::{Action, Keymap};
+use crate::profile;
+
+/// Name of the onboarding file inside the data directory
+const ONBOARDING_FILE: &str = "onboarding.toml";
+
+/// A step of the tutorial, in the order they are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TutorialStep {
+    /// Greeting, continued with any key
+    Welcome,
+    /// Waits for any move
+    Move,
+    /// Waits for a move that merges tiles
+    Merge,
+    /// Waits for an undo
+    Undo,
+    /// Closing words, continued with any key
+    Finish,
+}
+
+/// Something the player did that may complete a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TutorialEvent {
+    /// A key that isn't used by the current step otherwise
+    KeyPressed,
+    /// A move, and whether it merged tiles
+    Moved {
+        merged: bool,
+    },
+    Undone,
+}
+
+/// Progress through the tutorial
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tutorial {
+    step: TutorialStep,
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self {
+            step: TutorialStep::Welcome,
+        }
+    }
+}
+
+impl Tutorial {
+    pub fn step(&self) -> TutorialStep {
+        self.step
+    }
+
+    /// Whether the current step takes any key, so keys shouldn't reach the game
+    pub fn waits_for_key(&self) -> bool {
+        matches!(self.step, TutorialStep::Welcome | TutorialStep::Finish)
+    }
+
+    /// Go to the next step if the event completes the current one.
+    ///
+    /// Returns whether the tutorial is finished.
+    pub fn advance(&mut self, event: TutorialEvent) -> bool {
+        let next = match (self.step, event) {
+            (TutorialStep::Welcome, TutorialEvent::KeyPressed) => TutorialStep::Move,
+            (TutorialStep::Move, TutorialEvent::Moved { .. }) => TutorialStep::Merge,
+            (TutorialStep::Merge, TutorialEvent::Moved { merged: true }) => TutorialStep::Undo,
+            (TutorialStep::Undo, TutorialEvent::Undone) => TutorialStep::Finish,
+            (TutorialStep::Finish, TutorialEvent::KeyPressed) => return true,
+            _ => return false,
+        };
+        self.step = next;
+        false
+    }
+
+    /// Explanation of the current step, naming the keys of `keymap` and
+    /// pointing out a merge on `board`
+    pub fn text(&self, keymap: &Keymap, board: &[Vec<u32>]) -> String {
+        let keys = |action: Action| {
+            keymap
+                .keys(action)
+                .iter()
+                .map(|key| key.to_string())
+                .collect::<Vec<_>>()
+                .join(&format!(" {} ", i18n::text("tutorial.or")))
+        };
+        match self.step {
+            TutorialStep::Welcome => i18n::text("tutorial.welcome").to_string(),
+            TutorialStep::Move => i18n::format(
+                "tutorial.move",
+                &[
+                    ("up", &keys(Action::MoveUp)),
+                    ("down", &keys(Action::MoveDown)),
+                    ("left", &keys(Action::MoveLeft)),
+                    ("right", &keys(Action::MoveRight)),
+                ],
+            ),
+            TutorialStep::Merge => {
+                let mut text = i18n::text("tutorial.merge").to_string();
+                if let Some(direction) = merge_hint(board) {
+                    let direction = direction_name(direction);
+                    text.push(' ');
+                    text.push_str(&i18n::format("tutorial.try", &[("direction", &direction)]));
+                }
+                text
+            }
+            TutorialStep::Undo => i18n::format("tutorial.undo", &[("keys", &keys(Action::Undo))]),
+            TutorialStep::Finish => {
+                i18n::format("tutorial.done", &[("keys", &keys(Action::ShowHelp))])
+            }
+        }
+    }
+}
+
+/// Name of a direction in the language of the interface
+pub fn direction_name(direction: MovementDirection) -> &'static str {
+    match direction {
+        MovementDirection::Up => i18n::text("direction.up"),
+        MovementDirection::Down => i18n::text("direction.down"),
+        MovementDirection::Left => i18n::text("direction.left"),
+        MovementDirection::Right => i18n::text("direction.right"),
+    }
+}
+
+/// A direction that merges two tiles on the board, if there is one
+pub fn merge_hint(board: &[Vec<u32>]) -> Option<MovementDirection> {
+    // Neighbours with only empty cells between them merge
+    let has_pair = |line: &mut dyn Iterator<Item = u32>| {
+        let tiles: Vec<u32> = line.filter(|&tile| tile != 0).collect();
+        tiles.windows(2).any(|pair| pair[0] == pair[1])
+    };
+    if board.iter().any(|row| has_pair(&mut row.iter().copied())) {
+        return Some(MovementDirection::Left);
+    }
+    let columns = board.first().map_or(0, Vec::len);
+    if (0..columns).any(|j| has_pair(&mut board.iter().map(|row| row[j]))) {
+        return Some(MovementDirection::Up);
+    }
+    None
+}
+
+/// What the player has been shown already
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Onboarding {
+    pub tutorial_seen: bool,
+}
+
+impl Onboarding {
+    /// Default location of the onboarding file, if a data directory exists
+    pub fn default_path() -> Option<PathBuf> {
+        profile::data_dir().map(|dir| dir.join(ONBOARDING_FILE))
+    }
+
+    /// Load the onboarding state from a file, starting empty if it is missing
+    pub fn load(path: &Path) -> GameResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            GameError::SaveError(format!(
+                "Corrupt onboarding state {}: {}",
+                path.display(),
+                e.message()
+            ))
+        })
+    }
+
+    /// Write the onboarding state to a file, creating its directory if needed
+    pub fn save(&self, path: &Path) -> GameResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(|e| {
+            GameError::SerializationError(format!("Cannot encode onboarding state: {}", e))
+        })?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tutorial_steps() {
+        let mut tutorial = Tutorial::default();
+        assert!(tutorial.waits_for_key());
+        assert!(!tutorial.advance(TutorialEvent::KeyPressed));
+        assert_eq!(tutorial.step(), TutorialStep::Move);
+
+        // Undoing doesn't count as a move, a move without merges isn't a merge
+        tutorial.advance(TutorialEvent::Undone);
+        assert_eq!(tutorial.step(), TutorialStep::Move);
+        tutorial.advance(TutorialEvent::Moved { merged: false });
+        tutorial.advance(TutorialEvent::Moved { merged: false });
+        assert_eq!(tutorial.step(), TutorialStep::Merge);
+        tutorial.advance(TutorialEvent::Moved { merged: true });
+        tutorial.advance(TutorialEvent::Undone);
+        assert_eq!(tutorial.step(), TutorialStep::Finish);
+        assert!(tutorial.advance(TutorialEvent::KeyPressed));
+
+        let keymap = Keymap::default();
+        let text = Tutorial::default().text(&keymap, &[]);
+        assert!(text.starts_with("Welcome"));
+        tutorial.step = TutorialStep::Undo;
+        assert!(tutorial.text(&keymap, &[]).contains("u or z"));
+    }
+
+    #[test]
+    fn test_merge_hint() {
+        let row = vec![vec![2, 0, 2], vec![0, 4, 0], vec![0, 0, 8]];
+        assert_eq!(merge_hint(&row), Some(MovementDirection::Left));
+        let column = vec![vec![4, 0, 0], vec![0, 0, 0], vec![4, 2, 0]];
+        assert_eq!(merge_hint(&column), Some(MovementDirection::Up));
+        let blocked = vec![vec![2, 4], vec![4, 2]];
+        assert_eq!(merge_hint(&blocked), None);
+    }
+}
@@ -5,23 +5,162 @@
 //! with limited color support.
 
 use ratatui::{
-    style::{Color, Style},
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
+    widgets::Paragraph,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::game::GameEngine;
+use crate::cosmetics::Cosmetics;
+use crate::game::GameView;
+use crate::i18n;
+use capabilities::ColorDepth;
+
+pub mod ascii;
+pub mod big;
+pub mod capabilities;
+#[cfg(feature = "variants")]
+pub mod cube;
+pub mod theme;
+
+use theme::{PATTERNS, Theme};
 
 /// Trait for rendering a game
 pub trait GameRenderer {
-    /// Render the game state as a vector of text lines
-    fn render(&self, game: &dyn GameEngine) -> Vec<Line>;
+    /// Draw the game state into `area` of the frame
+    fn render(&self, frame: &mut Frame, area: Rect, game: &dyn GameView);
     fn is_color(&self) -> bool {
         true // Default to color support
     }
+    /// Whether only ASCII characters may be drawn, so the app replaces the
+    /// box drawing of its own widgets as well
+    fn is_ascii(&self) -> bool {
+        false
+    }
+    /// Terminal columns the tiles of a board with `columns` columns take up
+    fn board_width(&self, columns: usize) -> u16 {
+        columns as u16 * 8
+    }
+    /// Switch to a different color theme (ignored by renderers without colors)
+    fn set_theme(&mut self, _theme: Theme) {}
+    /// Use the colors the terminal supports, e.g. RGB gradients with 24-bit
+    /// colors (ignored by renderers without colors)
+    fn set_color_depth(&mut self, _depth: ColorDepth) {}
+    /// Switch to a different border style and accent (ignored by renderers without colors)
+    fn set_cosmetics(&mut self, _cosmetics: Cosmetics) {}
+    /// Enable or disable highlighting of the tiles changed by the last move
+    fn set_highlight(&mut self, _enabled: bool) {}
+    /// Abbreviate large tiles or write them out in wider cells (ignored by
+    /// renderers with cells of a fixed size)
+    fn set_tile_labels(&mut self, _labels: TileLabels) {}
+    /// Mark a cell, e.g. the one edited on the sandbox screen, or remove the
+    /// mark with `None`
+    fn set_cursor(&mut self, _cursor: Option<(usize, usize)>) {}
+    /// Shade every cell by its level from 0 to 1, row by row, or remove the
+    /// shading with `None` (ignored by renderers without colors)
+    fn set_heatmap(&mut self, _levels: Option<Vec<Vec<f64>>>) {}
 }
 
 /// Default renderer for the 2048 game
-pub struct DefaultRenderer;
+#[derive(Default)]
+pub struct DefaultRenderer {
+    theme: Theme,
+    colors: ColorDepth,
+    highlight: bool,
+    cosmetics: Cosmetics,
+    cursor: Option<(usize, usize)>,
+    labels: TileLabels,
+    heatmap: Option<Vec<Vec<f64>>>,
+}
+
+impl DefaultRenderer {
+    /// Create a renderer using the given color theme
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            colors: ColorDepth::default(),
+            highlight: false,
+            cosmetics: Cosmetics::default(),
+            cursor: None,
+            labels: TileLabels::default(),
+            heatmap: None,
+        }
+    }
+
+    /// Style for the tile at the given position, colored like the tile of a
+    /// regular game it corresponds to
+    fn cell_style(&self, game: &dyn GameView, pos: (usize, usize), num: u32) -> Style {
+        let num = game.color_tile(num);
+        let color = if self.colors == ColorDepth::TrueColor {
+            self.theme.rgb_tile_color(num)
+        } else {
+            self.theme.tile_color(num)
+        };
+        let mut style = Style::default()
+            .fg(color)
+            .add_modifier(self.theme.tile_modifier(num));
+        if let Some(level) = self
+            .heatmap
+            .as_ref()
+            .and_then(|levels| levels.get(pos.0)?.get(pos.1))
+            .filter(|&&level| level > 0.0)
+        {
+            style = style.bg(heat_color(*level, self.colors));
+        }
+        if !self.highlight {
+            style
+        } else if game.last_spawn().map(|(i, j, _)| (i, j)) == Some(pos) {
+            style.add_modifier(Modifier::REVERSED)
+        } else if game.merged_cells().contains(&pos) {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
+        }
+    }
+
+    /// `style` with the cursor drawn over it if it is on the cell
+    fn with_cursor(&self, style: Style, pos: (usize, usize)) -> Style {
+        if self.cursor == Some(pos) {
+            style.add_modifier(Modifier::REVERSED | Modifier::UNDERLINED)
+        } else {
+            style
+        }
+    }
+}
+
+/// Background of a cell on the heatmap, from cool for rarely to hot for
+/// most often
+fn heat_color(level: f64, depth: ColorDepth) -> Color {
+    let level = level.clamp(0.0, 1.0);
+    match depth {
+        ColorDepth::TrueColor => {
+            let mix = |cool: f64, hot: f64| (cool + (hot - cool) * level).round() as u8;
+            Color::Rgb(mix(20.0, 200.0), mix(30.0, 30.0), mix(90.0, 20.0))
+        }
+        _ => {
+            let steps = match depth {
+                ColorDepth::Indexed => [
+                    Color::Indexed(17),
+                    Color::Indexed(54),
+                    Color::Indexed(90),
+                    Color::Indexed(124),
+                    Color::Indexed(160),
+                ],
+                _ => [
+                    Color::Blue,
+                    Color::Cyan,
+                    Color::Magenta,
+                    Color::Red,
+                    Color::LightRed,
+                ],
+            };
+            let step = (level * (steps.len() - 1) as f64).round() as usize;
+            steps[step]
+        }
+    }
+}
 
 /// Get color for a number tile
 pub fn get_color(num: u32) -> Color {
@@ -43,45 +182,127 @@ pub fn get_color(num: u32) -> Color {
 }
 
 impl GameRenderer for DefaultRenderer {
-    fn render(&self, game: &dyn GameEngine) -> Vec<Line> {
-        let mut lines = vec![];
-        lines.push(Line::from(
-            "Score: ".to_string() + &game.score().to_string(),
-        ));
+    fn render(&self, frame: &mut Frame, area: Rect, game: &dyn GameView) {
+        let [status_area, board_area] = status_layout(area);
+        let status_style = match self.cosmetics.accent.color() {
+            Some(color) => Style::default().fg(color),
+            None => Style::default(),
+        };
+        frame.render_widget(
+            Paragraph::new(status_line(game)).style(status_style),
+            status_area,
+        );
+        let lines = self.tile_lines(game, self.cosmetics.border.chars(), PATTERNS, true);
+        frame.render_widget(Paragraph::new(lines), board_area);
+    }
+
+    fn board_width(&self, columns: usize) -> u16 {
+        columns as u16 * (self.labels.cell_width() as u16 + 3)
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.colors = depth;
+    }
+
+    fn set_highlight(&mut self, enabled: bool) {
+        self.highlight = enabled;
+    }
+
+    fn set_cosmetics(&mut self, cosmetics: Cosmetics) {
+        self.cosmetics = cosmetics;
+    }
+
+    fn set_cursor(&mut self, cursor: Option<(usize, usize)>) {
+        self.cursor = cursor;
+    }
+
+    fn set_tile_labels(&mut self, labels: TileLabels) {
+        self.labels = labels;
+    }
+
+    fn set_heatmap(&mut self, levels: Option<Vec<Vec<f64>>>) {
+        self.heatmap = levels;
+    }
+}
 
-        // Create a visual separator between score and board
-        lines.push(Line::from(""));
+impl DefaultRenderer {
+    /// Three lines per row of the board, drawing each tile as a box with the
+    /// given border characters (see [`BorderStyle::chars`]), filled with the
+    /// given patterns if the theme has them (see [`theme::PATTERNS`])
+    ///
+    /// [`BorderStyle::chars`]: crate::cosmetics::BorderStyle::chars
+    fn tile_lines(
+        &self,
+        game: &dyn GameView,
+        border: [char; 6],
+        patterns: [char; 5],
+        styled: bool,
+    ) -> Vec<Line<'static>> {
+        let mut lines = vec![];
+        let [
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+            horizontal,
+            vertical,
+        ] = border;
+        let width = self.labels.cell_width();
+        let rule = horizontal.to_string().repeat(width);
+        let top = format!("{}{}{} ", top_left, rule, top_right);
+        let bottom = format!("{}{}{} ", bottom_left, rule, bottom_right);
 
         // For each row in the board, we'll create 3 lines to make square cells
-        for row in game.board() {
+        for (i, row) in game.board().iter().enumerate() {
+            let styles: Vec<Style> = row
+                .iter()
+                .enumerate()
+                .map(|(j, &num)| {
+                    let style = if styled {
+                        self.cell_style(game, (i, j), num)
+                    } else {
+                        Style::default()
+                    };
+                    self.with_cursor(style, (i, j))
+                })
+                .collect();
+
             // Top border of the cells
             lines.push(Line::from(
-                row.iter()
-                    .map(|&num| Span::styled("┌─────┐ ", Style::default().fg(get_color(num))))
+                styles
+                    .iter()
+                    .map(|&style| Span::styled(top.clone(), style))
                     .collect::<Vec<Span>>(),
             ));
 
             // Cell content with the number
             lines.push(Line::from(
                 row.iter()
-                    .map(|&num| {
+                    .zip(&styles)
+                    .map(|(&num, &style)| {
                         let content = if num == 0 {
-                            "     ".to_string()
+                            " ".repeat(width)
                         } else {
-                            format!("{:^5}", num)
+                            let fill = self
+                                .theme
+                                .pattern(game.color_tile(num))
+                                .map_or(' ', |i| patterns[i]);
+                            centered(&self.labels.label(num), fill, width)
                         };
-                        Span::styled(
-                            format!("│{}│ ", content),
-                            Style::default().fg(get_color(num)),
-                        )
+                        Span::styled(format!("{}{}{} ", vertical, content, vertical), style)
                     })
                     .collect::<Vec<Span>>(),
             ));
 
             // Bottom border of the cells
             lines.push(Line::from(
-                row.iter()
-                    .map(|&num| Span::styled("└─────┘ ", Style::default().fg(get_color(num))))
+                styles
+                    .iter()
+                    .map(|&style| Span::styled(bottom.clone(), style))
                     .collect::<Vec<Span>>(),
             ));
         }
@@ -90,54 +311,291 @@ impl GameRenderer for DefaultRenderer {
     }
 }
 
-/// No-color renderer for the 2048 game (for terminals with limited color support)
-pub struct NoColorRenderer;
+/// How the numbers of the tiles are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TileLabels {
+    /// Tiles from 1024 on abbreviated to `1k`, `2k`, `4k`, …, in cells of
+    /// five columns
+    #[default]
+    Compact,
+    /// The exact numbers, in cells of seven columns; only tiles past that
+    /// are abbreviated
+    Exact,
+}
 
-impl GameRenderer for NoColorRenderer {
-    fn render(&self, game: &dyn GameEngine) -> Vec<Line> {
-        let mut lines = vec![];
-        lines.push(Line::from(
-            "Score: ".to_string() + &game.score().to_string(),
-        ));
+impl TileLabels {
+    /// All options, in the order the settings screen cycles through them
+    pub const ALL: [TileLabels; 2] = [TileLabels::Compact, TileLabels::Exact];
 
-        // Create a visual separator between score and board
-        lines.push(Line::from(""));
+    /// Columns inside a cell
+    pub fn cell_width(self) -> usize {
+        match self {
+            TileLabels::Compact => 5,
+            TileLabels::Exact => 7,
+        }
+    }
 
-        // For each row in the board, we'll create 3 lines to make square cells
-        for row in game.board() {
-            // Top border of the cells
-            lines.push(Line::from(
-                row.iter()
-                    .map(|_| Span::raw("┌─────┐ "))
-                    .collect::<Vec<Span>>(),
-            ));
+    /// Text of a tile, at most [`TileLabels::cell_width`] columns wide
+    pub fn label(self, num: u32) -> String {
+        let text = num.to_string();
+        match self {
+            TileLabels::Exact if text.len() <= self.cell_width() => text,
+            _ => abbreviated(num),
+        }
+    }
+}
 
-            // Cell content with the number
-            lines.push(Line::from(
+/// A number counted in 1024s, 1024²s or 1024³s with a `k`, `M` or `G`
+/// suffix if it is at least 1024, like `128k` for 131072
+fn abbreviated(num: u32) -> String {
+    [(1 << 30, 'G'), (1 << 20, 'M'), (1 << 10, 'k')]
+        .into_iter()
+        .find(|&(unit, _)| num >= unit)
+        .map_or(num.to_string(), |(unit, suffix)| {
+            format!("{}{}", num / unit, suffix)
+        })
+}
+
+/// A label centered in the `width` columns inside a tile, padded with `fill`
+fn centered(text: &str, fill: char, width: usize) -> String {
+    let padding = width.saturating_sub(text.chars().count());
+    let left = fill.to_string().repeat(padding / 2);
+    let right = fill.to_string().repeat(padding - padding / 2);
+    format!("{}{}{}", left, text, right)
+}
+
+/// Split an area into the score line with a blank line below it and the board
+fn status_layout(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(area)
+}
+
+/// Score line above the board, with the undos left if they are limited
+fn status_line(game: &dyn GameView) -> String {
+    let line = i18n::format(
+        "status.line",
+        &[("score", &game.score()), ("moves", &game.moves())],
+    );
+    let line = match game.combo() {
+        Some(multiplier) => format!(
+            "{}   {}",
+            line,
+            i18n::format("status.combo", &[("multiplier", &multiplier)])
+        ),
+        None => line,
+    };
+    match game.undos_left() {
+        Some(undos) => format!(
+            "{}   {}",
+            line,
+            i18n::format("status.undos_left", &[("undos", &undos)])
+        ),
+        None => line,
+    }
+}
+
+/// Compact text rendering of a board, one line per row, for side panels
+pub fn mini_board(board: &[Vec<u32>]) -> Vec<Line<'static>> {
+    board
+        .iter()
+        .map(|row| {
+            Line::from(
                 row.iter()
                     .map(|&num| {
-                        let content = if num == 0 {
-                            "     ".to_string()
+                        if num == 0 {
+                            format!("{:>5}", ".")
                         } else {
-                            format!("{:^5}", num)
-                        };
-                        Span::raw(format!("│{}│ ", content))
+                            format!("{:>5}", TileLabels::Compact.label(num))
+                        }
                     })
-                    .collect::<Vec<Span>>(),
-            ));
+                    .collect::<String>(),
+            )
+        })
+        .collect()
+}
 
-            // Bottom border of the cells
-            lines.push(Line::from(
-                row.iter()
-                    .map(|_| Span::raw("└─────┘ "))
-                    .collect::<Vec<Span>>(),
-            ));
-        }
+/// No-color renderer for the 2048 game (for terminals with limited color support)
+pub struct NoColorRenderer;
 
-        lines
+impl GameRenderer for NoColorRenderer {
+    fn render(&self, frame: &mut Frame, area: Rect, game: &dyn GameView) {
+        let [status_area, board_area] = status_layout(area);
+        frame.render_widget(Paragraph::new(status_line(game)), status_area);
+        frame.render_widget(Paragraph::new(plain_board_lines(game)), board_area);
     }
 
     fn is_color(&self) -> bool {
         false // No color support
     }
 }
+
+/// Three lines per row of the board, without colors
+fn plain_board_lines(game: &dyn GameView) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+
+    // For each row in the board, we'll create 3 lines to make square cells
+    for row in game.board() {
+        // Top border of the cells
+        lines.push(Line::from(
+            row.iter()
+                .map(|_| Span::raw("┌─────┐ "))
+                .collect::<Vec<Span>>(),
+        ));
+
+        // Cell content with the number
+        lines.push(Line::from(
+            row.iter()
+                .map(|&num| {
+                    let content = if num == 0 {
+                        "     ".to_string()
+                    } else {
+                        format!("{:^5}", TileLabels::Compact.label(num))
+                    };
+                    Span::raw(format!("│{}│ ", content))
+                })
+                .collect::<Vec<Span>>(),
+        ));
+
+        // Bottom border of the cells
+        lines.push(Line::from(
+            row.iter()
+                .map(|_| Span::raw("└─────┘ "))
+                .collect::<Vec<Span>>(),
+        ));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosmetics::BorderStyle;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    /// A fixed board, which is all a renderer needs
+    struct Board(Vec<Vec<u32>>);
+
+    impl GameView for Board {
+        fn score(&self) -> u64 {
+            12
+        }
+
+        fn board(&self) -> &[Vec<u32>] {
+            &self.0
+        }
+    }
+
+    /// Draw a view and return the rows of the screen
+    fn draw(renderer: &impl GameRenderer, view: &dyn GameView) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(24, 8)).unwrap();
+        terminal
+            .draw(|f| renderer.render(f, f.area(), view))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..8)
+            .map(|y| (0..24).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_render_read_only_view() {
+        let view = Board(vec![vec![2, 0], vec![0, 8]]);
+        let lines = draw(&NoColorRenderer, &view);
+        assert_eq!(lines[0].trim_end(), "Score: 12   Moves: 0");
+        assert_eq!(lines[3].trim_end(), "│  2  │ │     │");
+        assert!(!view.game_over());
+    }
+
+    #[test]
+    fn test_cosmetic_border_style() {
+        let view = Board(vec![vec![2, 0], vec![0, 8]]);
+        let mut renderer = DefaultRenderer::default();
+        renderer.set_cosmetics(Cosmetics {
+            border: BorderStyle::Double,
+            ..Cosmetics::default()
+        });
+        let lines = draw(&renderer, &view);
+        assert_eq!(lines[2].trim_end(), "╔═════╗ ╔═════╗");
+        assert_eq!(lines[3].trim_end(), "║  2  ║ ║     ║");
+    }
+
+    #[test]
+    fn test_heatmap_shades_cells() {
+        let view = Board(vec![vec![2, 0], vec![0, 8]]);
+        let mut renderer = DefaultRenderer::default();
+        renderer.set_heatmap(Some(vec![vec![1.0, 0.0], vec![0.5, 0.0]]));
+        let mut terminal = Terminal::new(TestBackend::new(24, 8)).unwrap();
+        terminal
+            .draw(|f| renderer.render(f, f.area(), &view))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer[(1, 3)].bg, Color::LightRed);
+        assert_eq!(buffer[(1, 6)].bg, Color::Magenta);
+        // Cells nothing happened on aren't shaded
+        assert_eq!(buffer[(9, 3)].bg, Color::Reset);
+
+        renderer.set_heatmap(None);
+        terminal
+            .draw(|f| renderer.render(f, f.area(), &view))
+            .unwrap();
+        assert_eq!(terminal.backend().buffer()[(1, 3)].bg, Color::Reset);
+    }
+
+    #[test]
+    fn test_accessibility_theme_patterns() {
+        let view = Board(vec![vec![2, 32], vec![2048, 8]]);
+        let renderer = DefaultRenderer::new(Theme::Colorblind);
+        let lines = draw(&renderer, &view);
+        assert_eq!(lines[3].trim_end(), "│  2  │ │:32::│");
+        assert_eq!(lines[6].trim_end(), "│▓2k▓▓│ │··8··│");
+        assert_eq!(
+            lines[6],
+            draw(&DefaultRenderer::new(Theme::Monochrome), &view)[6]
+        );
+        let lines = draw(&DefaultRenderer::default(), &view);
+        assert_eq!(lines[6].trim_end(), "│ 2k  │ │  8  │");
+    }
+
+    #[test]
+    fn test_tile_labels() {
+        let compact = TileLabels::Compact;
+        assert_eq!(compact.label(512), "512");
+        assert_eq!(compact.label(1024), "1k");
+        assert_eq!(compact.label(16384), "16k");
+        assert_eq!(compact.label(131072), "128k");
+        assert_eq!(compact.label(1 << 20), "1M");
+        assert_eq!(compact.label(1 << 31), "2G");
+        let exact = TileLabels::Exact;
+        assert_eq!(exact.label(131072), "131072");
+        assert_eq!(exact.label(1 << 23), "8388608");
+        assert_eq!(exact.label(1 << 24), "16M");
+
+        let view = Board(vec![vec![1 << 17, 1 << 31], vec![0, 2]]);
+        let lines = draw(&NoColorRenderer, &view);
+        assert_eq!(lines[3].trim_end(), "│128k │ │ 2G  │");
+        let mut renderer = DefaultRenderer::default();
+        assert_eq!(renderer.board_width(4), 32);
+        renderer.set_tile_labels(TileLabels::Exact);
+        assert_eq!(renderer.board_width(4), 40);
+        let lines = draw(&renderer, &view);
+        assert_eq!(lines[2].trim_end(), "┌───────┐ ┌───────┐");
+        assert_eq!(lines[3].trim_end(), "│131072 │ │  2G   │");
+    }
+
+    #[test]
+    fn test_cursor_marks_cell() {
+        let view = Board(vec![vec![2, 0], vec![0, 8]]);
+        let mut renderer = DefaultRenderer::default();
+        renderer.set_cursor(Some((1, 0)));
+        let mut terminal = Terminal::new(TestBackend::new(24, 8)).unwrap();
+        terminal
+            .draw(|f| renderer.render(f, f.area(), &view))
+            .unwrap();
+        // The second row of tiles starts below the status line and the first row
+        let buffer = terminal.backend().buffer();
+        assert!(buffer[(2, 6)].modifier.contains(Modifier::REVERSED));
+        assert!(!buffer[(10, 6)].modifier.contains(Modifier::REVERSED));
+        assert!(!buffer[(2, 3)].modifier.contains(Modifier::REVERSED));
+    }
+}
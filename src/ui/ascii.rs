@@ -0,0 +1,174 @@
+//! Renderer for terminals that can only show ASCII characters.
+//!
+//! Box drawing characters garble on such terminals, so tiles are drawn with
+//! `+`, `-` and `|`. The app also passes its finished frames through
+//! [`to_ascii`] when the renderer asks for it, which covers the borders,
+//! sparklines and charts of the screens around the board.
+
+use ratatui::{Frame, buffer::Buffer, layout::Rect, style::Style, widgets::Paragraph};
+
+use super::capabilities::ColorDepth;
+use super::theme::Theme;
+use super::{DefaultRenderer, GameRenderer, TileLabels, status_layout, status_line};
+use crate::cosmetics::Cosmetics;
+use crate::game::GameView;
+
+/// Corners, then the horizontal and vertical lines of a tile
+const ASCII_BORDER: [char; 6] = ['+', '+', '+', '+', '-', '|'];
+
+/// Stand-ins for the patterns of the accessibility themes, sparse to dense
+const ASCII_PATTERNS: [char; 5] = ['.', ':', '+', '=', '#'];
+
+/// Replace the characters outside ASCII in a drawn frame.
+///
+/// Box drawing becomes `+`, `-` and `|`, blocks and braille dots (bars,
+/// sparklines, charts) become `#`, and anything else becomes `?`.
+pub fn to_ascii(buffer: &mut Buffer) {
+    let area = buffer.area;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = &mut buffer[(x, y)];
+            if !cell.symbol().is_ascii() {
+                let replacement = cell.symbol().chars().next().map_or(' ', ascii_char);
+                cell.set_char(replacement);
+            }
+        }
+    }
+}
+
+/// ASCII stand-in for a single character
+fn ascii_char(c: char) -> char {
+    match c {
+        '─' | '━' | '═' | '┄' | '┅' | '╌' | '╍' => '-',
+        '│' | '┃' | '║' | '┆' | '┇' | '╎' | '╏' => '|',
+        '\u{2500}'..='\u{257f}' => '+',
+        '\u{2800}' => ' ',
+        '\u{2580}'..='\u{259f}' | '\u{2801}'..='\u{28ff}' | '•' | '·' => '#',
+        '…' => '.',
+        c if c.is_ascii() => c,
+        _ => '?',
+    }
+}
+
+/// Renderer drawing tiles with ASCII characters only
+pub struct AsciiRenderer {
+    tiles: DefaultRenderer,
+    color: bool,
+}
+
+impl Default for AsciiRenderer {
+    fn default() -> Self {
+        Self::new(Theme::default())
+    }
+}
+
+impl AsciiRenderer {
+    /// ASCII tiles colored with a theme
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            tiles: DefaultRenderer::new(theme),
+            color: true,
+        }
+    }
+
+    /// ASCII tiles for terminals without colors
+    pub fn without_color() -> Self {
+        Self {
+            tiles: DefaultRenderer::default(),
+            color: false,
+        }
+    }
+}
+
+impl GameRenderer for AsciiRenderer {
+    fn render(&self, frame: &mut Frame, area: Rect, game: &dyn GameView) {
+        let [status_area, board_area] = status_layout(area);
+        let status_style = match self.tiles.cosmetics.accent.color() {
+            Some(color) if self.color => Style::default().fg(color),
+            _ => Style::default(),
+        };
+        frame.render_widget(
+            Paragraph::new(status_line(game)).style(status_style),
+            status_area,
+        );
+        let lines = self
+            .tiles
+            .tile_lines(game, ASCII_BORDER, ASCII_PATTERNS, self.color);
+        frame.render_widget(Paragraph::new(lines), board_area);
+    }
+
+    fn is_color(&self) -> bool {
+        self.color
+    }
+
+    fn is_ascii(&self) -> bool {
+        true
+    }
+
+    fn board_width(&self, columns: usize) -> u16 {
+        self.tiles.board_width(columns)
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.tiles.set_theme(theme);
+    }
+
+    fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.tiles.set_color_depth(depth);
+    }
+
+    fn set_cosmetics(&mut self, cosmetics: Cosmetics) {
+        // Border styles need box drawing characters, only the accent applies
+        self.tiles.set_cosmetics(cosmetics);
+    }
+
+    fn set_highlight(&mut self, enabled: bool) {
+        self.tiles.set_highlight(enabled);
+    }
+
+    fn set_cursor(&mut self, cursor: Option<(usize, usize)>) {
+        self.tiles.set_cursor(cursor);
+    }
+    fn set_tile_labels(&mut self, labels: TileLabels) {
+        self.tiles.set_tile_labels(labels);
+    }
+
+    fn set_heatmap(&mut self, levels: Option<Vec<Vec<f64>>>) {
+        self.tiles.set_heatmap(levels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game2048;
+    use ratatui::{
+        Terminal,
+        backend::TestBackend,
+        widgets::{Block, Borders},
+    };
+
+    #[test]
+    fn test_ascii_tiles_and_frame() {
+        let game = Game2048::with_board(vec![vec![2, 0], vec![0, 1024]], 1);
+        let renderer = AsciiRenderer::without_color();
+        let mut terminal = Terminal::new(TestBackend::new(20, 10)).unwrap();
+        terminal
+            .draw(|f| {
+                let block = Block::default().borders(Borders::ALL);
+                let inner = block.inner(f.area());
+                f.render_widget(block, f.area());
+                renderer.render(f, inner, &game);
+                to_ascii(f.buffer_mut());
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let lines: Vec<String> = (0..10)
+            .map(|y| (0..20).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect();
+        assert_eq!(lines[0], "+------------------+");
+        assert_eq!(lines[3], "|+-----+ +-----+   |");
+        assert_eq!(lines[7], "||     | | 1k  |   |");
+        assert!(lines.iter().all(|line| line.is_ascii()));
+    }
+}
@@ -0,0 +1,272 @@
+//! Renderer drawing tile numbers with large block digits.
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use super::capabilities::ColorDepth;
+use super::theme::Theme;
+use super::{
+    DefaultRenderer, GameRenderer, NoColorRenderer, TileLabels, status_layout, status_line,
+};
+use crate::cosmetics::{BorderStyle, Cosmetics};
+use crate::game::GameView;
+
+/// Rows of a digit glyph
+const GLYPH_HEIGHT: usize = 5;
+
+/// Digits the tiles are sized for; larger numbers are drawn with plain text
+const TILE_DIGITS: usize = 4;
+
+/// Columns inside a tile: the digits, the gaps between them and one blank
+/// column on either side
+const TILE_INNER_WIDTH: usize = TILE_DIGITS * 4 + 1;
+
+/// Columns taken by a tile, including its borders and the gap to the next one
+const TILE_WIDTH: u16 = TILE_INNER_WIDTH as u16 + 3;
+
+/// Rows taken by a tile, including its borders
+const TILE_HEIGHT: u16 = GLYPH_HEIGHT as u16 + 2;
+
+/// Rows of the glyph of a digit, three columns wide
+fn glyph(digit: char) -> [&'static str; GLYPH_HEIGHT] {
+    match digit {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => [" █ ", "██ ", " █ ", " █ ", "███"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", "███", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        _ => ["   "; GLYPH_HEIGHT],
+    }
+}
+
+/// Row `row` of a number drawn with glyphs, centered in a tile
+fn number_row(num: u32, row: usize) -> String {
+    let text = if num == 0 {
+        String::new()
+    } else if num.to_string().len() > TILE_DIGITS {
+        // Too wide for glyphs, so the number is written once in the middle
+        if row == GLYPH_HEIGHT / 2 {
+            num.to_string()
+        } else {
+            String::new()
+        }
+    } else {
+        num.to_string()
+            .chars()
+            .map(|digit| glyph(digit)[row])
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    format!("{:^width$}", text, width = TILE_INNER_WIDTH)
+}
+
+/// Renderer with tiles large enough to read from across the room.
+///
+/// Tiles are drawn with block digits five rows high. When the area is too
+/// small for them, the game is drawn like the regular renderers do.
+pub struct BigTileRenderer {
+    tiles: DefaultRenderer,
+    color: bool,
+}
+
+impl Default for BigTileRenderer {
+    fn default() -> Self {
+        Self::new(Theme::default())
+    }
+}
+
+impl BigTileRenderer {
+    /// Big tiles colored with a theme
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            tiles: DefaultRenderer::new(theme),
+            color: true,
+        }
+    }
+
+    /// Big tiles for terminals without colors
+    pub fn without_color() -> Self {
+        Self {
+            tiles: DefaultRenderer::default(),
+            color: false,
+        }
+    }
+
+    /// Width and height of a board with the given rows and columns in big
+    /// tiles
+    fn board_size((rows, columns): (usize, usize)) -> (u16, u16) {
+        (columns as u16 * TILE_WIDTH, rows as u16 * TILE_HEIGHT)
+    }
+
+    fn board_lines(&self, game: &dyn GameView) -> Vec<Line<'static>> {
+        let border = if self.color {
+            self.tiles.cosmetics.border
+        } else {
+            BorderStyle::default()
+        };
+        let [
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+            horizontal,
+            vertical,
+        ] = border.chars();
+        let rule = horizontal.to_string().repeat(TILE_INNER_WIDTH);
+        let top = format!("{}{}{} ", top_left, rule, top_right);
+        let bottom = format!("{}{}{} ", bottom_left, rule, bottom_right);
+
+        let mut lines = vec![];
+        for (i, row) in game.board().iter().enumerate() {
+            let styles: Vec<Style> = row
+                .iter()
+                .enumerate()
+                .map(|(j, &num)| {
+                    let style = if self.color {
+                        self.tiles.cell_style(game, (i, j), num)
+                    } else {
+                        Style::default()
+                    };
+                    self.tiles.with_cursor(style, (i, j))
+                })
+                .collect();
+
+            lines.push(Line::from(
+                styles
+                    .iter()
+                    .map(|&style| Span::styled(top.clone(), style))
+                    .collect::<Vec<Span>>(),
+            ));
+            for glyph_row in 0..GLYPH_HEIGHT {
+                lines.push(Line::from(
+                    row.iter()
+                        .zip(&styles)
+                        .map(|(&num, &style)| {
+                            let content = number_row(num, glyph_row);
+                            Span::styled(format!("{}{}{} ", vertical, content, vertical), style)
+                        })
+                        .collect::<Vec<Span>>(),
+                ));
+            }
+            lines.push(Line::from(
+                styles
+                    .iter()
+                    .map(|&style| Span::styled(bottom.clone(), style))
+                    .collect::<Vec<Span>>(),
+            ));
+        }
+        lines
+    }
+}
+
+impl GameRenderer for BigTileRenderer {
+    fn render(&self, frame: &mut Frame, area: Rect, game: &dyn GameView) {
+        let [status_area, board_area] = status_layout(area);
+        let (width, height) = Self::board_size(game.dims());
+        if board_area.width < width || board_area.height < height {
+            if self.color {
+                self.tiles.render(frame, area, game);
+            } else {
+                NoColorRenderer.render(frame, area, game);
+            }
+            return;
+        }
+
+        let status_style = match self.tiles.cosmetics.accent.color() {
+            Some(color) if self.color => Style::default().fg(color),
+            _ => Style::default(),
+        };
+        frame.render_widget(
+            Paragraph::new(status_line(game)).style(status_style),
+            status_area,
+        );
+        frame.render_widget(Paragraph::new(self.board_lines(game)), board_area);
+    }
+
+    fn is_color(&self) -> bool {
+        self.color
+    }
+
+    fn board_width(&self, columns: usize) -> u16 {
+        Self::board_size((0, columns)).0
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.tiles.set_theme(theme);
+    }
+
+    fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.tiles.set_color_depth(depth);
+    }
+
+    fn set_cosmetics(&mut self, cosmetics: Cosmetics) {
+        self.tiles.set_cosmetics(cosmetics);
+    }
+
+    fn set_highlight(&mut self, enabled: bool) {
+        self.tiles.set_highlight(enabled);
+    }
+
+    fn set_cursor(&mut self, cursor: Option<(usize, usize)>) {
+        self.tiles.set_cursor(cursor);
+    }
+
+    fn set_tile_labels(&mut self, labels: TileLabels) {
+        // Only the small tiles drawn when the big ones don't fit are labeled
+        self.tiles.set_tile_labels(labels);
+    }
+
+    fn set_heatmap(&mut self, levels: Option<Vec<Vec<f64>>>) {
+        self.tiles.set_heatmap(levels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game2048;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    /// Draw a game and return the rows of the screen
+    fn draw(renderer: &BigTileRenderer, game: &Game2048, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|f| renderer.render(f, f.area(), game))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_big_digits_and_fallback() {
+        let game = Game2048::with_board(vec![vec![2048, 0], vec![0, 100_000]], 1);
+        let renderer = BigTileRenderer::without_color();
+
+        let lines = draw(&renderer, &game, 44, 16);
+        assert_eq!(
+            lines[3].trim_end(),
+            "│ ███ ███ █ █ ███ │ │                 │"
+        );
+        assert_eq!(
+            lines[9].trim_end(),
+            "┌─────────────────┐ ┌─────────────────┐"
+        );
+        assert!(lines[12].contains("100000"));
+
+        // Too small for big tiles, so the regular tiles are drawn
+        let lines = draw(&renderer, &game, 30, 16);
+        assert_eq!(lines[3].trim_end(), "│ 2k  │ │     │");
+    }
+}
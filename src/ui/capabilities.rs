@@ -0,0 +1,115 @@
+//! Detection of what the terminal can display.
+//!
+//! Terminals cannot be asked reliably, so the environment is probed the way
+//! most terminal programs do it: `NO_COLOR`, `COLORTERM` and `TERM` for the
+//! colors and the locale for Unicode support.
+
+/// Number of colors a terminal can show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// No colors, or colors turned off with `NO_COLOR`
+    None,
+    /// The 16 standard ANSI colors
+    #[default]
+    Basic,
+    /// The 256 color palette
+    Indexed,
+    /// 24-bit RGB colors
+    TrueColor,
+}
+
+/// What the terminal can display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub colors: ColorDepth,
+    /// Whether box drawing and other non-ASCII characters are shown correctly
+    pub unicode: bool,
+}
+
+impl Capabilities {
+    /// Capabilities of the terminal the game runs in
+    pub fn detect() -> Self {
+        Self::from_env(|name| std::env::var(name).ok())
+    }
+
+    /// Capabilities described by environment variables, looked up with `var`
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        // Empty variables count as unset
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+        let term = var("TERM");
+        let legacy_term = matches!(
+            term.as_deref(),
+            Some("dumb" | "vt52" | "vt100" | "vt102" | "vt220" | "ansi")
+        );
+
+        let colors = if var("NO_COLOR").is_some() || term.as_deref() == Some("dumb") {
+            ColorDepth::None
+        } else if var("COLORTERM").is_some_and(|value| value == "truecolor" || value == "24bit") {
+            ColorDepth::TrueColor
+        } else if term
+            .as_deref()
+            .is_some_and(|term| term.contains("256color"))
+        {
+            ColorDepth::Indexed
+        } else {
+            ColorDepth::Basic
+        };
+
+        // The first locale variable that is set decides, like in the C
+        // library. Without any nothing is known, so only the terminal decides.
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"].into_iter().find_map(var);
+        let utf8_locale = locale.is_none_or(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        });
+
+        Self {
+            colors,
+            unicode: utf8_locale && !legacy_term,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> Capabilities {
+        Capabilities::from_env(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_detect_capabilities() {
+        let modern = detect(&[
+            ("TERM", "xterm-256color"),
+            ("COLORTERM", "truecolor"),
+            ("LANG", "en_US.UTF-8"),
+        ]);
+        assert_eq!(modern.colors, ColorDepth::TrueColor);
+        assert!(modern.unicode);
+
+        let indexed = detect(&[("TERM", "screen-256color"), ("LC_ALL", "de_AT.utf8")]);
+        assert_eq!(indexed.colors, ColorDepth::Indexed);
+        assert!(indexed.unicode);
+
+        let no_color = detect(&[("TERM", "xterm"), ("NO_COLOR", "1"), ("LANG", "C")]);
+        assert_eq!(no_color.colors, ColorDepth::None);
+        assert!(!no_color.unicode);
+
+        // LC_ALL overrides LANG, empty variables count as unset
+        let latin1 = detect(&[("LC_ALL", "en_US.ISO-8859-1"), ("LANG", "en_US.UTF-8")]);
+        assert!(!latin1.unicode);
+        let empty = detect(&[("TERM", "xterm"), ("NO_COLOR", ""), ("LC_ALL", "")]);
+        assert_eq!(empty.colors, ColorDepth::Basic);
+        assert!(empty.unicode);
+
+        let legacy = detect(&[("TERM", "vt100"), ("LANG", "en_US.UTF-8")]);
+        assert_eq!(legacy.colors, ColorDepth::Basic);
+        assert!(!legacy.unicode);
+        assert_eq!(detect(&[("TERM", "dumb")]).colors, ColorDepth::None);
+    }
+}
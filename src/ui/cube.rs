@@ -0,0 +1,125 @@
+//! Renderer for the 3D variant, drawing the layers of the cube side by side.
+//!
+//! Every layer is drawn like the board of a regular game, below a label with
+//! its number, the front layer on the left.
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    text::Line,
+    widgets::Paragraph,
+};
+
+use super::theme::{PATTERNS, Theme};
+use super::{DefaultRenderer, GameRenderer, status_layout, status_line};
+use crate::board::Board;
+use crate::game::GameView;
+use crate::game::cube::Cube2048;
+use crate::i18n;
+
+/// Columns between two layers
+const LAYER_GAP: u16 = 2;
+
+/// One layer of a cube, seen as the board of a game with the cube's score
+struct LayerView<'a> {
+    game: &'a Cube2048,
+    layer: &'a Board,
+    index: usize,
+}
+
+impl GameView for LayerView<'_> {
+    fn score(&self) -> u64 {
+        self.game.score()
+    }
+
+    fn board(&self) -> &[Vec<u32>] {
+        self.layer.rows()
+    }
+
+    fn moves(&self) -> u32 {
+        self.game.moves()
+    }
+
+    fn last_spawn(&self) -> Option<(usize, usize, u32)> {
+        let (l, i, j) = self.game.last_spawn()?;
+        (l == self.index).then(|| (i, j, self.layer.get((i, j))))
+    }
+}
+
+/// Draws a [`Cube2048`] as its layers next to each other
+#[derive(Default)]
+pub struct CubeRenderer {
+    tiles: DefaultRenderer,
+}
+
+impl CubeRenderer {
+    /// Renderer drawing the tiles in the given color theme
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            tiles: DefaultRenderer::new(theme),
+        }
+    }
+
+    /// Columns the layers of a cube with `size` cells along each edge take up
+    pub fn width(&self, size: usize) -> u16 {
+        let layers = size as u16;
+        layers * self.tiles.board_width(size) + layers.saturating_sub(1) * LAYER_GAP
+    }
+
+    /// Draw the score line and the layers into `area` of the frame
+    pub fn render(&self, frame: &mut Frame, area: Rect, game: &Cube2048) {
+        let layers = game.board().layers();
+        let views: Vec<LayerView> = layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| LayerView { game, layer, index })
+            .collect();
+        let [status_area, layers_area] = status_layout(area);
+        frame.render_widget(Paragraph::new(status_line(&views[0])), status_area);
+
+        let layer_width = self.tiles.board_width(game.board().size());
+        let constraints = views.iter().enumerate().flat_map(|(index, _)| {
+            let gap = if index == 0 { 0 } else { LAYER_GAP };
+            [Constraint::Length(gap), Constraint::Length(layer_width)]
+        });
+        let areas = Layout::horizontal(constraints).split(layers_area);
+        for (view, &layer_area) in views.iter().zip(areas.iter().skip(1).step_by(2)) {
+            let label = i18n::format("cube.layer", &[("number", &(view.index + 1))]);
+            let mut lines = vec![Line::from(label)];
+            lines.extend(self.tiles.tile_lines(
+                view,
+                self.tiles.cosmetics.border.chars(),
+                PATTERNS,
+                true,
+            ));
+            frame.render_widget(Paragraph::new(lines), layer_area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::cube::DEFAULT_CUBE_SIZE;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    #[test]
+    fn test_layers_are_drawn_side_by_side() {
+        let game = Cube2048::new(DEFAULT_CUBE_SIZE, 1).unwrap();
+        let renderer = CubeRenderer::default();
+        let width = renderer.width(DEFAULT_CUBE_SIZE);
+        let mut terminal = Terminal::new(TestBackend::new(width, 14)).unwrap();
+        terminal
+            .draw(|f| renderer.render(f, f.area(), &game))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| -> String { (0..width).map(|x| buffer[(x, y)].symbol()).collect() };
+        assert!(row(0).starts_with("Score: 0"));
+        let labels = row(2);
+        for number in 1..=3 {
+            assert!(labels.contains(&format!("Layer {}", number)), "{labels}");
+        }
+        // Three layers of three tiles in every row of tiles
+        assert_eq!(row(3).matches('┌').count(), 9);
+    }
+}
@@ -0,0 +1,226 @@
+//! Color themes for the tile palette.
+//!
+//! Every theme has a palette of the 16 standard colors, which all terminals
+//! show, and an RGB gradient for terminals with 24-bit colors. The gradient
+//! is defined by a few key colors and keeps changing past 2048, so large
+//! tiles can still be told apart.
+//!
+//! The accessibility themes don't rely on color alone: they fill the space
+//! around the number with a pattern that gets denser with every other
+//! exponent, and make large tiles bold and the largest underlined, so the
+//! board stays readable for color-blind players.
+
+use ratatui::style::{Color, Modifier};
+use serde::{Deserialize, Serialize};
+
+/// Color theme used by the colored renderer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// The original palette
+    #[default]
+    Classic,
+    /// Cool blue and green tones
+    Ocean,
+    /// Warm red and yellow tones
+    Ember,
+    /// Blues and yellows that color-blind players can tell apart, with
+    /// patterns
+    Colorblind,
+    /// Shades of gray, with patterns
+    Monochrome,
+}
+
+/// Patterns filling tiles of the accessibility themes, from sparse to dense
+pub const PATTERNS: [char; 5] = ['·', ':', '░', '▒', '▓'];
+
+/// Exponent of the largest tile the gradients distinguish; larger tiles get
+/// its color
+const GRADIENT_END: u32 = 17;
+
+/// Key colors of the gradients, by the exponent of the tile (2 is 1, 2048 is
+/// 11). Colors between two keys are interpolated.
+const CLASSIC_GRADIENT: &[(u32, (u8, u8, u8))] = &[
+    (1, (238, 228, 218)),
+    (3, (242, 177, 121)),
+    (6, (246, 94, 59)),
+    (11, (237, 194, 46)),
+    (14, (60, 200, 140)),
+    (GRADIENT_END, (70, 90, 230)),
+];
+const OCEAN_GRADIENT: &[(u32, (u8, u8, u8))] = &[
+    (1, (200, 240, 250)),
+    (4, (80, 200, 220)),
+    (7, (30, 120, 200)),
+    (11, (40, 200, 120)),
+    (GRADIENT_END, (120, 60, 200)),
+];
+const EMBER_GRADIENT: &[(u32, (u8, u8, u8))] = &[
+    (1, (250, 230, 150)),
+    (4, (250, 160, 50)),
+    (7, (220, 50, 40)),
+    (11, (200, 40, 160)),
+    (GRADIENT_END, (90, 20, 60)),
+];
+/// Based on the Okabe-Ito palette, avoiding red next to green
+const COLORBLIND_GRADIENT: &[(u32, (u8, u8, u8))] = &[
+    (1, (86, 180, 233)),
+    (4, (0, 114, 178)),
+    (7, (230, 159, 0)),
+    (11, (240, 228, 66)),
+    (14, (204, 121, 167)),
+    (GRADIENT_END, (213, 94, 0)),
+];
+const MONOCHROME_GRADIENT: &[(u32, (u8, u8, u8))] =
+    &[(1, (150, 150, 150)), (GRADIENT_END, (255, 255, 255))];
+
+impl Theme {
+    /// All themes, in the order they are offered
+    pub const ALL: [Theme; 5] = [
+        Theme::Classic,
+        Theme::Ocean,
+        Theme::Ember,
+        Theme::Colorblind,
+        Theme::Monochrome,
+    ];
+
+    /// Get color for a number tile
+    pub fn tile_color(&self, num: u32) -> Color {
+        match self {
+            Theme::Classic => super::get_color(num),
+            Theme::Ocean => match num {
+                0 => Color::DarkGray,
+                2 | 4 => Color::Cyan,
+                8 | 16 => Color::Blue,
+                32 | 64 => Color::Green,
+                128 | 256 => Color::LightCyan,
+                512 | 1024 => Color::LightBlue,
+                _ => Color::LightGreen,
+            },
+            Theme::Ember => match num {
+                0 => Color::DarkGray,
+                2 | 4 => Color::Yellow,
+                8 | 16 => Color::Red,
+                32 | 64 => Color::Magenta,
+                128 | 256 => Color::LightYellow,
+                512 | 1024 => Color::LightRed,
+                _ => Color::LightMagenta,
+            },
+            Theme::Colorblind => match num {
+                0 => Color::DarkGray,
+                2 | 4 => Color::LightCyan,
+                8 | 16 => Color::Blue,
+                32 | 64 => Color::Yellow,
+                128 | 256 => Color::LightBlue,
+                512 | 1024 => Color::LightYellow,
+                _ => Color::White,
+            },
+            Theme::Monochrome => match num {
+                0 => Color::DarkGray,
+                2..=64 => Color::Gray,
+                _ => Color::White,
+            },
+        }
+    }
+
+    /// Whether the theme tells tiles apart by patterns and text styles too
+    pub fn has_patterns(&self) -> bool {
+        matches!(self, Theme::Colorblind | Theme::Monochrome)
+    }
+
+    /// Index in [`PATTERNS`] of the pattern filling a tile, `None` for
+    /// tiles without a pattern: empty cells, 2s and 4s, and every tile of
+    /// a theme without patterns
+    pub fn pattern(&self, num: u32) -> Option<usize> {
+        if !self.has_patterns() || num < 8 {
+            return None;
+        }
+        // 8 and 16 get the first pattern, 32 and 64 the second and so on
+        Some(((num.ilog2() as usize - 3) / 2).min(PATTERNS.len() - 1))
+    }
+
+    /// Text style of a tile: large tiles are bold and those from 2048 on
+    /// underlined as well, in themes with patterns
+    pub fn tile_modifier(&self, num: u32) -> Modifier {
+        match num {
+            _ if !self.has_patterns() => Modifier::empty(),
+            2048.. => Modifier::BOLD | Modifier::UNDERLINED,
+            128.. => Modifier::BOLD,
+            _ => Modifier::empty(),
+        }
+    }
+    /// RGB color for a number tile, for terminals with 24-bit colors
+    pub fn rgb_tile_color(&self, num: u32) -> Color {
+        if num == 0 {
+            return self.tile_color(0);
+        }
+        let gradient = match self {
+            Theme::Classic => CLASSIC_GRADIENT,
+            Theme::Ocean => OCEAN_GRADIENT,
+            Theme::Ember => EMBER_GRADIENT,
+            Theme::Colorblind => COLORBLIND_GRADIENT,
+            Theme::Monochrome => MONOCHROME_GRADIENT,
+        };
+        let exponent = num.ilog2().clamp(1, GRADIENT_END);
+        // The keys around the exponent; the first key is at exponent 1
+        let end = gradient
+            .iter()
+            .position(|&(key, _)| key >= exponent)
+            .unwrap_or(gradient.len() - 1);
+        let (to, (r2, g2, b2)) = gradient[end];
+        let (from, (r1, g1, b1)) = gradient[end.saturating_sub(1)];
+        if from == to {
+            return Color::Rgb(r1, g1, b1);
+        }
+        let t = f64::from(exponent - from) / f64::from(to - from);
+        let mix = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+        Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_continues_past_2048() {
+        let theme = Theme::Classic;
+        assert_eq!(theme.rgb_tile_color(2), Color::Rgb(238, 228, 218));
+        assert_eq!(theme.rgb_tile_color(2048), Color::Rgb(237, 194, 46));
+        // A third of the way from the key at 8 to the key at 64
+        assert_eq!(theme.rgb_tile_color(16), Color::Rgb(243, 149, 100));
+
+        let large = [4096, 8192, 16384, 32768, 65536, 131072];
+        let colors: Vec<Color> = large.iter().map(|&num| theme.rgb_tile_color(num)).collect();
+        for (i, color) in colors.iter().enumerate() {
+            assert!(!colors[i + 1..].contains(color));
+        }
+        assert_eq!(theme.rgb_tile_color(1 << 20), theme.rgb_tile_color(131072));
+        assert_eq!(theme.rgb_tile_color(0), Color::DarkGray);
+    }
+
+    #[test]
+    fn test_accessibility_themes_tell_tiles_apart_without_color() {
+        // Pairs of tiles from 2 and 4 to 2048 and 4096
+        let tiles: Vec<u32> = (1..=12).map(|exponent| 1 << exponent).collect();
+        for theme in [Theme::Colorblind, Theme::Monochrome] {
+            let looks: Vec<_> = tiles
+                .iter()
+                .map(|&num| (theme.pattern(num), theme.tile_modifier(num)))
+                .collect();
+            // The tiles of a pair look alike apart from their numbers, and
+            // every pair looks different
+            for pair in looks.chunks(2) {
+                assert_eq!(pair[0], pair[1]);
+            }
+            let mut pairs: Vec<_> = looks.iter().step_by(2).collect();
+            pairs.dedup();
+            assert_eq!(pairs.len(), 6);
+            assert_eq!(theme.pattern(2), None);
+            assert_eq!(theme.pattern(8), Some(0));
+            assert_eq!(theme.pattern(1 << 20), Some(PATTERNS.len() - 1));
+        }
+        assert_eq!(Theme::Classic.pattern(2048), None);
+        assert_eq!(Theme::Classic.tile_modifier(2048), Modifier::empty());
+    }
+}
@@ -0,0 +1,884 @@
+//! End-to-end tests driving the whole app loop with scripted input and a
+//! test backend instead of a terminal.
+
+use std::io;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::backend::{Backend, ClearType, TestBackend, WindowSize};
+use ratatui::buffer::Cell;
+use ratatui::layout::{Position, Size};
+use ratatui::{Terminal, style::Modifier};
+use tui_2048::game::MergeRule;
+use tui_2048::input::{ChannelInput, ScriptedInput};
+use tui_2048::prelude::*;
+
+/// Text of the last drawn frame, one string per row
+fn screen<R: GameRenderer>(app: &App<Game2048, R, TestBackend>) -> Vec<String> {
+    let buffer = app.terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect()
+        })
+        .collect()
+}
+
+fn contains(screen: &[String], text: &str) -> bool {
+    screen.iter().any(|row| row.contains(text))
+}
+
+#[test]
+fn test_play_seeded_game_to_the_end() {
+    let game = Game2048::builder().size(3).seed(2048).build().unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_input(ScriptedInput::keys([KeyCode::Left, KeyCode::Up]));
+
+    // The app stops when the script runs out, leaving the last frame behind
+    assert!(matches!(app.run(), Err(GameError::InputError(_))));
+    let moves = app.game().moves();
+    assert!(moves > 0);
+    let frame = screen(&app);
+    assert!(contains(&frame, &format!("Score: {}", app.game().score())));
+    assert!(contains(&frame, &format!("Moves: {}", moves)));
+
+    // Circle through the directions until the small board fills up, then quit
+    let directions = [KeyCode::Left, KeyCode::Down, KeyCode::Right, KeyCode::Up];
+    let keys = directions
+        .into_iter()
+        .cycle()
+        .take(400)
+        .chain([KeyCode::Char('q')]);
+    let mut app = app.with_input(ScriptedInput::keys(keys));
+    assert_eq!(app.run().unwrap(), AppOutcome::Quit);
+    assert!(app.game().game_over());
+
+    let frame = screen(&app);
+    assert!(contains(&frame, "Game Over!"));
+    assert!(contains(&frame, &format!("{} moves", app.game().moves())));
+    assert!(!contains(&frame, "(not ranked)"));
+}
+
+#[test]
+fn test_game_over_tells_unranked_games_apart() {
+    // 8s make a game harder than a regular one, free undos beyond the usual
+    // limit make it easier, so neither is ranked
+    let games = [
+        Game2048::builder().size(3).eight_chance(0.5),
+        Game2048::builder().size(3).unlimited_undo(),
+    ];
+    for builder in games {
+        let game = builder.seed(2048).build().unwrap();
+        let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        let directions = [KeyCode::Left, KeyCode::Down, KeyCode::Right, KeyCode::Up];
+        let keys = directions
+            .into_iter()
+            .cycle()
+            .take(400)
+            .chain([KeyCode::Char('q')]);
+        let mut app =
+            App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys(keys));
+        assert_eq!(app.run().unwrap(), AppOutcome::Quit);
+        assert!(app.game().game_over());
+        assert!(contains(&screen(&app), "(not ranked)"));
+    }
+}
+
+#[test]
+fn test_rectangular_board_is_drawn_with_all_its_columns() {
+    let game = Game2048::builder().dims(2, 5).seed(3).build().unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let mut app =
+        App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys([KeyCode::Left]));
+    assert!(app.run().is_err());
+    // Two rows of tiles, each with five tiles
+    let frame = screen(&app);
+    let tile_rows: Vec<&String> = frame.iter().filter(|row| row.contains("┌─────┐")).collect();
+    assert_eq!(tile_rows.len(), 2);
+    assert!(
+        tile_rows
+            .iter()
+            .all(|row| row.matches("┌─────┐").count() == 5)
+    );
+}
+
+#[test]
+fn test_restart_keeps_the_settings_of_the_game() {
+    let game = Game2048::builder()
+        .size(3)
+        .seed(2048)
+        .win_tile(64)
+        .build()
+        .unwrap();
+    let first_board = game.board().to_vec();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let directions = [KeyCode::Left, KeyCode::Down, KeyCode::Right, KeyCode::Up];
+    let keys = directions
+        .into_iter()
+        .cycle()
+        .take(400)
+        .chain([KeyCode::Char('r')]);
+    let mut app = App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys(keys));
+    assert_eq!(app.run().unwrap(), AppOutcome::Restarted);
+
+    // Without a game factory the game is reset, replaying the seeded tiles
+    assert_eq!(app.game().board(), first_board);
+    assert_eq!(app.game().win_tile(), 64);
+    assert_eq!(app.game().moves(), 0);
+}
+
+#[test]
+fn test_board_and_panels_are_drawn_in_place() {
+    // Any backend works, here one keeping the frame in memory
+    let game = Game2048::from_board([[2, 0], [0, 16]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(64, 20)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys([]));
+    assert!(app.run().is_err());
+
+    assert_eq!(
+        screen(&app),
+        [
+            "                                                                ",
+            "                                                                ",
+            "  ┌2048──────────────────────────────────────────────────────┐  ",
+            "  │Score: 0   Moves: 0         ┌────────────────────────────┐│  ",
+            "  │                            │Score:     0                ││  ",
+            "  │┌─────┐ ┌─────┐             │Best:      0                ││  ",
+            "  ││  2  │ │     │             └────────────────────────────┘│  ",
+            "  │└─────┘ └─────┘             ┌Score───────────────────────┐│  ",
+            "  │┌─────┐ ┌─────┐             │                            ││  ",
+            "  ││     │ │ 16  │             │                            ││  ",
+            "  │└─────┘ └─────┘             └────────────────────────────┘│  ",
+            "  └─────────────────────────────────────── 0:00  0.0 moves/s ┘  ",
+            "  ┌Events────────────────────────────────────────────────────┐  ",
+            "  │                                                          │  ",
+            "  │                                                          │  ",
+            "  │                                                          │  ",
+            "  │                                                          │  ",
+            "  └──────────────────────────────────────────────────────────┘  ",
+            "                                                                ",
+            "                                                                ",
+        ]
+    );
+}
+
+#[test]
+fn test_events_sent_from_another_thread_drive_the_app() {
+    let game = Game2048::builder().seed(2048).build().unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let (sender, input) = ChannelInput::new();
+    let mut app = App::new(game, NoColorRenderer, terminal).with_input(input);
+    let player = std::thread::spawn(move || {
+        for code in [KeyCode::Left, KeyCode::Up, KeyCode::Char('q')] {
+            sender.send(Event::Key(KeyEvent::from(code))).unwrap();
+        }
+    });
+    assert_eq!(app.run().unwrap(), AppOutcome::Quit);
+    player.join().unwrap();
+    assert_eq!(app.game().moves(), 2);
+}
+
+#[test]
+fn test_solve_and_fail_puzzles() {
+    let puzzles = tui_2048::puzzles::parse(
+        r#"
+        [[puzzles]]
+        name = "Pair"
+        board = [[2, 2, 0], [0, 0, 0], [0, 0, 0]]
+        target = 4
+        max_moves = 1
+        seed = 1
+
+        [[puzzles]]
+        name = "Stack"
+        board = [[4, 0, 0], [4, 0, 0], [8, 0, 0]]
+        target = 16
+        max_moves = 2
+        seed = 2
+        "#,
+    )
+    .unwrap();
+    let path =
+        std::env::temp_dir().join(format!("tui_2048_app_puzzles_{}.toml", std::process::id()));
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    // Start the first puzzle and solve it, which opens the select screen on the second
+    // The second puzzle is failed with two quick moves to the right
+    let config = tui_2048::config::Config {
+        move_interval_ms: 0,
+        ..Default::default()
+    };
+    let mut app = App::new(Game2048::default(), NoColorRenderer, terminal)
+        .with_config(&config)
+        .unwrap()
+        .with_puzzles(puzzles, path.clone(), tui_2048::puzzles::Puzzle::game)
+        .unwrap()
+        .with_input(ScriptedInput::keys([KeyCode::Enter, KeyCode::Left]));
+    assert!(app.run().is_err());
+    let frame = screen(&app);
+    assert!(contains(&frame, "Solved Pair in 1 move"));
+    assert!(contains(&frame, "solved in 1"));
+
+    // Moving away from the stack runs out of moves and starts it over
+    let mut app = app.with_input(ScriptedInput::keys([KeyCode::Esc, KeyCode::Right]));
+    assert!(app.run().is_err());
+    assert!(contains(
+        &screen(&app),
+        "Puzzle Stack: Reach 16 in 2 moves, 1 left"
+    ));
+    let mut app = app.with_input(ScriptedInput::keys([KeyCode::Right]));
+    assert!(app.run().is_err());
+    assert_eq!(app.game().moves(), 0);
+    assert!(contains(&screen(&app), "Stack failed"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_tutorial_steps_through_the_first_moves() {
+    use tui_2048::tutorial::Onboarding;
+
+    let path = std::env::temp_dir().join(format!(
+        "tui_2048_app_onboarding_{}.toml",
+        std::process::id()
+    ));
+    let game = Game2048::from_board([[2, 2, 0], [0, 0, 0], [0, 0, 0]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_tutorial(path.clone())
+        .with_input(ScriptedInput::keys([KeyCode::Enter, KeyCode::Down]));
+    assert!(app.run().is_err());
+    // The welcome key doesn't reach the game, the move does
+    assert_eq!(app.game().moves(), 1);
+    let frame = screen(&app);
+    assert!(contains(&frame, "Tutorial (Esc to skip)"));
+    assert!(contains(&frame, "Merge a pair! Try Left."));
+
+    let keys = [KeyCode::Left, KeyCode::Char('u'), KeyCode::Enter];
+    let mut app = app.with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    let frame = screen(&app);
+    assert!(!contains(&frame, "Tutorial (Esc to skip)"));
+    assert!(contains(&frame, "Tutorial finished"));
+    assert!(Onboarding::load(&path).unwrap().tutorial_seen);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_pause_hides_the_board() {
+    let game = Game2048::from_board([[2, 0], [0, 1024]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_input(ScriptedInput::keys([KeyCode::Char(' ')]));
+    assert!(app.run().is_err());
+    let frame = screen(&app);
+    assert!(contains(&frame, "Paused - press any key to resume"));
+    // The side panel still shows the best tile, but no tiles are drawn
+    assert!(!contains(&frame, " 1k  │"));
+
+    // Any key resumes without moving
+    let mut app = app.with_input(ScriptedInput::keys([KeyCode::Left]));
+    assert!(app.run().is_err());
+    assert_eq!(app.game().moves(), 0);
+    assert!(contains(&screen(&app), " 1k  │"));
+}
+
+#[test]
+fn test_record_session_to_asciicast() {
+    let path =
+        std::env::temp_dir().join(format!("tui_2048_app_record_{}.cast", std::process::id()));
+    let game = Game2048::from_board([[2, 0], [0, 2]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_recording(&path)
+        .unwrap()
+        .with_input(ScriptedInput::keys([KeyCode::Left]));
+    assert!(app.run().is_err());
+    drop(app);
+
+    let cast = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let mut lines = cast.lines();
+    let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert_eq!(header["version"], 2);
+    assert_eq!(
+        (header["width"].clone(), header["height"].clone()),
+        (60.into(), 20.into())
+    );
+    // The first frame draws the board, and the move draws at least one more
+    let frames: Vec<serde_json::Value> = lines.map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert!(frames.len() >= 2);
+    assert!(frames[0][2].as_str().unwrap().contains("2048"));
+}
+
+#[test]
+fn test_export_replay_after_game_over() {
+    let dir = std::env::temp_dir().join(format!("tui_2048_app_export_{}", std::process::id()));
+    let game = Game2048::builder().size(2).seed(3).build().unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let directions = [KeyCode::Left, KeyCode::Down, KeyCode::Right, KeyCode::Up];
+    let keys = directions.into_iter().cycle().take(100).chain([
+        KeyCode::Char('e'),
+        KeyCode::Char('g'),
+        KeyCode::Char('q'),
+    ]);
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_export_dir(dir.clone())
+        .with_input(ScriptedInput::keys(keys));
+    assert_eq!(app.run().unwrap(), AppOutcome::Quit);
+    assert!(app.game().game_over());
+    assert!(contains(&screen(&app), "Press 'e' to save the replay"));
+
+    let mut files: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let name = format!(
+        "2048-{}-{}",
+        tui_2048::date::Date::today(),
+        app.game().score()
+    );
+    assert_eq!(files, [format!("{name}.gif"), format!("{name}.svg")]);
+}
+
+#[test]
+fn test_snapshot_saves_the_board() {
+    let dir = std::env::temp_dir().join(format!("tui_2048_app_snapshot_{}", std::process::id()));
+    let game = Game2048::from_board([[2, 0], [0, 16]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_export_dir(dir.clone())
+        .with_snapshot_format(tui_2048::export::PngSnapshot)
+        .with_input(ScriptedInput::keys([KeyCode::Char('x')]));
+    assert!(app.run().is_err());
+    assert!(contains(&screen(&app), "Board saved to"));
+
+    let name = format!("2048-{}-move-0", tui_2048::date::Date::today());
+    let text = std::fs::read_to_string(dir.join(format!("{name}.txt"))).unwrap();
+    let png = std::fs::read(dir.join(format!("{name}.png"))).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(text.contains("│    2 │      │"));
+    assert!(text.contains("│      │   16 │"));
+    assert!(png.starts_with(b"\x89PNG"));
+}
+
+#[test]
+fn test_save_to_a_named_slot() {
+    use tui_2048::saves::{SavedGame, Saves};
+
+    let dir = std::env::temp_dir().join(format!("tui_2048_app_slots_{}", std::process::id()));
+    let game = Game2048::from_board([[2, 0], [0, 16]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let mut keys = vec![KeyCode::Char('g'), KeyCode::Char('w')];
+    keys.extend("late game".chars().map(KeyCode::Char));
+    keys.push(KeyCode::Enter);
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_save_slots(Saves::new(dir.clone()), |game| SavedGame::new(game, None))
+        .with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    assert!(contains(&screen(&app), "Saved to slot late game"));
+
+    let games = Saves::new(dir.clone()).list().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].slot.as_deref(), Some("late game"));
+    assert_eq!(games[0].board, [[2, 0], [0, 16]]);
+}
+
+#[test]
+fn test_versus_second_player_wins_on_the_arrow_keys() {
+    let first = Game2048::from_board([[2, 0], [0, 4]]).unwrap();
+    let second = Game2048::from_board([[1024, 1024], [0, 0]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let keys = [KeyCode::Char('d'), KeyCode::Left];
+    let mut app = App::new(first, NoColorRenderer, terminal)
+        .with_versus(second)
+        .with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+
+    // Player 1 moved with WASD without touching player 2's board
+    assert_eq!(app.game().board()[0][1], 2);
+    let frame = screen(&app);
+    assert!(contains(&frame, "Player 2 wins!"));
+    assert!(contains(&frame, "Player 1: 0, Player 2: 2048"));
+}
+
+#[test]
+fn test_versus_high_merges_send_junk() {
+    let first = Game2048::from_board([[32, 32, 0], [0, 0, 0], [0, 0, 0]]).unwrap();
+    let second = Game2048::from_board([[2, 0, 0], [0, 0, 0], [0, 0, 0]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let mut app = App::new(first, NoColorRenderer, terminal)
+        .with_versus(second)
+        .with_input(ScriptedInput::keys([KeyCode::Char('a')]));
+    assert!(app.run().is_err());
+    assert!(contains(
+        &screen(&app),
+        "Player 1 attacks: 1 junk to Player 2"
+    ));
+}
+
+#[test]
+fn test_merges_charge_a_swap_power_up() {
+    let game = Game2048::from_board([[2, 2, 2, 2, 2, 2]; 6]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+    // 18 merges charge a swap; swap the first tile with the fourth cell
+    let keys = [
+        KeyCode::Char('a'),
+        KeyCode::Char('2'),
+        KeyCode::Char('1'),
+        KeyCode::Enter,
+        KeyCode::Right,
+        KeyCode::Right,
+        KeyCode::Right,
+        KeyCode::Enter,
+    ];
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_power_ups()
+        .with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+
+    assert_eq!(app.game().board()[0][3], 4);
+    let frame = screen(&app);
+    assert!(contains(&frame, "Swap charged, 1 ready"));
+    assert!(contains(&frame, "Remove isn't charged yet"));
+    assert!(contains(&frame, "Swap used!"));
+    assert!(contains(&frame, "1 Swap:      0/3"));
+}
+
+#[test]
+fn test_history_goes_back_to_an_earlier_board() {
+    let board = [[2, 2, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
+    let game = Game2048::from_board(board).unwrap();
+    let terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+    let keys = [
+        KeyCode::Char('a'),
+        KeyCode::Char('g'),
+        KeyCode::Char('h'),
+        KeyCode::Left,
+        KeyCode::Enter,
+    ];
+    let mut app = App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+
+    assert_eq!(app.game().board(), board);
+    assert_eq!(app.game().moves(), 0);
+    assert!(contains(&screen(&app), "Went back to move 0"));
+}
+
+#[test]
+fn test_undo_key_is_greyed_out_without_moves_to_undo() {
+    let board = [[2, 2, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
+    let game = Game2048::from_board(board).unwrap();
+    let terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_input(ScriptedInput::keys([KeyCode::Char('u')]));
+    assert!(app.run().is_err());
+    assert!(contains(&screen(&app), "Nothing to undo"));
+
+    // The undo line of the side panel is dimmed until a move can be undone
+    let undo_is_dim = |app: &App<Game2048, NoColorRenderer, TestBackend>| {
+        let frame = screen(app);
+        let y = frame.iter().position(|row| row.contains("undo ")).unwrap();
+        let x = frame[y][..frame[y].find("undo ").unwrap()].chars().count();
+        let buffer = app.terminal.backend().buffer();
+        buffer[(x as u16, y as u16)]
+            .modifier
+            .contains(Modifier::DIM)
+    };
+    assert!(undo_is_dim(&app));
+    let mut app = app.with_input(ScriptedInput::keys([KeyCode::Char('a')]));
+    assert!(app.run().is_err());
+    assert!(!undo_is_dim(&app));
+}
+
+#[test]
+fn test_heatmap_shades_where_tiles_merged() {
+    let board = [[2, 2, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
+    let game = Game2048::from_board(board).unwrap();
+    let terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+    let hot = |app: &App<Game2048, DefaultRenderer, TestBackend>| {
+        app.terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .filter(|cell| cell.bg == ratatui::style::Color::LightRed)
+            .count()
+    };
+    let mut app = App::new(game, DefaultRenderer::default(), terminal)
+        .with_input(ScriptedInput::keys([KeyCode::Char('a')]));
+    assert!(app.run().is_err());
+    assert_eq!(hot(&app), 0);
+
+    // Spawns first, then merges
+    let keys = [KeyCode::Char('g'), KeyCode::Char('m')];
+    let mut app = app.with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    assert!(contains(&screen(&app), "Heatmap: where tiles spawned"));
+    assert!(hot(&app) > 0);
+    let mut app = app.with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    assert!(contains(&screen(&app), "Heatmap: where tiles merged"));
+    assert!(hot(&app) > 0);
+    let mut app = app.with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    assert_eq!(hot(&app), 0);
+}
+
+#[test]
+fn test_trainer_grades_moves() {
+    let board = [[64, 64, 0, 0], [2, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]];
+    let trainer = |key| {
+        let game = Game2048::from_board(board).unwrap();
+        let terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        let mut app = App::new(game, NoColorRenderer, terminal)
+            .with_trainer()
+            .with_input(ScriptedInput::keys([key]));
+        assert!(app.run().is_err());
+        screen(&app)
+    };
+
+    let frame = trainer(KeyCode::Char('a'));
+    assert!(contains(&frame, "Trainer (unranked)"));
+    assert!(contains(&frame, "Best move!"));
+    assert!(contains(&frame, "Accuracy:  100%"));
+
+    // Moving the 64s apart isn't the best move
+    let frame = trainer(KeyCode::Char('s'));
+    assert!(contains(&frame, "best was Left"));
+    assert!(contains(&frame, "Accuracy:  0%"));
+
+    // The search only knows regular merges, so divide games aren't graded
+    let game = Game2048::builder()
+        .merge_rule(MergeRule::Halve)
+        .seed(1)
+        .build()
+        .unwrap();
+    let terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_trainer()
+        .with_input(ScriptedInput::keys([KeyCode::Left, KeyCode::Right]));
+    assert!(app.run().is_err());
+    assert!(app.game().moves() > 0);
+    let frame = screen(&app);
+    assert!(contains(&frame, "regular merges"));
+    assert!(!contains(&frame, "Best move!"));
+}
+
+#[test]
+fn test_game_over_analysis() {
+    // Moving right spawns a 4 in the corner, which leaves no move
+    let board = vec![
+        vec![2, 4, 2, 4],
+        vec![4, 2, 4, 2],
+        vec![2, 4, 2, 4],
+        vec![8, 16, 8, 0],
+    ];
+    let game = Game2048::builder()
+        .seed(1)
+        .four_chance(1.0)
+        .resume(tui_2048::game::Progress {
+            board,
+            score: 0,
+            moves: 0,
+            undos_used: 0,
+            history: Vec::new(),
+        })
+        .build()
+        .unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let keys = [KeyCode::Right, KeyCode::Char('v')];
+    let mut app = App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    assert!(app.game().game_over());
+
+    let frame = screen(&app);
+    assert!(contains(&frame, "Analysis against the expectimax search"));
+    assert!(contains(&frame, "Moves rated"));
+    assert!(contains(&frame, "Efficiency"));
+
+    // The search only knows regular merges, so divide games aren't reviewed
+    let board = vec![
+        vec![2, 4, 2, 4],
+        vec![4, 2, 4, 2],
+        vec![2, 4, 2, 4],
+        vec![8, 16, 8, 0],
+    ];
+    let game = Game2048::builder()
+        .merge_rule(MergeRule::Halve)
+        .seed(1)
+        .four_chance(1.0)
+        .resume(tui_2048::game::Progress {
+            board,
+            score: 0,
+            moves: 0,
+            undos_used: 0,
+            history: Vec::new(),
+        })
+        .build()
+        .unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let keys = [KeyCode::Right, KeyCode::Char('v')];
+    let mut app = App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    assert!(app.game().game_over());
+
+    let frame = screen(&app);
+    assert!(contains(&frame, "regular merges"));
+    assert!(!contains(&frame, "Moves rated"));
+}
+
+#[test]
+fn test_notifications_stack_in_the_corner() {
+    let game = Game2048::from_board([[2, 0], [0, 16]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let keys = [
+        KeyCode::Char('p'),
+        KeyCode::Char('p'),
+        KeyCode::Char('g'),
+        KeyCode::Char('c'),
+    ];
+    let mut app = App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+
+    let frame = screen(&app);
+    // The newest at the bottom, the repeated one only once
+    assert!(frame[28].contains("Cosmetics are not available in this mode"));
+    assert!(frame[25].contains("Nothing to undo"));
+    assert_eq!(
+        frame
+            .iter()
+            .filter(|row| row.contains("Nothing to undo"))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_debug_overlay_lists_events() {
+    let game = Game2048::from_board([[2, 0], [0, 16]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let keys = [KeyCode::Char('h'), KeyCode::F(12)];
+    let mut app = App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+
+    let frame = screen(&app);
+    assert!(frame[0].contains("Debug (F12)"));
+    assert!(contains(&frame, "Screen playing"));
+    assert!(contains(&frame, "Frame "));
+    // The newest event first
+    let f12 = frame
+        .iter()
+        .position(|row| row.contains("Key F12"))
+        .unwrap();
+    let h = frame.iter().position(|row| row.contains("Key h")).unwrap();
+    assert!(f12 < h);
+}
+
+/// Test backend counting the frames drawn on it
+struct CountingBackend {
+    inner: TestBackend,
+    frames: usize,
+}
+
+impl Backend for CountingBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.inner.draw(content)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.inner.show_cursor()
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        self.inner.get_cursor_position()
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.inner.set_cursor_position(position)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.inner.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        self.inner.clear_region(clear_type)
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        self.inner.size()
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        self.inner.window_size()
+    }
+
+    // The terminal flushes once per frame
+    fn flush(&mut self) -> io::Result<()> {
+        self.frames += 1;
+        self.inner.flush()
+    }
+}
+
+fn counting_terminal() -> Terminal<CountingBackend> {
+    Terminal::new(CountingBackend {
+        inner: TestBackend::new(80, 30),
+        frames: 0,
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_unchanged_frames_are_not_drawn() {
+    let game = Game2048::from_board([[2, 0], [0, 16]]).unwrap();
+    let release = KeyEvent::new_with_kind(KeyCode::F(9), KeyModifiers::NONE, KeyEventKind::Release);
+    let events = [release; 3]
+        .into_iter()
+        .chain([KeyEvent::from(KeyCode::Left)])
+        .map(Event::Key);
+    let mut app =
+        App::new(game, NoColorRenderer, counting_terminal()).with_input(ScriptedInput::new(events));
+    assert!(app.run().is_err());
+    // The first frame and the one after the move; the releases change nothing
+    assert_eq!(app.terminal.backend().frames, 2);
+}
+
+#[test]
+fn test_max_fps_caps_the_frames_drawn() {
+    let game = Game2048::from_board([[2, 0], [0, 16]]).unwrap();
+    let config = Config {
+        max_fps: 1,
+        ..Config::default()
+    };
+    let keys = [KeyCode::Char('p'), KeyCode::Char('p'), KeyCode::Char('p')];
+    let mut app = App::new(game, NoColorRenderer, counting_terminal())
+        .with_config(&config)
+        .unwrap()
+        .with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    // The keys arrive within the second after the first frame
+    assert_eq!(app.terminal.backend().frames, 1);
+}
+
+#[test]
+fn test_held_key_repeats_are_dropped() {
+    let game = Game2048::from_board([[0, 0, 2], [0, 0, 0], [0, 0, 0]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_input(ScriptedInput::keys([KeyCode::Left; 5]));
+    assert!(app.run().is_err());
+    // The repeats follow the first move too fast to be taken
+    assert_eq!(app.game().moves(), 1);
+}
+
+#[test]
+fn test_settings_screen_saves_and_applies_changes() {
+    use tui_2048::config::Config;
+
+    let path =
+        std::env::temp_dir().join(format!("tui_2048_app_settings_{}.toml", std::process::id()));
+    let terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+    // Board size one larger, then bind undo to Backspace
+    let mut keys = vec![KeyCode::Char('g'), KeyCode::Char('o'), KeyCode::Right];
+    keys.extend([KeyCode::Down; 10]);
+    keys.extend([KeyCode::Enter, KeyCode::Backspace]);
+    let mut app = App::new(Game2048::default(), NoColorRenderer, terminal)
+        .with_configured_game_factory(|config| {
+            Game2048::builder().size(config.size).build().unwrap()
+        })
+        .with_config_path(path.clone())
+        .with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    let frame = screen(&app);
+    assert!(contains(&frame, "Board size          5x5"));
+    assert!(contains(&frame, "Keys: undo          Backspace"));
+    // Nothing is saved or restarted until the screen is closed
+    assert!(!path.exists());
+    assert_eq!(app.game().size(), 4);
+
+    let mut app = app.with_input(ScriptedInput::keys([KeyCode::Esc]));
+    assert!(app.run().is_err());
+    assert_eq!(app.game().size(), 5);
+    assert!(contains(&screen(&app), "Settings saved"));
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.size, 5);
+    assert_eq!(config.keys["undo"], vec!["Backspace"]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "sandbox")]
+#[test]
+fn test_edit_and_play_a_sandbox_position() {
+    let game = Game2048::from_board([[2, 0, 0], [0, 0, 0], [0, 0, 0]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    // An empty board can't be played
+    let mut app = App::new(game, NoColorRenderer, terminal)
+        .with_game_factory(|| Game2048::builder().size(3).build().unwrap())
+        .with_sandbox()
+        .with_input(ScriptedInput::keys([KeyCode::Backspace, KeyCode::Enter]));
+    assert!(app.run().is_err());
+    assert!(contains(&screen(&app), "Place a tile before playing"));
+
+    // Two 8s in the top row, then merge them
+    let plus = KeyCode::Char('+');
+    let keys = [
+        KeyCode::Right,
+        plus,
+        plus,
+        plus,
+        KeyCode::Right,
+        plus,
+        plus,
+        plus,
+    ];
+    let mut app = app.with_input(ScriptedInput::keys(keys));
+    assert!(app.run().is_err());
+    assert!(contains(&screen(&app), "Sandbox: row 1, column 3"));
+    assert_eq!(app.game().board()[0], [0, 8, 8]);
+
+    let mut app = app.with_input(ScriptedInput::keys([KeyCode::Enter, KeyCode::Left]));
+    assert!(app.run().is_err());
+    assert_eq!(app.game().board()[0][0], 16);
+    assert_eq!(app.game().moves(), 1);
+    assert!(contains(
+        &screen(&app),
+        "Sandbox (unranked, restart to edit)"
+    ));
+
+    // Restarting returns to the position played from
+    let mut app = app.with_input(ScriptedInput::keys([KeyCode::Char('r')]));
+    assert!(app.run().is_err());
+    assert_eq!(app.game().board()[0], [0, 8, 8]);
+    assert_eq!(app.game().moves(), 0);
+    assert!(contains(&screen(&app), "Sandbox: row 1, column 1"));
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_run_async_with_an_event_stream() {
+    use crossterm::event::{Event, KeyEvent};
+
+    let game = Game2048::from_board([[2, 2, 0], [0, 0, 0], [0, 0, 0]]).unwrap();
+    let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal);
+    let keys = [KeyCode::Left, KeyCode::Char('q')];
+    let mut events = futures::stream::iter(keys.map(|code| Ok(Event::Key(KeyEvent::from(code)))));
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let outcome = runtime.block_on(app.run_async_with(&mut events)).unwrap();
+    assert_eq!(outcome, AppOutcome::Quit);
+    assert_eq!(app.game().moves(), 1);
+    assert_eq!(app.game().score(), 4);
+}
@@ -0,0 +1,99 @@
+//! Snapshots of whole frames, driving the app with scripted keys and
+//! comparing what a test backend drew with the snapshots in `snapshots/`.
+//!
+//! After an intended change of the layout, review and accept the new frames
+//! with `cargo insta review`.
+
+use crossterm::event::KeyCode;
+use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
+use tui_2048::game::Progress;
+use tui_2048::input::ScriptedInput;
+use tui_2048::menu::Menu;
+use tui_2048::prelude::*;
+
+/// Text of a frame, one line per row with the trailing blanks removed
+fn frame_text(buffer: &Buffer) -> String {
+    (0..buffer.area.height)
+        .map(|y| {
+            let row: String = (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect();
+            row.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Play the keys on a game and return the last frame.
+///
+/// The frames show the play time and the moves per second, which stay at
+/// `0:00` and `0.0` as long as the keys take less than a second.
+fn play(game: Game2048, keys: impl IntoIterator<Item = KeyCode> + 'static) -> String {
+    let terminal = Terminal::new(TestBackend::new(72, 24)).unwrap();
+    let mut app = App::new(game, NoColorRenderer, terminal).with_input(ScriptedInput::keys(keys));
+    // The app stops when the script runs out
+    let _ = app.run();
+    frame_text(app.terminal.backend().buffer())
+}
+
+fn seeded_game() -> Game2048 {
+    Game2048::builder().seed(2048).build().unwrap()
+}
+
+#[test]
+fn test_main_screen() {
+    insta::assert_snapshot!(play(seeded_game(), [KeyCode::Left, KeyCode::Up]));
+}
+
+#[test]
+fn test_help_screen() {
+    let keys = [KeyCode::Char('g'), KeyCode::Char('?')];
+    insta::assert_snapshot!(play(seeded_game(), keys));
+}
+
+#[test]
+fn test_chord_popup() {
+    insta::assert_snapshot!(play(seeded_game(), [KeyCode::Char('g')]));
+}
+
+#[test]
+fn test_settings_screen() {
+    let keys = [KeyCode::Char('g'), KeyCode::Char('o')];
+    insta::assert_snapshot!(play(seeded_game(), keys));
+}
+
+#[test]
+fn test_game_over_screen() {
+    // Moving right spawns a 4 on the only empty cell, leaving no move
+    let game = Game2048::builder()
+        .seed(1)
+        .four_chance(1.0)
+        .resume(Progress {
+            board: vec![vec![2, 4], vec![16, 0]],
+            score: 20,
+            moves: 3,
+            undos_used: 0,
+            history: Vec::new(),
+        })
+        .build()
+        .unwrap();
+    insta::assert_snapshot!(play(game, [KeyCode::Right]));
+}
+
+#[test]
+fn test_start_menu() {
+    let mut terminal = Terminal::new(TestBackend::new(72, 24)).unwrap();
+    let mut menu = Menu::new(true, vec!["Games played: 3".to_string()]);
+    terminal.draw(|f| menu.draw(f)).unwrap();
+    insta::assert_snapshot!(frame_text(terminal.backend().buffer()));
+
+    // The variant entry shows the variant picked with the right key
+    for code in [KeyCode::Down; 4].into_iter().chain([KeyCode::Right]) {
+        menu.handle_key(code);
+    }
+    terminal.draw(|f| menu.draw(f)).unwrap();
+    insta::assert_snapshot!(
+        "start_menu_variant",
+        frame_text(terminal.backend().buffer())
+    );
+}